@@ -29,6 +29,8 @@ pub struct AnalogBassDrum {
 
     // Replace the resonator in "free running" (sustain) mode.
     oscillator: SineOscillator,
+
+    sample_rate: Option<f32>,
 }
 
 impl AnalogBassDrum {
@@ -52,6 +54,19 @@ impl AnalogBassDrum {
         self.oscillator.init();
     }
 
+    /// Sets the sample rate in Hz the drum is rendered at, so that the pulses and the
+    /// decay last the same time at any rate. `f0` passed to [`Self::render`] is then
+    /// normalized to this rate. `None` (default) uses [`SAMPLE_RATE`].
+    #[inline]
+    pub fn set_sample_rate(&mut self, sample_rate: Option<f32>) {
+        self.sample_rate = sample_rate.map(|sample_rate| sample_rate.max(1000.0));
+    }
+
+    #[inline]
+    pub fn sample_rate(&self) -> Option<f32> {
+        self.sample_rate
+    }
+
     #[allow(clippy::too_many_arguments)]
     #[inline]
     pub fn render(
@@ -66,20 +81,22 @@ impl AnalogBassDrum {
         self_fm_amount: f32,
         out: &mut [f32],
     ) {
-        const TRIGGER_PULSE_DURATION: i32 = (1.0e-3 * SAMPLE_RATE) as i32;
-        const FM_PULSE_DURATION: i32 = (6.0e-3 * SAMPLE_RATE) as i32;
-        const PULSE_DECAY_TIME: f32 = 0.2e-3 * SAMPLE_RATE;
-        const PULSE_FILTER_TIME: f32 = 0.1e-3 * SAMPLE_RATE;
-        const RETRIG_PULSE_DURATION: f32 = 0.05 * SAMPLE_RATE;
+        let sample_rate = self.sample_rate.unwrap_or(SAMPLE_RATE);
+        let trigger_pulse_duration = (1.0e-3 * sample_rate) as i32;
+        let fm_pulse_duration = (6.0e-3 * sample_rate) as i32;
+        let pulse_decay_time = 0.2e-3 * sample_rate;
+        let pulse_filter_time = 0.1e-3 * sample_rate;
+        let retrig_pulse_duration = 0.05 * sample_rate;
 
         let scale = 0.001 / f0;
-        let q = 1500.0 * semitones_to_ratio(decay * 80.0);
+        // The resonator rings for about q / pi samples, so q scales with the rate.
+        let q = 1500.0 * semitones_to_ratio(decay * 80.0) * (sample_rate / SAMPLE_RATE);
         let tone_f = f32::min(4.0 * f0 * semitones_to_ratio(tone * 108.0), 1.0);
         let exciter_leak = 0.08 * (tone + 0.25);
 
         if trigger {
-            self.pulse_remaining_samples = TRIGGER_PULSE_DURATION;
-            self.fm_pulse_remaining_samples = FM_PULSE_DURATION;
+            self.pulse_remaining_samples = trigger_pulse_duration;
+            self.fm_pulse_remaining_samples = fm_pulse_duration;
             self.pulse_height = 3.0 + 7.0 * accent;
             self.lp_out = 0.0;
         }
@@ -99,7 +116,7 @@ impl AnalogBassDrum {
                 };
                 self.pulse = pulse;
             } else {
-                self.pulse *= 1.0 - 1.0 / PULSE_DECAY_TIME;
+                self.pulse *= 1.0 - 1.0 / pulse_decay_time;
                 pulse = self.pulse;
             }
             if sustain {
@@ -107,7 +124,7 @@ impl AnalogBassDrum {
             }
 
             // C40 / R163 / R162 / D83
-            one_pole(&mut self.pulse_lp, pulse, 1.0 / PULSE_FILTER_TIME);
+            one_pole(&mut self.pulse_lp, pulse, 1.0 / pulse_filter_time);
             pulse = diode((pulse - self.pulse_lp) + pulse * 0.044);
 
             // Q41 / Q42
@@ -123,12 +140,12 @@ impl AnalogBassDrum {
                 };
             } else {
                 // C39 / R161
-                self.retrig_pulse *= 1.0 - 1.0 / RETRIG_PULSE_DURATION;
+                self.retrig_pulse *= 1.0 - 1.0 / retrig_pulse_duration;
             }
             if sustain {
                 fm_pulse = 0.0;
             }
-            one_pole(&mut self.fm_pulse_lp, fm_pulse, 1.0 / PULSE_FILTER_TIME);
+            one_pole(&mut self.fm_pulse_lp, fm_pulse, 1.0 / pulse_filter_time);
 
             // Q43 and R170 leakage
             let punch = 0.7 + diode(10.0 * self.lp_out - 1.0);
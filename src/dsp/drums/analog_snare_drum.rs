@@ -8,7 +8,7 @@ use crate::stmlib::dsp::filter::{FilterMode, FrequencyApproximation, Svf};
 use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 use crate::stmlib::dsp::units::semitones_to_ratio;
 use crate::stmlib::dsp::{one_pole, soft_clip};
-use crate::stmlib::utils::random;
+use crate::stmlib::utils::random::RandomSource;
 
 const NUM_MODES: usize = 5;
 
@@ -26,6 +26,10 @@ pub struct AnalogSnareDrum {
 
     // Replace the resonators in "free running" (sustain) mode.
     oscillator: [SineOscillator; NUM_MODES],
+
+    sample_rate: Option<f32>,
+
+    random: RandomSource,
 }
 
 impl AnalogSnareDrum {
@@ -46,6 +50,20 @@ impl AnalogSnareDrum {
             self.oscillator[i].init();
         }
         self.noise_filter.init();
+        self.random.init(None, 0);
+    }
+
+    /// Sets the sample rate in Hz the drum is rendered at, so that the pulse and the decays last the
+    /// same time at any rate. `f0` passed to [`Self::render`] is then normalized to this
+    /// rate. `None` (default) uses [`SAMPLE_RATE`].
+    #[inline]
+    pub fn set_sample_rate(&mut self, sample_rate: Option<f32>) {
+        self.sample_rate = sample_rate.map(|sample_rate| sample_rate.max(1000.0));
+    }
+
+    #[inline]
+    pub fn sample_rate(&self) -> Option<f32> {
+        self.sample_rate
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -62,17 +80,20 @@ impl AnalogSnareDrum {
         out: &mut [f32],
     ) {
         let decay_xt = decay * (1.0 + decay * (decay - 1.0));
-        const TRIGGER_PULSE_DURATION: i32 = (1.0e-3 * SAMPLE_RATE) as i32;
-        const PULSE_DECAY_TIME: i32 = (0.1e-3 * SAMPLE_RATE) as i32;
-        let q = 2000.0 * semitones_to_ratio(decay_xt * 84.0);
-        let noise_envelope_decay =
-            1.0 - 0.0017 * semitones_to_ratio(-decay * (50.0 + snappy * 10.0));
+        let sample_rate = self.sample_rate.unwrap_or(SAMPLE_RATE);
+        let trigger_pulse_duration = (1.0e-3 * sample_rate) as i32;
+        let pulse_decay_time = (0.1e-3 * sample_rate) as i32;
+        let q = 2000.0 * semitones_to_ratio(decay_xt * 84.0) * (sample_rate / SAMPLE_RATE);
+        let noise_envelope_decay = 1.0
+            - 0.0017
+                * (SAMPLE_RATE / sample_rate)
+                * semitones_to_ratio(-decay * (50.0 + snappy * 10.0));
         let exciter_leak = snappy * (2.0 - snappy) * 0.1;
 
         snappy = (snappy * 1.1 - 0.05).clamp(0.0, 1.0);
 
         if trigger {
-            self.pulse_remaining_samples = TRIGGER_PULSE_DURATION;
+            self.pulse_remaining_samples = trigger_pulse_duration;
             self.pulse_height = 3.0 + 7.0 * accent;
             self.noise_envelope = 2.0;
         }
@@ -129,7 +150,7 @@ impl AnalogSnareDrum {
                 };
                 self.pulse = pulse;
             } else {
-                self.pulse *= 1.0 - 1.0 / (PULSE_DECAY_TIME as f32);
+                self.pulse *= 1.0 - 1.0 / (pulse_decay_time as f32);
                 pulse = self.pulse;
             }
 
@@ -156,7 +177,7 @@ impl AnalogSnareDrum {
             shell = soft_clip(shell);
 
             // C56 / R194 / Q48 / C54 / R188 / D54
-            let mut noise = 2.0 * random::get_float() - 1.0;
+            let mut noise = 2.0 * self.random.get_float() - 1.0;
             if noise < 0.0 {
                 noise = 0.0;
             }
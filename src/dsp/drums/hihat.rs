@@ -14,7 +14,7 @@ use crate::dsp::SAMPLE_RATE;
 use crate::stmlib::dsp::filter::{FilterMode, FrequencyApproximation, Svf};
 use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 use crate::stmlib::dsp::units::semitones_to_ratio;
-use crate::stmlib::utils::random;
+use crate::stmlib::utils::random::RandomSource;
 
 pub enum NoiseType {
     Square,
@@ -38,6 +38,10 @@ pub struct Hihat {
 
     noise_coloration_svf: Svf,
     hpf: Svf,
+
+    sample_rate: Option<f32>,
+
+    random: RandomSource,
 }
 
 impl Hihat {
@@ -55,6 +59,24 @@ impl Hihat {
         self.ring_mod_noise.init();
         self.noise_coloration_svf.init();
         self.hpf.init();
+        self.random.init(None, 0);
+    }
+
+    /// Seeds the random source, see [`RandomSource::init`].
+    pub(crate) fn init_random(&mut self, seed: Option<u32>, stream: u32) {
+        self.random.init(seed, stream);
+    }
+
+    /// Sets the sample rate in Hz the hihat is rendered at, so that the envelope lasts the
+    /// same time at any rate. `None` (default) uses [`SAMPLE_RATE`].
+    #[inline]
+    pub fn set_sample_rate(&mut self, sample_rate: Option<f32>) {
+        self.sample_rate = sample_rate.map(|sample_rate| sample_rate.max(1000.0));
+    }
+
+    #[inline]
+    pub fn sample_rate(&self) -> Option<f32> {
+        self.sample_rate
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -76,8 +98,10 @@ impl Hihat {
         resonance: bool,
         two_stage_envelope: bool,
     ) {
-        let envelope_decay = 1.0 - 0.003 * semitones_to_ratio(-decay * 84.0);
-        let cut_decay = 1.0 - 0.0025 * semitones_to_ratio(-decay * 36.0);
+        // The decay coefficients are tuned for 48 kHz.
+        let time_scale = SAMPLE_RATE / self.sample_rate.unwrap_or(SAMPLE_RATE);
+        let envelope_decay = 1.0 - 0.003 * time_scale * semitones_to_ratio(-decay * 84.0);
+        let cut_decay = 1.0 - 0.0025 * time_scale * semitones_to_ratio(-decay * 36.0);
 
         if trigger {
             self.envelope = (1.5 + 0.5 * (1.0 - decay)) * (0.3 + 0.7 * accent);
@@ -116,7 +140,7 @@ impl Hihat {
             self.noise_clock += noise_f;
             if self.noise_clock >= 1.0 {
                 self.noise_clock -= 1.0;
-                self.noise_sample = random::get_float() - 0.5;
+                self.noise_sample = self.random.get_float() - 0.5;
             }
             *out_sample += noisiness * (self.noise_sample - *out_sample);
         }
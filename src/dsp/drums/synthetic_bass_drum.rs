@@ -12,7 +12,7 @@ use crate::stmlib::dsp::filter::{FilterMode, FrequencyApproximation, Svf};
 use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 use crate::stmlib::dsp::units::semitones_to_ratio;
 use crate::stmlib::dsp::{one_pole, slope};
-use crate::stmlib::utils::random;
+use crate::stmlib::utils::random::RandomSource;
 
 #[derive(Debug, Default)]
 pub struct SyntheticBassDrum {
@@ -36,6 +36,11 @@ pub struct SyntheticBassDrum {
 
     body_env_pulse_width: i32,
     fm_pulse_width: i32,
+
+    transient: Option<f32>,
+    sample_rate: Option<f32>,
+
+    random: RandomSource,
 }
 
 impl SyntheticBassDrum {
@@ -58,6 +63,33 @@ impl SyntheticBassDrum {
 
         self.click.init();
         self.noise.init();
+        self.random.init(None, 0);
+        self.noise.random.init(None, 1);
+    }
+
+    /// Sets the sample rate in Hz the drum is rendered at, so that the pulses and the
+    /// envelopes last the same time at any rate. `f0` passed to [`Self::render`] is then
+    /// normalized to this rate. `None` (default) uses [`SAMPLE_RATE`].
+    #[inline]
+    pub fn set_sample_rate(&mut self, sample_rate: Option<f32>) {
+        self.sample_rate = sample_rate.map(|sample_rate| sample_rate.max(1000.0));
+    }
+
+    #[inline]
+    pub fn sample_rate(&self) -> Option<f32> {
+        self.sample_rate
+    }
+
+    /// Overrides the level of the click and noise transient, from `0.0` to `1.0`. `None`
+    /// (default) uses `tone`, so that brighter kicks have a sharper attack.
+    #[inline]
+    pub fn set_transient(&mut self, transient: Option<f32>) {
+        self.transient = transient.map(|transient| transient.clamp(0.0, 1.0));
+    }
+
+    #[inline]
+    pub fn transient(&self) -> Option<f32> {
+        self.transient
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -82,26 +114,27 @@ impl SyntheticBassDrum {
 
         dirtiness *= f32::max(1.0 - 8.0 * f0, 0.0);
 
-        let fm_decay = 1.0 - 1.0 / (0.008 * (1.0 + fm_envelope_decay * 4.0) * SAMPLE_RATE);
+        let sample_rate = self.sample_rate.unwrap_or(SAMPLE_RATE);
+        let fm_decay = 1.0 - 1.0 / (0.008 * (1.0 + fm_envelope_decay * 4.0) * sample_rate);
 
-        let body_env_decay = 1.0 - 1.0 / (0.02 * SAMPLE_RATE) * semitones_to_ratio(-decay * 60.0);
-        let transient_env_decay = 1.0 - 1.0 / (0.005 * SAMPLE_RATE);
+        let body_env_decay = 1.0 - 1.0 / (0.02 * sample_rate) * semitones_to_ratio(-decay * 60.0);
+        let transient_env_decay = 1.0 - 1.0 / (0.005 * sample_rate);
         let tone_f = f32::min(4.0 * f0 * semitones_to_ratio(tone * 108.0), 1.0);
-        let transient_level = tone;
+        let transient_level = self.transient.unwrap_or(tone);
 
         if trigger {
             self.fm = 1.0;
             self.body_env = 0.3 + 0.7 * accent;
             self.transient_env = self.body_env;
-            self.body_env_pulse_width = (SAMPLE_RATE * 0.001) as i32;
-            self.fm_pulse_width = (SAMPLE_RATE * 0.0013) as i32;
+            self.body_env_pulse_width = (sample_rate * 0.001) as i32;
+            self.fm_pulse_width = (sample_rate * 0.0013) as i32;
         }
 
         let mut sustain_gain =
             ParameterInterpolator::new(&mut self.sustain_gain, accent * decay, out.len());
 
         for out_sample in out.iter_mut() {
-            one_pole(&mut self.phase_noise, random::get_float() - 0.5, 0.002);
+            one_pole(&mut self.phase_noise, self.random.get_float() - 0.5, 0.002);
 
             let mut mix = 0.0;
 
@@ -211,6 +244,8 @@ impl SyntheticBassDrumClick {
 pub struct SyntheticBassDrumAttackNoise {
     lp: f32,
     hp: f32,
+
+    random: RandomSource,
 }
 
 impl SyntheticBassDrumAttackNoise {
@@ -221,11 +256,12 @@ impl SyntheticBassDrumAttackNoise {
     pub fn init(&mut self) {
         self.lp = 0.0;
         self.hp = 0.0;
+        self.random.init(None, 0);
     }
 
     #[inline]
     pub fn render(&mut self) -> f32 {
-        let sample = random::get_float();
+        let sample = self.random.get_float();
         one_pole(&mut self.lp, sample, 0.05);
         one_pole(&mut self.hp, self.lp, 0.005);
 
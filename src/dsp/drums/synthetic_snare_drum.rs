@@ -15,7 +15,7 @@ use crate::stmlib::dsp::filter::{FilterMode, FrequencyApproximation, OnePole, Sv
 use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 use crate::stmlib::dsp::sqrt;
 use crate::stmlib::dsp::units::semitones_to_ratio;
-use crate::stmlib::utils::random;
+use crate::stmlib::utils::random::RandomSource;
 
 #[derive(Debug, Default)]
 pub struct SyntheticSnareDrum {
@@ -29,6 +29,10 @@ pub struct SyntheticSnareDrum {
     drum_lp: OnePole,
     snare_hp: OnePole,
     snare_lp: Svf,
+
+    sample_rate: Option<f32>,
+
+    random: RandomSource,
 }
 
 impl SyntheticSnareDrum {
@@ -47,6 +51,20 @@ impl SyntheticSnareDrum {
         self.drum_lp.init();
         self.snare_hp.init();
         self.snare_lp.init();
+        self.random.init(None, 0);
+    }
+
+    /// Sets the sample rate in Hz the drum is rendered at, so that the envelopes last the
+    /// same time at any rate. `f0` passed to [`Self::render`] is then normalized to this
+    /// rate. `None` (default) uses [`SAMPLE_RATE`].
+    #[inline]
+    pub fn set_sample_rate(&mut self, sample_rate: Option<f32>) {
+        self.sample_rate = sample_rate.map(|sample_rate| sample_rate.max(1000.0));
+    }
+
+    #[inline]
+    pub fn sample_rate(&self) -> Option<f32> {
+        self.sample_rate
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -62,14 +80,15 @@ impl SyntheticSnareDrum {
         mut snappy: f32,
         out: &mut [f32],
     ) {
+        let sample_rate = self.sample_rate.unwrap_or(SAMPLE_RATE);
         let decay_xt = decay * (1.0 + decay * (decay - 1.0));
         fm_amount *= fm_amount;
         let drum_decay = 1.0
-            - 1.0 / (0.015 * SAMPLE_RATE)
+            - 1.0 / (0.015 * sample_rate)
                 * semitones_to_ratio(-decay_xt * 72.0 - fm_amount * 12.0 + snappy * 7.0);
         let snare_decay =
-            1.0 - 1.0 / (0.01 * SAMPLE_RATE) * semitones_to_ratio(-decay * 60.0 - snappy * 7.0);
-        let fm_decay = 1.0 - 1.0 / (0.007 * SAMPLE_RATE);
+            1.0 - 1.0 / (0.01 * sample_rate) * semitones_to_ratio(-decay * 60.0 - snappy * 7.0);
+        let fm_decay = 1.0 - 1.0 / (0.007 * sample_rate);
 
         snappy = snappy * 1.1 - 0.05;
         snappy = snappy.clamp(0.0, 1.0);
@@ -95,7 +114,7 @@ impl SyntheticSnareDrum {
             self.fm = 1.0;
             self.phase[0] = 0.0;
             self.phase[1] = 0.0;
-            self.hold_counter = ((0.04 + decay * 0.03) * SAMPLE_RATE) as i32;
+            self.hold_counter = ((0.04 + decay * 0.03) * sample_rate) as i32;
         }
 
         let mut sustain_gain =
@@ -164,7 +183,7 @@ impl SyntheticSnareDrum {
             drum *= self.drum_amplitude * drum_level;
             drum = self.drum_lp.process(drum, FilterMode::LowPass);
 
-            let noise = random::get_float();
+            let noise = self.random.get_float();
             let mut snare = self.snare_lp.process(noise, FilterMode::LowPass);
             snare = self.snare_hp.process(snare, FilterMode::HighPass);
             snare = (snare + 0.1) * (self.snare_amplitude + self.fm) * snare_level;
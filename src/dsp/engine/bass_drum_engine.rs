@@ -16,10 +16,12 @@
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
-use super::{note_to_frequency, Engine, EngineParameters, TriggerState};
+use super::{note_to_frequency, Engine, EngineParameters, SustainSwitch};
 use crate::dsp::drums::analog_bass_drum::AnalogBassDrum;
 use crate::dsp::drums::synthetic_bass_drum::SyntheticBassDrum;
 use crate::dsp::fx::overdrive::Overdrive;
+use crate::stmlib::dsp::crossfade;
+use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 
 #[derive(Debug, Default)]
 pub struct BassDrumEngine {
@@ -27,12 +29,80 @@ pub struct BassDrumEngine {
     synthetic_bass_drum: SyntheticBassDrum,
 
     overdrive: Overdrive,
+    sustain_switch: SustainSwitch,
+
+    fm_amount: Option<f32>,
+    dirtiness: Option<f32>,
+    model_blend: f32,
+    previous_model_blend: f32,
 }
 
 impl BassDrumEngine {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Sustain also with a patched trigger, for drones. Toggling
+    /// the sustain mode fades between both modes. Default is `false`, which only
+    /// sustains with an unpatched trigger.
+    #[inline]
+    pub fn set_sustain(&mut self, sustain: bool) {
+        self.sustain_switch.set_forced(sustain);
+    }
+
+    #[inline]
+    pub fn sustain(&self) -> bool {
+        self.sustain_switch.forced()
+    }
+
+    /// Crossfades *OUT* from the analog model (`0.0`) to the synthetic model (`1.0`).
+    /// *AUX* always renders the synthetic model. Default is `0.0`.
+    #[inline]
+    pub fn set_model_blend(&mut self, blend: f32) {
+        self.model_blend = blend.clamp(0.0, 1.0);
+    }
+
+    #[inline]
+    pub fn model_blend(&self) -> f32 {
+        self.model_blend
+    }
+
+    /// Overrides the level of the click and noise transient of the synthetic model on
+    /// *AUX* set by *TIMBRE*, from `0.0` to `1.0`. `None` (default) uses *TIMBRE*.
+    #[inline]
+    pub fn set_transient(&mut self, transient: Option<f32>) {
+        self.synthetic_bass_drum.set_transient(transient);
+    }
+
+    #[inline]
+    pub fn transient(&self) -> Option<f32> {
+        self.synthetic_bass_drum.transient()
+    }
+
+    /// Overrides the depth of the pitch sweep of the synthetic model on *AUX* set by
+    /// *HARMONICS*, from `0.0` to `1.0`. `None` (default) uses *HARMONICS*.
+    #[inline]
+    pub fn set_fm_amount(&mut self, fm_amount: Option<f32>) {
+        self.fm_amount = fm_amount.map(|fm_amount| fm_amount.clamp(0.0, 1.0));
+    }
+
+    #[inline]
+    pub fn fm_amount(&self) -> Option<f32> {
+        self.fm_amount
+    }
+
+    /// Overrides the amount of noise distorting the waveform of the synthetic model on
+    /// *AUX*, from `0.0` to `1.0`. `None` (default) uses *MORPH*, or *HARMONICS* when
+    /// sustaining.
+    #[inline]
+    pub fn set_dirtiness(&mut self, dirtiness: Option<f32>) {
+        self.dirtiness = dirtiness.map(|dirtiness| dirtiness.clamp(0.0, 1.0));
+    }
+
+    #[inline]
+    pub fn dirtiness(&self) -> Option<f32> {
+        self.dirtiness
+    }
 }
 
 impl Engine for BassDrumEngine {
@@ -40,6 +110,8 @@ impl Engine for BassDrumEngine {
         self.analog_bass_drum.init();
         self.synthetic_bass_drum.init();
         self.overdrive.init();
+        self.sustain_switch.init();
+        self.previous_model_blend = self.model_blend;
     }
 
     #[inline]
@@ -57,8 +129,7 @@ impl Engine for BassDrumEngine {
         let drive =
             f32::max(parameters.harmonics * 2.0 - 1.0, 0.0) * f32::max(1.0 - 16.0 * f0, 0.0);
 
-        let sustain = matches!(parameters.trigger, TriggerState::Unpatched);
-        let trigger = matches!(parameters.trigger, TriggerState::RisingEdge);
+        let (sustain, trigger) = self.sustain_switch.process(&parameters.trigger, out.len());
 
         self.analog_bass_drum.render(
             sustain,
@@ -81,14 +152,29 @@ impl Engine for BassDrumEngine {
             f0,
             parameters.timbre,
             parameters.morph,
-            if sustain {
+            self.dirtiness.unwrap_or(if sustain {
                 parameters.harmonics
             } else {
                 0.4 - 0.25 * parameters.morph * parameters.morph
-            },
-            f32::min(parameters.harmonics * 2.0, 1.0),
+            }),
+            self.fm_amount
+                .unwrap_or(f32::min(parameters.harmonics * 2.0, 1.0)),
             f32::max(parameters.harmonics * 2.0 - 1.0, 0.0),
             aux,
         );
+
+        if self.model_blend > 0.0 || self.previous_model_blend > 0.0 {
+            let mut blend_modulation = ParameterInterpolator::new(
+                &mut self.previous_model_blend,
+                self.model_blend,
+                out.len(),
+            );
+            for (out_sample, aux_sample) in out.iter_mut().zip(aux.iter()) {
+                *out_sample = crossfade(*out_sample, *aux_sample, blend_modulation.next());
+            }
+        }
+
+        self.sustain_switch.apply(out);
+        self.sustain_switch.apply(aux);
     }
 }
@@ -15,6 +15,9 @@
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
+#[allow(unused_imports)]
+use num_traits::float::Float;
+
 use super::{note_to_frequency, Engine, EngineParameters};
 use crate::dsp::chords::chord_bank::{ChordBank, CHORD_NUM_VOICES};
 use crate::dsp::oscillator::string_synth_oscillator::StringSynthOscillator;
@@ -33,6 +36,10 @@ pub struct ChordEngine<'a> {
     morph_lp: f32,
     timbre_lp: f32,
 
+    ratios: [f32; CHORD_NUM_VOICES],
+    note_amplitudes: [f32; CHORD_NUM_VOICES],
+    voice_pans: Option<[f32; CHORD_NUM_VOICES]>,
+
     wavetable: [&'a [i16]; 15],
 }
 
@@ -50,6 +57,9 @@ impl<'a> Default for ChordEngine<'a> {
             chords: ChordBank::new(),
             morph_lp: 0.0,
             timbre_lp: 0.0,
+            ratios: [1.0; CHORD_NUM_VOICES],
+            note_amplitudes: [0.0; CHORD_NUM_VOICES],
+            voice_pans: None,
             wavetable: [
                 &WAV_INTEGRATED_WAVES[wt_index(2, 6, 1)..],
                 &WAV_INTEGRATED_WAVES[wt_index(2, 6, 6)..],
@@ -82,6 +92,8 @@ impl<'a> Engine for ChordEngine<'a> {
 
         self.morph_lp = 0.0;
         self.timbre_lp = 0.0;
+        self.ratios = [1.0; CHORD_NUM_VOICES];
+        self.note_amplitudes = [0.0; CHORD_NUM_VOICES];
 
         self.reset();
     }
@@ -97,6 +109,83 @@ impl<'a> Engine for ChordEngine<'a> {
         out: &mut [f32],
         aux: &mut [f32],
         _already_enveloped: &mut bool,
+    ) {
+        self.render_voices(parameters, out, aux, Routing::Inversion);
+
+        for (out_sample, aux_sample) in out.iter_mut().zip(aux.iter_mut()) {
+            *out_sample += *aux_sample;
+            *aux_sample *= 3.0;
+        }
+    }
+}
+
+impl<'a> ChordEngine<'a> {
+    /// Returns the notes of the voices of the last rendered block as semitone offsets
+    /// to the played note, after inversion and transposition by *TIMBRE*.
+    ///
+    /// While crossfading between two inversions, two voices play the same note an octave
+    /// apart, see `current_amplitudes`.
+    pub fn current_notes(&self) -> [f32; CHORD_NUM_VOICES] {
+        self.ratios.map(|ratio| 12.0 * ratio.log2())
+    }
+
+    /// Returns the amplitudes of the voices of the last rendered block. Silent voices
+    /// have an amplitude of `0.0`.
+    pub fn current_amplitudes(&self) -> [f32; CHORD_NUM_VOICES] {
+        self.note_amplitudes
+    }
+
+    /// Places each voice in the stereo field of `render_stereo`, from `-1.0` (left) to
+    /// `1.0` (right). `None` (default) pans odd and even voices to opposite sides.
+    ///
+    /// Voices are numbered as in `current_notes`. The mono output of `render` is not
+    /// affected.
+    pub fn set_voice_pans(&mut self, pans: Option<&[f32; CHORD_NUM_VOICES]>) {
+        self.voice_pans = pans.map(|pans| pans.map(|pan| pan.clamp(-1.0, 1.0)));
+    }
+
+    #[inline]
+    pub fn voice_pans(&self) -> Option<&[f32; CHORD_NUM_VOICES]> {
+        self.voice_pans.as_ref()
+    }
+
+    /// Renders the chord as a stereo pair.
+    ///
+    /// Odd and even voices, or the voices placed by `set_voice_pans`, are panned by
+    /// `spread` (0.0 to 1.0). `left + right` always equals the *OUT* signal of `render`,
+    /// so at a spread of `0.0` both channels carry half of the mono mix.
+    pub fn render_stereo(
+        &mut self,
+        parameters: &EngineParameters,
+        left: &mut [f32],
+        right: &mut [f32],
+        spread: f32,
+    ) {
+        let spread = spread.clamp(0.0, 1.0);
+
+        if let Some(pans) = self.voice_pans {
+            self.render_voices(parameters, left, right, Routing::Pan(pans, spread));
+            return;
+        }
+
+        self.render_voices(parameters, left, right, Routing::Split);
+
+        for (left_sample, right_sample) in left.iter_mut().zip(right.iter_mut()) {
+            let mid = (*left_sample + *right_sample) * 0.5;
+            let side = (*left_sample - *right_sample) * 0.5 * spread;
+            *left_sample = mid + side;
+            *right_sample = mid - side;
+        }
+    }
+
+    /// Renders the voices into `out` and `aux` as selected by `routing`.
+    #[inline]
+    fn render_voices(
+        &mut self,
+        parameters: &EngineParameters,
+        out: &mut [f32],
+        aux: &mut [f32],
+        routing: Routing,
     ) {
         one_pole(&mut self.morph_lp, parameters.morph, 0.1);
         one_pole(&mut self.timbre_lp, parameters.timbre, 0.1);
@@ -104,16 +193,16 @@ impl<'a> Engine for ChordEngine<'a> {
         self.chords.set_chord(parameters.harmonics);
 
         let mut harmonics: [f32; CHORD_NUM_HARMONICS * 2 + 2] = [0.0; CHORD_NUM_HARMONICS * 2 + 2];
-        let mut note_amplitudes: [f32; CHORD_NUM_VOICES] = [0.0; CHORD_NUM_VOICES];
         let registration = f32::max(1.0 - self.morph_lp * 2.15, 0.0);
 
         compute_registration(registration, &mut harmonics);
         harmonics[CHORD_NUM_HARMONICS * 2] = 0.0;
 
-        let mut ratios: [f32; CHORD_NUM_VOICES] = [0.0; CHORD_NUM_VOICES];
-        let aux_note_mask =
-            self.chords
-                .compute_chord_inversion(self.timbre_lp, &mut ratios, &mut note_amplitudes);
+        let aux_note_mask = self.chords.compute_chord_inversion(
+            self.timbre_lp,
+            &mut self.ratios,
+            &mut self.note_amplitudes,
+        );
 
         out.fill(0.0);
         aux.fill(0.0);
@@ -122,72 +211,100 @@ impl<'a> Engine for ChordEngine<'a> {
         let waveform = f32::max((self.morph_lp - 0.535) * 2.15, 0.0);
 
         for note in 0..CHORD_NUM_VOICES {
-            let mut wavetable_amount = 50.0 * (self.morph_lp - FADE_POINT[note]);
-            wavetable_amount = wavetable_amount.clamp(0.0, 1.0);
-
-            let mut divide_down_amount = 1.0 - wavetable_amount;
-            let destination = ((1 << note) & aux_note_mask) != 0;
-
-            let note_f0 = f0 * ratios[note];
-            let mut divide_down_gain = 4.0 - note_f0 * 32.0;
-            divide_down_gain = divide_down_gain.clamp(0.0, 1.0);
-            divide_down_amount *= divide_down_gain;
-
-            if wavetable_amount > 0.0 {
-                if destination {
-                    self.wavetable_voice[note].render(
-                        note_f0 * 1.004,
-                        note_amplitudes[note] * wavetable_amount,
-                        waveform,
-                        &self.wavetable,
-                        aux,
-                        128,
-                        15,
-                        true,
-                        true,
-                    );
-                } else {
-                    self.wavetable_voice[note].render(
-                        note_f0 * 1.004,
-                        note_amplitudes[note] * wavetable_amount,
-                        waveform,
-                        &self.wavetable,
-                        out,
-                        128,
-                        15,
-                        true,
-                        true,
-                    );
+            let destination = match routing {
+                Routing::Inversion => ((1 << note) & aux_note_mask) != 0,
+                Routing::Split => note & 1 != 0,
+                Routing::Pan(pans, spread) => {
+                    // Linear panning, both channels sum up to the voice. *AUX* holds the
+                    // sum of the voices and *OUT* the left channel: removing the sum
+                    // from *OUT* before adding the voice to it and adding it back
+                    // afterwards leaves the voice with its left gain in *OUT*.
+                    let left_gain = 0.5 - 0.5 * pans[note] * spread;
+                    for (out_sample, aux_sample) in out.iter_mut().zip(aux.iter()) {
+                        *out_sample -= left_gain * aux_sample;
+                    }
+                    self.render_voice(note, f0, waveform, &harmonics, aux);
+                    for (out_sample, aux_sample) in out.iter_mut().zip(aux.iter()) {
+                        *out_sample += left_gain * aux_sample;
+                    }
+                    continue;
                 }
-            }
+            };
+
+            self.render_voice(
+                note,
+                f0,
+                waveform,
+                &harmonics,
+                if destination { aux } else { out },
+            );
+        }
 
-            if divide_down_amount > 0.0 {
-                if destination {
-                    self.divide_down_voice[note].render(
-                        note_f0,
-                        &harmonics,
-                        note_amplitudes[note] * divide_down_amount,
-                        aux,
-                    );
-                } else {
-                    self.divide_down_voice[note].render(
-                        note_f0,
-                        &harmonics,
-                        note_amplitudes[note] * divide_down_amount,
-                        out,
-                    );
-                }
+        if let Routing::Pan(..) = routing {
+            // The right channel is the sum of the voices minus the left channel.
+            for (out_sample, aux_sample) in out.iter().zip(aux.iter_mut()) {
+                *aux_sample -= out_sample;
             }
         }
+    }
 
-        for (out_sample, aux_sample) in out.iter_mut().zip(aux.iter_mut()) {
-            *out_sample += *aux_sample;
-            *aux_sample *= 3.0;
+    /// Adds a voice to `out`.
+    #[inline]
+    fn render_voice(
+        &mut self,
+        note: usize,
+        f0: f32,
+        waveform: f32,
+        harmonics: &[f32; CHORD_NUM_HARMONICS * 2 + 2],
+        out: &mut [f32],
+    ) {
+        let mut wavetable_amount = 50.0 * (self.morph_lp - FADE_POINT[note]);
+        wavetable_amount = wavetable_amount.clamp(0.0, 1.0);
+
+        let mut divide_down_amount = 1.0 - wavetable_amount;
+
+        let note_f0 = f0 * self.ratios[note];
+        let mut divide_down_gain = 4.0 - note_f0 * 32.0;
+        divide_down_gain = divide_down_gain.clamp(0.0, 1.0);
+        divide_down_amount *= divide_down_gain;
+
+        if wavetable_amount > 0.0 {
+            self.wavetable_voice[note].render(
+                note_f0 * 1.004,
+                self.note_amplitudes[note] * wavetable_amount,
+                waveform,
+                &self.wavetable,
+                out,
+                128,
+                15,
+                true,
+                true,
+            );
+        }
+
+        if divide_down_amount > 0.0 {
+            self.divide_down_voice[note].render(
+                note_f0,
+                harmonics,
+                self.note_amplitudes[note] * divide_down_amount,
+                out,
+            );
         }
     }
 }
 
-impl<'a> ChordEngine<'a> {}
+/// Destination of the voices.
+#[derive(Debug, Clone, Copy)]
+enum Routing {
+    /// The chord inversion decides which voices are routed to *AUX*.
+    Inversion,
+
+    /// Odd voices go to *AUX*.
+    Split,
+
+    /// Each voice is panned between *OUT* and *AUX* with a pan position and spread.
+    Pan([f32; CHORD_NUM_VOICES], f32),
+}
 
 const fn wt_index(bank: usize, row: usize, column: usize) -> usize {
     (bank * 64 + row * 8 + column) * 132
@@ -12,26 +12,120 @@
 //! *AUX* signal: simulation of filtered waveforms by windowed sine waves -
 //! a recreation of Braids’ Z*** models. *HARMONICS* controls the filter type (peaking, LP, BP, HP),
 //! with smooth variation from one response to another.
+//!
+//! With [`GrainEngine::set_grain_size`], *OUT* switches to overlapping Hann-windowed
+//! grains of both formants instead, spaced according to [`GrainEngine::set_overlap`].
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
 use super::{note_to_frequency, Engine, EngineParameters};
 use crate::dsp::oscillator::grainlet_oscillator::GrainletOscillator;
+use crate::dsp::oscillator::sine_oscillator::sine;
 use crate::dsp::oscillator::z_oscillator::ZOscillator;
+use crate::dsp::SAMPLE_RATE;
 use crate::stmlib::dsp::filter::{FilterMode, FrequencyApproximation, OnePole};
 use crate::stmlib::dsp::units::semitones_to_ratio;
 
+/// Maximum number of simultaneous grains in the windowed mode.
+pub const MAX_GRAINS: usize = 4;
+
+/// Longest grain in seconds.
+pub const MAX_GRAIN_SIZE: f32 = 1.0;
+
+/// Shortest grain in seconds.
+pub const MIN_GRAIN_SIZE: f32 = 0.001;
+
 #[derive(Debug, Default)]
 pub struct GrainEngine {
     grainlet: [GrainletOscillator; 2],
     z_oscillator: ZOscillator,
     dc_blocker: [OnePole; 2],
+
+    grain_size: Option<f32>,
+    overlap: f32,
+    grain_phase: [Option<f32>; MAX_GRAINS],
+    next_grain_phase: f32,
+    formant_phase: [f32; 2],
 }
 
 impl GrainEngine {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Renders *OUT* as overlapping windowed grains of the given length in seconds,
+    /// from sparse clicks to smooth pads. The formants are still set by *TIMBRE* and
+    /// *HARMONICS*, but the note no longer sets the rate of the grains. `None` (default)
+    /// uses the grainlet oscillators synced to the note.
+    #[inline]
+    pub fn set_grain_size(&mut self, grain_size: Option<f32>) {
+        self.grain_size =
+            grain_size.map(|grain_size| grain_size.clamp(MIN_GRAIN_SIZE, MAX_GRAIN_SIZE));
+    }
+
+    /// Returns the grain size in seconds, if set.
+    #[inline]
+    pub fn grain_size(&self) -> Option<f32> {
+        self.grain_size
+    }
+
+    /// Sets the fraction of each grain overlapping with the next one in the windowed mode
+    /// (0.0 to 0.75). `0.0` (default) plays the grains back to back, `0.5` and above gives
+    /// a constant level.
+    #[inline]
+    pub fn set_overlap(&mut self, overlap: f32) {
+        self.overlap = overlap.clamp(0.0, 1.0 - 1.0 / MAX_GRAINS as f32);
+    }
+
+    #[inline]
+    pub fn overlap(&self) -> f32 {
+        self.overlap
+    }
+
+    /// Renders Hann-windowed grains of two continuously running sine waves.
+    #[inline]
+    fn render_windowed(&mut self, grain_size: f32, f1: f32, f2: f32, out: &mut [f32]) {
+        let grain_increment = 1.0 / (grain_size * SAMPLE_RATE);
+        let spacing = 1.0 - self.overlap;
+        let next_grain_increment = grain_increment / spacing;
+
+        // Overlapping Hann windows sum to 0.5 / spacing on average, and to a constant
+        // from an overlap of 0.5.
+        let gain = 0.5 * f32::min(1.0, 2.0 * spacing);
+
+        for out_sample in out.iter_mut() {
+            self.next_grain_phase += next_grain_increment;
+            if self.next_grain_phase >= 1.0 {
+                self.next_grain_phase -= 1.0;
+                if let Some(grain) = self.grain_phase.iter_mut().find(|grain| grain.is_none()) {
+                    *grain = Some(self.next_grain_phase * spacing);
+                }
+            }
+
+            self.formant_phase[0] += f1;
+            if self.formant_phase[0] >= 1.0 {
+                self.formant_phase[0] -= 1.0;
+            }
+            self.formant_phase[1] += f2;
+            if self.formant_phase[1] >= 1.0 {
+                self.formant_phase[1] -= 1.0;
+            }
+
+            let mut window = 0.0;
+            for grain in self.grain_phase.iter_mut() {
+                if let Some(phase) = grain {
+                    window += 0.5 + 0.5 * sine(*phase + 0.75);
+                    *phase += grain_increment;
+                    if *phase >= 1.0 {
+                        *grain = None;
+                    }
+                }
+            }
+
+            *out_sample =
+                gain * window * (sine(self.formant_phase[0]) + sine(self.formant_phase[1]));
+        }
+    }
 }
 
 impl Engine for GrainEngine {
@@ -41,6 +135,9 @@ impl Engine for GrainEngine {
         self.z_oscillator.init();
         self.dc_blocker[0].init();
         self.dc_blocker[1].init();
+        self.grain_phase = [None; MAX_GRAINS];
+        self.next_grain_phase = 1.0;
+        self.formant_phase = [0.0; 2];
     }
 
     #[inline]
@@ -64,8 +161,13 @@ impl Engine for GrainEngine {
         let carrier_bleed_fixed = carrier_bleed * (2.0 - carrier_bleed);
         let carrier_shape = 0.33 + (parameters.morph - 0.33) * f32::max(1.0 - f0 * 24.0, 0.0);
 
-        self.grainlet[0].render(f0, f1, carrier_shape, carrier_bleed_fixed, out);
-        self.grainlet[1].render(f0, f1 * ratio, carrier_shape, carrier_bleed_fixed, aux);
+        if let Some(grain_size) = self.grain_size {
+            self.render_windowed(grain_size, f1, f1 * ratio, out);
+            aux.fill(0.0);
+        } else {
+            self.grainlet[0].render(f0, f1, carrier_shape, carrier_bleed_fixed, out);
+            self.grainlet[1].render(f0, f1 * ratio, carrier_shape, carrier_bleed_fixed, aux);
+        }
         self.dc_blocker[0].set_f(0.3 * f0, FrequencyApproximation::Dirty);
 
         for (out_sample, aux_sample) in out.iter_mut().zip(aux.iter()) {
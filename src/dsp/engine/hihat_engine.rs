@@ -16,7 +16,7 @@
 
 use core::alloc::GlobalAlloc;
 
-use super::{note_to_frequency, Engine, EngineParameters, TriggerState};
+use super::{note_to_frequency, Engine, EngineParameters, SustainSwitch};
 use crate::dsp::allocate_buffer;
 use crate::dsp::drums::hihat::{Hihat, NoiseType, VcaType};
 
@@ -25,6 +25,8 @@ pub struct HihatEngine<'a> {
     hi_hat_1: Hihat,
     hi_hat_2: Hihat,
 
+    sustain_switch: SustainSwitch,
+
     temp_buffer_1: &'a mut [f32],
     temp_buffer_2: &'a mut [f32],
 }
@@ -34,16 +36,32 @@ impl<'a> HihatEngine<'a> {
         Self {
             hi_hat_1: Hihat::default(),
             hi_hat_2: Hihat::default(),
+            sustain_switch: SustainSwitch::new(),
             temp_buffer_1: allocate_buffer(buffer_allocator, block_size).unwrap(),
             temp_buffer_2: allocate_buffer(buffer_allocator, block_size).unwrap(),
         }
     }
+
+    /// Sustain also with a patched trigger, for drones and open hi-hats. Toggling
+    /// the sustain mode fades between both modes. Default is `false`, which only
+    /// sustains with an unpatched trigger.
+    #[inline]
+    pub fn set_sustain(&mut self, sustain: bool) {
+        self.sustain_switch.set_forced(sustain);
+    }
+
+    #[inline]
+    pub fn sustain(&self) -> bool {
+        self.sustain_switch.forced()
+    }
 }
 
 impl<'a> Engine for HihatEngine<'a> {
     fn init(&mut self) {
         self.hi_hat_1.init();
         self.hi_hat_2.init();
+        self.hi_hat_2.init_random(None, 1);
+        self.sustain_switch.init();
     }
 
     #[inline]
@@ -56,8 +74,7 @@ impl<'a> Engine for HihatEngine<'a> {
     ) {
         let f0 = note_to_frequency(parameters.note);
 
-        let sustain = matches!(parameters.trigger, TriggerState::Unpatched);
-        let trigger = matches!(parameters.trigger, TriggerState::RisingEdge);
+        let (sustain, trigger) = self.sustain_switch.process(&parameters.trigger, out.len());
 
         self.hi_hat_1.render(
             sustain,
@@ -67,8 +84,8 @@ impl<'a> Engine for HihatEngine<'a> {
             parameters.timbre,
             parameters.morph,
             parameters.harmonics,
-            self.temp_buffer_1,
-            self.temp_buffer_2,
+            &mut self.temp_buffer_1[..out.len()],
+            &mut self.temp_buffer_2[..out.len()],
             out,
             NoiseType::Square,
             VcaType::Swing,
@@ -84,13 +101,16 @@ impl<'a> Engine for HihatEngine<'a> {
             parameters.timbre,
             parameters.morph,
             parameters.harmonics,
-            self.temp_buffer_1,
-            self.temp_buffer_2,
+            &mut self.temp_buffer_1[..aux.len()],
+            &mut self.temp_buffer_2[..aux.len()],
             aux,
             NoiseType::RingMod,
             VcaType::Linear,
             false,
             true,
         );
+
+        self.sustain_switch.apply(out);
+        self.sustain_switch.apply(aux);
     }
 }
@@ -19,7 +19,12 @@ pub mod virtual_analog_engine;
 pub mod waveshaping_engine;
 pub mod wavetable_engine;
 
-use crate::dsp::A0;
+#[allow(unused_imports)]
+use num_traits::float::Float;
+
+use crate::dsp::oscillator::oscillator::MAX_FREQUENCY;
+use crate::dsp::{A0, SAMPLE_RATE};
+use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 use crate::stmlib::dsp::units::semitones_to_ratio;
 
 pub trait Engine {
@@ -27,6 +32,10 @@ pub trait Engine {
 
     fn reset(&mut self) {}
 
+    /// Prepare for rendering with `parameters` right after the engine has been
+    /// selected, so that the first block is not silent. Does nothing by default.
+    fn prime(&mut self, _parameters: &EngineParameters) {}
+
     fn render(
         &mut self,
         parameters: &EngineParameters,
@@ -76,6 +85,132 @@ impl Default for TriggerState {
     }
 }
 
+/// Signal rendered to *AUX* by engines offering a sub-oscillator.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AuxMode {
+    /// The engine's own *AUX* signal.
+    #[default]
+    Default,
+
+    /// Square wave one octave below the *OUT* fundamental.
+    SubOctave,
+
+    /// Square wave two octaves below the *OUT* fundamental.
+    SubTwoOctaves,
+}
+
+impl AuxMode {
+    /// Returns the frequency ratio of the sub-oscillator to the *OUT* fundamental,
+    /// `None` for [`AuxMode::Default`].
+    #[inline]
+    pub fn sub_ratio(&self) -> Option<f32> {
+        match self {
+            Self::Default => None,
+            Self::SubOctave => Some(0.5),
+            Self::SubTwoOctaves => Some(0.25),
+        }
+    }
+}
+
+/// Level of the sub-oscillator selected with [`AuxMode`].
+const SUB_OSCILLATOR_GAIN: f32 = 0.5;
+
+/// Length of the fades of [`SustainSwitch`], 5 ms.
+const SUSTAIN_FADE_LENGTH: f32 = SAMPLE_RATE * 0.005;
+
+/// Sustain mode switch of the drum engines.
+///
+/// The drum models sustain when the trigger is unpatched, or when sustain is forced on
+/// with `set_forced`. When the mode changes, the previous mode fades out before the new
+/// mode fades in, so that toggling does not click.
+#[derive(Debug)]
+pub struct SustainSwitch {
+    forced: bool,
+    sustain: Option<bool>,
+    pending_trigger: bool,
+    previous_gain: f32,
+    gain: f32,
+}
+
+impl Default for SustainSwitch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SustainSwitch {
+    pub fn new() -> Self {
+        Self {
+            forced: false,
+            sustain: None,
+            pending_trigger: false,
+            previous_gain: 1.0,
+            gain: 1.0,
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.sustain = None;
+        self.pending_trigger = false;
+        self.previous_gain = 1.0;
+        self.gain = 1.0;
+    }
+
+    /// Force sustain mode, also with a patched trigger. Default is `false`.
+    #[inline]
+    pub fn set_forced(&mut self, forced: bool) {
+        self.forced = forced;
+    }
+
+    #[inline]
+    pub fn forced(&self) -> bool {
+        self.forced
+    }
+
+    /// Returns the sustain and trigger flags to render the next block of `size` samples
+    /// with.
+    ///
+    /// A rising edge arriving while the previous mode fades out is delayed until the new
+    /// mode is rendered.
+    #[inline]
+    pub fn process(&mut self, trigger: &TriggerState, size: usize) -> (bool, bool) {
+        let sustain = self.forced || matches!(trigger, TriggerState::Unpatched);
+        let mut trigger = matches!(trigger, TriggerState::RisingEdge);
+        let rendered_sustain = *self.sustain.get_or_insert(sustain);
+        let step = size as f32 / SUSTAIN_FADE_LENGTH;
+
+        self.previous_gain = self.gain;
+
+        if sustain != rendered_sustain && self.gain > 0.0 {
+            self.gain = (self.gain - step).max(0.0);
+            self.pending_trigger |= trigger;
+            return (rendered_sustain, false);
+        }
+
+        self.sustain = Some(sustain);
+        self.gain = (self.gain + step).min(1.0);
+        trigger |= self.pending_trigger;
+        self.pending_trigger = false;
+
+        (sustain, trigger)
+    }
+
+    /// Applies the fade of the last processed block to `buffer`.
+    #[inline]
+    pub fn apply(&self, buffer: &mut [f32]) {
+        if self.previous_gain == 1.0 && self.gain == 1.0 {
+            return;
+        }
+
+        let mut previous_gain = self.previous_gain;
+        let mut gain = ParameterInterpolator::new(&mut previous_gain, self.gain, buffer.len());
+
+        for sample in buffer.iter_mut() {
+            *sample *= gain.next();
+        }
+    }
+}
+
 #[inline]
 pub fn note_to_frequency(mut midi_note: f32) -> f32 {
     midi_note -= 9.0;
@@ -83,3 +218,22 @@ pub fn note_to_frequency(mut midi_note: f32) -> f32 {
 
     A0 * 0.25 * semitones_to_ratio(midi_note)
 }
+
+/// Clamps a normalized frequency derived from [`note_to_frequency`] to the range from
+/// `min_frequency` to [`MAX_FREQUENCY`].
+///
+/// Engines scaling gains or frequencies by the note frequency use this to keep their
+/// output stable over the full note range from `-119.0` to `120.0`.
+#[inline]
+pub fn clamp_frequency(frequency: f32, min_frequency: f32) -> f32 {
+    frequency.clamp(min_frequency, MAX_FREQUENCY)
+}
+
+/// Inverse of [`note_to_frequency`], returns the MIDI note of a normalized frequency.
+///
+/// Round-trips within the note range of [`note_to_frequency`], which resolves
+/// 1/256th of a semitone.
+#[inline]
+pub fn frequency_to_note(frequency: f32) -> f32 {
+    12.0 * Float::log2(frequency / (A0 * 0.25)) + 9.0
+}
@@ -17,13 +17,17 @@ use core::alloc::GlobalAlloc;
 
 use super::{note_to_frequency, Engine, EngineParameters, TriggerState};
 use crate::dsp::allocate_buffer;
+use crate::dsp::physical_modelling::exciter::ExciterType;
 use crate::dsp::physical_modelling::modal_voice::ModalVoice;
+use crate::dsp::physical_modelling::sympathetic_strings::SympatheticStrings;
 use crate::stmlib::dsp::one_pole;
 
 #[derive(Debug)]
 pub struct ModalEngine<'a> {
     voice: ModalVoice,
     harmonics_lp: f32,
+    structure: Option<f32>,
+    sympathetic_strings: SympatheticStrings,
 
     temp_buffer_1: &'a mut [f32],
     temp_buffer_2: &'a mut [f32],
@@ -34,10 +38,76 @@ impl<'a> ModalEngine<'a> {
         Self {
             voice: ModalVoice::default(),
             harmonics_lp: 0.0,
+            structure: None,
+            sympathetic_strings: SympatheticStrings::new(),
             temp_buffer_1: allocate_buffer(buffer_allocator, block_size).unwrap(),
             temp_buffer_2: allocate_buffer(buffer_allocator, block_size).unwrap(),
         }
     }
+
+    /// Set the number of resonator modes from 1 to `MAX_NUM_MODES` (24). Fewer modes
+    /// reduce the CPU load. Default is `MAX_NUM_MODES`.
+    #[inline]
+    pub fn set_resolution(&mut self, resolution: usize) {
+        self.voice.set_resolution(resolution);
+    }
+
+    #[inline]
+    pub fn resolution(&self) -> usize {
+        self.voice.resolution()
+    }
+
+    /// Set the excitation position from `0.0` to `1.0`, changing the relative amplitudes
+    /// of the modes. Default is `0.015`.
+    #[inline]
+    pub fn set_position(&mut self, position: f32) {
+        self.voice.set_position(position);
+    }
+
+    #[inline]
+    pub fn position(&self) -> f32 {
+        self.voice.position()
+    }
+
+    /// Override the structure (inharmonicity) set by *HARMONICS* with a value from
+    /// `0.0` to `1.0`. `None` (default) follows *HARMONICS*.
+    #[inline]
+    pub fn set_structure(&mut self, structure: Option<f32>) {
+        self.structure = structure.map(|structure| structure.clamp(0.0, 1.0));
+    }
+
+    #[inline]
+    pub fn structure(&self) -> Option<f32> {
+        self.structure
+    }
+
+    /// Select the excitation signal. With `ExciterType::Bow` and `ExciterType::Blow`,
+    /// the resonator is excited for as long as the trigger is held high.
+    #[inline]
+    pub fn set_exciter(&mut self, exciter: ExciterType) {
+        self.voice.set_exciter(exciter);
+    }
+
+    #[inline]
+    pub fn exciter(&self) -> ExciterType {
+        self.voice.exciter()
+    }
+
+    /// Adds sympathetic strings tuned to MIDI notes, e.g. the notes of a scale, which
+    /// ring with the *OUT* signal and are mixed into it. Up to
+    /// [`MAX_SYMPATHETIC_STRINGS`] notes are used. `None` (default) disables them.
+    ///
+    /// [`MAX_SYMPATHETIC_STRINGS`]: crate::dsp::physical_modelling::sympathetic_strings::MAX_SYMPATHETIC_STRINGS
+    #[inline]
+    pub fn set_sympathetic(&mut self, notes: Option<&[f32]>) {
+        self.sympathetic_strings.set_notes(notes.unwrap_or(&[]));
+    }
+
+    #[inline]
+    pub fn sympathetic(&self) -> Option<&[f32]> {
+        let notes = self.sympathetic_strings.notes();
+        (!notes.is_empty()).then_some(notes)
+    }
 }
 
 impl<'a> Engine for ModalEngine<'a> {
@@ -48,6 +118,7 @@ impl<'a> Engine for ModalEngine<'a> {
 
     fn reset(&mut self) {
         self.voice.init();
+        self.sympathetic_strings.init();
     }
 
     #[inline]
@@ -61,10 +132,17 @@ impl<'a> Engine for ModalEngine<'a> {
         out.fill(0.0);
         aux.fill(0.0);
 
-        one_pole(&mut self.harmonics_lp, parameters.harmonics, 0.01);
+        let structure = self.structure.unwrap_or(parameters.harmonics);
+        one_pole(&mut self.harmonics_lp, structure, 0.01);
 
         let sustain = matches!(parameters.trigger, TriggerState::Unpatched);
         let trigger = matches!(parameters.trigger, TriggerState::RisingEdge);
+        let gate = matches!(
+            parameters.trigger,
+            TriggerState::RisingEdge | TriggerState::High
+        );
+
+        self.voice.set_gate(gate);
 
         self.voice.render(
             sustain,
@@ -74,10 +152,16 @@ impl<'a> Engine for ModalEngine<'a> {
             self.harmonics_lp,
             parameters.timbre,
             parameters.morph,
-            self.temp_buffer_1,
-            self.temp_buffer_2,
+            &mut self.temp_buffer_1[..out.len()],
+            &mut self.temp_buffer_2[..out.len()],
             out,
             aux,
         );
+
+        if self.sympathetic().is_some() {
+            let in_ = &mut self.temp_buffer_1[..out.len()];
+            in_.copy_from_slice(out);
+            self.sympathetic_strings.process(in_, out);
+        }
     }
 }
@@ -9,6 +9,10 @@
 //!
 //! *AUX* signal: variant employing two band-pass filters, with their separation
 //! controlled by *HARMONICS*.
+//!
+//! The noise source can be colored with [`NoiseEngine::set_noise_color`] and *AUX* turned
+//! into a pitched noise band with [`NoiseEngine::set_aux_resonance`]. The noise can be
+//! made reproducible with [`NoiseEngine::set_seed`] or [`crate::set_deterministic`].
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
@@ -17,6 +21,7 @@ use core::alloc::GlobalAlloc;
 use super::{note_to_frequency, Engine, EngineParameters, TriggerState};
 use crate::dsp::allocate_buffer;
 use crate::dsp::noise::clocked_noise::ClockedNoise;
+use crate::dsp::noise::noise_color::{NoiseColor, NoiseColorFilter};
 use crate::stmlib::dsp::filter::{FilterMode, FrequencyApproximation, Svf};
 use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 use crate::stmlib::dsp::sqrt;
@@ -25,6 +30,7 @@ use crate::stmlib::dsp::units::semitones_to_ratio;
 #[derive(Debug)]
 pub struct NoiseEngine<'a> {
     clocked_noise: [ClockedNoise; 2],
+    noise_color_filter: [NoiseColorFilter; 2],
     lp_hp_filter: Svf,
     bp_filter: [Svf; 2],
 
@@ -33,6 +39,9 @@ pub struct NoiseEngine<'a> {
     previous_q: f32,
     previous_mode: f32,
 
+    aux_resonance: f32,
+    seed: Option<u32>,
+
     temp_buffer: &'a mut [f32],
 }
 
@@ -40,21 +49,76 @@ impl<'a> NoiseEngine<'a> {
     pub fn new<T: GlobalAlloc>(buffer_allocator: &T, block_size: usize) -> Self {
         Self {
             clocked_noise: [ClockedNoise::default(), ClockedNoise::default()],
+            noise_color_filter: [NoiseColorFilter::new(), NoiseColorFilter::new()],
             lp_hp_filter: Svf::default(),
             bp_filter: [Svf::default(), Svf::default()],
             previous_f0: 0.0,
             previous_f1: 0.0,
             previous_q: 0.0,
             previous_mode: 0.0,
+            aux_resonance: 0.0,
+            seed: None,
             temp_buffer: allocate_buffer(buffer_allocator, block_size).unwrap(),
         }
     }
+
+    /// Sets the color of the clocked noise before filtering. Default is
+    /// [`NoiseColor::White`].
+    #[inline]
+    pub fn set_noise_color(&mut self, color: NoiseColor) {
+        self.noise_color_filter[0].set_color(color);
+        self.noise_color_filter[1].set_color(color);
+    }
+
+    /// Returns the color of the clocked noise.
+    #[inline]
+    pub fn noise_color(&self) -> NoiseColor {
+        self.noise_color_filter[0].color()
+    }
+
+    /// Tunes both *AUX* band-pass filters to the note and raises their resonance, turning
+    /// *AUX* into a pitched noise band (0.0 to 1.0). At `1.0`, the resonance is 6 octaves
+    /// above the one set by *MORPH*. Default is `0.0`, the plain dual band-pass variant.
+    #[inline]
+    pub fn set_aux_resonance(&mut self, resonance: f32) {
+        self.aux_resonance = resonance.clamp(0.0, 1.0);
+    }
+
+    #[inline]
+    pub fn aux_resonance(&self) -> f32 {
+        self.aux_resonance
+    }
+
+    /// Draws the noise from generators seeded with `seed`, so that renders after setting
+    /// the same seed or after `init` are identical. `None` (default) uses the seed set with
+    /// [`crate::set_deterministic`], or the shared random generator if there is none.
+    #[inline]
+    pub fn set_seed(&mut self, seed: Option<u32>) {
+        self.seed = seed;
+        self.init_random();
+    }
+
+    /// Returns the seed, if set.
+    #[inline]
+    pub fn seed(&self) -> Option<u32> {
+        self.seed
+    }
+
+    #[inline]
+    fn init_random(&mut self) {
+        for (stream, clocked_noise) in self.clocked_noise.iter_mut().enumerate() {
+            clocked_noise.init_random(self.seed, stream as u32);
+        }
+    }
 }
 
 impl<'a> Engine for NoiseEngine<'a> {
     fn init(&mut self) {
         self.clocked_noise[0].init();
         self.clocked_noise[1].init();
+        self.init_random();
+        self.noise_color_filter[0].init();
+        self.noise_color_filter[1].init();
         self.lp_hp_filter.init();
         self.bp_filter[0].init();
         self.bp_filter[1].init();
@@ -84,7 +148,10 @@ impl<'a> Engine for NoiseEngine<'a> {
         let q = 0.5 * semitones_to_ratio(parameters.morph * 120.0);
         let sync = trigger;
         self.clocked_noise[0].render(sync, clock_f, aux);
-        self.clocked_noise[1].render(sync, clock_f * f1 / f0, self.temp_buffer);
+        let temp_buffer = &mut self.temp_buffer[..out.len()];
+        self.clocked_noise[1].render(sync, clock_f * f1 / f0, temp_buffer);
+        self.noise_color_filter[0].process(aux);
+        self.noise_color_filter[1].process(temp_buffer);
 
         let mut f0_modulation = ParameterInterpolator::new(&mut self.previous_f0, f0, out.len());
         let mut f1_modulation = ParameterInterpolator::new(&mut self.previous_f1, f1, out.len());
@@ -92,8 +159,11 @@ impl<'a> Engine for NoiseEngine<'a> {
         let mut mode_modulation =
             ParameterInterpolator::new(&mut self.previous_mode, parameters.harmonics, out.len());
 
+        let aux_resonance = self.aux_resonance;
+        let aux_q_ratio = semitones_to_ratio(aux_resonance * 72.0);
+
         let in_1 = aux;
-        let in_2 = &self.temp_buffer;
+        let in_2 = &self.temp_buffer[..out.len()];
 
         for (out_sample, (in_1_sample, in_2_sample)) in
             out.iter_mut().zip(in_1.iter_mut().zip(in_2.iter()))
@@ -104,8 +174,6 @@ impl<'a> Engine for NoiseEngine<'a> {
             let gain = 1.0 / sqrt((0.5 + q) * 40.0 * f0);
             self.lp_hp_filter
                 .set_f_q(f0, q, FrequencyApproximation::Accurate);
-            self.bp_filter[0].set_f_q(f0, q, FrequencyApproximation::Accurate);
-            self.bp_filter[1].set_f_q(f1, q, FrequencyApproximation::Accurate);
 
             let input_1 = *in_1_sample * gain;
             let input_2 = *in_2_sample * gain;
@@ -114,8 +182,23 @@ impl<'a> Engine for NoiseEngine<'a> {
                 core::slice::from_mut(out_sample),
                 mode_modulation.next(),
             );
-            *in_1_sample = self.bp_filter[0].process(input_1, FilterMode::BandPass)
-                + self.bp_filter[1].process(input_2, FilterMode::BandPass);
+
+            let (aux_f1, aux_q, aux_gain) = if aux_resonance == 0.0 {
+                (f1, q, 1.0)
+            } else {
+                // Keeps the level of the narrower band close to the one of the original.
+                let aux_q = q * aux_q_ratio;
+                (
+                    f1 + (f0 - f1) * aux_resonance,
+                    aux_q,
+                    sqrt((0.5 + q) / (0.5 + aux_q)),
+                )
+            };
+            self.bp_filter[0].set_f_q(f0, aux_q, FrequencyApproximation::Accurate);
+            self.bp_filter[1].set_f_q(aux_f1, aux_q, FrequencyApproximation::Accurate);
+            *in_1_sample = (self.bp_filter[0].process(input_1, FilterMode::BandPass)
+                + self.bp_filter[1].process(input_2, FilterMode::BandPass))
+                * aux_gain;
         }
     }
 }
@@ -9,6 +9,9 @@
 //!   then increasingly resonant band-pass filters.
 //!
 //! *AUX* signal: raw dust noise.
+//!
+//! Density and filter resonance can also be set directly, and the particles can be
+//! made reproducible with [`ParticleEngine::set_seed`] or [`crate::set_deterministic`].
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
@@ -18,11 +21,12 @@ use core::alloc::GlobalAlloc;
 use num_traits::float::Float;
 
 use super::{note_to_frequency, Engine, EngineParameters, TriggerState};
-use crate::dsp::allocate_buffer;
 use crate::dsp::fx::diffuser::Diffuser;
 use crate::dsp::noise::particle::Particle;
+use crate::dsp::{allocate_buffer, SAMPLE_RATE};
 use crate::stmlib::dsp::filter::{FilterMode, FrequencyApproximation, Svf};
 use crate::stmlib::dsp::units::semitones_to_ratio;
+use crate::stmlib::utils::random::{self, RandomGenerator};
 
 const NUM_PARTICLES: usize = 6;
 
@@ -31,6 +35,12 @@ pub struct ParticleEngine<'a> {
     particle: [Particle; NUM_PARTICLES],
     diffuser: Diffuser,
     post_filter: Svf,
+
+    density: Option<f32>,
+    filter_q: Option<f32>,
+    seed: Option<u32>,
+    random: RandomGenerator,
+
     temp_buffer: &'a mut [f32],
 }
 
@@ -40,9 +50,62 @@ impl<'a> ParticleEngine<'a> {
             particle: core::array::from_fn(|_| Particle::new()),
             diffuser: Diffuser::new(),
             post_filter: Svf::new(),
+            density: None,
+            filter_q: None,
+            seed: None,
+            random: RandomGenerator::new(0),
             temp_buffer: allocate_buffer(buffer_allocator, block_size).unwrap(),
         }
     }
+
+    /// Overrides the particle density set by *TIMBRE* with an average rate in particles
+    /// per second. `None` (default) uses *TIMBRE*.
+    #[inline]
+    pub fn set_density(&mut self, density: Option<f32>) {
+        self.density = density.map(|density| density.max(0.0));
+    }
+
+    /// Returns the particle density override in particles per second, if set.
+    #[inline]
+    pub fn density(&self) -> Option<f32> {
+        self.density
+    }
+
+    /// Overrides the resonance of the band-pass filters set by *MORPH* (0.5 and above).
+    /// `None` (default) uses *MORPH*.
+    #[inline]
+    pub fn set_filter_q(&mut self, q: Option<f32>) {
+        self.filter_q = q.map(|q| q.max(0.5));
+    }
+
+    /// Returns the resonance override, if set.
+    #[inline]
+    pub fn filter_q(&self) -> Option<f32> {
+        self.filter_q
+    }
+
+    /// Draws the timing and frequencies of the particles from a generator seeded with
+    /// `seed`, so that renders after setting the same seed or after `init` are identical.
+    /// `None` (default) uses the seed set with [`crate::set_deterministic`], or the shared
+    /// random generator if there is none.
+    #[inline]
+    pub fn set_seed(&mut self, seed: Option<u32>) {
+        self.seed = seed;
+        if let Some(seed) = seed {
+            self.random.seed(seed);
+        }
+    }
+
+    /// Returns the seed, if set.
+    #[inline]
+    pub fn seed(&self) -> Option<u32> {
+        self.seed
+    }
+
+    #[inline]
+    fn effective_seed(&self) -> Option<u32> {
+        self.seed.or_else(random::deterministic_seed)
+    }
 }
 
 impl<'a> Engine for ParticleEngine<'a> {
@@ -52,6 +115,9 @@ impl<'a> Engine for ParticleEngine<'a> {
         }
         self.diffuser.init();
         self.post_filter.init();
+        if let Some(seed) = self.effective_seed() {
+            self.random.seed(seed);
+        }
         self.reset();
     }
 
@@ -68,15 +134,23 @@ impl<'a> Engine for ParticleEngine<'a> {
         _already_enveloped: &mut bool,
     ) {
         let f0 = note_to_frequency(parameters.note);
-        let density_sqrt = note_to_frequency(60.0 + parameters.timbre * parameters.timbre * 72.0);
-        let density = density_sqrt * density_sqrt * (1.0 / NUM_PARTICLES as f32);
+        let density = match self.density {
+            Some(rate) => f32::min(rate / SAMPLE_RATE, 1.0),
+            None => {
+                let density_sqrt =
+                    note_to_frequency(60.0 + parameters.timbre * parameters.timbre * 72.0);
+                density_sqrt * density_sqrt
+            }
+        } * (1.0 / NUM_PARTICLES as f32);
         let gain = 1.0 / density;
-        let q_sqrt = semitones_to_ratio(if parameters.morph >= 0.5 {
-            (parameters.morph - 0.5) * 120.0
-        } else {
-            0.0
+        let q = self.filter_q.unwrap_or_else(|| {
+            let q_sqrt = semitones_to_ratio(if parameters.morph >= 0.5 {
+                (parameters.morph - 0.5) * 120.0
+            } else {
+                0.0
+            });
+            0.5 + q_sqrt * q_sqrt
         });
-        let q = 0.5 + q_sqrt * q_sqrt;
         let spread = 48.0 * parameters.harmonics * parameters.harmonics;
         let raw_diffusion_sqrt = 2.0 * (parameters.morph - 0.5).abs();
         let raw_diffusion = raw_diffusion_sqrt * raw_diffusion_sqrt;
@@ -90,16 +164,34 @@ impl<'a> Engine for ParticleEngine<'a> {
         out.fill(0.0);
         aux.fill(0.0);
 
-        for particle in &mut self.particle {
-            particle.render(sync, density, gain, f0, spread, q, out, aux);
+        if self.effective_seed().is_some() {
+            let random = &mut self.random;
+            for particle in &mut self.particle {
+                particle.render_with_random(
+                    sync,
+                    density,
+                    gain,
+                    f0,
+                    spread,
+                    q,
+                    out,
+                    aux,
+                    &mut || random.get_float(),
+                );
+            }
+        } else {
+            for particle in &mut self.particle {
+                particle.render(sync, density, gain, f0, spread, q, out, aux);
+            }
         }
 
         self.post_filter
             .set_f_q(f32::min(f0, 0.49), 0.5, FrequencyApproximation::Dirty);
+        let temp_buffer = &mut self.temp_buffer[..out.len()];
         self.post_filter
-            .process_buffer(out, self.temp_buffer, FilterMode::LowPass);
+            .process_buffer(out, temp_buffer, FilterMode::LowPass);
 
-        out.copy_from_slice(self.temp_buffer);
+        out.copy_from_slice(temp_buffer);
 
         self.diffuser
             .process(0.8 * diffusion * diffusion, 0.5 * diffusion + 0.25, out);
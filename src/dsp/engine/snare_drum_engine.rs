@@ -12,26 +12,58 @@
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
-use super::{note_to_frequency, Engine, EngineParameters, TriggerState};
+use super::{note_to_frequency, Engine, EngineParameters, SustainSwitch};
 use crate::dsp::drums::analog_snare_drum::AnalogSnareDrum;
 use crate::dsp::drums::synthetic_snare_drum::SyntheticSnareDrum;
+use crate::stmlib::dsp::crossfade;
+use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 
 #[derive(Debug, Default)]
 pub struct SnareDrumEngine {
     analog_snare_drum: AnalogSnareDrum,
     synthetic_snare_drum: SyntheticSnareDrum,
+    sustain_switch: SustainSwitch,
+    model_blend: f32,
+    previous_model_blend: f32,
 }
 
 impl SnareDrumEngine {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Sustain also with a patched trigger, for drones. Toggling
+    /// the sustain mode fades between both modes. Default is `false`, which only
+    /// sustains with an unpatched trigger.
+    #[inline]
+    pub fn set_sustain(&mut self, sustain: bool) {
+        self.sustain_switch.set_forced(sustain);
+    }
+
+    #[inline]
+    pub fn sustain(&self) -> bool {
+        self.sustain_switch.forced()
+    }
+
+    /// Crossfades *OUT* from the analog model (`0.0`) to the synthetic model (`1.0`).
+    /// *AUX* always renders the synthetic model. Default is `0.0`.
+    #[inline]
+    pub fn set_model_blend(&mut self, blend: f32) {
+        self.model_blend = blend.clamp(0.0, 1.0);
+    }
+
+    #[inline]
+    pub fn model_blend(&self) -> f32 {
+        self.model_blend
+    }
 }
 
 impl Engine for SnareDrumEngine {
     fn init(&mut self) {
         self.analog_snare_drum.init();
         self.synthetic_snare_drum.init();
+        self.sustain_switch.init();
+        self.previous_model_blend = self.model_blend;
     }
 
     #[inline]
@@ -44,8 +76,7 @@ impl Engine for SnareDrumEngine {
     ) {
         let f0 = note_to_frequency(parameters.note);
 
-        let sustain = matches!(parameters.trigger, TriggerState::Unpatched);
-        let trigger = matches!(parameters.trigger, TriggerState::RisingEdge);
+        let (sustain, trigger) = self.sustain_switch.process(&parameters.trigger, out.len());
 
         self.analog_snare_drum.render(
             sustain,
@@ -68,5 +99,19 @@ impl Engine for SnareDrumEngine {
             parameters.harmonics,
             aux,
         );
+
+        if self.model_blend > 0.0 || self.previous_model_blend > 0.0 {
+            let mut blend_modulation = ParameterInterpolator::new(
+                &mut self.previous_model_blend,
+                self.model_blend,
+                out.len(),
+            );
+            for (out_sample, aux_sample) in out.iter_mut().zip(aux.iter()) {
+                *out_sample = crossfade(*out_sample, *aux_sample, blend_modulation.next());
+            }
+        }
+
+        self.sustain_switch.apply(out);
+        self.sustain_switch.apply(aux);
     }
 }
@@ -41,6 +41,12 @@ pub struct SpeechEngine<'a> {
     temp_buffer_2: &'a mut [f32],
     prosody_amount: f32,
     speed: f32,
+    formant_shift: f32,
+
+    pending_word: Option<(usize, usize)>,
+    word_bank: i32,
+    word_velocity: f32,
+    word_playback: bool,
 }
 
 impl<'a> SpeechEngine<'a> {
@@ -54,6 +60,11 @@ impl<'a> SpeechEngine<'a> {
             temp_buffer_2: allocate_buffer(buffer_allocator, block_size).unwrap(),
             prosody_amount: 0.0,
             speed: 1.0,
+            formant_shift: 0.0,
+            pending_word: None,
+            word_bank: 0,
+            word_velocity: 1.0,
+            word_playback: false,
         }
     }
 }
@@ -72,6 +83,8 @@ impl<'a> Engine for SpeechEngine<'a> {
 
     fn reset(&mut self) {
         self.lpc_speech_synth_controller.reset();
+        self.pending_word = None;
+        self.word_playback = false;
     }
 
     #[inline]
@@ -85,10 +98,43 @@ impl<'a> Engine for SpeechEngine<'a> {
         let f0 = note_to_frequency(parameters.note);
 
         let group = parameters.harmonics * 6.0;
+        let formant_shift = (parameters.timbre + self.formant_shift).clamp(0.0, 1.0);
 
         let sustain = matches!(parameters.trigger, TriggerState::Unpatched);
         let trigger = matches!(parameters.trigger, TriggerState::RisingEdge);
 
+        if let Some((bank, word)) = self.pending_word.take() {
+            self.lpc_speech_synth_controller.trigger_word(bank, word);
+            self.word_bank = bank as i32;
+            self.word_playback = true;
+        }
+
+        // A word started with `trigger_word` plays until it has finished or a new
+        // trigger is received.
+        if self.word_playback && !trigger {
+            *already_enveloped = true;
+
+            self.lpc_speech_synth_controller.render(
+                false,
+                false,
+                self.word_bank,
+                f0,
+                self.prosody_amount,
+                self.speed,
+                0.0,
+                formant_shift,
+                self.word_velocity,
+                aux,
+                out,
+            );
+
+            self.word_playback = self.lpc_speech_synth_controller.playing_word();
+
+            return;
+        }
+
+        self.word_playback = false;
+
         // Interpolates between the 3 models: naive, SAM, LPC.
         if group <= 2.0 {
             *already_enveloped = false;
@@ -100,8 +146,8 @@ impl<'a> Engine for SpeechEngine<'a> {
                     trigger,
                     f0,
                     parameters.morph,
-                    parameters.timbre,
-                    self.temp_buffer_1,
+                    formant_shift,
+                    &mut self.temp_buffer_1[..out.len()],
                     aux,
                     out,
                 );
@@ -114,7 +160,7 @@ impl<'a> Engine for SpeechEngine<'a> {
                     0.0,
                     0.0,
                     parameters.morph,
-                    parameters.timbre,
+                    formant_shift,
                     1.0,
                     aux,
                     out,
@@ -126,9 +172,9 @@ impl<'a> Engine for SpeechEngine<'a> {
                 sustain,
                 f0,
                 parameters.morph,
-                parameters.timbre,
-                self.temp_buffer_1,
-                self.temp_buffer_2,
+                formant_shift,
+                &mut self.temp_buffer_1[..out.len()],
+                &mut self.temp_buffer_2[..out.len()],
             );
 
             blend = blend * blend * (3.0 - 2.0 * blend);
@@ -154,7 +200,7 @@ impl<'a> Engine for SpeechEngine<'a> {
                 self.prosody_amount,
                 self.speed,
                 parameters.morph,
-                parameters.timbre,
+                formant_shift,
                 if replay_prosody {
                     parameters.accent
                 } else {
@@ -175,4 +221,37 @@ impl<'a> SpeechEngine<'a> {
     pub fn set_speed(&mut self, speed: f32) {
         self.speed = speed;
     }
+
+    /// Shift the formants of all speech models independently of the pitch, in the
+    /// range from `-1.0` to `1.0`. The shift is added to *TIMBRE* and moves the
+    /// formants of each model in the same direction as *TIMBRE* does. Default is `0.0`.
+    pub fn set_formant_shift(&mut self, formant_shift: f32) {
+        self.formant_shift = formant_shift.clamp(-1.0, 1.0);
+    }
+
+    /// Set the level of the consonants relative to the vowels of the SAM and naive
+    /// speech models, in the range from `0.0` to `2.0`. Default is `1.0`.
+    pub fn set_consonant_level(&mut self, level: f32) {
+        self.naive_speech_synth.set_consonant_level(level);
+        self.sam_speech_synth.set_consonant_level(level);
+    }
+
+    /// Set the duration of the consonants of the SAM and naive speech models in
+    /// milliseconds. Default is `50.0`.
+    pub fn set_consonant_duration_ms(&mut self, duration: f32) {
+        self.naive_speech_synth.set_consonant_duration_ms(duration);
+        self.sam_speech_synth.set_consonant_duration_ms(duration);
+    }
+
+    /// Play a word from one of the LPC word banks on the next render, independent
+    /// of the word selection by *MORPH*. The playback is deterministic and lasts until
+    /// the word has finished or a new trigger is received.
+    ///
+    /// - `bank`: word bank in the range from `0` to `NUM_WORD_BANKS - 1`.
+    /// - `index`: word index within the bank, clamped to the number of words.
+    /// - `velocity`: playback level in the range from `0.0` to `1.0`.
+    pub fn trigger_word(&mut self, bank: usize, index: usize, velocity: f32) {
+        self.pending_word = Some((bank.min(NUM_WORD_BANKS - 1), index));
+        self.word_velocity = velocity.clamp(0.0, 1.0);
+    }
 }
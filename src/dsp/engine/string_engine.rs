@@ -18,7 +18,9 @@ use core::alloc::GlobalAlloc;
 use super::{note_to_frequency, Engine, EngineParameters, TriggerState};
 use crate::dsp::allocate_buffer;
 use crate::dsp::physical_modelling::delay_line::DelayLine;
+use crate::dsp::physical_modelling::exciter::ExciterType;
 use crate::dsp::physical_modelling::string_voice::StringVoice;
+use crate::dsp::physical_modelling::sympathetic_strings::SympatheticStrings;
 
 const NUM_STRINGS: usize = 3;
 
@@ -29,6 +31,7 @@ pub struct StringEngine<'a> {
     f0: [f32; NUM_STRINGS],
     f0_delay: DelayLine<'a, f32, 16>,
     active_string: usize,
+    sympathetic_strings: SympatheticStrings,
     temp_buffer_1: &'a mut [f32],
     temp_buffer_2: &'a mut [f32],
 }
@@ -45,16 +48,48 @@ impl<'a> StringEngine<'a> {
                     .unwrap(),
             ),
             active_string: 0,
+            sympathetic_strings: SympatheticStrings::new(),
             temp_buffer_1: allocate_buffer(buffer_allocator, block_size).unwrap(),
             temp_buffer_2: allocate_buffer(buffer_allocator, block_size).unwrap(),
         }
     }
+
+    /// Select the excitation signal of all strings. With `ExciterType::Bow` and
+    /// `ExciterType::Blow`, the active string is excited for as long as the trigger
+    /// is held high.
+    pub fn set_exciter(&mut self, exciter: ExciterType) {
+        for voice in &mut self.voice {
+            voice.set_exciter(exciter);
+        }
+    }
+
+    #[inline]
+    pub fn exciter(&self) -> ExciterType {
+        self.voice[0].exciter()
+    }
+
+    /// Adds sympathetic strings tuned to MIDI notes, e.g. the notes of a scale, which
+    /// ring with the *OUT* signal and are mixed into it. Up to
+    /// [`MAX_SYMPATHETIC_STRINGS`] notes are used. `None` (default) disables them.
+    ///
+    /// [`MAX_SYMPATHETIC_STRINGS`]: crate::dsp::physical_modelling::sympathetic_strings::MAX_SYMPATHETIC_STRINGS
+    #[inline]
+    pub fn set_sympathetic(&mut self, notes: Option<&[f32]>) {
+        self.sympathetic_strings.set_notes(notes.unwrap_or(&[]));
+    }
+
+    #[inline]
+    pub fn sympathetic(&self) -> Option<&[f32]> {
+        let notes = self.sympathetic_strings.notes();
+        (!notes.is_empty()).then_some(notes)
+    }
 }
 
 impl<'a> Engine for StringEngine<'a> {
     fn init(&mut self) {
-        for voice in &mut self.voice {
+        for (stream, voice) in self.voice.iter_mut().enumerate() {
             voice.init();
+            voice.init_random(None, stream as u32);
         }
         self.f0 = [0.0; NUM_STRINGS];
         self.active_string = NUM_STRINGS - 1;
@@ -66,6 +101,7 @@ impl<'a> Engine for StringEngine<'a> {
         for voice in &mut self.voice {
             voice.reset();
         }
+        self.sympathetic_strings.init();
     }
 
     #[inline]
@@ -78,6 +114,10 @@ impl<'a> Engine for StringEngine<'a> {
     ) {
         let sustain = matches!(parameters.trigger, TriggerState::Unpatched);
         let trigger = matches!(parameters.trigger, TriggerState::RisingEdge);
+        let gate = matches!(
+            parameters.trigger,
+            TriggerState::RisingEdge | TriggerState::High
+        );
 
         if trigger {
             // 8 in original firmware version.
@@ -94,6 +134,7 @@ impl<'a> Engine for StringEngine<'a> {
         aux.fill(0.0);
 
         for i in 0..NUM_STRINGS {
+            self.voice[i].set_gate(gate && i == self.active_string);
             self.voice[i].render(
                 sustain && i == self.active_string,
                 trigger && i == self.active_string,
@@ -102,11 +143,17 @@ impl<'a> Engine for StringEngine<'a> {
                 parameters.harmonics,
                 parameters.timbre * parameters.timbre,
                 parameters.morph,
-                self.temp_buffer_1,
-                self.temp_buffer_2,
+                &mut self.temp_buffer_1[..out.len()],
+                &mut self.temp_buffer_2[..out.len()],
                 out,
                 aux,
             );
         }
+
+        if self.sympathetic().is_some() {
+            let in_ = &mut self.temp_buffer_1[..out.len()];
+            in_.copy_from_slice(out);
+            self.sympathetic_strings.process(in_, out);
+        }
     }
 }
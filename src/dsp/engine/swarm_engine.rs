@@ -9,6 +9,9 @@
 //!   each other: the result is a stack of eight randomly frequency-modulated waveforms.
 //!
 //! *AUX* signal: variant with sine wave oscillators.
+//!
+//! The grains can be made reproducible with [`SwarmEngine::set_seed`] or
+//! [`crate::set_deterministic`].
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
@@ -19,21 +22,40 @@ use crate::stmlib::dsp::one_pole;
 use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 use crate::stmlib::dsp::polyblep::{next_blep_sample, this_blep_sample};
 use crate::stmlib::dsp::units::semitones_to_ratio;
-use crate::stmlib::utils::random;
+use crate::stmlib::utils::random::RandomSource;
 
 const NUM_SWARM_VOICES: usize = 8;
 
 #[derive(Debug, Default)]
 pub struct SwarmEngine {
     swarm_voice: [SwarmVoice; NUM_SWARM_VOICES],
+    seed: Option<u32>,
 }
 
 impl SwarmEngine {
     pub fn new() -> Self {
         Self {
             swarm_voice: core::array::from_fn(|_| SwarmVoice::default()),
+            seed: None,
         }
     }
+
+    /// Draws the grains from generators seeded with `seed`, so that renders after setting
+    /// the same seed or after `init` are identical. `None` (default) uses the seed set with
+    /// [`crate::set_deterministic`], or the shared random generator if there is none.
+    #[inline]
+    pub fn set_seed(&mut self, seed: Option<u32>) {
+        self.seed = seed;
+        for swarm_voice in &mut self.swarm_voice {
+            swarm_voice.init_random(seed);
+        }
+    }
+
+    /// Returns the seed, if set.
+    #[inline]
+    pub fn seed(&self) -> Option<u32> {
+        self.seed
+    }
 }
 
 impl Engine for SwarmEngine {
@@ -47,6 +69,7 @@ impl Engine for SwarmEngine {
         for i in 0..NUM_SWARM_VOICES as i32 {
             let rank = i.wrapping_sub(n) as f32 / n as f32;
             self.swarm_voice[i as usize].init(rank);
+            self.swarm_voice[i as usize].init_random(self.seed);
         }
     }
 
@@ -106,10 +129,17 @@ impl SwarmVoice {
     pub fn init(&mut self, rank: f32) {
         self.rank = rank;
         self.envelope.init();
+        self.init_random(None);
         self.saw.init();
         self.sine.init();
     }
 
+    #[inline]
+    pub(crate) fn init_random(&mut self, seed: Option<u32>) {
+        // Each voice draws its own sequence.
+        self.envelope.random.init(seed, self.rank.to_bits());
+    }
+
     #[allow(clippy::too_many_arguments)]
     #[inline]
     pub fn render(
@@ -205,6 +235,8 @@ pub struct GrainEnvelope {
     amplitude: f32,
     previous_size_ratio: f32,
     filter_coefficient: f32,
+
+    random: RandomSource,
 }
 
 impl GrainEnvelope {
@@ -220,6 +252,7 @@ impl GrainEnvelope {
         self.amplitude = 0.5;
         self.previous_size_ratio = 0.0;
         self.filter_coefficient = 0.0;
+        self.random.init(None, 0);
     }
 
     #[inline]
@@ -240,12 +273,12 @@ impl GrainEnvelope {
 
         if randomize {
             self.from += self.interval;
-            self.interval = random::get_float() - self.from;
+            self.interval = self.random.get_float() - self.from;
             // Randomize the duration of the grain.
             if burst_mode {
-                self.fm *= 0.8 + 0.2 * random::get_float();
+                self.fm *= 0.8 + 0.2 * self.random.get_float();
             } else {
-                self.fm = 0.5 + 1.5 * random::get_float();
+                self.fm = 0.5 + 1.5 * self.random.get_float();
             }
         }
     }
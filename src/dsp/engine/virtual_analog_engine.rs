@@ -10,13 +10,18 @@
 //!
 //! *AUX* signal: sum of two hardsync’ed waveforms, the shape of which is controlled by *MORPH*
 //! and detuning by *HARMONICS*.
+//!
+//! The variable square of *OUT* can be hard-synced to an external frequency with
+//! [`VirtualAnalogEngine::set_sync`]. *AUX* can be replaced by a sub-oscillator with
+//! [`VirtualAnalogEngine::set_aux_mode`].
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
 use core::alloc::GlobalAlloc;
 
-use super::{note_to_frequency, Engine, EngineParameters};
+use super::{note_to_frequency, AuxMode, Engine, EngineParameters, SUB_OSCILLATOR_GAIN};
 use crate::dsp::allocate_buffer;
+use crate::dsp::oscillator::oscillator::{Oscillator, OscillatorShape};
 use crate::dsp::oscillator::variable_saw_oscillator::VariableSawOscillator;
 use crate::dsp::oscillator::variable_shape_oscillator::VariableShapeOscillator;
 use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
@@ -28,9 +33,12 @@ pub struct VirtualAnalogEngine<'a> {
 
     sync: VariableShapeOscillator,
     variable_saw: VariableSawOscillator,
+    sub_oscillator: Oscillator,
 
     auxiliary_amount: f32,
     xmod_amount: f32,
+    external_sync: Option<f32>,
+    aux_mode: AuxMode,
     temp_buffer: &'a mut [f32],
 }
 
@@ -41,11 +49,39 @@ impl<'a> VirtualAnalogEngine<'a> {
             auxiliary: VariableShapeOscillator::new(),
             sync: VariableShapeOscillator::new(),
             variable_saw: VariableSawOscillator::new(),
+            sub_oscillator: Oscillator::new(),
             auxiliary_amount: 0.0,
             xmod_amount: 0.0,
+            external_sync: None,
+            aux_mode: AuxMode::Default,
             temp_buffer: allocate_buffer(buffer_allocator, block_size).unwrap(),
         }
     }
+
+    /// Hard-syncs the variable square of *OUT* to an external master frequency,
+    /// normalized to the sample rate. `None` (default) keeps the oscillator free running.
+    #[inline]
+    pub fn set_sync(&mut self, master_frequency: Option<f32>) {
+        self.external_sync = master_frequency;
+    }
+
+    /// Returns the external master frequency, if set.
+    #[inline]
+    pub fn sync(&self) -> Option<f32> {
+        self.external_sync
+    }
+
+    /// Selects the signal rendered to *AUX*. Defaults to [`AuxMode::Default`].
+    #[inline]
+    pub fn set_aux_mode(&mut self, aux_mode: AuxMode) {
+        self.aux_mode = aux_mode;
+    }
+
+    /// Returns the signal rendered to *AUX*.
+    #[inline]
+    pub fn aux_mode(&self) -> AuxMode {
+        self.aux_mode
+    }
 }
 
 impl<'a> Engine for VirtualAnalogEngine<'a> {
@@ -55,6 +91,7 @@ impl<'a> Engine for VirtualAnalogEngine<'a> {
         self.auxiliary.set_master_phase(0.25);
         self.sync.init();
         self.variable_saw.init();
+        self.sub_oscillator.init();
         self.auxiliary_amount = 0.0;
         self.xmod_amount = 0.0;
     }
@@ -131,14 +168,15 @@ impl<'a> Engine for VirtualAnalogEngine<'a> {
         saw_gain = saw_gain.clamp(0.02, 1.0);
 
         let square_sync_f = note_to_frequency(parameters.note + square_sync_ratio);
+        let square_master_f = self.external_sync.unwrap_or(primary_f);
 
         self.sync.render(
-            primary_f,
+            square_master_f,
             square_sync_f,
             square_pw,
             1.0,
             0.0,
-            self.temp_buffer,
+            &mut self.temp_buffer[..out.len()],
             true,
             false,
         );
@@ -160,6 +198,20 @@ impl<'a> Engine for VirtualAnalogEngine<'a> {
             *out_sample = *out_sample * saw_gain_modulation.next()
                 + square_gain_modulation.next() * *temp_sample;
         }
+
+        if let Some(ratio) = self.aux_mode.sub_ratio() {
+            self.sub_oscillator.render(
+                primary_f * ratio,
+                0.5,
+                None,
+                aux,
+                OscillatorShape::Square,
+                false,
+            );
+            for aux_sample in aux.iter_mut() {
+                *aux_sample *= SUB_OSCILLATOR_GAIN;
+            }
+        }
     }
 }
 
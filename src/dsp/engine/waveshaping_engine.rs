@@ -7,14 +7,15 @@
 //! - *TIMBRE:* wavefolder amount.
 //! - *MORPH:* waveform asymmetry.
 //!
-//! *AUX* signal: variant employing another wavefolder curve, as available in *Warps*.
+//! *AUX* signal: variant employing another wavefolder curve, as available in *Warps*,
+//! or a sub-oscillator selected with [`WaveshapingEngine::set_aux_mode`].
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
 #[allow(unused_imports)]
 use num_traits::float::Float;
 
-use super::{note_to_frequency, Engine, EngineParameters};
+use super::{note_to_frequency, AuxMode, Engine, EngineParameters, SUB_OSCILLATOR_GAIN};
 use crate::dsp::oscillator::oscillator::{Oscillator, OscillatorShape};
 use crate::dsp::oscillator::sine_oscillator::sine;
 use crate::dsp::resources::{fold::LUT_FOLD, fold::LUT_FOLD_2, waveshape::LOOKUP_TABLE_I16_TABLE};
@@ -28,12 +29,27 @@ pub struct WaveshapingEngine {
     previous_shape: f32,
     previous_wavefolder_gain: f32,
     previous_overtone_gain: f32,
+
+    sub_oscillator: Oscillator,
+    aux_mode: AuxMode,
 }
 
 impl WaveshapingEngine {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Selects the signal rendered to *AUX*. Defaults to [`AuxMode::Default`].
+    #[inline]
+    pub fn set_aux_mode(&mut self, aux_mode: AuxMode) {
+        self.aux_mode = aux_mode;
+    }
+
+    /// Returns the signal rendered to *AUX*.
+    #[inline]
+    pub fn aux_mode(&self) -> AuxMode {
+        self.aux_mode
+    }
 }
 
 impl Engine for WaveshapingEngine {
@@ -43,6 +59,7 @@ impl Engine for WaveshapingEngine {
         self.previous_shape = 0.0;
         self.previous_wavefolder_gain = 0.0;
         self.previous_overtone_gain = 0.0;
+        self.sub_oscillator.init();
     }
 
     #[inline]
@@ -124,6 +141,14 @@ impl Engine for WaveshapingEngine {
             *out_sample = fold;
             *aux_sample = sine + (fold_2 - sine) * overtone_gain_modulation.next();
         }
+
+        if let Some(ratio) = self.aux_mode.sub_ratio() {
+            self.sub_oscillator
+                .render(f0 * ratio, 0.5, None, aux, OscillatorShape::Square, false);
+            for aux_sample in aux.iter_mut() {
+                *aux_sample *= SUB_OSCILLATOR_GAIN;
+            }
+        }
     }
 }
 
@@ -12,25 +12,37 @@
 //!
 //! Engine parameters:
 //! - *HARMONICS:* bank selection. 4 interpolated banks followed by the same 4 banks,
-//!   in reverse order, without interpolation.
+//!   in reverse order, without interpolation. Between both halves, the quantization of
+//!   the wave position fades in over the span of one bank, so sweeping *HARMONICS* across
+//!   the midpoint does not click.
 //! - *TIMBRE:* row index. Within a row, the waves are sorted by spectral brightness
 //!   (except for bank D which is a mess!).
 //! - *MORPH:* column index.
 //!
-//! *AUX* signal: low-fi (5-bit) output.
+//! *AUX* signal: low-fi (5-bit) output, see [`WavetableEngine::set_aux_bit_depth`].
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
-use super::{note_to_frequency, Engine, EngineParameters};
+use super::{clamp_frequency, note_to_frequency, Engine, EngineParameters};
 use crate::dsp::oscillator::wavetable_oscillator::{interpolate_wave_hermite, Differentiator};
 use crate::dsp::resources::waves::WAV_INTEGRATED_WAVES;
-use crate::dsp::A0;
+use crate::dsp::{A0, SAMPLE_RATE};
 use crate::stmlib::dsp::one_pole;
 use crate::stmlib::dsp::parameter_interpolator::SimpleParameterInterpolator;
 
 const TABLE_SIZE: usize = 128;
 const TABLE_SIZE_F: f32 = TABLE_SIZE as f32;
 
+// Below about 2 Hz, the gain of the differentiator would amplify the quantization noise
+// of the integrated waves more than the waveform itself.
+const MIN_DIFFERENTIATOR_FREQUENCY: f32 = 2.0 / SAMPLE_RATE;
+
+/// Default bit depth of the low-fi *AUX* output.
+pub const DEFAULT_AUX_BIT_DEPTH: u8 = 5;
+
+/// Maximum bit depth of the low-fi *AUX* output.
+pub const MAX_AUX_BIT_DEPTH: u8 = 16;
+
 #[derive(Debug)]
 pub struct WavetableEngine<'a> {
     phase: f32,
@@ -51,6 +63,9 @@ pub struct WavetableEngine<'a> {
     diff_out: Differentiator,
 
     wavetables: &'a [i16; 25344],
+
+    aux_bit_depth: u8,
+    aux_scale: f32,
 }
 
 impl<'a> Default for WavetableEngine<'a> {
@@ -80,6 +95,9 @@ impl<'a> WavetableEngine<'a> {
             diff_out: Differentiator::new(),
 
             wavetables: &WAV_INTEGRATED_WAVES,
+
+            aux_bit_depth: DEFAULT_AUX_BIT_DEPTH,
+            aux_scale: (1 << DEFAULT_AUX_BIT_DEPTH) as f32,
         }
     }
 
@@ -87,6 +105,20 @@ impl<'a> WavetableEngine<'a> {
         self.wavetables = wavetables;
     }
 
+    /// Sets the resolution of the low-fi *AUX* output in bits per unit of amplitude, from
+    /// `1` to [`MAX_AUX_BIT_DEPTH`]. Lower values are grittier, `3` is very coarse and
+    /// [`MAX_AUX_BIT_DEPTH`] nearly clean. Default is [`DEFAULT_AUX_BIT_DEPTH`].
+    #[inline]
+    pub fn set_aux_bit_depth(&mut self, bits: u8) {
+        self.aux_bit_depth = bits.clamp(1, MAX_AUX_BIT_DEPTH);
+        self.aux_scale = (1 << self.aux_bit_depth) as f32;
+    }
+
+    #[inline]
+    pub fn aux_bit_depth(&self) -> u8 {
+        self.aux_bit_depth
+    }
+
     #[inline]
     fn read_wave(
         &self,
@@ -144,6 +176,7 @@ impl<'a> Engine for WavetableEngine<'a> {
         let y = self.y_pre_lp;
         let z = self.z_pre_lp;
 
+        // Crossfade from the interpolated to the non-interpolated banks.
         let quantization = (z - 3.0).clamp(0.0, 1.0);
         let lp_coefficient = (2.0 * f0 * (4.0 - 3.0 * quantization)).clamp(0.01, 0.1);
 
@@ -179,7 +212,8 @@ impl<'a> Engine for WavetableEngine<'a> {
         for (out_sample, aux_sample) in out.iter_mut().zip(aux.iter_mut()) {
             let f0 = f0_modulation.update(&mut self.previous_f0);
 
-            let gain = (1.0 / (f0 * 131072.0)) * (0.95 - f0);
+            let gain = (1.0 / (clamp_frequency(f0, MIN_DIFFERENTIATOR_FREQUENCY) * 131072.0))
+                * (0.95 - f0);
             let cutoff = f32::min(TABLE_SIZE_F * f0, 1.0);
 
             one_pole(
@@ -259,7 +293,7 @@ impl<'a> Engine for WavetableEngine<'a> {
                 let mut mix = xyz0 + (xyz1 - xyz0) * z_fractional;
                 mix = self.diff_out.process(cutoff, mix) * gain;
                 *out_sample = mix;
-                *aux_sample = (((mix * 32.0) as i32) as f32) / 32.0;
+                *aux_sample = (((mix * self.aux_scale) as i32) as f32) / self.aux_scale;
             }
         }
     }
@@ -2,7 +2,7 @@
 
 // Based on MIT-licensed code (c) 2021 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
-use crate::stmlib::utils::random;
+use crate::stmlib::utils::random::RandomSource;
 
 #[derive(Debug, Default, PartialEq, Eq)]
 pub enum ArpeggiatorMode {
@@ -38,6 +38,8 @@ pub struct Arpeggiator {
     note: i32,
     octave: i32,
     direction: i32,
+
+    random: RandomSource,
 }
 
 impl Arpeggiator {
@@ -47,6 +49,7 @@ impl Arpeggiator {
 
     pub fn init(&mut self) {
         self.mode = ArpeggiatorMode::Up;
+        self.random.init(None, 0);
         self.reset();
     }
 
@@ -85,7 +88,7 @@ impl Arpeggiator {
 
         if self.mode == ArpeggiatorMode::Random {
             loop {
-                let w = random::get_word();
+                let w = self.random.get_word();
                 let octave = ((w >> 4) as i32) % self.range;
                 let note = ((w >> 20) as i32) % num_notes;
                 if octave != self.octave || note != self.note {
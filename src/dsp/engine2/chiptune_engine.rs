@@ -19,6 +19,7 @@ use num_traits::float::Float;
 use super::arpeggiator::{Arpeggiator, ArpeggiatorMode};
 use crate::dsp::chords::chord_bank::{ChordBank, CHORD_NUM_VOICES};
 use crate::dsp::engine::{note_to_frequency, Engine, EngineParameters, TriggerState};
+use crate::dsp::fx::sample_rate_reducer::quantize;
 use crate::dsp::oscillator::nes_triangle_oscillator::NesTriangleOscillator;
 use crate::dsp::oscillator::super_square_oscillator::SuperSquareOscillator;
 use crate::dsp::SAMPLE_RATE;
@@ -28,6 +29,9 @@ use crate::stmlib::dsp::units::semitones_to_ratio;
 
 pub const NO_ENVELOPE: f32 = 2.0;
 
+/// Resolution of the pseudo-DAC of the NES triangle, as used by the original firmware.
+pub const TRIANGLE_DAC_BITS: u8 = 5;
+
 #[derive(Debug, Default)]
 pub struct ChiptuneEngine {
     voice: [SuperSquareOscillator; CHORD_NUM_VOICES],
@@ -40,6 +44,8 @@ pub struct ChiptuneEngine {
     envelope_shape: f32,
     envelope_state: f32,
     aux_envelope_amount: f32,
+
+    dac_bits: Option<u8>,
 }
 
 impl ChiptuneEngine {
@@ -55,6 +61,8 @@ impl ChiptuneEngine {
             envelope_shape: 0.0,
             envelope_state: 0.0,
             aux_envelope_amount: 0.0,
+
+            dac_bits: None,
         }
     }
 
@@ -62,6 +70,21 @@ impl ChiptuneEngine {
     pub fn set_envelope_shape(&mut self, envelope_shape: f32) {
         self.envelope_shape = envelope_shape;
     }
+
+    /// Set the resolution in bits (1 to 16) of a pseudo-DAC quantizing the square voices
+    /// and the steps of the NES triangle.
+    ///
+    /// `None` (default) leaves the square voices unquantized and renders the triangle with
+    /// [`TRIANGLE_DAC_BITS`], like the original firmware.
+    #[inline]
+    pub fn set_dac_bits(&mut self, bits: Option<u8>) {
+        self.dac_bits = bits.map(|bits| bits.clamp(1, 16));
+    }
+
+    #[inline]
+    pub fn dac_bits(&self) -> Option<u8> {
+        self.dac_bits
+    }
 }
 
 impl Engine for ChiptuneEngine {
@@ -141,8 +164,16 @@ impl Engine for ChiptuneEngine {
             }
         }
 
+        if let Some(bits) = self.dac_bits {
+            for out_sample in out.iter_mut() {
+                *out_sample = quantize(*out_sample, bits as u32);
+            }
+        }
+
         // Render bass note.
-        self.bass.render(f0 * 0.5 * root_transposition, aux, 5);
+        let triangle_bits = self.dac_bits.unwrap_or(TRIANGLE_DAC_BITS);
+        self.bass
+            .render(f0 * 0.5 * root_transposition, aux, triangle_bits as u32);
 
         // Apply envelope if necessary.
         if self.envelope_shape != NO_ENVELOPE {
@@ -17,19 +17,22 @@ use crate::dsp::fm::{
     patch::{Patch, SYX_SIZE},
     voice::{Voice, VoiceParameters},
 };
-use crate::dsp::{allocate_buffer, SAMPLE_RATE};
+use crate::dsp::resources::sysex::SYX_BANKS;
+use crate::dsp::{allocate_buffer, allocate_value, SAMPLE_RATE};
 use crate::stmlib::dsp::hysteresis_quantizer::HysteresisQuantizer2;
 use crate::stmlib::dsp::soft_clip;
 
 const NUM_SIX_OP_VOICES: usize = 2;
 const NUM_PATCHES_PER_BANK: usize = 32;
 
-static mut ALGORITHMS: Option<Algorithms<6, 32>> = None;
-
-static mut PATCHES: Option<[Patch; NUM_PATCHES_PER_BANK]> = None;
-
 #[derive(Debug)]
 pub struct SixOpEngine<'a> {
+    algorithms: &'a Algorithms<6, 32>,
+    patches: [Patch; NUM_PATCHES_PER_BANK],
+    morphed_patch: Option<Patch>,
+    scrub: Option<f32>,
+    scrub_position: f32,
+
     patch_index_quantizer: HysteresisQuantizer2,
     voice: [FmVoice<'a>; NUM_SIX_OP_VOICES],
 
@@ -37,30 +40,93 @@ pub struct SixOpEngine<'a> {
 
     active_voice: i32,
     rendered_voice: i32,
+
+    feedback: Option<u8>,
 }
 
 impl<'a> SixOpEngine<'a> {
     pub fn new<A: GlobalAlloc>(buffer_allocator: &A, block_size: usize) -> Self {
-        unsafe {
-            let mut algorithms = Algorithms::<6, 32>::new();
-            algorithms.init();
-            ALGORITHMS = Some(algorithms);
-            let patches: [Patch; NUM_PATCHES_PER_BANK] = core::array::from_fn(|_| Patch::new());
-            PATCHES = Some(patches);
-        }
+        let mut algorithms = Algorithms::<6, 32>::new();
+        algorithms.init();
+
         Self {
+            algorithms: allocate_value(buffer_allocator, algorithms).unwrap(),
+            patches: core::array::from_fn(|_| Patch::new()),
+            morphed_patch: None,
+            scrub: None,
+            scrub_position: 0.0,
             patch_index_quantizer: HysteresisQuantizer2::new(),
             voice: core::array::from_fn(|_| FmVoice::new(buffer_allocator, block_size)),
             temp_buffer: allocate_buffer(buffer_allocator, block_size).unwrap(),
             active_voice: 0,
             rendered_voice: 0,
+            feedback: None,
         }
     }
 
-    pub fn load_syx_bank(&mut self, bank: &[u8; 4096]) {
-        let patches = unsafe { PATCHES.as_mut().unwrap() };
+    /// Overrides the feedback amount (0 to 7) of the loaded patches for the next render.
+    #[inline]
+    pub fn set_feedback(&mut self, feedback: u8) {
+        self.feedback = Some(feedback);
+    }
+
+    /// Plays a patch interpolated between the loaded patches `a` and `b` instead of
+    /// the patch selected by *HARMONICS*, see [`Patch::morph`].
+    ///
+    /// Every change of the interpolated patch causes a patch setup in the voices,
+    /// so `t` is best updated at control rate.
+    pub fn morph_patches(&mut self, a: usize, b: usize, t: f32) {
+        let a = a.min(NUM_PATCHES_PER_BANK - 1);
+        let b = b.min(NUM_PATCHES_PER_BANK - 1);
+        self.morphed_patch = Some(self.patches[a].morph(&self.patches[b], t));
+    }
+
+    /// Returns to the patch selection by *HARMONICS*.
+    pub fn clear_patch_morph(&mut self) {
+        self.morphed_patch = None;
+    }
+
+    /// Returns the number of banks embedded in the crate resources.
+    pub fn builtin_bank_count() -> usize {
+        SYX_BANKS.len()
+    }
+
+    /// Loads one of the embedded banks. Returns `false` if `index` is out of range.
+    pub fn load_builtin_bank(&mut self, index: usize) -> bool {
+        match SYX_BANKS.get(index) {
+            Some(bank) => {
+                self.load_syx_bank(bank);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the patch of the most recently triggered voice, if any.
+    pub fn active_patch(&self) -> Option<&Patch> {
+        self.voice[self.active_voice as usize].patch()
+    }
+
+    /// Freeze the envelopes at a position in the range from `0.0` to `1.0` instead of
+    /// the one set by *MORPH*, while the trigger input is unpatched. The range covers
+    /// the first 1.5 seconds of the envelopes with the gate held.
+    pub fn set_scrub(&mut self, position: f32) {
+        self.scrub = Some(position.clamp(0.0, 1.0));
+    }
+
+    /// Returns to scrubbing the envelopes with *MORPH*.
+    pub fn clear_scrub(&mut self) {
+        self.scrub = None;
+    }
 
-        for (i, patch) in patches.iter_mut().enumerate() {
+    /// Returns the envelope position used by the last render in unpatched mode.
+    #[inline]
+    pub fn scrub(&self) -> f32 {
+        self.scrub_position
+    }
+
+    pub fn load_syx_bank(&mut self, bank: &[u8; 4096]) {
+        for (i, patch) in self.patches.iter_mut().enumerate() {
             (*patch).unpack(&bank[i * SYX_SIZE..]);
         }
 
@@ -74,16 +140,32 @@ impl<'a> Engine for SixOpEngine<'a> {
     fn init(&mut self) {
         self.patch_index_quantizer.init(32, 0.005, false);
 
-        for voice in self.voice.iter_mut() {
-            unsafe {
-                voice.init(ALGORITHMS.as_ref().unwrap(), SAMPLE_RATE);
-            }
+        for (stream, voice) in self.voice.iter_mut().enumerate() {
+            voice.init(self.algorithms, SAMPLE_RATE);
+            voice.lfo.init_random(None, stream as u32);
         }
 
         self.active_voice = (NUM_SIX_OP_VOICES - 1) as i32;
         self.rendered_voice = 0;
     }
 
+    /// Load the selected patch into all voices and pre-compute it, instead of
+    /// skipping the first render for the patch setup.
+    fn prime(&mut self, parameters: &EngineParameters) {
+        let patch_index = self
+            .patch_index_quantizer
+            .process(parameters.harmonics * 1.02) as usize;
+        let patch = match &self.morphed_patch {
+            Some(patch) => patch,
+            None => &self.patches[patch_index],
+        };
+
+        for voice in self.voice.iter_mut() {
+            voice.load_patch(Some(patch));
+            voice.setup();
+        }
+    }
+
     fn render(
         &mut self,
         parameters: &EngineParameters,
@@ -94,17 +176,21 @@ impl<'a> Engine for SixOpEngine<'a> {
         let patch_index = self
             .patch_index_quantizer
             .process(parameters.harmonics * 1.02) as usize;
+        let patch = match &self.morphed_patch {
+            Some(patch) => patch,
+            None => &self.patches[patch_index],
+        };
 
         if parameters.trigger == TriggerState::Unpatched {
-            let t = parameters.morph;
+            let t = self.scrub.unwrap_or(parameters.morph);
+            self.scrub_position = t;
             self.voice[0].mutable_lfo().scrub(2.0 * SAMPLE_RATE * t);
 
             let pitch_mod = self.voice[0].lfo().pitch_mod();
             let amp_mod = self.voice[0].lfo().amp_mod();
 
             for (i, voice) in self.voice.iter_mut().enumerate() {
-                let patches = unsafe { PATCHES.as_ref().unwrap() };
-                voice.load_patch(Some(&patches[patch_index]));
+                voice.load_patch(Some(patch));
                 let p = voice.mutable_parameters();
                 p.sustain = i == 0;
                 p.gate = false;
@@ -117,9 +203,10 @@ impl<'a> Engine for SixOpEngine<'a> {
         } else {
             if parameters.trigger == TriggerState::RisingEdge {
                 self.active_voice = (self.active_voice + 1) % NUM_SIX_OP_VOICES as i32;
-                let patches = unsafe { PATCHES.as_ref().unwrap() };
-                self.voice[self.active_voice as usize].load_patch(Some(&patches[patch_index]));
+                self.voice[self.active_voice as usize].load_patch(Some(patch));
                 self.voice[self.active_voice as usize].mutable_lfo().reset();
+            } else if self.morphed_patch.is_some() {
+                self.voice[self.active_voice as usize].load_patch(Some(patch));
             }
             let p = self.voice[self.active_voice as usize].mutable_parameters();
             p.note = parameters.note;
@@ -159,12 +246,17 @@ impl<'a> Engine for SixOpEngine<'a> {
 
         out.fill(0.0);
 
+        let feedback = self.feedback.take();
+
+        let temp_buffer = &mut self.temp_buffer[..out.len()];
+
         for voice in self.voice.iter_mut() {
-            self.temp_buffer.fill(0.0);
+            voice.set_feedback(feedback);
+            temp_buffer.fill(0.0);
 
-            voice.render(self.temp_buffer);
+            voice.render(temp_buffer);
 
-            for (out_sample, temp_sample) in out.iter_mut().zip(self.temp_buffer.iter()) {
+            for (out_sample, temp_sample) in out.iter_mut().zip(temp_buffer.iter()) {
                 *out_sample = soft_clip(*out_sample + *temp_sample * 0.25);
             }
         }
@@ -175,7 +267,7 @@ impl<'a> Engine for SixOpEngine<'a> {
 
 #[derive(Debug)]
 pub struct FmVoice<'a> {
-    patch: Option<&'a Patch>,
+    loaded: bool,
 
     lfo: Lfo,
     voice: Voice<'a, 6, 32>,
@@ -189,7 +281,7 @@ pub struct FmVoice<'a> {
 impl<'a> FmVoice<'a> {
     pub fn new<T: GlobalAlloc>(buffer_allocator: &T, block_size: usize) -> Self {
         Self {
-            patch: None,
+            loaded: false,
             lfo: Lfo::new(),
             voice: Voice::<'a, 6, 32>::new(),
             parameters: VoiceParameters::new(),
@@ -211,16 +303,16 @@ impl<'a> FmVoice<'a> {
         self.parameters.pitch_mod = 0.0;
         self.parameters.amp_mod = 0.0;
 
-        self.patch = None;
+        self.loaded = false;
     }
 
-    pub fn load_patch(&mut self, patch: Option<&'a Patch>) {
-        if patch == self.patch {
+    pub fn load_patch(&mut self, patch: Option<&Patch>) {
+        if patch == self.patch() {
             return;
         }
 
-        self.patch = patch;
-        self.voice.set_patch(self.patch);
+        self.loaded = patch.is_some();
+        self.voice.set_patch(patch);
 
         if let Some(patch) = patch {
             self.lfo.set(&patch.modulations);
@@ -229,28 +321,39 @@ impl<'a> FmVoice<'a> {
 
     #[inline]
     pub fn render(&mut self, out: &mut [f32]) {
-        if self.patch.is_none() {
+        if !self.loaded {
             return;
         }
 
+        let size = out.len();
         let buffers = [
             RefCell::new(out),
-            RefCell::new(self.temp_buffer_1),
-            RefCell::new(self.temp_buffer_2),
-            RefCell::new(self.temp_buffer_3),
+            RefCell::new(&mut self.temp_buffer_1[..size]),
+            RefCell::new(&mut self.temp_buffer_2[..size]),
+            RefCell::new(&mut self.temp_buffer_3[..size]),
         ];
 
         self.voice.render(&self.parameters, &buffers);
     }
 
+    /// Pre-compute the loaded patch, see `Voice::setup`.
+    #[inline]
+    pub fn setup(&mut self) {
+        self.voice.setup();
+    }
+
     #[inline]
     pub fn unload_patch(&mut self) {
-        self.patch = None;
+        self.loaded = false;
     }
 
     #[inline]
     pub fn patch(&self) -> Option<&Patch> {
-        self.patch
+        if self.loaded {
+            self.voice.patch()
+        } else {
+            None
+        }
     }
 
     #[inline]
@@ -268,6 +371,11 @@ impl<'a> FmVoice<'a> {
         &self.lfo
     }
 
+    #[inline]
+    pub fn set_feedback(&mut self, feedback: Option<u8>) {
+        self.voice.set_feedback(feedback);
+    }
+
     #[inline]
     pub fn set_modulations(&mut self, pitch_mod: f32, amp_mod: f32) {
         self.parameters.pitch_mod = pitch_mod;
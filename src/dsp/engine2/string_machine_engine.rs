@@ -22,7 +22,7 @@ use crate::stmlib::dsp::filter::{FilterMode, FrequencyApproximation, NaiveSvf};
 use crate::stmlib::dsp::one_pole;
 use crate::stmlib::dsp::units::semitones_to_ratio;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct StringMachineEngine {
     chords: ChordBank,
 
@@ -32,6 +32,9 @@ pub struct StringMachineEngine {
 
     morph_lp: f32,
     timbre_lp: f32,
+
+    chorus_depth: f32,
+    chorus_rate: f32,
 }
 
 impl StringMachineEngine {
@@ -45,8 +48,30 @@ impl StringMachineEngine {
 
             morph_lp: 0.0,
             timbre_lp: 0.0,
+
+            chorus_depth: 1.0,
+            chorus_rate: 1.0,
         }
     }
+
+    /// Scales the chorus depth set by *TIMBRE* (0.0 to 1.5). Defaults to `1.0`.
+    #[inline]
+    pub fn set_chorus_depth(&mut self, depth: f32) {
+        // Keeps the modulated delay within the ensemble delay lines.
+        self.chorus_depth = depth.clamp(0.0, 1.5);
+    }
+
+    /// Scales the rate of the chorus LFOs. Defaults to `1.0`.
+    #[inline]
+    pub fn set_chorus_rate(&mut self, rate: f32) {
+        self.chorus_rate = rate.max(0.0);
+    }
+}
+
+impl Default for StringMachineEngine {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Engine for StringMachineEngine {
@@ -74,6 +99,35 @@ impl Engine for StringMachineEngine {
         out: &mut [f32],
         aux: &mut [f32],
         _already_enveloped: &mut bool,
+    ) {
+        self.render_spread(parameters, out, aux, 0.0);
+    }
+}
+
+impl StringMachineEngine {
+    /// Renders the string machine as a stereo pair.
+    ///
+    /// `spread` (0.0 to 1.0) reduces the cross-feed between voices 1&3 and 2&4 in the
+    /// mixdown before the ensemble, up to fully separated channels. At a spread of `0.0`,
+    /// `left` and `right` are *OUT* and *AUX* of `render`. The mixdown keeps `left + right`
+    /// equal to *OUT* + *AUX*, but once the ensemble is engaged (`timbre` away from `0.5`)
+    /// it processes both channels differently, so the sums only match at a spread of `0.0`.
+    pub fn render_stereo(
+        &mut self,
+        parameters: &EngineParameters,
+        left: &mut [f32],
+        right: &mut [f32],
+        spread: f32,
+    ) {
+        self.render_spread(parameters, left, right, spread.clamp(0.0, 1.0));
+    }
+
+    fn render_spread(
+        &mut self,
+        parameters: &EngineParameters,
+        out: &mut [f32],
+        aux: &mut [f32],
+        spread: f32,
     ) {
         one_pole(&mut self.morph_lp, parameters.morph, 0.1);
         one_pole(&mut self.timbre_lp, parameters.timbre, 0.1);
@@ -106,19 +160,22 @@ impl Engine for StringMachineEngine {
         self.svf[0].set_f_q(cutoff, 1.0, FrequencyApproximation::Dirty);
         self.svf[1].set_f_q(cutoff * 1.5, 1.0, FrequencyApproximation::Dirty);
 
-        // Mixdown.
+        // Mixdown. The gains of both channels always sum up to 0.99.
+        let direct = 0.66 + 0.33 * spread;
+        let cross = 0.33 * (1.0 - spread);
         for (out_sample, aux_sample) in out.iter_mut().zip(aux.iter_mut()) {
             let l = self.svf[0].process(*out_sample, FilterMode::LowPass);
             let r = self.svf[1].process(*aux_sample, FilterMode::LowPass);
-            *out_sample = 0.66 * l + 0.33 * r;
-            *aux_sample = 0.66 * r + 0.33 * l;
+            *out_sample = direct * l + cross * r;
+            *aux_sample = direct * r + cross * l;
         }
 
         // Ensemble FX.
         let amount = f32::abs(parameters.timbre - 0.5) * 2.0;
-        let depth = 0.35 + 0.65 * parameters.timbre;
+        let depth = (0.35 + 0.65 * parameters.timbre) * self.chorus_depth;
         self.ensemble.set_amount(amount);
         self.ensemble.set_depth(depth);
+        self.ensemble.set_rate(self.chorus_rate);
         self.ensemble.process(out, aux);
     }
 }
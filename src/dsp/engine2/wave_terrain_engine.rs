@@ -109,8 +109,12 @@ impl<'a> Engine for WaveTerrainEngine<'a> {
 
         // Use the "magic sine" algorithm to generate sin and cos functions for the
         // trajectory coordinates.
-        self.path
-            .render_quadrature(f0 * SCALE, radius, self.temp_buffer_1, self.temp_buffer_2);
+        self.path.render_quadrature(
+            f0 * SCALE,
+            radius,
+            &mut self.temp_buffer_1[..2 * out.len()],
+            &mut self.temp_buffer_2[..2 * out.len()],
+        );
 
         let offset =
             SimpleParameterInterpolator::new(self.offset, 1.9 * parameters.morph - 1.0, out.len());
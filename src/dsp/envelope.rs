@@ -2,18 +2,38 @@
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
-#[derive(Debug, Default)]
+#[allow(unused_imports)]
+use num_traits::float::Float;
+
+/// Default amount by which the cutoff of the LPG follows its envelope.
+pub const DEFAULT_BRIGHTNESS_TRACKING: f32 = 0.5;
+
+#[derive(Debug, Clone)]
 pub struct LpgEnvelope {
     vactrol_state: f32,
     gain: f32,
     frequency: f32,
     hf_bleed: f32,
     ramp_up: bool,
+    brightness_tracking: f32,
+}
+
+impl Default for LpgEnvelope {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl LpgEnvelope {
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            vactrol_state: 0.0,
+            gain: 0.0,
+            frequency: 0.0,
+            hf_bleed: 0.0,
+            ramp_up: false,
+            brightness_tracking: DEFAULT_BRIGHTNESS_TRACKING,
+        }
     }
 
     pub fn init(&mut self) {
@@ -28,6 +48,25 @@ impl LpgEnvelope {
         self.ramp_up = true;
     }
 
+    /// Set how strongly the cutoff falls with the envelope (0.0 to 1.0). At `0.0`, the
+    /// cutoff stays open while the level decays, higher values darken the tail faster.
+    /// Default is [`DEFAULT_BRIGHTNESS_TRACKING`].
+    #[inline]
+    pub fn set_brightness_tracking(&mut self, tracking: f32) {
+        self.brightness_tracking = tracking.clamp(0.0, 1.0);
+    }
+
+    #[inline]
+    pub fn brightness_tracking(&self) -> f32 {
+        self.brightness_tracking
+    }
+
+    /// Returns `true` during the attack following a trigger.
+    #[inline]
+    pub fn ramping_up(&self) -> bool {
+        self.ramp_up
+    }
+
     #[inline]
     pub fn process_ping(&mut self, attack: f32, short_decay: f32, decay_tail: f32, hf: f32) {
         if self.ramp_up {
@@ -64,8 +103,17 @@ impl LpgEnvelope {
         };
         self.vactrol_state += vactrol_coefficient * vactrol_error;
 
+        // The default tracking follows the fourth power of the vactrol state.
+        let brightness = if self.brightness_tracking == DEFAULT_BRIGHTNESS_TRACKING {
+            vactrol_state_4
+        } else {
+            self.vactrol_state
+                .max(0.0)
+                .powf(8.0 * self.brightness_tracking)
+        };
+
         self.gain = self.vactrol_state;
-        self.frequency = 0.003 + 0.3 * vactrol_state_4 + hf * 0.04;
+        self.frequency = 0.003 + 0.3 * brightness + hf * 0.04;
         self.hf_bleed = (tail_2 + (1.0 - tail_2) * hf) * hf * hf;
     }
 
@@ -85,7 +133,7 @@ impl LpgEnvelope {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct DecayEnvelope {
     value: f32,
 }
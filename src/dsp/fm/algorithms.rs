@@ -60,6 +60,65 @@ impl<const NUM_OPERATORS: usize, const NUM_ALGORITHMS: usize>
         (self.opcode(algorithm, op) & OPCODE_DESTINATION_MASK) != 0
     }
 
+    /// Returns the routing of a single operator, decoded from the algorithm definition.
+    #[inline]
+    pub fn operator_routing(&self, algorithm: u32, op: u32) -> OperatorRouting {
+        let opcode = self.opcode(algorithm, op);
+
+        OperatorRouting {
+            input_index: ((opcode & OPCODE_SOURCE_MASK) >> 4) as u32,
+            output_index: (opcode & OPCODE_DESTINATION_MASK) as u32,
+            additive: (opcode & OPCODE_ADDITIVE_FLAG) != 0,
+            feedback_input: (opcode & OPCODE_SOURCE_MASK) == OPCODE_SOURCE_FEEDBACK,
+            feedback_source: (opcode & OPCODE_FEEDBACK_SOURCE_FLAG) != 0,
+        }
+    }
+
+    /// Returns which operators modulate each other and which ones are carriers, e.g. to
+    /// draw a diagram of the algorithm.
+    pub fn routing(&self, algorithm: u32) -> AlgorithmGraph<NUM_OPERATORS> {
+        let mut graph = AlgorithmGraph {
+            modulators: [0; NUM_OPERATORS],
+            carriers: [false; NUM_OPERATORS],
+            feedback_source: None,
+            feedback_destination: None,
+        };
+
+        // Operators whose output is currently in each buffer.
+        let mut buffers = [0u32; 4];
+
+        for op in 0..NUM_OPERATORS {
+            let routing = self.operator_routing(algorithm, op as u32);
+
+            if routing.feedback_source {
+                graph.feedback_source = Some(op);
+            }
+
+            if routing.feedback_input {
+                graph.feedback_destination = Some(op);
+            } else if routing.input_index != 0 {
+                graph.modulators[op] = buffers[routing.input_index as usize];
+            }
+
+            let buffer = &mut buffers[routing.output_index as usize];
+            if routing.additive {
+                *buffer |= 1 << op;
+            } else {
+                *buffer = 1 << op;
+            }
+
+            graph.carriers[op] = routing.output_index == 0;
+        }
+
+        if let (Some(source), Some(destination)) =
+            (graph.feedback_source, graph.feedback_destination)
+        {
+            graph.modulators[destination] |= 1 << source;
+        }
+
+        graph
+    }
+
     #[inline]
     fn get_renderer(&self, n: u32, modulation_source: i32, additive: bool) -> Option<RenderFn> {
         for renderer in self.renderers() {
@@ -184,6 +243,46 @@ impl RenderCall {
     }
 }
 
+/// Routing of a single operator.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OperatorRouting {
+    /// Index of the buffer used as modulation input, `0` if the operator is not modulated.
+    pub input_index: u32,
+    /// Index of the buffer the operator writes to, `0` being the output.
+    pub output_index: u32,
+    /// Output is added to the buffer instead of replacing it.
+    pub additive: bool,
+    /// Operator is modulated by the feedback path.
+    pub feedback_input: bool,
+    /// Operator output feeds the feedback path.
+    pub feedback_source: bool,
+}
+
+/// Routing of all operators of an algorithm.
+///
+/// Operators are indexed in the order of the patch, i.e. index `0` is operator 6 of a
+/// 6-operator algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlgorithmGraph<const NUM_OPERATORS: usize> {
+    /// Operators modulating each operator as a bit mask, bit `n` standing for index `n`.
+    /// The feedback path is included.
+    pub modulators: [u32; NUM_OPERATORS],
+    /// Operators written to the output.
+    pub carriers: [bool; NUM_OPERATORS],
+    /// Operator feeding the feedback path.
+    pub feedback_source: Option<usize>,
+    /// Operator modulated by the feedback path.
+    pub feedback_destination: Option<usize>,
+}
+
+impl<const NUM_OPERATORS: usize> AlgorithmGraph<NUM_OPERATORS> {
+    /// Returns if operator `source` modulates operator `op`.
+    #[inline]
+    pub fn is_modulated_by(&self, op: usize, source: usize) -> bool {
+        self.modulators[op] & (1 << source) != 0
+    }
+}
+
 #[derive(Debug)]
 struct RendererSpecs {
     n: u32,
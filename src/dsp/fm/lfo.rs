@@ -5,7 +5,7 @@
 use super::dx_units::{lfo_delay, lfo_frequency, pitch_mod_sensitivity};
 use super::patch::ModulationParameters;
 use crate::dsp::oscillator::sine_oscillator::sine;
-use crate::stmlib::utils::random;
+use crate::stmlib::utils::random::RandomSource;
 
 #[derive(Debug, Default)]
 pub enum Waveform {
@@ -53,6 +53,8 @@ pub struct Lfo {
     reset_phase: bool,
 
     phase_integral: i32,
+
+    random: RandomSource,
 }
 
 impl Lfo {
@@ -79,6 +81,12 @@ impl Lfo {
         self.reset_phase = false;
 
         self.phase_integral = 0;
+
+        self.random.init(None, 0);
+    }
+
+    pub(crate) fn init_random(&mut self, seed: Option<u32>, stream: u32) {
+        self.random.init(seed, stream);
     }
 
     #[inline]
@@ -114,7 +122,7 @@ impl Lfo {
 
         if self.phase >= 1.0 {
             self.phase -= 1.0;
-            self.random_value = random::get_float();
+            self.random_value = self.random.get_float();
         }
 
         self.value = self.value();
@@ -136,7 +144,7 @@ impl Lfo {
 
         if phase_integral != self.phase_integral {
             self.phase_integral = phase_integral;
-            self.random_value = random::get_float();
+            self.random_value = self.random.get_float();
         }
 
         self.value = self.value();
@@ -4,7 +4,7 @@
 
 pub const SYX_SIZE: usize = 128;
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Patch {
     pub op: [Operator; 6],
     pub pitch_envelope: Envelope,
@@ -84,15 +84,96 @@ impl Patch {
 
         self.active_operators = 0x3F;
     }
+
+    /// Returns a patch interpolated between `self` (`t = 0.0`) and `other` (`t = 1.0`).
+    ///
+    /// Levels, rates, frequencies, feedback and modulation amounts are interpolated
+    /// and rounded to the nearest integer. Settings without a meaningful in-between
+    /// value (algorithm, curves, oscillator mode, waveforms, name) switch at `t = 0.5`.
+    pub fn morph(&self, other: &Patch, t: f32) -> Patch {
+        let t = t.clamp(0.0, 1.0);
+        let pick = |a: u8, b: u8| if t < 0.5 { a } else { b };
+
+        let mut patch = self.clone();
+
+        for (op, (a, b)) in patch.op.iter_mut().zip(self.op.iter().zip(other.op.iter())) {
+            op.envelope = a.envelope.morph(&b.envelope, t);
+
+            op.keyboard_scaling.left_depth = lerp(
+                a.keyboard_scaling.left_depth,
+                b.keyboard_scaling.left_depth,
+                t,
+            );
+            op.keyboard_scaling.right_depth = lerp(
+                a.keyboard_scaling.right_depth,
+                b.keyboard_scaling.right_depth,
+                t,
+            );
+            op.keyboard_scaling.left_curve =
+                pick(a.keyboard_scaling.left_curve, b.keyboard_scaling.left_curve);
+            op.keyboard_scaling.right_curve = pick(
+                a.keyboard_scaling.right_curve,
+                b.keyboard_scaling.right_curve,
+            );
+            op.keyboard_scaling.break_point = lerp(
+                a.keyboard_scaling.break_point,
+                b.keyboard_scaling.break_point,
+                t,
+            );
+
+            op.rate_scaling = lerp(a.rate_scaling, b.rate_scaling, t);
+            op.amp_mod_sensitivity = lerp(a.amp_mod_sensitivity, b.amp_mod_sensitivity, t);
+            op.velocity_sensitivity = lerp(a.velocity_sensitivity, b.velocity_sensitivity, t);
+            op.level = lerp(a.level, b.level, t);
+
+            op.mode = pick(a.mode, b.mode);
+            op.coarse = lerp(a.coarse, b.coarse, t);
+            op.fine = lerp(a.fine, b.fine, t);
+            op.detune = lerp(a.detune, b.detune, t);
+        }
+
+        patch.pitch_envelope = self.pitch_envelope.morph(&other.pitch_envelope, t);
+        patch.algorithm = pick(self.algorithm, other.algorithm);
+        patch.feedback = lerp(self.feedback, other.feedback, t);
+        patch.reset_phase = pick(self.reset_phase, other.reset_phase);
+
+        let (a, b) = (&self.modulations, &other.modulations);
+        patch.modulations.delay = lerp(a.delay, b.delay, t);
+        patch.modulations.rate = lerp(a.rate, b.rate, t);
+        patch.modulations.pitch_mod_depth = lerp(a.pitch_mod_depth, b.pitch_mod_depth, t);
+        patch.modulations.amp_mod_depth = lerp(a.amp_mod_depth, b.amp_mod_depth, t);
+        patch.modulations.reset_phase = pick(a.reset_phase, b.reset_phase);
+        patch.modulations.waveform = pick(a.waveform, b.waveform);
+        patch.modulations.pitch_mod_sensitivity =
+            lerp(a.pitch_mod_sensitivity, b.pitch_mod_sensitivity, t);
+
+        patch.transpose = lerp(self.transpose, other.transpose, t);
+
+        if t >= 0.5 {
+            patch.name = other.name;
+            patch.active_operators = other.active_operators;
+        }
+
+        patch
+    }
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Envelope {
     pub rate: [u8; 4],
     pub level: [u8; 4],
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
+impl Envelope {
+    fn morph(&self, other: &Envelope, t: f32) -> Envelope {
+        Envelope {
+            rate: core::array::from_fn(|i| lerp(self.rate[i], other.rate[i], t)),
+            level: core::array::from_fn(|i| lerp(self.level[i], other.level[i], t)),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct KeyboardScaling {
     pub left_depth: u8,
     pub right_depth: u8,
@@ -101,7 +182,7 @@ pub struct KeyboardScaling {
     pub break_point: u8,
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Operator {
     pub envelope: Envelope,
     pub keyboard_scaling: KeyboardScaling,
@@ -117,7 +198,7 @@ pub struct Operator {
     pub detune: u8,
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct ModulationParameters {
     pub delay: u8,
     pub rate: u8,
@@ -127,3 +208,9 @@ pub struct ModulationParameters {
     pub waveform: u8,
     pub pitch_mod_sensitivity: u8,
 }
+
+/// Interpolates between two parameter values, rounding to the nearest integer.
+#[inline]
+fn lerp(a: u8, b: u8, t: f32) -> u8 {
+    (a as f32 + (b as f32 - a as f32) * t + 0.5) as u8
+}
@@ -15,6 +15,7 @@ use super::dx_units::{
 use super::envelope::{OperatorEnvelope, PitchEnvelope};
 use super::operator::Operator;
 use super::patch::Patch;
+use crate::dsp::oscillator::sine_oscillator::sine_pm;
 use crate::stmlib::dsp::units::semitones_to_ratio_safe;
 
 #[derive(Debug, Default)]
@@ -27,6 +28,8 @@ pub struct VoiceParameters {
     pub envelope_control: f32,
     pub pitch_mod: f32,
     pub amp_mod: f32,
+    /// Ignore the pitch envelope of the patch, keeping `pitch_mod`.
+    pub disable_pitch_envelope: bool,
 }
 
 impl VoiceParameters {
@@ -52,12 +55,15 @@ pub struct Voice<'a, const NUM_OPERATORS: usize, const NUM_ALGORITHMS: usize> {
     note: f32,
 
     ratios: [f32; NUM_OPERATORS],
+    detune: [f32; NUM_OPERATORS],
+    operator_mute: [bool; NUM_OPERATORS],
     level_headroom: [f32; NUM_OPERATORS],
     level: [f32; NUM_OPERATORS],
 
     feedback_state: [f32; 2],
 
-    patch: Option<&'a Patch>,
+    patch: Option<Patch>,
+    feedback: Option<u8>,
 
     dirty: bool,
 }
@@ -90,12 +96,15 @@ impl<'a, const NUM_OPERATORS: usize, const NUM_ALGORITHMS: usize>
             note: 0.0,
 
             ratios: [0.0; NUM_OPERATORS],
+            detune: [1.0; NUM_OPERATORS],
+            operator_mute: [false; NUM_OPERATORS],
             level_headroom: [0.0; NUM_OPERATORS],
             level: [0.0; NUM_OPERATORS],
 
             feedback_state: [0.0; 2],
 
             patch: None,
+            feedback: None,
 
             dirty: false,
         }
@@ -138,12 +147,47 @@ impl<'a, const NUM_OPERATORS: usize, const NUM_ALGORITHMS: usize>
         self.dirty = true;
     }
 
+    /// Loads a copy of `patch`, so that the voice does not borrow its source. This lets
+    /// an engine own its bank and its voices side by side, a copy being a plain copy
+    /// of the patch data.
     #[inline]
-    pub fn set_patch(&mut self, patch: Option<&'a Patch>) {
-        self.patch = patch;
+    pub fn set_patch(&mut self, patch: Option<&Patch>) {
+        self.patch = patch.cloned();
         self.dirty = true;
     }
 
+    #[inline]
+    pub fn patch(&self) -> Option<&Patch> {
+        self.patch.as_ref()
+    }
+
+    /// Sets an additional detuning in cents for each operator, in the same order as
+    /// the algorithm definitions.
+    #[inline]
+    pub fn set_operator_detune(&mut self, cents: [f32; NUM_OPERATORS]) {
+        for (detune, cents) in self.detune.iter_mut().zip(cents.iter()) {
+            *detune = semitones_to_ratio_safe(*cents * 0.01);
+        }
+    }
+
+    /// Mutes operators, in the same order as the algorithm definitions, without changing
+    /// the patch, e.g. to solo a carrier. Muted modulators stop modulating.
+    #[inline]
+    pub fn set_operator_mute(&mut self, mask: [bool; NUM_OPERATORS]) {
+        self.operator_mute = mask;
+    }
+
+    #[inline]
+    pub fn operator_mute(&self) -> [bool; NUM_OPERATORS] {
+        self.operator_mute
+    }
+
+    /// Overrides the feedback amount (0 to 7) of the patch, `None` restores it.
+    #[inline]
+    pub fn set_feedback(&mut self, feedback: Option<u8>) {
+        self.feedback = feedback.map(|feedback| feedback.min(7));
+    }
+
     /// Pre-compute everything that can be pre-computed once a patch is loaded:
     /// - envelope constants
     /// - frequency ratios
@@ -153,7 +197,7 @@ impl<'a, const NUM_OPERATORS: usize, const NUM_ALGORITHMS: usize>
             return false;
         }
 
-        if let Some(patch) = self.patch {
+        if let Some(patch) = &self.patch {
             self.pitch_envelope
                 .set(&patch.pitch_envelope.rate, &patch.pitch_envelope.level);
 
@@ -196,7 +240,128 @@ impl<'a, const NUM_OPERATORS: usize, const NUM_ALGORITHMS: usize>
             return;
         }
 
+        let mut f = [0.0; NUM_OPERATORS];
+        let mut a = [0.0; NUM_OPERATORS];
         let envelope_rate = buffers[0].borrow().len() as f32;
+
+        if self.compute_operators(parameters, envelope_rate, &mut f, &mut a) {
+            let patch = self.patch.as_ref().unwrap();
+            let mut i = 0;
+
+            while i < NUM_OPERATORS {
+                let call = self
+                    .algorithms
+                    .unwrap()
+                    .render_call(patch.algorithm as u32, i as u32);
+
+                if let Some(render_fn) = call.render_fn {
+                    render_fn(
+                        &mut self.operator[i..],
+                        &f[i..],
+                        &a[i..],
+                        &mut self.feedback_state,
+                        self.feedback.unwrap_or(patch.feedback) as i32,
+                        &buffers[call.input_index as usize],
+                        &buffers[call.output_index as usize],
+                    );
+                }
+
+                i += call.n as usize;
+            }
+        }
+    }
+
+    /// Renders the voice like `render`, additionally writing the output of each
+    /// operator into `operators`.
+    ///
+    /// `operators[0]` holds operator 6 and `operators[NUM_OPERATORS - 1]` operator 1,
+    /// following the order of the algorithm definitions. The mixed result is added
+    /// to `out`, which should be cleared beforehand. This path renders sample by
+    /// sample and is intended for analysis and visualization rather than for
+    /// real-time use.
+    pub fn render_operators_separately(
+        &mut self,
+        parameters: &VoiceParameters,
+        out: &mut [f32],
+        operators: &mut [&mut [f32]; NUM_OPERATORS],
+    ) {
+        if self.setup() {
+            return;
+        }
+
+        let mut f = [0.0; NUM_OPERATORS];
+        let mut a = [0.0; NUM_OPERATORS];
+        let envelope_rate = out.len() as f32;
+
+        if self.compute_operators(parameters, envelope_rate, &mut f, &mut a) {
+            let patch = self.patch.as_ref().unwrap();
+            let algorithms = self.algorithms.unwrap();
+            let scale = 1.0 / out.len() as f32;
+
+            let mut frequency = [0u32; NUM_OPERATORS];
+            let mut amplitude_increment = [0.0; NUM_OPERATORS];
+
+            for i in 0..NUM_OPERATORS {
+                frequency[i] = (f32::min(f[i], 0.5) * 4294967296.0) as u32;
+                amplitude_increment[i] = (f32::min(a[i], 4.0) - self.operator[i].amplitude) * scale;
+            }
+
+            let feedback = self.feedback.unwrap_or(patch.feedback);
+            let fb_scale = if feedback != 0 {
+                (1 << feedback) as f32 / 512.0
+            } else {
+                0.0
+            };
+
+            for (n, out_sample) in out.iter_mut().enumerate() {
+                // Same layout as the buffers of `render`, with index 0 being the output.
+                let mut buffers = [*out_sample, 0.0, 0.0, 0.0];
+
+                for i in 0..NUM_OPERATORS {
+                    let routing = algorithms.operator_routing(patch.algorithm as u32, i as u32);
+
+                    let pm = if routing.input_index == 0 {
+                        0.0
+                    } else if routing.feedback_input {
+                        (self.feedback_state[0] + self.feedback_state[1]) * fb_scale
+                    } else {
+                        buffers[routing.input_index as usize]
+                    };
+
+                    let operator = &mut self.operator[i];
+                    operator.phase = operator.phase.wrapping_add(frequency[i]);
+                    let value = sine_pm(operator.phase, pm) * operator.amplitude;
+                    operator.amplitude += amplitude_increment[i];
+
+                    if routing.feedback_source {
+                        self.feedback_state[1] = self.feedback_state[0];
+                        self.feedback_state[0] = value;
+                    }
+
+                    if routing.additive {
+                        buffers[routing.output_index as usize] += value;
+                    } else {
+                        buffers[routing.output_index as usize] = value;
+                    }
+
+                    operators[i][n] = value;
+                }
+
+                *out_sample = buffers[0];
+            }
+        }
+    }
+
+    /// Updates the envelopes and computes the frequency and amplitude of each operator.
+    /// Returns whether a patch is loaded.
+    #[inline]
+    fn compute_operators(
+        &mut self,
+        parameters: &VoiceParameters,
+        envelope_rate: f32,
+        f: &mut [f32; NUM_OPERATORS],
+        a: &mut [f32; NUM_OPERATORS],
+    ) -> bool {
         let ad_scale = pow_2_fast((0.5 - parameters.envelope_control) * 8.0, 1);
         let r_scale = pow_2_fast(-f32::abs(parameters.envelope_control - 0.3) * 8.0, 1);
         let gate_duration = 1.5 * self.sample_rate;
@@ -212,7 +377,11 @@ impl<'a, const NUM_OPERATORS: usize, const NUM_ALGORITHMS: usize>
                 .0
                 .render(parameters.gate, envelope_rate, ad_scale, r_scale)
         };
-        let pitch_mod = pitch_envelope + parameters.pitch_mod;
+        let pitch_mod = if parameters.disable_pitch_envelope {
+            parameters.pitch_mod
+        } else {
+            pitch_envelope + parameters.pitch_mod
+        };
         let f0 = self.a0 * 0.25 * semitones_to_ratio_safe(parameters.note - 9.0 + pitch_mod * 12.0);
 
         // Sample the note and velocity (used for scaling) only when a trigger
@@ -225,7 +394,7 @@ impl<'a, const NUM_OPERATORS: usize, const NUM_ALGORITHMS: usize>
             self.note = parameters.note;
         }
 
-        if let Some(patch) = self.patch {
+        if let Some(patch) = &self.patch {
             // Reset operator phase if a note on is detected & if the patch requires it.
             if note_on && patch.reset_phase != 0 {
                 for i in 0..NUM_OPERATORS {
@@ -234,13 +403,11 @@ impl<'a, const NUM_OPERATORS: usize, const NUM_ALGORITHMS: usize>
             }
 
             // Compute frequencies and amplitudes.
-            let mut f = [0.0; NUM_OPERATORS];
-            let mut a = [0.0; NUM_OPERATORS];
-
             for i in 0..NUM_OPERATORS {
                 let op = &patch.op[i];
 
                 f[i] = self.ratios[i]
+                    * self.detune[i]
                     * (if self.ratios[i] < 0.0 {
                         -self.one_hz
                     } else {
@@ -283,31 +450,14 @@ impl<'a, const NUM_OPERATORS: usize, const NUM_ALGORITHMS: usize>
                 let sensitivity = amp_mod_sensitivity(op.amp_mod_sensitivity);
                 let log_level_mod = sensitivity * parameters.amp_mod - 1.0;
                 let level_mod = 1.0 - pow_2_fast(6.4 * log_level_mod, 2);
-                a[i] = pow_2_fast(-14.0 + level * level_mod, 2);
-            }
-
-            let mut i = 0;
-
-            while i < NUM_OPERATORS {
-                let call = self
-                    .algorithms
-                    .unwrap()
-                    .render_call(patch.algorithm as u32, i as u32);
-
-                if let Some(render_fn) = call.render_fn {
-                    render_fn(
-                        &mut self.operator[i..],
-                        &f[i..],
-                        &a[i..],
-                        &mut self.feedback_state,
-                        patch.feedback as i32,
-                        &buffers[call.input_index as usize],
-                        &buffers[call.output_index as usize],
-                    );
-                }
-
-                i += call.n as usize;
+                a[i] = if self.operator_mute[i] {
+                    0.0
+                } else {
+                    pow_2_fast(-14.0 + level * level_mod, 2)
+                };
             }
         }
+
+        self.patch.is_some()
     }
 }
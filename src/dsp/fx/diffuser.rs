@@ -4,9 +4,10 @@
 
 use super::{DataFormat12Bit, FxContext, FxEngine};
 use crate::dsp::SAMPLE_RATE;
+use crate::stmlib::dsp::crossfade;
 use crate::stmlib::dsp::delay_line::DelayLine;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Diffuser {
     ap1: DelayLine<i16, 126>,
     ap2: DelayLine<i16, 180>,
@@ -18,6 +19,13 @@ pub struct Diffuser {
 
     engine: FxEngine<8192, DataFormat12Bit>,
     lp_decay: f32,
+    mix: f32,
+}
+
+impl Default for Diffuser {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Diffuser {
@@ -33,6 +41,7 @@ impl Diffuser {
 
             engine: FxEngine::new(),
             lp_decay: 0.0,
+            mix: 1.0,
         }
     }
 
@@ -66,6 +75,7 @@ impl Diffuser {
 
         for in_out_sample in in_out.iter_mut() {
             self.engine.start(&mut c);
+            let dry = *in_out_sample;
 
             c.read(*in_out_sample);
             c.read_line(&mut self.ap1, kap);
@@ -88,8 +98,25 @@ impl Diffuser {
             c.write_with_scale(&mut wet, 0.0);
 
             *in_out_sample += amount * (wet - *in_out_sample);
+
+            if self.mix < 1.0 {
+                *in_out_sample = crossfade(dry, *in_out_sample, self.mix);
+            }
         }
 
         self.lp_decay = lp;
     }
+
+    /// Sets the balance between the input and the processed signal, from `0.0` (dry) to
+    /// `1.0` (fully processed), on top of the `amount` passed to [`Diffuser::process`].
+    /// Default is `1.0`.
+    #[inline]
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+    }
+
+    #[inline]
+    pub fn mix(&self) -> f32 {
+        self.mix
+    }
 }
@@ -4,9 +4,27 @@
 
 use super::{DataFormat32Bit, FxContext, FxEngine};
 use crate::dsp::oscillator::sine_oscillator::sine_raw;
+use crate::stmlib::dsp::crossfade;
 use crate::stmlib::dsp::delay_line::DelayLine;
 
-#[derive(Debug, Default)]
+/// Maximum number of delayed voices per channel.
+pub const MAX_VOICES: usize = 6;
+
+/// Default number of delayed voices per channel.
+pub const DEFAULT_VOICES: usize = 3;
+
+// Gain of each delayed voice by number of voices, keeping the sum of all voices at 0.99.
+const VOICE_GAIN: [f32; MAX_VOICES] = [0.99, 0.495, 0.33, 0.2475, 0.198, 0.165];
+
+// Initial LFO phases of the voices. The first three voices are 0.33 cycles apart.
+const VOICE_PHASE: [u32; MAX_VOICES] =
+    [0, 1417339207, 2834678415, 708669603, 2126008811, 3543348019];
+
+// LFO rates of the voices. The voices beyond the default ones have their own rates,
+// so that their modulation does not cancel out with the other voices.
+const VOICE_RATE: [f32; MAX_VOICES] = [1.0, 1.0, 1.0, 0.81, 1.23, 0.67];
+
+#[derive(Debug)]
 pub struct Ensemble {
     line_l: DelayLine<f32, 511>,
     line_r: DelayLine<f32, 511>,
@@ -15,9 +33,20 @@ pub struct Ensemble {
 
     amount: f32,
     depth: f32,
+    rate: f32,
 
-    phase_1: u32,
-    phase_2: u32,
+    phase_1: [u32; MAX_VOICES],
+    phase_2: [u32; MAX_VOICES],
+
+    voice_count: usize,
+    stereo: bool,
+    mix: f32,
+}
+
+impl Default for Ensemble {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Ensemble {
@@ -30,15 +59,20 @@ impl Ensemble {
 
             amount: 0.0,
             depth: 0.0,
+            rate: 1.0,
 
-            phase_1: 0,
-            phase_2: 0,
+            phase_1: VOICE_PHASE,
+            phase_2: VOICE_PHASE,
+
+            voice_count: DEFAULT_VOICES,
+            stereo: true,
+            mix: 1.0,
         }
     }
 
     pub fn init(&mut self) {
-        self.phase_1 = 0;
-        self.phase_2 = 0;
+        self.phase_1 = VOICE_PHASE;
+        self.phase_2 = VOICE_PHASE;
     }
 
     pub fn reset(&mut self) {
@@ -57,29 +91,23 @@ impl Ensemble {
 
         for (left_sample, right_sample) in left.iter_mut().zip(right.iter_mut()) {
             self.engine.start(&mut c);
+            let (dry_l, dry_r) = (*left_sample, *right_sample);
             let dry_amount = 1.0 - self.amount * 0.5;
 
-            // Update LFO.
-            let one_third = 1417339207;
-            let two_third = 2834678415;
-
-            self.phase_1 = self.phase_1.wrapping_add(67289); // 0.75 Hz
-            self.phase_2 = self.phase_2.wrapping_add(589980); // 6.57 Hz
-            let slow_0 = sine_raw(self.phase_1);
-            let slow_120 = sine_raw(self.phase_1.wrapping_add(one_third));
-            let slow_240 = sine_raw(self.phase_1.wrapping_add(two_third));
-            let fast_0 = sine_raw(self.phase_2);
-            let fast_120 = sine_raw(self.phase_2.wrapping_add(one_third));
-            let fast_240 = sine_raw(self.phase_2.wrapping_add(two_third));
-
             // Max deviation: 176
             let a = self.depth * 160.0;
             let b = self.depth * 16.0;
 
-            let mod_1 = slow_0 * a + fast_0 * b;
-            let mod_2 = slow_120 * a + fast_120 * b;
-            let mod_3 = slow_240 * a + fast_240 * b;
+            // Update the LFOs of the active voices.
+            let mut modulation = [0.0; MAX_VOICES];
+            for (n, modulation) in modulation.iter_mut().enumerate().take(self.voice_count) {
+                let rate = self.rate * VOICE_RATE[n];
+                self.phase_1[n] = self.phase_1[n].wrapping_add((67289.0 * rate) as u32); // 0.75 Hz
+                self.phase_2[n] = self.phase_2[n].wrapping_add((589980.0 * rate) as u32); // 6.57 Hz
+                *modulation = sine_raw(self.phase_1[n]) * a + sine_raw(self.phase_2[n]) * b;
+            }
 
+            let gain = VOICE_GAIN[self.voice_count - 1];
             let mut wet = 0.0;
 
             // Sum L & R channel to send to chorus line.
@@ -88,17 +116,43 @@ impl Ensemble {
             c.read_with_scale(*right_sample, 1.0);
             c.write_line(&mut self.line_r, 0.0);
 
-            c.interpolate(&mut self.line_l, mod_1 + 192.0, 0.0, 0.33);
-            c.interpolate(&mut self.line_l, mod_2 + 192.0, 0.0, 0.33);
-            c.interpolate(&mut self.line_r, mod_3 + 192.0, 0.0, 0.33);
+            // Every third voice is taken from the opposite channel.
+            for (n, modulation) in modulation.iter().take(self.voice_count).enumerate() {
+                let line = if n % 3 == 2 {
+                    &mut self.line_r
+                } else {
+                    &mut self.line_l
+                };
+                c.interpolate(line, modulation + 192.0, 0.0, gain);
+            }
             c.write(&mut wet);
-            *left_sample = wet * self.amount + *left_sample * dry_amount;
-
-            c.interpolate(&mut self.line_r, mod_1 + 192.0, 0.0, 0.33);
-            c.interpolate(&mut self.line_r, mod_2 + 192.0, 0.0, 0.33);
-            c.interpolate(&mut self.line_l, mod_3 + 192.0, 0.0, 0.33);
+            let wet_l = wet;
+
+            for (n, modulation) in modulation.iter().take(self.voice_count).enumerate() {
+                let line = if n % 3 == 2 {
+                    &mut self.line_l
+                } else {
+                    &mut self.line_r
+                };
+                c.interpolate(line, modulation + 192.0, 0.0, gain);
+            }
             c.write(&mut wet);
-            *right_sample = wet * self.amount + *right_sample * dry_amount;
+            let wet_r = wet;
+
+            let (wet_l, wet_r) = if self.stereo {
+                (wet_l, wet_r)
+            } else {
+                let wet = (wet_l + wet_r) * 0.5;
+                (wet, wet)
+            };
+
+            *left_sample = wet_l * self.amount + *left_sample * dry_amount;
+            *right_sample = wet_r * self.amount + *right_sample * dry_amount;
+
+            if self.mix < 1.0 {
+                *left_sample = crossfade(dry_l, *left_sample, self.mix);
+                *right_sample = crossfade(dry_r, *right_sample, self.mix);
+            }
         }
     }
 
@@ -111,4 +165,47 @@ impl Ensemble {
     pub fn set_depth(&mut self, depth: f32) {
         self.depth = depth;
     }
+
+    /// Sets the LFO rate as a multiple of the default rates (0.75 Hz and 6.57 Hz).
+    #[inline]
+    pub fn set_rate(&mut self, rate: f32) {
+        self.rate = rate;
+    }
+
+    /// Sets the number of delayed voices per channel (1 to [`MAX_VOICES`]). Fewer voices
+    /// give a plain chorus, more voices a denser ensemble. Default is [`DEFAULT_VOICES`].
+    /// The LFOs of the voices beyond `count` are paused.
+    #[inline]
+    pub fn set_voice_count(&mut self, count: usize) {
+        self.voice_count = count.clamp(1, MAX_VOICES);
+    }
+
+    #[inline]
+    pub fn voice_count(&self) -> usize {
+        self.voice_count
+    }
+
+    /// Enables different delayed voices on both channels. When disabled, both channels
+    /// receive the same wet signal. Default is `true`.
+    #[inline]
+    pub fn set_stereo(&mut self, stereo: bool) {
+        self.stereo = stereo;
+    }
+
+    #[inline]
+    pub fn stereo(&self) -> bool {
+        self.stereo
+    }
+
+    /// Sets the balance between the input and the processed signal, from `0.0` (dry) to
+    /// `1.0` (fully processed), e.g. to use the effect as a send. Default is `1.0`.
+    #[inline]
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+    }
+
+    #[inline]
+    pub fn mix(&self) -> f32 {
+        self.mix
+    }
 }
@@ -6,10 +6,25 @@ use crate::stmlib::dsp::clip_16;
 use crate::stmlib::dsp::filter::{FilterMode, FrequencyApproximation, Svf};
 use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 
+/// Response of the low pass gate to its control signal.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LpgResponse {
+    /// Amplitude only, the filter is bypassed.
+    Vca,
+
+    /// Filter only, the gain is ignored.
+    LowPass,
+
+    /// Amplitude and filter, blended by `hf_bleed`. This is the default.
+    #[default]
+    Blended,
+}
+
 #[derive(Debug, Default)]
 pub struct LowPassGate {
     previous_gain: f32,
     filter: Svf,
+    response: LpgResponse,
 }
 
 impl LowPassGate {
@@ -22,6 +37,20 @@ impl LowPassGate {
         self.filter.init();
     }
 
+    #[inline]
+    pub fn set_response(&mut self, response: LpgResponse) {
+        self.response = response;
+    }
+
+    #[inline]
+    fn response_parameters(&self, gain: f32, hf_bleed: f32) -> (f32, f32) {
+        match self.response {
+            LpgResponse::Vca => (gain, 1.0),
+            LpgResponse::LowPass => (1.0, 0.0),
+            LpgResponse::Blended => (gain, hf_bleed),
+        }
+    }
+
     #[inline]
     pub fn process_replacing(
         &mut self,
@@ -30,6 +59,7 @@ impl LowPassGate {
         hf_bleed: f32,
         in_out: &mut [f32],
     ) {
+        let (gain, hf_bleed) = self.response_parameters(gain, hf_bleed);
         let mut gain_modulation =
             ParameterInterpolator::new(&mut self.previous_gain, gain, in_out.len());
         self.filter
@@ -52,6 +82,7 @@ impl LowPassGate {
         out: &mut [i16],
         stride: usize,
     ) {
+        let (gain, hf_bleed) = self.response_parameters(gain, hf_bleed);
         let mut gain_modulation =
             ParameterInterpolator::new(&mut self.previous_gain, gain, out.len());
         self.filter
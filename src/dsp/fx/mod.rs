@@ -5,6 +5,8 @@ pub mod ensemble;
 pub mod low_pass_gate;
 pub mod overdrive;
 pub mod sample_rate_reducer;
+pub mod tape;
+pub mod widener;
 
 use core::marker::PhantomData;
 
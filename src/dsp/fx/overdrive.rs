@@ -2,23 +2,68 @@
 
 // Based on MIT-licensed code (c) 2014 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
+use crate::dsp::downsampler::Downsampler;
 use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
-use crate::stmlib::dsp::soft_clip;
+use crate::stmlib::dsp::{crossfade, soft_clip};
 
-#[derive(Debug, Default)]
+const OVERSAMPLING: usize = 4;
+
+#[derive(Debug)]
 pub struct Overdrive {
     pre_gain: f32,
     post_gain: f32,
+
+    antialias: bool,
+    previous_sample: f32,
+    fir_state: f32,
+
+    mix: f32,
+}
+
+impl Default for Overdrive {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Overdrive {
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            pre_gain: 0.0,
+            post_gain: 0.0,
+
+            antialias: false,
+            previous_sample: 0.0,
+            fir_state: 0.0,
+
+            mix: 1.0,
+        }
     }
 
     pub fn init(&mut self) {
         self.pre_gain = 0.0;
         self.post_gain = 0.0;
+        self.previous_sample = 0.0;
+        self.fir_state = 0.0;
+    }
+
+    /// Run the clipper at 4x the sample rate to reduce aliasing at high drive,
+    /// at a higher CPU cost. Default is `false`.
+    #[inline]
+    pub fn set_antialias(&mut self, antialias: bool) {
+        self.antialias = antialias;
+    }
+
+    /// Sets the balance between the input and the processed signal, from `0.0` (dry) to
+    /// `1.0` (fully processed). Default is `1.0`.
+    #[inline]
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+    }
+
+    #[inline]
+    pub fn mix(&self) -> f32 {
+        self.mix
     }
 
     #[inline]
@@ -36,9 +81,41 @@ impl Overdrive {
         let mut post_gain_modulation =
             ParameterInterpolator::new(&mut self.post_gain, post_gain, in_out.len());
 
-        for in_out_sample in in_out.iter_mut() {
-            let pre = pre_gain_modulation.next() * *in_out_sample;
-            *in_out_sample = soft_clip(pre) * post_gain_modulation.next();
+        if self.antialias {
+            let mut downsampler = Downsampler::new(&mut self.fir_state);
+
+            for in_out_sample in in_out.iter_mut() {
+                let pre_gain = pre_gain_modulation.next();
+
+                // Linear interpolation up to the oversampled rate.
+                let step = (*in_out_sample - self.previous_sample) / OVERSAMPLING as f32;
+                for j in 0..OVERSAMPLING {
+                    let sample = self.previous_sample + step * (j + 1) as f32;
+                    downsampler.accumulate(j, soft_clip(pre_gain * sample));
+                }
+                // Delay the dry signal by the latency of the resampling, 7/8 of a
+                // sample, so that both paths stay aligned.
+                let dry = self.previous_sample + step * 0.5;
+                self.previous_sample = *in_out_sample;
+
+                *in_out_sample = downsampler.read() * post_gain_modulation.next();
+
+                if self.mix < 1.0 {
+                    *in_out_sample = crossfade(dry, *in_out_sample, self.mix);
+                }
+            }
+        } else {
+            for in_out_sample in in_out.iter_mut() {
+                let dry = *in_out_sample;
+                let pre = pre_gain_modulation.next() * dry;
+                *in_out_sample = soft_clip(pre) * post_gain_modulation.next();
+
+                if self.mix < 1.0 {
+                    *in_out_sample = crossfade(dry, *in_out_sample, self.mix);
+                }
+            }
+
+            self.previous_sample = in_out.last().copied().unwrap_or(self.previous_sample);
         }
     }
 }
@@ -2,8 +2,19 @@
 
 // Based on MIT-licensed code (c) 2014 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
+#[allow(unused_imports)]
+use num_traits::float::Float;
+
 use crate::stmlib::dsp::polyblep::{next_blep_sample, this_blep_sample};
 
+/// Quantizes a signal in the range from `-1.0` to `1.0` to a resolution of `bits` bits,
+/// like a DAC of that resolution. Zero is preserved.
+#[inline]
+pub fn quantize(x: f32, bits: u32) -> f32 {
+    let scale = (1 << (bits.clamp(1, 24) - 1)) as f32;
+    (x * scale).round() / scale
+}
+
 #[derive(Debug, Default)]
 pub struct SampleRateReducer {
     phase: f32,
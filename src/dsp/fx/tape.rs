@@ -0,0 +1,181 @@
+//! Tape simulation.
+//!
+//! A pre-emphasis shelf boosts the treble before a soft saturation stage, so that high
+//! frequencies saturate first, and the matching de-emphasis shelf restores the balance
+//! afterwards. Wow and flutter modulate the delay of a short delay line.
+
+#[allow(unused_imports)]
+use num_traits::float::Float;
+
+use crate::dsp::oscillator::sine_oscillator::sine;
+use crate::dsp::SAMPLE_RATE;
+use crate::stmlib::dsp::delay_line::DelayLine;
+use crate::stmlib::dsp::filter::{FilterMode, FrequencyApproximation, OnePole};
+use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
+use crate::stmlib::dsp::{crossfade, soft_limit};
+
+/// Latency of the effect in samples, around which wow and flutter modulate the delay.
+pub const TAPE_DELAY: usize = 160;
+
+const DELAY_LINE_SIZE: usize = 512;
+
+// Corner frequency and gain of the emphasis shelves, +9.5 dB above 3 kHz.
+const EMPHASIS_FREQUENCY: f32 = 3000.0 / SAMPLE_RATE;
+const EMPHASIS_GAIN: f32 = 3.0;
+
+// Rates of the wow and flutter LFOs and their maximum pitch deviation, 17 and 5 cents.
+const WOW_FREQUENCY: f32 = 0.5 / SAMPLE_RATE;
+const WOW_DEVIATION: f32 = 0.01;
+const FLUTTER_FREQUENCY: f32 = 7.0 / SAMPLE_RATE;
+const FLUTTER_DEVIATION: f32 = 0.003;
+
+// Maximum drive of the saturation stage, +18 dB.
+const MAX_DRIVE: f32 = 8.0;
+
+#[derive(Debug)]
+pub struct Tape {
+    pre_emphasis: OnePole,
+    de_emphasis: OnePole,
+    line: DelayLine<f32, DELAY_LINE_SIZE>,
+
+    wow_phase: f32,
+    flutter_phase: f32,
+
+    wow: f32,
+    flutter: f32,
+    saturation: f32,
+    previous_saturation: f32,
+}
+
+impl Default for Tape {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tape {
+    pub fn new() -> Self {
+        let mut pre_emphasis = OnePole::new();
+        pre_emphasis.set_f(EMPHASIS_FREQUENCY, FrequencyApproximation::Exact);
+
+        // The inverse of the pre-emphasis shelf has its corner lower by the shelf gain,
+        // in the warped frequency domain of the filter.
+        let mut de_emphasis = OnePole::new();
+        let g = OnePole::tan(EMPHASIS_FREQUENCY, FrequencyApproximation::Exact) / EMPHASIS_GAIN;
+        de_emphasis.set_f(
+            g.atan() / core::f32::consts::PI,
+            FrequencyApproximation::Exact,
+        );
+
+        Self {
+            pre_emphasis,
+            de_emphasis,
+            line: DelayLine::new(),
+
+            wow_phase: 0.0,
+            flutter_phase: 0.0,
+
+            wow: 0.0,
+            flutter: 0.0,
+            saturation: 0.0,
+            previous_saturation: 0.0,
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.reset();
+        self.previous_saturation = self.saturation;
+    }
+
+    pub fn reset(&mut self) {
+        self.pre_emphasis.reset();
+        self.de_emphasis.reset();
+        self.line.reset();
+        self.wow_phase = 0.0;
+        self.flutter_phase = 0.0;
+    }
+
+    /// Set the depth of the slow pitch drift at 0.5 Hz, from `0.0` to `1.0` (17 cents).
+    /// Default is `0.0`.
+    #[inline]
+    pub fn set_wow(&mut self, wow: f32) {
+        self.wow = wow.clamp(0.0, 1.0);
+    }
+
+    #[inline]
+    pub fn wow(&self) -> f32 {
+        self.wow
+    }
+
+    /// Set the depth of the fast pitch modulation at 7 Hz, from `0.0` to `1.0` (5 cents).
+    /// Default is `0.0`.
+    #[inline]
+    pub fn set_flutter(&mut self, flutter: f32) {
+        self.flutter = flutter.clamp(0.0, 1.0);
+    }
+
+    #[inline]
+    pub fn flutter(&self) -> f32 {
+        self.flutter
+    }
+
+    /// Set the amount of saturation from `0.0` (clean) to `1.0` (+18 dB of drive). The
+    /// level of quiet signals is unchanged. Default is `0.0`.
+    #[inline]
+    pub fn set_saturation(&mut self, saturation: f32) {
+        self.saturation = saturation.clamp(0.0, 1.0);
+    }
+
+    #[inline]
+    pub fn saturation(&self) -> f32 {
+        self.saturation
+    }
+
+    /// Process a buffer in place. The signal is delayed by [`TAPE_DELAY`] samples.
+    #[inline]
+    pub fn process(&mut self, in_out: &mut [f32]) {
+        let mut saturation_modulation = ParameterInterpolator::new(
+            &mut self.previous_saturation,
+            self.saturation,
+            in_out.len(),
+        );
+
+        let wow_amplitude =
+            self.wow * WOW_DEVIATION / (2.0 * core::f32::consts::PI * WOW_FREQUENCY);
+        let flutter_amplitude =
+            self.flutter * FLUTTER_DEVIATION / (2.0 * core::f32::consts::PI * FLUTTER_FREQUENCY);
+
+        for in_out_sample in in_out.iter_mut() {
+            let high = self
+                .pre_emphasis
+                .process(*in_out_sample, FilterMode::HighPass);
+            let emphasized = *in_out_sample + (EMPHASIS_GAIN - 1.0) * high;
+
+            let saturation = saturation_modulation.next();
+            let drive = 1.0 + (MAX_DRIVE - 1.0) * saturation;
+            let saturated = crossfade(
+                emphasized,
+                soft_limit(emphasized * drive) / drive,
+                saturation,
+            );
+
+            self.wow_phase += WOW_FREQUENCY;
+            if self.wow_phase >= 1.0 {
+                self.wow_phase -= 1.0;
+            }
+            self.flutter_phase += FLUTTER_FREQUENCY;
+            if self.flutter_phase >= 1.0 {
+                self.flutter_phase -= 1.0;
+            }
+
+            self.line.write(saturated);
+            let delay = TAPE_DELAY as f32
+                + wow_amplitude * sine(self.wow_phase)
+                + flutter_amplitude * sine(self.flutter_phase);
+            let delayed = self.line.read_hermite(delay);
+
+            let low = self.de_emphasis.process(delayed, FilterMode::LowPass);
+            *in_out_sample = low + (delayed - low) / EMPHASIS_GAIN;
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Stereo widener.
+//!
+//! Turns a mono signal into a stereo pair by adding a delayed copy of the signal
+//! to the left channel and subtracting it from the right one (Haas effect in the
+//! side channel). Both channels sum back to the dry signal, so the effect is
+//! mono compatible at any width.
+
+use crate::stmlib::dsp::delay_line::DelayLine;
+use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
+
+/// Maximum delay between the mid and side channel in samples.
+pub const MAX_WIDENER_DELAY: usize = 2048;
+
+#[derive(Debug)]
+pub struct Widener {
+    line: DelayLine<f32, MAX_WIDENER_DELAY>,
+
+    delay: usize,
+    width: f32,
+    previous_width: f32,
+}
+
+impl Default for Widener {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widener {
+    pub fn new() -> Self {
+        Self {
+            line: DelayLine::new(),
+
+            delay: 480,
+            width: 0.0,
+            previous_width: 0.0,
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.line.init();
+        self.previous_width = self.width;
+    }
+
+    pub fn reset(&mut self) {
+        self.line.reset();
+    }
+
+    /// Set the width from `0.0` (mono) to `1.0`. Default is `0.0`.
+    #[inline]
+    pub fn set_width(&mut self, width: f32) {
+        self.width = width.clamp(0.0, 1.0);
+    }
+
+    /// Set the delay of the side channel in samples. Default is 480 (10 ms).
+    #[inline]
+    pub fn set_delay_samples(&mut self, delay: usize) {
+        self.delay = delay.clamp(1, MAX_WIDENER_DELAY - 1);
+    }
+
+    /// Process a mono buffer into a stereo pair. All buffers must have the same length.
+    #[inline]
+    pub fn process(&mut self, in_: &[f32], left: &mut [f32], right: &mut [f32]) {
+        let mut width_modulation =
+            ParameterInterpolator::new(&mut self.previous_width, self.width, in_.len());
+
+        for ((in_sample, left_sample), right_sample) in
+            in_.iter().zip(left.iter_mut()).zip(right.iter_mut())
+        {
+            self.line.write(*in_sample);
+            let side = self.line.read_with_delay(self.delay) * width_modulation.next();
+            *left_sample = *in_sample + side;
+            *right_sample = *in_sample - side;
+        }
+    }
+}
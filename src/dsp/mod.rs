@@ -13,6 +13,7 @@ pub mod fx;
 pub mod noise;
 pub mod oscillator;
 pub mod physical_modelling;
+pub mod polyphony;
 pub mod resources;
 pub mod speech;
 pub mod voice;
@@ -22,8 +23,31 @@ use core::alloc::{GlobalAlloc, Layout};
 /// Audio sample rate in Hz.
 pub const SAMPLE_RATE: f32 = 48000.0;
 
-/// Normalized frequency of note A0.
-pub const A0: f32 = (440.0 / 8.0) / SAMPLE_RATE;
+/// Frequency of the reference note `A0` of the firmware in Hz.
+///
+/// Despite its name, this is 55 Hz, i.e. MIDI note 33 (A1). [`engine::note_to_frequency`]
+/// takes MIDI notes, so note 33 maps to [`A0`] and note 21 to a quarter of it.
+pub const A0_HZ: f32 = 440.0 / 8.0;
+
+/// Normalized frequency of the reference note `A0`, i.e. [`A0_HZ`] divided by
+/// [`SAMPLE_RATE`].
+///
+/// All pitch helpers of the crate, e.g. [`engine::note_to_frequency`], return frequencies
+/// normalized this way. Use [`normalized_to_hz`] and [`hz_to_normalized`] to convert
+/// from and to absolute frequencies instead of rescaling by hand.
+pub const A0: f32 = A0_HZ / SAMPLE_RATE;
+
+/// Converts a frequency normalized to [`SAMPLE_RATE`] to Hz.
+#[inline]
+pub fn normalized_to_hz(frequency: f32) -> f32 {
+    frequency * SAMPLE_RATE
+}
+
+/// Converts a frequency in Hz to a frequency normalized to [`SAMPLE_RATE`].
+#[inline]
+pub fn hz_to_normalized(frequency: f32) -> f32 {
+    frequency / SAMPLE_RATE
+}
 
 /// Allocate a zeroed buffer of f32s with a given number of elements.
 pub fn allocate_buffer<A: GlobalAlloc>(
@@ -58,5 +82,32 @@ pub fn allocate<T, A: GlobalAlloc>(allocator: &A) -> Result<&'static mut T, Allo
     block.ok_or(AllocError)
 }
 
+/// Moves `value` into memory provided by `allocator`.
+///
+/// Like the buffers from [`allocate_buffer`], the memory is never returned to the
+/// allocator and `value` is never dropped.
+pub fn allocate_value<'a, T, A: GlobalAlloc>(
+    allocator: &A,
+    value: T,
+) -> Result<&'a mut T, AllocError> {
+    let layout = Layout::new::<T>();
+
+    if layout.size() == 0 {
+        let block = core::ptr::NonNull::<T>::dangling().as_ptr();
+        unsafe { block.write(value) };
+        return Ok(unsafe { &mut *block });
+    }
+
+    let block = unsafe { allocator.alloc(layout) as *mut T };
+
+    if block.is_null() {
+        return Err(AllocError);
+    }
+
+    unsafe { block.write(value) };
+
+    Ok(unsafe { &mut *block })
+}
+
 #[derive(Debug)]
 pub struct AllocError;
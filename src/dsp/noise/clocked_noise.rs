@@ -4,7 +4,7 @@
 
 use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 use crate::stmlib::dsp::polyblep::{next_blep_sample, this_blep_sample};
-use crate::stmlib::utils::random;
+use crate::stmlib::utils::random::RandomSource;
 
 #[derive(Debug, Default)]
 pub struct ClockedNoise {
@@ -15,6 +15,8 @@ pub struct ClockedNoise {
 
     // For interpolation of parameters.
     frequency: f32,
+
+    random: RandomSource,
 }
 
 impl ClockedNoise {
@@ -27,6 +29,12 @@ impl ClockedNoise {
         self.sample = 0.0;
         self.next_sample = 0.0;
         self.frequency = 0.001;
+        self.random.init(None, 0);
+    }
+
+    /// Seeds the random source, see [`RandomSource::init`].
+    pub(crate) fn init_random(&mut self, seed: Option<u32>, stream: u32) {
+        self.random.init(seed, stream);
     }
 
     #[inline]
@@ -47,7 +55,7 @@ impl ClockedNoise {
             next_sample = 0.0;
 
             let frequency = fm.next();
-            let raw_sample = random::get_float() * 2.0 - 1.0;
+            let raw_sample = self.random.get_float() * 2.0 - 1.0;
             let raw_amount = 4.0 * (frequency - 0.25);
             let raw_amount = raw_amount.clamp(0.0, 1.0);
 
@@ -6,8 +6,15 @@ use crate::stmlib::utils::random;
 
 #[inline]
 pub fn dust(frequency: f32) -> f32 {
+    dust_with_random(frequency, &mut random::get_float)
+}
+
+/// Like `dust`, drawing uniform random numbers in `[0, 1)` from `random` instead of the
+/// shared generator.
+#[inline]
+pub fn dust_with_random<R: FnMut() -> f32>(frequency: f32, random: &mut R) -> f32 {
     let inv_frequency = 1.0 / frequency;
-    let u = random::get_float();
+    let u = random();
 
     if u < frequency {
         u * inv_frequency
@@ -2,5 +2,6 @@
 
 pub mod clocked_noise;
 pub mod dust;
+pub mod noise_color;
 pub mod particle;
 pub mod smooth_random_generator;
@@ -0,0 +1,96 @@
+//! Spectral shaping of white noise into pink or brown noise.
+
+/// Spectral tilt of a noise source.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseColor {
+    /// Flat spectrum.
+    #[default]
+    White,
+
+    /// -3 dB per octave.
+    Pink,
+
+    /// -6 dB per octave, down to about 10 Hz.
+    Brown,
+}
+
+// Pole and input gain of the parallel one-pole filters of the pink noise approximation
+// by Paul Kellet, accurate to ±0.05 dB above 9.2 Hz at 44.1 kHz.
+const PINK_POLES: [f32; 6] = [0.99886, 0.99332, 0.969, 0.8665, 0.55, -0.7616];
+const PINK_GAINS: [f32; 6] = [
+    0.055_517_9,
+    0.075_075_9,
+    0.153_852,
+    0.310_485_6,
+    0.532_952_2,
+    -0.016_898,
+];
+const PINK_DIRECT_GAIN: f32 = 0.5362;
+const PINK_DELAYED_GAIN: f32 = 0.115_926;
+const PINK_NORMALIZATION: f32 = 0.2;
+
+// Leaky integration with a corner at about 10 Hz removes DC from brown noise.
+const BROWN_LEAK: f32 = 0.9987;
+const BROWN_NORMALIZATION: f32 = 0.05;
+
+/// Filter turning white noise into noise of a given [`NoiseColor`], keeping the level
+/// roughly constant.
+#[derive(Debug, Default)]
+pub struct NoiseColorFilter {
+    color: NoiseColor,
+    pink_state: [f32; 6],
+    pink_delayed: f32,
+    brown_state: f32,
+}
+
+impl NoiseColorFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn init(&mut self) {
+        self.pink_state = [0.0; 6];
+        self.pink_delayed = 0.0;
+        self.brown_state = 0.0;
+    }
+
+    #[inline]
+    pub fn set_color(&mut self, color: NoiseColor) {
+        self.color = color;
+    }
+
+    #[inline]
+    pub fn color(&self) -> NoiseColor {
+        self.color
+    }
+
+    #[inline]
+    pub fn process(&mut self, in_out: &mut [f32]) {
+        match self.color {
+            NoiseColor::White => {}
+            NoiseColor::Pink => {
+                for in_out_sample in in_out.iter_mut() {
+                    let white = *in_out_sample;
+                    let mut pink = self.pink_delayed + white * PINK_DIRECT_GAIN;
+                    for ((state, pole), gain) in self
+                        .pink_state
+                        .iter_mut()
+                        .zip(PINK_POLES.iter())
+                        .zip(PINK_GAINS.iter())
+                    {
+                        *state = pole * *state + white * gain;
+                        pink += *state;
+                    }
+                    self.pink_delayed = white * PINK_DELAYED_GAIN;
+                    *in_out_sample = pink * PINK_NORMALIZATION;
+                }
+            }
+            NoiseColor::Brown => {
+                for in_out_sample in in_out.iter_mut() {
+                    self.brown_state = BROWN_LEAK * self.brown_state + *in_out_sample;
+                    *in_out_sample = self.brown_state * BROWN_NORMALIZATION;
+                }
+            }
+        }
+    }
+}
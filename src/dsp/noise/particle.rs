@@ -36,7 +36,36 @@ impl Particle {
         out: &mut [f32],
         aux: &mut [f32],
     ) {
-        let mut u = random::get_float();
+        self.render_with_random(
+            sync,
+            density,
+            gain,
+            frequency,
+            spread,
+            q,
+            out,
+            aux,
+            &mut random::get_float,
+        );
+    }
+
+    /// Render like `render`, drawing uniform random numbers in `[0, 1)` from `random`
+    /// instead of the shared generator.
+    #[allow(clippy::too_many_arguments)]
+    #[inline]
+    pub fn render_with_random<R: FnMut() -> f32>(
+        &mut self,
+        sync: bool,
+        density: f32,
+        gain: f32,
+        frequency: f32,
+        spread: f32,
+        q: f32,
+        out: &mut [f32],
+        aux: &mut [f32],
+        random: &mut R,
+    ) {
+        let mut u = random();
         if sync {
             u = density;
         }
@@ -47,7 +76,7 @@ impl Particle {
             if u <= density {
                 s = u * gain;
                 if can_radomize_frequency {
-                    let u = 2.0 * random::get_float() - 1.0;
+                    let u = 2.0 * random() - 1.0;
                     let f = f32::min(semitones_to_ratio(spread * u) * frequency, 0.25);
                     self.pre_gain = 0.5 / sqrt(q * f * sqrt(density));
                     self.filter.set_f_q(f, q, FrequencyApproximation::Dirty);
@@ -57,7 +86,7 @@ impl Particle {
             }
             *aux_sample += s;
             *out_sample += self.filter.process(self.pre_gain * s, FilterMode::BandPass);
-            u = random::get_float();
+            u = random();
         }
     }
 }
@@ -2,13 +2,15 @@
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
-use crate::stmlib::utils::random;
+use crate::stmlib::utils::random::RandomSource;
 
 #[derive(Debug, Default)]
 pub struct SmoothRandomGenerator {
     phase: f32,
     from: f32,
     interval: f32,
+
+    random: RandomSource,
 }
 
 impl SmoothRandomGenerator {
@@ -20,6 +22,7 @@ impl SmoothRandomGenerator {
         self.phase = 0.0;
         self.from = 0.0;
         self.interval = 0.0;
+        self.random.init(None, 0);
     }
 
     #[inline]
@@ -29,7 +32,7 @@ impl SmoothRandomGenerator {
         if self.phase >= 1.0 {
             self.phase -= 1.0;
             self.from += self.interval;
-            self.interval = random::get_float() * 2.0 - 1.0 - self.from;
+            self.interval = self.random.get_float() * 2.0 - 1.0 - self.from;
         }
 
         let t = self.phase * self.phase * (3.0 - 2.0 * self.phase);
@@ -35,6 +35,10 @@ impl FormantOscillator {
         self.phase_shift = 0.0;
     }
 
+    /// Renders a block.
+    ///
+    /// Like the frequencies, `phase_shift` is interpolated across the block from the
+    /// value of the previous call, so it can be swept from block to block without clicks.
     #[inline]
     pub fn render(
         &mut self,
@@ -5,11 +5,31 @@
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
+#[allow(unused_imports)]
+use num_traits::float::Float;
+
 use crate::dsp::oscillator::oscillator::MAX_FREQUENCY;
 use crate::dsp::oscillator::sine_oscillator::sine;
 use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 use crate::stmlib::dsp::polyblep::this_blep_sample;
 
+/// Envelope applied to each grain.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum GrainletWindow {
+    /// Phase-distorted sine, its width and skew controlled by the carrier shape.
+    #[default]
+    Sine,
+
+    /// Raised cosine.
+    Hann,
+
+    /// Truncated gaussian.
+    Gaussian,
+
+    /// Linear attack and decay.
+    Triangular,
+}
+
 #[derive(Debug, Default)]
 pub struct GrainletOscillator {
     // Oscillator state.
@@ -22,6 +42,8 @@ pub struct GrainletOscillator {
     formant_frequency: f32,
     carrier_shape: f32,
     carrier_bleed: f32,
+
+    window: GrainletWindow,
 }
 
 impl GrainletOscillator {
@@ -40,6 +62,18 @@ impl GrainletOscillator {
         self.carrier_bleed = 0.0;
     }
 
+    /// Selects the envelope applied to each grain. For all windows except
+    /// [`GrainletWindow::Sine`], the carrier shape narrows the grain.
+    #[inline]
+    pub fn set_window(&mut self, window: GrainletWindow) {
+        self.window = window;
+    }
+
+    #[inline]
+    pub fn window(&self) -> GrainletWindow {
+        self.window
+    }
+
     #[inline]
     pub fn render(
         &mut self,
@@ -66,6 +100,7 @@ impl GrainletOscillator {
             ParameterInterpolator::new(&mut self.carrier_bleed, carrier_bleed, out.len());
 
         let mut next_sample = self.next_sample;
+        let window = self.window;
 
         for out_sample in out.iter_mut() {
             let mut this_sample = next_sample;
@@ -81,6 +116,7 @@ impl GrainletOscillator {
                 self.carrier_phase -= 1.0;
                 let reset_time = self.carrier_phase / f0;
                 let before = grainlet(
+                    window,
                     1.0,
                     self.formant_phase + (1.0 - reset_time) * f1,
                     carrier_shape_modulation.subsample(1.0 - reset_time),
@@ -88,6 +124,7 @@ impl GrainletOscillator {
                 );
 
                 let after = grainlet(
+                    window,
                     0.0,
                     0.0,
                     carrier_shape_modulation.subsample(1.0),
@@ -106,6 +143,7 @@ impl GrainletOscillator {
             }
 
             next_sample += grainlet(
+                window,
                 self.carrier_phase,
                 self.formant_phase,
                 carrier_shape_modulation.next(),
@@ -150,8 +188,39 @@ fn carrier(mut phase: f32, mut shape: f32) -> f32 {
 }
 
 #[inline]
-fn grainlet(carrier_phase: f32, formant_phase: f32, shape: f32, bleed: f32) -> f32 {
-    let carrier = carrier(carrier_phase, shape);
+fn window_envelope(window: GrainletWindow, phase: f32, shape: f32) -> f32 {
+    let t = 1.0 - shape;
+    let x = phase * (1.0 + t * t * t * 15.0);
+    if window != GrainletWindow::Sine && x >= 1.0 {
+        return 0.0;
+    }
+
+    match window {
+        GrainletWindow::Sine => carrier(phase, shape),
+        GrainletWindow::Hann => 0.5 + 0.5 * sine(x + 0.75),
+        GrainletWindow::Gaussian => {
+            // Offset and scaled so that the window reaches 0 at both ends.
+            let x = (x - 0.5) * (1.0 / GAUSSIAN_WIDTH);
+            (Float::exp(-0.5 * x * x) - GAUSSIAN_EDGE) * (1.0 / (1.0 - GAUSSIAN_EDGE))
+        }
+        GrainletWindow::Triangular => 1.0 - (2.0 * x - 1.0).abs(),
+    }
+}
+
+const GAUSSIAN_WIDTH: f32 = 0.15;
+
+// exp(-0.5 * (0.5 / GAUSSIAN_WIDTH)^2)
+const GAUSSIAN_EDGE: f32 = 0.003_865_9;
+
+#[inline]
+fn grainlet(
+    window: GrainletWindow,
+    carrier_phase: f32,
+    formant_phase: f32,
+    shape: f32,
+    bleed: f32,
+) -> f32 {
+    let carrier = window_envelope(window, carrier_phase, shape);
     let formant = sine(formant_phase);
     carrier * (formant + bleed) / (1.0 + bleed)
 }
@@ -15,6 +15,9 @@ use crate::stmlib::dsp::polyblep::{
 pub const MAX_FREQUENCY: f32 = 0.25;
 pub const MIN_FREQUENCY: f32 = 0.000001;
 
+/// Per-sample recovery of the FM limiter gain, about 20 ms at 48 kHz.
+const FM_LIMITER_RELEASE: f32 = 0.001;
+
 #[derive(Debug)]
 pub enum OscillatorShape {
     ImpulseTrain,
@@ -39,6 +42,10 @@ pub struct Oscillator {
     // For interpolation of parameters.
     frequency: f32,
     pw: f32,
+
+    // FM depth limiter.
+    fm_limit: Option<f32>,
+    fm_gain: f32,
 }
 
 impl Oscillator {
@@ -55,6 +62,23 @@ impl Oscillator {
 
         self.frequency = 0.001;
         self.pw = 0.5;
+
+        self.fm_gain = 1.0;
+    }
+
+    /// Limit the instantaneous frequency reached with external FM to `limit`, normalized
+    /// to the sample rate, by attenuating the modulator instead of clamping the frequency.
+    /// The attenuation is released slowly, avoiding the harsh aliasing of a modulator
+    /// repeatedly hitting the frequency clamp. Default is `None`, which disables the
+    /// limiter.
+    #[inline]
+    pub fn set_fm_limit(&mut self, limit: Option<f32>) {
+        self.fm_limit = limit.map(|limit| limit.clamp(MIN_FREQUENCY, MAX_FREQUENCY));
+    }
+
+    #[inline]
+    pub fn fm_limit(&self) -> Option<f32> {
+        self.fm_limit
     }
 
     #[inline]
@@ -88,7 +112,19 @@ impl Oscillator {
 
             let mut frequency = fm.next();
             if let Some(external_fm) = external_fm {
-                frequency *= 1.0 + external_fm[external_fm_index];
+                let modulator = external_fm[external_fm_index];
+                if let Some(limit) = self.fm_limit {
+                    self.fm_gain += (1.0 - self.fm_gain) * FM_LIMITER_RELEASE;
+                    let modulated = frequency * (1.0 + modulator * self.fm_gain);
+                    if modulated.abs() > limit {
+                        let target = limit.copysign(modulated);
+                        self.fm_gain =
+                            ((target / frequency - 1.0) / modulator).clamp(0.0, self.fm_gain);
+                    }
+                    frequency *= 1.0 + modulator * self.fm_gain;
+                } else {
+                    frequency *= 1.0 + modulator;
+                }
                 external_fm_index += 1;
                 if !through_zero_fm {
                     frequency = frequency.clamp(MIN_FREQUENCY, MAX_FREQUENCY);
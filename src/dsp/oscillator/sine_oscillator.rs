@@ -1,10 +1,13 @@
 //! Simple sine oscillator (wavetable) + fast sine oscillator (magic circle).
 //
 //! The fast implementation might glitch a bit under heavy modulations of the
-//! frequency.
+//! frequency, which can be corrected with [`FastSineOscillator::resync`].
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
+#[allow(unused_imports)]
+use num_traits::float::Float;
+
 use crate::dsp::resources::sine::{LUT_SINE, LUT_SINE_BITS, LUT_SINE_SIZE};
 use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 use crate::stmlib::dsp::rsqrt::fast_rsqrt_carmack;
@@ -134,6 +137,15 @@ impl FastSineOscillator {
         self.amplitude = 0.0;
     }
 
+    /// Moves the state back onto the unit circle at `phase` (in cycles), removing any
+    /// amplitude drift. The next rendered sample continues from `cos(2π phase)`.
+    #[inline]
+    pub fn resync(&mut self, phase: f32) {
+        let phase = phase - phase.floor();
+        self.x = sine(phase + 0.25);
+        self.y = -sine(phase);
+    }
+
     #[inline]
     pub fn render(&mut self, frequency: f32, out: &mut [f32]) {
         self.render_internal(frequency, 1.0, out, RenderMode::Normal);
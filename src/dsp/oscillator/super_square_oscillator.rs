@@ -1,15 +1,82 @@
 //! Two hard-sync'ed square waves with a meta-parameter, also faking PWM.
 //! Based on VariableShapeOscillator, with hard-coded pulse width (0.5),
 //! waveshape (only square), and sync enabled by default.
+//!
+//! Optionally, several detuned copies of the oscillator can be stacked.
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
 use crate::dsp::oscillator::oscillator::MAX_FREQUENCY;
 use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 use crate::stmlib::dsp::polyblep::{next_blep_sample, this_blep_sample};
+use crate::stmlib::dsp::units::semitones_to_ratio;
 
-#[derive(Debug, Default)]
+pub const MAX_SUPER_SQUARE_VOICES: usize = 7;
+
+#[derive(Debug)]
 pub struct SuperSquareOscillator {
+    voice: [SquarePair; MAX_SUPER_SQUARE_VOICES],
+    voice_count: usize,
+    detune: f32,
+}
+
+impl Default for SuperSquareOscillator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SuperSquareOscillator {
+    pub fn new() -> Self {
+        Self {
+            voice: core::array::from_fn(|_| SquarePair::default()),
+            voice_count: 1,
+            detune: 0.0,
+        }
+    }
+
+    pub fn init(&mut self) {
+        for voice in self.voice.iter_mut() {
+            voice.init();
+        }
+    }
+
+    /// Set the number of stacked oscillators in the range from `1` to
+    /// `MAX_SUPER_SQUARE_VOICES`. Default is `1`.
+    #[inline]
+    pub fn set_voice_count(&mut self, voice_count: usize) {
+        self.voice_count = voice_count.clamp(1, MAX_SUPER_SQUARE_VOICES);
+    }
+
+    /// Set the detuning in cents between the lowest and the highest stacked
+    /// oscillator, which are spread evenly around the frequency. Default is `0.0`.
+    #[inline]
+    pub fn set_detune(&mut self, cents: f32) {
+        self.detune = cents.max(0.0);
+    }
+
+    #[inline]
+    pub fn render(&mut self, frequency: f32, shape: f32, out: &mut [f32]) {
+        out.fill(0.0);
+
+        let voice_count = self.voice_count;
+        let gain = 1.0 / voice_count as f32;
+
+        for (i, voice) in self.voice.iter_mut().take(voice_count).enumerate() {
+            let ratio = if voice_count > 1 {
+                let position = i as f32 / (voice_count - 1) as f32 - 0.5;
+                semitones_to_ratio(position * self.detune * 0.01)
+            } else {
+                1.0
+            };
+
+            voice.render_add(frequency * ratio, shape, gain, out);
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct SquarePair {
     master_phase: f32,
     slave_phase: f32,
     next_sample: f32,
@@ -19,12 +86,8 @@ pub struct SuperSquareOscillator {
     slave_frequency: f32,
 }
 
-impl SuperSquareOscillator {
-    pub fn new() -> Self {
-        Self::default()
-    }
-
-    pub fn init(&mut self) {
+impl SquarePair {
+    fn init(&mut self) {
         self.master_phase = 0.0;
         self.slave_phase = 0.0;
         self.next_sample = 0.0;
@@ -35,7 +98,7 @@ impl SuperSquareOscillator {
     }
 
     #[inline]
-    pub fn render(&mut self, mut frequency: f32, shape: f32, out: &mut [f32]) {
+    fn render_add(&mut self, mut frequency: f32, shape: f32, gain: f32, out: &mut [f32]) {
         let mut master_frequency = frequency;
         frequency *= if shape < 0.5 {
             0.51 + 0.98 * shape
@@ -125,7 +188,7 @@ impl SuperSquareOscillator {
             }
 
             next_sample += if self.slave_phase < 0.5 { 0.0 } else { 1.0 };
-            *out_sample = 2.0 * this_sample - 1.0;
+            *out_sample += (2.0 * this_sample - 1.0) * gain;
         }
 
         self.next_sample = next_sample;
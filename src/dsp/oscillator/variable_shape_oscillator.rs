@@ -55,6 +55,26 @@ impl VariableShapeOscillator {
         self.master_phase = phase;
     }
 
+    #[inline]
+    pub fn master_phase(&self) -> f32 {
+        self.master_phase
+    }
+
+    #[inline]
+    pub fn slave_phase(&self) -> f32 {
+        self.slave_phase
+    }
+
+    /// Sets the phases of the master and slave oscillators (0.0 to 1.0) and clears any
+    /// pending discontinuity, for reproducible sync starts. With sync enabled, the slave
+    /// resets on the first sample at which the master phase reaches 1.0.
+    pub fn reset_phases(&mut self, master: f32, slave: f32) {
+        self.master_phase = master - master.floor();
+        self.slave_phase = slave - slave.floor();
+        self.next_sample = 0.0;
+        self.high = self.slave_phase >= self.previous_pw;
+    }
+
     #[allow(clippy::too_many_arguments)]
     #[inline]
     pub fn render(
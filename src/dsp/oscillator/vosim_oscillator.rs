@@ -38,11 +38,37 @@ impl VosimOscillator {
 
     #[inline]
     pub fn render(
+        &mut self,
+        carrier_frequency: f32,
+        formant_frequency_1: f32,
+        formant_frequency_2: f32,
+        carrier_shape: f32,
+        out: &mut [f32],
+    ) {
+        self.render_ex(
+            carrier_frequency,
+            formant_frequency_1,
+            formant_frequency_2,
+            carrier_shape,
+            1.0,
+            1.0,
+            out,
+        );
+    }
+
+    /// Render with explicit amplitudes for the two formants.
+    ///
+    /// `render` is equivalent to calling this with both amplitudes set to `1.0`.
+    #[allow(clippy::too_many_arguments)]
+    #[inline]
+    pub fn render_ex(
         &mut self,
         mut carrier_frequency: f32,
         mut formant_frequency_1: f32,
         mut formant_frequency_2: f32,
         carrier_shape: f32,
+        formant_amplitude_1: f32,
+        formant_amplitude_2: f32,
         out: &mut [f32],
     ) {
         if carrier_frequency >= MAX_FREQUENCY {
@@ -97,7 +123,10 @@ impl VosimOscillator {
             let reset_amplitude = sine(reset_phase);
             let formant_0 = sine(self.formant_1_phase + reset_phase) - reset_amplitude;
             let formant_1 = sine(self.formant_2_phase + reset_phase) - reset_amplitude;
-            *out_sample = carrier * (formant_0 + formant_1) * 0.25 + reset_amplitude;
+            *out_sample = carrier
+                * (formant_0 * formant_amplitude_1 + formant_1 * formant_amplitude_2)
+                * 0.25
+                + reset_amplitude;
         }
     }
 }
@@ -8,6 +8,22 @@ use crate::dsp::oscillator::oscillator::MAX_FREQUENCY;
 use crate::stmlib::dsp::one_pole;
 use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 
+/// Interpolation between adjacent waves of the wavetable.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WaveInterp {
+    /// Linear crossfade between two waves, as in the original firmware.
+    Linear,
+
+    /// Hermite (Catmull-Rom) interpolation over four waves, without kinks at the waves.
+    /// This is the default.
+    #[default]
+    Hermite,
+
+    /// Cubic B-spline over four waves. The smoothest transition, at the cost of slightly
+    /// blurring the individual waves.
+    Cubic,
+}
+
 #[derive(Debug, Default)]
 pub struct WavetableOscillator {
     // Oscillator state.
@@ -20,6 +36,8 @@ pub struct WavetableOscillator {
     lp: f32,
 
     differentiator: Differentiator,
+    interpolation: WaveInterp,
+    reverse: bool,
 }
 
 impl WavetableOscillator {
@@ -36,6 +54,30 @@ impl WavetableOscillator {
         self.differentiator.init();
     }
 
+    /// Sets the interpolation between adjacent waves when sweeping the waveform. The
+    /// waves themselves are always read with linear interpolation.
+    #[inline]
+    pub fn set_interpolation(&mut self, interpolation: WaveInterp) {
+        self.interpolation = interpolation;
+    }
+
+    #[inline]
+    pub fn interpolation(&self) -> WaveInterp {
+        self.interpolation
+    }
+
+    /// Reads the waves backward, playing them time-reversed. The waves are read with the
+    /// same interpolation and band-limiting as forward. Default is `false`.
+    #[inline]
+    pub fn set_reverse(&mut self, reverse: bool) {
+        self.reverse = reverse;
+    }
+
+    #[inline]
+    pub fn reverse(&self) -> bool {
+        self.reverse
+    }
+
     #[allow(clippy::too_many_arguments)]
     #[inline]
     pub fn render(
@@ -83,9 +125,16 @@ impl WavetableOscillator {
                 1.0 / (f0 * 131072.0)
             };
 
-            phase += f0;
-            if phase >= 1.0 {
-                phase -= 1.0;
+            if self.reverse {
+                phase -= f0;
+                if phase < 0.0 {
+                    phase += 1.0;
+                }
+            } else {
+                phase += f0;
+                if phase >= 1.0 {
+                    phase -= 1.0;
+                }
             }
 
             let waveform = waveform_modulation.next();
@@ -99,9 +148,32 @@ impl WavetableOscillator {
             let x0 = interpolate_wave(wavetable[waveform_integral], p_integral, p_fractional);
             let x1 = interpolate_wave(wavetable[waveform_integral + 1], p_integral, p_fractional);
 
-            let s = self
-                .differentiator
-                .process(cutoff, (x0 + (x1 - x0) * waveform_fractional) * scale);
+            let x = match self.interpolation {
+                WaveInterp::Linear => x0 + (x1 - x0) * waveform_fractional,
+                WaveInterp::Hermite | WaveInterp::Cubic => {
+                    let xm1 = interpolate_wave(
+                        wavetable[waveform_integral.saturating_sub(1)],
+                        p_integral,
+                        p_fractional,
+                    );
+                    let x2 = interpolate_wave(
+                        wavetable[(waveform_integral + 2).min(num_waves - 1)],
+                        p_integral,
+                        p_fractional,
+                    );
+                    if self.interpolation == WaveInterp::Hermite {
+                        hermite(xm1, x0, x1, x2, waveform_fractional)
+                    } else {
+                        b_spline(xm1, x0, x1, x2, waveform_fractional)
+                    }
+                }
+            };
+
+            // The waves are integrated, so that reading them backward inverts the
+            // polarity of their derivative.
+            let x = if self.reverse { -x } else { x };
+
+            let s = self.differentiator.process(cutoff, x * scale);
             one_pole(&mut lp, s, cutoff);
             *out_sample += amplitude_modulation.next() * lp;
         }
@@ -155,12 +227,30 @@ where
     let x0 = table[index_integral + 1].to_f32().unwrap_or_default();
     let x1 = table[index_integral + 2].to_f32().unwrap_or_default();
     let x2 = table[index_integral + 3].to_f32().unwrap_or_default();
+
+    hermite(xm1, x0, x1, x2, index_fractional)
+}
+
+/// Hermite interpolation between `x0` and `x1`.
+#[inline]
+fn hermite(xm1: f32, x0: f32, x1: f32, x2: f32, f: f32) -> f32 {
     let c = (x1 - xm1) * 0.5;
     let v = x0 - x1;
     let w = c + v;
     let a = w + v + (x2 - x0) * 0.5;
     let b_neg = w + a;
-    let f = index_fractional;
 
     (((a * f) - b_neg) * f + c) * f + x0
 }
+
+/// Uniform cubic B-spline approximation between `x0` and `x1`.
+#[inline]
+fn b_spline(xm1: f32, x0: f32, x1: f32, x2: f32, f: f32) -> f32 {
+    let f2 = f * f;
+    let f3 = f2 * f;
+    let wm1 = (1.0 - f) * (1.0 - f) * (1.0 - f);
+    let w0 = 3.0 * f3 - 6.0 * f2 + 4.0;
+    let w1 = -3.0 * f3 + 3.0 * f2 + 3.0 * f + 1.0;
+
+    (wm1 * xm1 + w0 * x0 + w1 * x1 + f3 * x2) * (1.0 / 6.0)
+}
@@ -0,0 +1,77 @@
+//! Sustained excitation for the physical models.
+//!
+//! The modal and string voices are struck by default. The bow and blow exciters
+//! instead feed them with noise for as long as a gate is held.
+
+use crate::stmlib::dsp::one_pole;
+use crate::stmlib::utils::random::RandomSource;
+
+/// Excitation signal of a physical model.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExciterType {
+    /// Short click or noise burst on each trigger. This is the default.
+    #[default]
+    Mallet,
+
+    /// Low-pass filtered noise with a fast attack while the gate is held.
+    Bow,
+
+    /// Band-pass filtered noise with a slow, breathy attack while the gate is held.
+    Blow,
+}
+
+/// Gated noise source with an attack/release envelope.
+#[derive(Debug, Default)]
+pub struct NoiseExciter {
+    level: f32,
+    random: RandomSource,
+}
+
+impl NoiseExciter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn init(&mut self) {
+        self.level = 0.0;
+        self.random.init(None, 0);
+    }
+
+    pub(crate) fn init_random(&mut self, seed: Option<u32>, stream: u32) {
+        self.random.init(seed, stream);
+    }
+
+    /// Current level of the envelope.
+    #[inline]
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// Render the excitation for `exciter_type`. Renders silence for `ExciterType::Mallet`.
+    #[inline]
+    pub fn render(
+        &mut self,
+        exciter_type: ExciterType,
+        gate: bool,
+        amplitude: f32,
+        out: &mut [f32],
+    ) {
+        let (attack, release) = match exciter_type {
+            ExciterType::Mallet => {
+                self.level = 0.0;
+                out.fill(0.0);
+                return;
+            }
+            ExciterType::Bow => (0.005, 0.002),
+            ExciterType::Blow => (0.0005, 0.001),
+        };
+
+        let target = if gate { 1.0 } else { 0.0 };
+        let coefficient = if gate { attack } else { release };
+
+        for out_sample in out.iter_mut() {
+            one_pole(&mut self.level, target, coefficient);
+            *out_sample = (2.0 * self.random.get_float() - 1.0) * self.level * amplitude;
+        }
+    }
+}
@@ -1,7 +1,9 @@
 //! Physical modelling components.
 
 pub mod delay_line;
+pub mod exciter;
 pub mod modal_voice;
 pub mod resonator;
 pub mod string;
 pub mod string_voice;
+pub mod sympathetic_strings;
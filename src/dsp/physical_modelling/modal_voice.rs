@@ -5,25 +5,107 @@
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
+use super::exciter::{ExciterType, NoiseExciter};
 use super::resonator::{Resonator, ResonatorSvf, MAX_NUM_MODES};
-use crate::dsp::noise::dust::dust;
+use crate::dsp::noise::dust::dust_with_random;
 use crate::stmlib::dsp::filter::FilterMode;
 use crate::stmlib::dsp::units::semitones_to_ratio;
+use crate::stmlib::utils::random::RandomSource;
 
-#[derive(Debug, Default)]
+const DEFAULT_POSITION: f32 = 0.015;
+
+#[derive(Debug)]
 pub struct ModalVoice {
     excitation_filter: ResonatorSvf<1>,
     resonator: Resonator,
+    resolution: usize,
+    position: f32,
+
+    exciter: ExciterType,
+    noise_exciter: NoiseExciter,
+    gate: bool,
+
+    random: RandomSource,
+}
+
+impl Default for ModalVoice {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ModalVoice {
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            excitation_filter: ResonatorSvf::new(),
+            resonator: Resonator::new(),
+            resolution: MAX_NUM_MODES,
+            position: DEFAULT_POSITION,
+
+            exciter: ExciterType::Mallet,
+            noise_exciter: NoiseExciter::new(),
+            gate: false,
+
+            random: RandomSource::new(),
+        }
     }
 
     pub fn init(&mut self) {
         self.excitation_filter.init();
-        self.resonator.init(0.015, MAX_NUM_MODES);
+        self.resonator.init(self.position, self.resolution);
+        self.noise_exciter.init();
+        self.noise_exciter.init_random(None, 1);
+        self.gate = false;
+        self.random.init(None, 0);
+    }
+
+    /// Select the excitation signal. Default is `ExciterType::Mallet`.
+    #[inline]
+    pub fn set_exciter(&mut self, exciter: ExciterType) {
+        self.exciter = exciter;
+    }
+
+    #[inline]
+    pub fn exciter(&self) -> ExciterType {
+        self.exciter
+    }
+
+    /// Set the gate state used by the bow and blow exciters for the next rendered block.
+    #[inline]
+    pub fn set_gate(&mut self, gate: bool) {
+        self.gate = gate;
+    }
+
+    /// Set the number of modes of the resonator (1 to `MAX_NUM_MODES`). Default is
+    /// `MAX_NUM_MODES`. Re-initializes the resonator if the number changes.
+    pub fn set_resolution(&mut self, resolution: usize) {
+        let resolution = resolution.clamp(1, MAX_NUM_MODES);
+
+        if resolution != self.resolution {
+            self.resolution = resolution;
+            self.resonator.init(self.position, resolution);
+        }
+    }
+
+    #[inline]
+    pub fn resolution(&self) -> usize {
+        self.resolution
+    }
+
+    /// Set the excitation position from `0.0` to `1.0`. Default is `0.015`, close to the
+    /// edge of the object. Only the mode amplitudes are recomputed, so it can be modulated.
+    pub fn set_position(&mut self, position: f32) {
+        let position = position.clamp(0.0, 1.0);
+
+        if position != self.position {
+            self.position = position;
+            self.resonator.set_position(position);
+        }
+    }
+
+    #[inline]
+    pub fn position(&self) -> f32 {
+        self.position
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -56,10 +138,16 @@ impl ModalVoice {
         let q = if sustain { 0.7 } else { 1.5 };
 
         // Synthesize excitation signal.
-        if sustain {
+        if self.exciter != ExciterType::Mallet {
+            let amplitude = (0.0015 + 0.001 * accent) * (1.0 - damping * 0.5) / cutoff.max(0.01);
+            self.noise_exciter
+                .render(self.exciter, self.gate || sustain, amplitude, temp);
+        } else if sustain {
             let dust_f = 0.00005 + 0.99995 * density * density;
             for sample_temp in temp.iter_mut() {
-                *sample_temp = dust(dust_f) * (4.0 - dust_f * 3.0) * accent;
+                *sample_temp = dust_with_random(dust_f, &mut || self.random.get_float())
+                    * (4.0 - dust_f * 3.0)
+                    * accent;
             }
         } else {
             for temp_sample in temp.iter_mut() {
@@ -72,13 +160,18 @@ impl ModalVoice {
             }
         }
 
+        let filter_mode = if self.exciter == ExciterType::Blow {
+            FilterMode::BandPass
+        } else {
+            FilterMode::LowPass
+        };
         self.excitation_filter.process(
             core::slice::from_ref(&cutoff),
             core::slice::from_ref(&q),
             core::slice::from_ref(&1.0),
             temp,
             temp_2,
-            FilterMode::LowPass,
+            filter_mode,
             false,
         );
 
@@ -5,8 +5,8 @@
 use crate::dsp::resources::stiffness::LUT_STIFFNESS;
 use crate::stmlib::dsp::cosine_oscillator::{CosineOscillator, CosineOscillatorMode};
 use crate::stmlib::dsp::filter::{FilterMode, FrequencyApproximation, OnePole};
-use crate::stmlib::dsp::interpolate;
 use crate::stmlib::dsp::units::semitones_to_ratio;
+use crate::stmlib::dsp::{flush_denormal, interpolate};
 
 pub const MAX_NUM_MODES: usize = 24;
 pub const MODE_BATCH_SIZE: usize = 4;
@@ -27,34 +27,36 @@ impl Resonator {
 
     pub fn init(&mut self, position: f32, resolution: usize) {
         self.resolution = usize::min(resolution, MAX_NUM_MODES);
+        self.set_position(position);
 
+        for i in 0..(MAX_NUM_MODES / MODE_BATCH_SIZE) {
+            self.mode_filters[i].init();
+        }
+    }
+
+    /// Set the excitation position, which determines the amplitudes of the modes.
+    /// The state of the mode filters is kept.
+    pub fn set_position(&mut self, position: f32) {
         let mut amplitudes = CosineOscillator::new();
         amplitudes.init(position, CosineOscillatorMode::Approximate);
 
-        for i in 0..resolution {
+        for i in 0..self.resolution {
             self.mode_amplitude[i] = amplitudes.next() * 0.25;
         }
-
-        for i in 0..(MAX_NUM_MODES / MODE_BATCH_SIZE) {
-            self.mode_filters[i].init();
-        }
     }
 
     #[inline]
     pub fn process(
         &mut self,
-        mut f0: f32,
+        f0: f32,
         structure: f32,
         mut brightness: f32,
         damping: f32,
         in_: &[f32],
         out: &mut [f32],
     ) {
-        let mut stiffness = interpolate(&LUT_STIFFNESS, structure, 64.0);
-        f0 *= nth_harmonic_compensation(3, stiffness);
+        let frequencies = self.mode_frequencies(f0, structure);
 
-        let mut harmonic = f0;
-        let mut stretch_factor = 1.0;
         let q_sqrt = semitones_to_ratio(damping * 79.7);
         let mut q = 500.0 * q_sqrt * q_sqrt;
         brightness *= 1.0 - structure * 0.3;
@@ -69,11 +71,8 @@ impl Resonator {
         let mut batch_processor_index = 0;
         let mut batch_processor = &mut self.mode_filters[0];
 
-        for i in 0..self.resolution {
-            let mut mode_frequency = harmonic * stretch_factor;
-            if mode_frequency >= 0.499 {
-                mode_frequency = 0.499;
-            }
+        for (i, mode_frequency) in frequencies.iter().take(self.resolution).enumerate() {
+            let mode_frequency = *mode_frequency;
             let mode_attenuation = 1.0 - mode_frequency * 2.0;
 
             mode_f[batch_counter] = mode_frequency;
@@ -98,6 +97,39 @@ impl Resonator {
                 }
             }
 
+            q *= q_loss;
+        }
+
+        // Process the modes of an incomplete last batch, with the unused ones muted.
+        if batch_counter != 0 {
+            mode_a[batch_counter..].fill(0.0);
+            batch_processor.process(
+                &mode_f,
+                &mode_q,
+                &mode_a,
+                in_,
+                out,
+                FilterMode::BandPass,
+                true,
+            );
+        }
+    }
+
+    /// Returns the frequencies of the modes, as used by [`Resonator::process`] with the
+    /// same `f0` and `structure`. The frequencies are normalized to the sample rate, with
+    /// the modes beyond the resolution set to `0.0`.
+    pub fn mode_frequencies(&self, mut f0: f32, structure: f32) -> [f32; MAX_NUM_MODES] {
+        let mut frequencies = [0.0; MAX_NUM_MODES];
+
+        let mut stiffness = interpolate(&LUT_STIFFNESS, structure, 64.0);
+        f0 *= nth_harmonic_compensation(3, stiffness);
+
+        let mut harmonic = f0;
+        let mut stretch_factor = 1.0;
+
+        for frequency in frequencies.iter_mut().take(self.resolution) {
+            *frequency = f32::min(harmonic * stretch_factor, 0.499);
+
             stretch_factor += stiffness;
 
             if stiffness < 0.0 {
@@ -111,8 +143,14 @@ impl Resonator {
             }
 
             harmonic += f0;
-            q *= q_loss;
         }
+
+        frequencies
+    }
+
+    #[inline]
+    pub fn resolution(&self) -> usize {
+        self.resolution
     }
 }
 
@@ -202,6 +240,12 @@ impl<const BATCH_SIZE: usize> ResonatorSvf<BATCH_SIZE> {
             self.state_1[..BATCH_SIZE].copy_from_slice(&state_1[..BATCH_SIZE]);
             self.state_2[..BATCH_SIZE].copy_from_slice(&state_2[..BATCH_SIZE])
         }
+
+        // Keep the decaying modes out of the denormal range.
+        for (state_1, state_2) in self.state_1.iter_mut().zip(self.state_2.iter_mut()) {
+            *state_1 = flush_denormal(*state_1);
+            *state_2 = flush_denormal(*state_2);
+        }
     }
 }
 
@@ -13,14 +13,50 @@ use crate::dsp::{allocate_buffer, SAMPLE_RATE};
 use crate::stmlib::dsp::filter::{DcBlocker, FilterMode, FrequencyApproximation, Svf};
 use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 use crate::stmlib::dsp::units::semitones_to_ratio;
-use crate::stmlib::dsp::{crossfade, interpolate, one_pole};
-use crate::stmlib::utils::random;
+use crate::stmlib::dsp::{crossfade, flush_denormal, interpolate, one_pole};
+use crate::stmlib::utils::random::RandomSource;
 
 pub const DELAY_LINE_SIZE: usize = 1024;
 
+/// Position of the bow, as a fraction of the string length from the bridge.
+const BOW_POSITION: f32 = 0.127;
+
 pub enum StringNonLinearity {
     CurvedBridge,
     Dispersion,
+
+    /// Friction of a bow moving across the string, which makes the string self-oscillate.
+    /// `force` is the bow pressure from `0.0` to `1.0`, `velocity` the bow speed, with
+    /// values around `0.1` to `0.5` giving a steady tone.
+    Bow {
+        force: f32,
+        velocity: f32,
+    },
+}
+
+/// Preset of brightness, damping and non-linearity evoking a string material.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringMaterial {
+    /// Bright and long sustain, with the inharmonicity of a stiff string.
+    Steel,
+
+    /// Dull and short sustain.
+    Nylon,
+
+    /// Warm, with a medium sustain and a slight buzz of the bridge.
+    Gut,
+}
+
+impl StringMaterial {
+    /// Returns the non-linearity amount, brightness and damping of the material, as
+    /// passed to [`String::process`].
+    pub fn parameters(&self) -> (f32, f32, f32) {
+        match self {
+            StringMaterial::Steel => (0.3, 0.8, 0.8),
+            StringMaterial::Nylon => (0.0, 0.3, 0.5),
+            StringMaterial::Gut => (-0.25, 0.45, 0.6),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -39,6 +75,10 @@ pub struct String<'a> {
     // do not fit the delay line. Rarely used.
     src_phase: f32,
     out_sample: [f32; 2],
+
+    material: Option<StringMaterial>,
+
+    random: RandomSource,
 }
 
 impl<'a> String<'a> {
@@ -55,9 +95,15 @@ impl<'a> String<'a> {
             curved_bridge: 0.0,
             src_phase: 0.0,
             out_sample: [0.0; 2],
+            material: None,
+            random: RandomSource::new(),
         }
     }
 
+    pub(crate) fn init_random(&mut self, seed: Option<u32>, stream: u32) {
+        self.random.init(seed, stream);
+    }
+
     pub fn reset(&mut self) {
         self.string.reset();
         self.stretch.reset();
@@ -70,6 +116,18 @@ impl<'a> String<'a> {
         self.src_phase = 0.0;
     }
 
+    /// Sets a material preset overriding the non-linearity amount, brightness and damping
+    /// passed to [`String::process`]. `None` uses the passed values, which is the default.
+    #[inline]
+    pub fn set_material(&mut self, material: Option<StringMaterial>) {
+        self.material = material;
+    }
+
+    #[inline]
+    pub fn material(&self) -> Option<StringMaterial> {
+        self.material
+    }
+
     #[inline]
     pub fn process(
         &mut self,
@@ -80,6 +138,12 @@ impl<'a> String<'a> {
         in_: &[f32],
         out: &mut [f32],
     ) {
+        let (non_linearity_amount, brightness, damping) = self
+            .material
+            .map_or((non_linearity_amount, brightness, damping), |material| {
+                material.parameters()
+            });
+
         if non_linearity_amount <= 0.0 {
             self.process_internal(
                 f0,
@@ -103,6 +167,32 @@ impl<'a> String<'a> {
         }
     }
 
+    /// Renders the string excited by a bow, see [`StringNonLinearity::Bow`]. The string
+    /// keeps sounding as long as the bow moves, while a bow at rest damps it. `in_` adds
+    /// further excitation.
+    #[allow(clippy::too_many_arguments)]
+    #[inline]
+    pub fn process_bowed(
+        &mut self,
+        f0: f32,
+        force: f32,
+        velocity: f32,
+        brightness: f32,
+        damping: f32,
+        in_: &[f32],
+        out: &mut [f32],
+    ) {
+        self.process_internal(
+            f0,
+            0.0,
+            brightness,
+            damping,
+            in_,
+            out,
+            StringNonLinearity::Bow { force, velocity },
+        );
+    }
+
     #[allow(clippy::too_many_arguments)]
     #[inline]
     fn process_internal(
@@ -174,43 +264,84 @@ impl<'a> String<'a> {
                 let mut delay = delay_modulation.next();
                 let mut s;
 
-                if matches!(non_linearity, StringNonLinearity::Dispersion) {
-                    let noise = random::get_float() - 0.5;
-                    one_pole(&mut self.dispersion_noise, noise, noise_filter);
-                    delay *= 1.0 + self.dispersion_noise * noise_amount;
+                if let StringNonLinearity::Bow { force, velocity } = non_linearity {
+                    // The loop is split at the bow into a short bridge segment, using the
+                    // stretch line, and a long nut segment. Both ends reflect the waves
+                    // with an inverted polarity, the damping happens at the bridge.
+                    let bridge_delay = delay * BOW_POSITION;
+                    let nut_delay = delay - bridge_delay;
+
+                    let nut_reflection = -self.string.read_hermite(nut_delay);
+                    let mut bridge_reflection = -self.stretch.read_hermite(bridge_delay);
+                    self.dc_blocker
+                        .process(core::slice::from_mut(&mut bridge_reflection));
+                    bridge_reflection = self
+                        .iir_damping_filter
+                        .process(bridge_reflection, FilterMode::LowPass);
+                    bridge_reflection = flush_denormal(bridge_reflection);
+
+                    // Stick-slip friction: the bow drags the string along while their
+                    // velocities are close and lets it slip when they differ too much.
+                    // The velocity change is sent towards both ends.
+                    let velocity_difference = velocity - (nut_reflection + bridge_reflection);
+                    let slope = 5.0 - 4.0 * force.clamp(0.0, 1.0);
+                    let friction =
+                        f32::min((velocity_difference * slope).abs() + 0.75, 100.0).powi(-4);
+                    let excitation =
+                        velocity_difference * friction.min(1.0) + (*in_sample).clamp(-20.0, 20.0);
+
+                    self.string
+                        .write(flush_denormal(bridge_reflection + excitation));
+                    self.stretch
+                        .write(flush_denormal(nut_reflection + excitation));
+
+                    s = bridge_reflection;
                 } else {
-                    delay *= 1.0 - self.curved_bridge * bridge_curving;
-                }
+                    if matches!(non_linearity, StringNonLinearity::Dispersion) {
+                        let noise = self.random.get_float() - 0.5;
+                        one_pole(&mut self.dispersion_noise, noise, noise_filter);
+                        delay *= 1.0 + self.dispersion_noise * noise_amount;
+                    } else {
+                        delay *= 1.0 - self.curved_bridge * bridge_curving;
+                    }
 
-                if matches!(non_linearity, StringNonLinearity::Dispersion) {
-                    let ap_delay = delay * stretch_point;
-                    let main_delay =
-                        delay - ap_delay * (0.408 - stretch_point * 0.308) * stretch_correction;
-                    if ap_delay >= 4.0 && main_delay >= 4.0 {
-                        s = self.string.read_with_delay_frac(main_delay);
-                        s = self.stretch.allpass(s, ap_delay as usize, ap_gain);
+                    if matches!(non_linearity, StringNonLinearity::Dispersion) {
+                        let ap_delay = delay * stretch_point;
+                        let main_delay =
+                            delay - ap_delay * (0.408 - stretch_point * 0.308) * stretch_correction;
+                        if ap_delay >= 4.0 && main_delay >= 4.0 {
+                            s = self.string.read_with_delay_frac(main_delay);
+                            s = self.stretch.allpass(s, ap_delay as usize, ap_gain);
+                        } else {
+                            s = self.string.read_hermite(delay);
+                        }
                     } else {
                         s = self.string.read_hermite(delay);
                     }
-                } else {
-                    s = self.string.read_hermite(delay);
-                }
 
-                if matches!(non_linearity, StringNonLinearity::CurvedBridge) {
-                    let value = s.abs() - 0.025;
-                    let sign = if s > 0.0 { 1.0 } else { -1.5 };
-                    self.curved_bridge = (value.abs() + value) * sign;
-                }
+                    if matches!(non_linearity, StringNonLinearity::CurvedBridge) {
+                        let value = s.abs() - 0.025;
+                        let sign = if s > 0.0 { 1.0 } else { -1.5 };
+                        self.curved_bridge = (value.abs() + value) * sign;
+                    }
 
-                s += (*in_sample).clamp(-20.0, 20.0);
-                self.dc_blocker.process(core::slice::from_mut(&mut s));
-                s = self.iir_damping_filter.process(s, FilterMode::LowPass);
-                self.string.write(s);
+                    s += (*in_sample).clamp(-20.0, 20.0);
+                    self.dc_blocker.process(core::slice::from_mut(&mut s));
+                    s = self.iir_damping_filter.process(s, FilterMode::LowPass);
+                    s = flush_denormal(s);
+                    self.string.write(s);
+                }
 
                 self.out_sample[1] = self.out_sample[0];
                 self.out_sample[0] = s;
             }
             *out_sample += crossfade(self.out_sample[1], self.out_sample[0], self.src_phase);
         }
+
+        // Keep the feedback path out of the denormal range when the string decays to
+        // silence. The samples written to the delay lines are flushed above.
+        self.dc_blocker.flush_denormals();
+        self.iir_damping_filter.flush_denormals();
+        self.dispersion_noise = flush_denormal(self.dispersion_noise);
     }
 }
@@ -4,17 +4,24 @@
 
 use core::alloc::GlobalAlloc;
 
+use super::exciter::{ExciterType, NoiseExciter};
 use super::string::String;
-use crate::dsp::noise::dust::dust;
+use crate::dsp::noise::dust::dust_with_random;
 use crate::stmlib::dsp::filter::{FilterMode, FrequencyApproximation, Svf};
 use crate::stmlib::dsp::units::semitones_to_ratio;
-use crate::stmlib::utils::random;
+use crate::stmlib::utils::random::RandomSource;
 
 #[derive(Debug)]
 pub struct StringVoice<'a> {
     excitation_filter: Svf,
     string: String<'a>,
     remaining_noise_samples: usize,
+
+    exciter: ExciterType,
+    noise_exciter: NoiseExciter,
+    gate: bool,
+
+    random: RandomSource,
 }
 
 impl<'a> StringVoice<'a> {
@@ -23,19 +30,53 @@ impl<'a> StringVoice<'a> {
             excitation_filter: Svf::default(),
             string: String::new(buffer_allocator),
             remaining_noise_samples: 0,
+
+            exciter: ExciterType::Mallet,
+            noise_exciter: NoiseExciter::new(),
+            gate: false,
+
+            random: RandomSource::new(),
         }
     }
 
     pub fn init(&mut self) {
         self.excitation_filter.init();
         self.remaining_noise_samples = 0;
+        self.noise_exciter.init();
+        self.gate = false;
+        self.init_random(None, 0);
         self.reset();
     }
 
+    /// Seeds the random sources of the voice. Voices of the same engine pass different
+    /// `stream`s.
+    pub(crate) fn init_random(&mut self, seed: Option<u32>, stream: u32) {
+        self.random.init(seed, 3 * stream);
+        self.string.init_random(seed, 3 * stream + 1);
+        self.noise_exciter.init_random(seed, 3 * stream + 2);
+    }
+
     pub fn reset(&mut self) {
         self.string.reset();
     }
 
+    /// Select the excitation signal. Default is `ExciterType::Mallet`.
+    #[inline]
+    pub fn set_exciter(&mut self, exciter: ExciterType) {
+        self.exciter = exciter;
+    }
+
+    #[inline]
+    pub fn exciter(&self) -> ExciterType {
+        self.exciter
+    }
+
+    /// Set the gate state used by the bow and blow exciters for the next rendered block.
+    #[inline]
+    pub fn set_gate(&mut self, gate: bool) {
+        self.gate = gate;
+    }
+
     #[allow(clippy::too_many_arguments)]
     #[inline]
     pub fn render(
@@ -57,25 +98,39 @@ impl<'a> StringVoice<'a> {
         brightness += 0.25 * accent * (1.0 - brightness);
         damping += 0.25 * accent * (1.0 - damping);
 
+        let sustained_exciter = self.exciter != ExciterType::Mallet;
+
         // Synthesize excitation signal.
-        if trigger || sustain {
+        if trigger || sustain || sustained_exciter {
             let range = 72.0;
             let f = 4.0 * f0;
             let cutoff = f32::min(
                 f * semitones_to_ratio((brightness * (2.0 - brightness) - 0.5) * range),
                 0.499,
             );
-            let q = if sustain { 1.0 } else { 0.5 };
-            self.remaining_noise_samples = (1.0 / f0) as usize;
+            let q = if sustain || sustained_exciter {
+                1.0
+            } else {
+                0.5
+            };
+            if trigger || sustain {
+                self.remaining_noise_samples = (1.0 / f0) as usize;
+            }
             self.excitation_filter
                 .set_f_q(cutoff, q, FrequencyApproximation::Dirty);
         }
 
-        if sustain {
+        if sustained_exciter {
+            let amplitude = 0.1 + 0.1 * accent;
+            self.noise_exciter
+                .render(self.exciter, self.gate || sustain, amplitude, temp);
+        } else if sustain {
             let dust_f = 0.00005 + 0.99995 * density * density;
 
             for sample_temp in temp.iter_mut() {
-                *sample_temp = dust(dust_f) * (8.0 - dust_f * 6.0) * accent;
+                *sample_temp = dust_with_random(dust_f, &mut || self.random.get_float())
+                    * (8.0 - dust_f * 6.0)
+                    * accent;
             }
         } else if self.remaining_noise_samples > 0 {
             let mut noise_samples = usize::min(self.remaining_noise_samples, out.len());
@@ -83,7 +138,7 @@ impl<'a> StringVoice<'a> {
             let mut tail = out.len() - noise_samples;
             let mut start_index = 0;
             while noise_samples > 0 {
-                temp[start_index] = 2.0 * random::get_float() - 1.0;
+                temp[start_index] = 2.0 * self.random.get_float() - 1.0;
                 start_index += 1;
                 noise_samples -= 1;
             }
@@ -98,8 +153,13 @@ impl<'a> StringVoice<'a> {
             }
         }
 
+        let filter_mode = if self.exciter == ExciterType::Blow {
+            FilterMode::BandPass
+        } else {
+            FilterMode::LowPass
+        };
         self.excitation_filter
-            .process_buffer(temp, temp_2, FilterMode::LowPass);
+            .process_buffer(temp, temp_2, filter_mode);
 
         for (aux_sample, temp_sample) in aux.iter_mut().zip(temp_2.iter()) {
             *aux_sample += *temp_sample;
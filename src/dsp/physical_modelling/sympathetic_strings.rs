@@ -0,0 +1,101 @@
+//! Bank of lightly damped resonators ringing in sympathy with an input signal, like the
+//! sympathetic strings of a piano or a sitar.
+
+use super::resonator::ResonatorSvf;
+use crate::dsp::engine::note_to_frequency;
+use crate::stmlib::dsp::filter::FilterMode;
+
+/// Maximum number of sympathetic strings.
+pub const MAX_SYMPATHETIC_STRINGS: usize = 12;
+
+// Number of harmonics resonating per string.
+const NUM_HARMONICS: usize = 4;
+
+// Quality factor of the fundamental, for a decay time of several seconds. The harmonics
+// decay faster.
+const Q: f32 = 1500.0;
+const Q_LOSS: f32 = 0.7;
+
+// Level of the strings, relative to the input signal.
+const LEVEL: f32 = 0.5;
+
+#[derive(Debug, Default)]
+pub struct SympatheticStrings {
+    notes: [f32; MAX_SYMPATHETIC_STRINGS],
+    num_strings: usize,
+
+    frequency: [[f32; NUM_HARMONICS]; MAX_SYMPATHETIC_STRINGS],
+    q: [[f32; NUM_HARMONICS]; MAX_SYMPATHETIC_STRINGS],
+    gain: [[f32; NUM_HARMONICS]; MAX_SYMPATHETIC_STRINGS],
+    filters: [ResonatorSvf<NUM_HARMONICS>; MAX_SYMPATHETIC_STRINGS],
+}
+
+impl SympatheticStrings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn init(&mut self) {
+        for filter in self.filters.iter_mut() {
+            filter.init();
+        }
+    }
+
+    /// Tunes the strings to MIDI notes, ignoring notes beyond
+    /// [`MAX_SYMPATHETIC_STRINGS`]. An empty slice disables the strings. The strings are
+    /// only silenced if the notes change.
+    pub fn set_notes(&mut self, notes: &[f32]) {
+        let notes = &notes[..notes.len().min(MAX_SYMPATHETIC_STRINGS)];
+
+        if notes == self.notes() {
+            return;
+        }
+
+        self.num_strings = notes.len();
+        self.notes[..self.num_strings].copy_from_slice(notes);
+
+        for (i, note) in self.notes[..self.num_strings].iter().enumerate() {
+            let f0 = note_to_frequency(*note);
+            let mut q = Q;
+
+            for harmonic in 0..NUM_HARMONICS {
+                let frequency = f0 * (harmonic + 1) as f32;
+
+                // The band-pass output has a gain of q at the resonance.
+                self.frequency[i][harmonic] = frequency.min(0.499);
+                self.q[i][harmonic] = q;
+                self.gain[i][harmonic] = if frequency < 0.45 {
+                    LEVEL / (q * (harmonic + 1) as f32)
+                } else {
+                    0.0
+                };
+
+                q *= Q_LOSS;
+            }
+        }
+
+        self.init();
+    }
+
+    /// Returns the MIDI notes the strings are tuned to.
+    #[inline]
+    pub fn notes(&self) -> &[f32] {
+        &self.notes[..self.num_strings]
+    }
+
+    /// Feeds `in_` into the strings and adds their output to `out`.
+    #[inline]
+    pub fn process(&mut self, in_: &[f32], out: &mut [f32]) {
+        for i in 0..self.num_strings {
+            self.filters[i].process(
+                &self.frequency[i],
+                &self.q[i],
+                &self.gain[i],
+                in_,
+                out,
+                FilterMode::BandPass,
+                true,
+            );
+        }
+    }
+}
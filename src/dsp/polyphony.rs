@@ -0,0 +1,186 @@
+//! Polyphonic pool of voices played with note-on and note-off events.
+
+use core::alloc::GlobalAlloc;
+
+use super::voice::{Modulations, Patch, Voice, VoiceBuilder};
+use crate::dsp::allocate_buffer;
+
+/// State of a voice of the pool.
+#[derive(Debug, Default, Clone, Copy)]
+struct VoiceSlot {
+    /// Note played by the voice, kept after note-off for the release tail.
+    note: Option<u8>,
+
+    /// Level in the range from `0.0` to `1.0`, derived from the velocity.
+    level: f32,
+
+    /// Flag if the key is held.
+    gate: bool,
+
+    /// Flag if the trigger has to be low for one block before the next note.
+    retrigger: bool,
+
+    /// Value of the note counter at the last note-on, to find the oldest voice.
+    age: u32,
+}
+
+/// Pool of `N` voices, rendering all of them with the same patch.
+///
+/// Each voice receives the note of its last note-on as [`Patch::note`], while trigger and
+/// level are patched: the trigger is high as long as the key is held and the level follows
+/// the velocity, so the LPG closes on note-off. When all voices are busy, the one with the
+/// oldest note-on is stolen.
+#[derive(Debug)]
+pub struct Polyphony<'a, const N: usize> {
+    voices: [Voice<'a>; N],
+    slots: [VoiceSlot; N],
+    note_counter: u32,
+
+    out_buffer: &'a mut [f32],
+    aux_buffer: &'a mut [f32],
+}
+
+impl<'a, const N: usize> Polyphony<'a, N> {
+    /// Creates a pool of voices with all engines.
+    pub fn new<T: GlobalAlloc>(buffer_allocator: &T, block_size: usize) -> Self {
+        Self::from_builder(&VoiceBuilder::new(), buffer_allocator, block_size)
+    }
+
+    /// Creates a pool of voices built by `builder`, e.g. to save memory by enabling only
+    /// the engines in use.
+    pub fn from_builder<T: GlobalAlloc>(
+        builder: &VoiceBuilder,
+        buffer_allocator: &T,
+        block_size: usize,
+    ) -> Self {
+        Self {
+            voices: core::array::from_fn(|_| builder.build(buffer_allocator, block_size)),
+            slots: [VoiceSlot::default(); N],
+            note_counter: 0,
+
+            out_buffer: allocate_buffer(buffer_allocator, block_size).unwrap(),
+            aux_buffer: allocate_buffer(buffer_allocator, block_size).unwrap(),
+        }
+    }
+
+    pub fn init(&mut self) {
+        for voice in self.voices.iter_mut() {
+            voice.init();
+        }
+
+        self.slots = [VoiceSlot::default(); N];
+        self.note_counter = 0;
+    }
+
+    /// Starts a note with a MIDI note number and velocity (`1` to `127`), on the voice
+    /// already playing the note, a free voice or the oldest one. A velocity of `0` is a
+    /// note-off.
+    pub fn note_on(&mut self, note: u8, velocity: u8) {
+        if velocity == 0 {
+            self.note_off(note);
+            return;
+        }
+
+        let index = self
+            .slots
+            .iter()
+            .position(|slot| slot.note == Some(note))
+            .or_else(|| self.oldest(|slot| !slot.gate))
+            .or_else(|| self.oldest(|_| true));
+
+        let Some(index) = index else {
+            return;
+        };
+
+        self.note_counter = self.note_counter.wrapping_add(1);
+
+        let slot = &mut self.slots[index];
+        slot.retrigger = slot.gate;
+        slot.note = Some(note);
+        slot.level = f32::from(velocity.min(127)) / 127.0;
+        slot.gate = true;
+        slot.age = self.note_counter;
+    }
+
+    /// Releases all voices playing `note`.
+    pub fn note_off(&mut self, note: u8) {
+        for slot in self.slots.iter_mut() {
+            if slot.note == Some(note) {
+                slot.gate = false;
+            }
+        }
+    }
+
+    /// Releases all voices.
+    pub fn all_notes_off(&mut self) {
+        for slot in self.slots.iter_mut() {
+            slot.gate = false;
+        }
+    }
+
+    /// Returns the number of voices with a held key.
+    pub fn active_voices(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.gate).count()
+    }
+
+    /// Returns the voices, e.g. to change their settings.
+    pub fn voices_mut(&mut self) -> &mut [Voice<'a>] {
+        &mut self.voices
+    }
+
+    /// Renders all voices that have played a note with `patch` and sums their outputs.
+    pub fn render(&mut self, patch: &Patch, out: &mut [f32], aux: &mut [f32]) {
+        out.fill(0.0);
+        aux.fill(0.0);
+
+        let size = out.len();
+
+        for (voice, slot) in self.voices.iter_mut().zip(self.slots.iter_mut()) {
+            let Some(note) = slot.note else {
+                continue;
+            };
+
+            let trigger = slot.gate && !slot.retrigger;
+            slot.retrigger = false;
+
+            let voice_patch = Patch {
+                note: f32::from(note),
+                ..patch.clone()
+            };
+
+            let modulations = Modulations {
+                trigger: if trigger { 1.0 } else { 0.0 },
+                level: if slot.gate { slot.level } else { 0.0 },
+                trigger_patched: true,
+                level_patched: true,
+                ..Default::default()
+            };
+
+            let voice_out = &mut self.out_buffer[..size];
+            let voice_aux = &mut self.aux_buffer[..size];
+            voice.render(&voice_patch, &modulations, voice_out, voice_aux);
+
+            for (sample, voice_sample) in out.iter_mut().zip(voice_out.iter()) {
+                *sample += *voice_sample;
+            }
+            for (sample, voice_sample) in aux.iter_mut().zip(voice_aux.iter()) {
+                *sample += *voice_sample;
+            }
+        }
+    }
+
+    /// Returns the index of the voice with the oldest note-on among those matching
+    /// `filter`.
+    fn oldest(&self, filter: impl Fn(&VoiceSlot) -> bool) -> Option<usize> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| filter(slot))
+            .max_by_key(|(_, slot)| {
+                // Voices that never played are the best candidates.
+                slot.note
+                    .map_or(u32::MAX, |_| self.note_counter.wrapping_sub(slot.age))
+            })
+            .map(|(index, _)| index)
+    }
+}
@@ -2,6 +2,9 @@
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
+/// All built-in banks, in the order used by the six op engines of the voice.
+pub const SYX_BANKS: [&[u8; 4096]; 3] = [&SYX_BANK_0, &SYX_BANK_1, &SYX_BANK_2];
+
 pub const SYX_BANK_0: [u8; 4096] = [
     99, 58, 99, 60, 99, 0, 0, 0, 0, 0, 0, 0, 56, 0, 79, 2, 0, 99, 99, 99, 60, 99, 99, 99, 0, 0, 0,
     0, 0, 56, 0, 50, 2, 0, 99, 99, 99, 66, 99, 99, 99, 0, 0, 0, 0, 0, 56, 0, 70, 0, 0, 99, 41, 99,
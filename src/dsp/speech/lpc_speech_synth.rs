@@ -4,7 +4,7 @@
 
 use crate::dsp::resources::lpc::{LUT_LPC_EXCITATION_PULSE, LUT_LPC_EXCITATION_PULSE_SIZE};
 use crate::stmlib::dsp::polyblep::{next_blep_sample, this_blep_sample};
-use crate::stmlib::utils::random;
+use crate::stmlib::utils::random::RandomSource;
 
 pub const LPC_ORDER: usize = 10;
 pub const LPC_SPEECH_SYNTH_DEFAULT_F0: f32 = 100.0;
@@ -71,6 +71,10 @@ pub struct LpcSpeechSynth {
 
     k: [f32; LPC_ORDER],
     s: [f32; LPC_ORDER + 1],
+
+    excitation_pulse: Option<[i8; LUT_LPC_EXCITATION_PULSE_SIZE]>,
+
+    random: RandomSource,
 }
 
 impl LpcSpeechSynth {
@@ -89,6 +93,24 @@ impl LpcSpeechSynth {
 
         self.k.fill(0.0);
         self.s.fill(0.0);
+
+        self.random.init(None, 0);
+    }
+
+    /// Replaces the glottal pulse exciting the filter in voiced frames with a custom table,
+    /// for a different vocal character. The table is read with a step of 32 samples per
+    /// output sample, so it spans 20 samples at the 8 kHz rate of the LPC frames.
+    /// `None` (default) uses [`LUT_LPC_EXCITATION_PULSE`].
+    #[inline]
+    pub fn set_excitation_pulse(&mut self, pulse: Option<&[i8; LUT_LPC_EXCITATION_PULSE_SIZE]>) {
+        self.excitation_pulse = pulse.copied();
+    }
+
+    #[inline]
+    pub fn excitation_pulse(&self) -> &[i8; LUT_LPC_EXCITATION_PULSE_SIZE] {
+        self.excitation_pulse
+            .as_ref()
+            .unwrap_or(&LUT_LPC_EXCITATION_PULSE)
     }
 
     #[inline]
@@ -105,6 +127,10 @@ impl LpcSpeechSynth {
         f = f.clamp(0.0, 0.5);
 
         let mut next_sample = self.next_sample;
+        let excitation_pulse = self
+            .excitation_pulse
+            .as_ref()
+            .unwrap_or(&LUT_LPC_EXCITATION_PULSE);
 
         for (excitation_sample, output_sample) in excitation.iter_mut().zip(output.iter_mut()) {
             self.phase += f;
@@ -120,7 +146,7 @@ impl LpcSpeechSynth {
                 let mut discontinuity = 0.0;
                 if self.excitation_pulse_sample_index < LUT_LPC_EXCITATION_PULSE_SIZE {
                     self.excitation_pulse_sample_index -= reset_sample;
-                    let s = LUT_LPC_EXCITATION_PULSE[self.excitation_pulse_sample_index];
+                    let s = excitation_pulse[self.excitation_pulse_sample_index];
                     discontinuity = (s as f32) / 128.0 * self.pulse_energy;
                 }
 
@@ -132,14 +158,14 @@ impl LpcSpeechSynth {
 
             let mut e: [f32; 11] = [0.0; 11];
 
-            e[10] = if random::get_sample() > 0 {
+            e[10] = if self.random.get_sample() > 0 {
                 self.noise_energy
             } else {
                 -self.noise_energy
             };
 
             if self.excitation_pulse_sample_index < LUT_LPC_EXCITATION_PULSE_SIZE {
-                let s = LUT_LPC_EXCITATION_PULSE[self.excitation_pulse_sample_index];
+                let s = excitation_pulse[self.excitation_pulse_sample_index];
                 next_sample += (s as f32) / 128.0 * self.pulse_energy;
                 self.excitation_pulse_sample_index += 32;
             }
@@ -61,6 +61,32 @@ impl<'a> LpcSpeechSynthController<'a> {
         self.word_bank.reset();
     }
 
+    /// Start the playback of a word from a bank with the synth in a clean state.
+    ///
+    /// The word is played by subsequent calls to `render` with the same bank and
+    /// `free_running` set to `false`.
+    pub fn trigger_word(&mut self, bank: usize, word: usize) {
+        self.word_bank.load(bank);
+        self.word_bank.get_word_boundaries_by_index(
+            word,
+            &mut self.playback_frame,
+            &mut self.last_playback_frame,
+        );
+        self.remaining_frame_samples = 0;
+
+        self.clock_phase = 0.0;
+        self.sample = [0.0; 2];
+        self.next_sample = [0.0; 2];
+        self.synth.init();
+    }
+
+    /// Return `true` while the frames of a triggered word are still being played.
+    #[inline]
+    pub fn playing_word(&self) -> bool {
+        self.playback_frame != -1
+            && (self.playback_frame < self.last_playback_frame || self.remaining_frame_samples > 0)
+    }
+
     #[allow(clippy::too_many_arguments)]
     #[inline]
     pub fn render(
@@ -326,6 +352,23 @@ impl<'a> LpcSpeechSynthWordBank<'a> {
         self.frames
     }
 
+    #[inline]
+    pub fn num_words(&self) -> usize {
+        self.num_words
+    }
+
+    #[inline]
+    pub fn get_word_boundaries_by_index(&self, word: usize, start: &mut i32, end: &mut i32) {
+        if self.num_words == 0 {
+            *start = -1;
+            *end = -1;
+        } else {
+            let word = word.min(self.num_words - 1);
+            *start = self.word_boundaries[word] as i32;
+            *end = self.word_boundaries[word + 1] as i32 - 1;
+        }
+    }
+
     #[inline]
     pub fn get_word_boundaries(&self, address: f32, start: &mut i32, end: &mut i32) {
         if self.num_words == 0 {
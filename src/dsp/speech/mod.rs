@@ -6,3 +6,10 @@ pub mod lpc_speech_synth_phonemes;
 pub mod lpc_speech_synth_words;
 pub mod naive_speech_synth;
 pub mod sam_speech_synth;
+
+/// Default duration of the consonants of [`sam_speech_synth::SamSpeechSynth`] and
+/// [`naive_speech_synth::NaiveSpeechSynth`] in milliseconds.
+pub const DEFAULT_CONSONANT_DURATION_MS: f32 = 50.0;
+
+/// Maximum duration of the consonants in milliseconds.
+pub const MAX_CONSONANT_DURATION_MS: f32 = 500.0;
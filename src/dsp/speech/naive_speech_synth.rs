@@ -3,16 +3,18 @@
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
+use super::{DEFAULT_CONSONANT_DURATION_MS, MAX_CONSONANT_DURATION_MS};
 use crate::dsp::oscillator::oscillator::{Oscillator, OscillatorShape};
 use crate::dsp::{A0, SAMPLE_RATE};
 use crate::stmlib::dsp::filter::{FilterMode, FrequencyApproximation, Svf};
+use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
 use crate::stmlib::dsp::units::semitones_to_ratio;
 
 const NUM_FORMANTS: usize = 5;
 const NUM_PHONEMES: usize = 5;
 const NUM_REGISTERS: usize = 5;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct NaiveSpeechSynth {
     pulse: Oscillator,
     frequency: f32,
@@ -20,17 +22,37 @@ pub struct NaiveSpeechSynth {
 
     filter: [Svf; NUM_FORMANTS],
     pulse_coloration: Svf,
+
+    consonant_level: f32,
+    consonant_duration_ms: f32,
+    consonant_gain: f32,
+}
+
+impl Default for NaiveSpeechSynth {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl NaiveSpeechSynth {
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            pulse: Oscillator::new(),
+            frequency: 0.0,
+            click_duration: 0,
+            filter: core::array::from_fn(|_| Svf::new()),
+            pulse_coloration: Svf::new(),
+            consonant_level: 1.0,
+            consonant_duration_ms: DEFAULT_CONSONANT_DURATION_MS,
+            consonant_gain: 1.0,
+        }
     }
 
     pub fn init(&mut self) {
         self.pulse.init();
         self.frequency = 0.0;
         self.click_duration = 0;
+        self.consonant_gain = 1.0;
 
         for filter in &mut self.filter {
             filter.init();
@@ -40,6 +62,30 @@ impl NaiveSpeechSynth {
             .set_f_q(800.0 / SAMPLE_RATE, 0.5, FrequencyApproximation::Dirty);
     }
 
+    /// Sets the level of the consonants relative to the vowels (0.0 to 2.0). Default is
+    /// `1.0`.
+    #[inline]
+    pub fn set_consonant_level(&mut self, level: f32) {
+        self.consonant_level = level.clamp(0.0, 2.0);
+    }
+
+    #[inline]
+    pub fn consonant_level(&self) -> f32 {
+        self.consonant_level
+    }
+
+    /// Sets the duration of the consonants in milliseconds (0.0 to
+    /// [`MAX_CONSONANT_DURATION_MS`]). Default is [`DEFAULT_CONSONANT_DURATION_MS`].
+    #[inline]
+    pub fn set_consonant_duration_ms(&mut self, duration: f32) {
+        self.consonant_duration_ms = duration.clamp(0.0, MAX_CONSONANT_DURATION_MS);
+    }
+
+    #[inline]
+    pub fn consonant_duration_ms(&self) -> f32 {
+        self.consonant_duration_ms
+    }
+
     #[allow(clippy::too_many_arguments)]
     #[inline]
     pub fn render(
@@ -53,7 +99,7 @@ impl NaiveSpeechSynth {
         output: &mut [f32],
     ) {
         if click {
-            self.click_duration = (SAMPLE_RATE * 0.05) as usize;
+            self.click_duration = (SAMPLE_RATE * self.consonant_duration_ms / 1000.0) as usize;
         }
         self.click_duration -= usize::min(self.click_duration, output.len());
 
@@ -77,6 +123,17 @@ impl NaiveSpeechSynth {
             *excitation_sample *= 4.0;
         }
 
+        let consonant_gain = if self.click_duration != 0 {
+            self.consonant_level
+        } else {
+            1.0
+        };
+        let mut consonant_gain_modulation =
+            ParameterInterpolator::new(&mut self.consonant_gain, consonant_gain, excitation.len());
+        for excitation_sample in excitation.iter_mut() {
+            *excitation_sample *= consonant_gain_modulation.next();
+        }
+
         let p = phoneme * (NUM_PHONEMES as f32 - 1.001);
         let r = vocal_register * (NUM_REGISTERS as f32 - 1.001);
 
@@ -2,6 +2,7 @@
 
 // Based on MIT-licensed code (c) 2016 by Emilie Gillet (emilie.o.gillet@gmail.com)
 
+use super::{DEFAULT_CONSONANT_DURATION_MS, MAX_CONSONANT_DURATION_MS};
 use crate::dsp::oscillator::sine_oscillator::sine_raw;
 use crate::dsp::SAMPLE_RATE;
 use crate::stmlib::dsp::parameter_interpolator::ParameterInterpolator;
@@ -12,7 +13,7 @@ const NUM_VOWELS: usize = 9;
 const NUM_CONSONANTS: usize = 8;
 const NUM_PHONEMES: usize = NUM_VOWELS + NUM_CONSONANTS;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct SamSpeechSynth {
     phase: f32,
     frequency: f32,
@@ -23,11 +24,30 @@ pub struct SamSpeechSynth {
     formant_phase: [u32; 3],
     consonant_samples: usize,
     consonant_index: f32,
+
+    consonant_level: f32,
+    consonant_duration_ms: f32,
+}
+
+impl Default for SamSpeechSynth {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SamSpeechSynth {
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            phase: 0.0,
+            frequency: 0.0,
+            pulse_next_sample: 0.0,
+            pulse_lp: 0.0,
+            formant_phase: [0; 3],
+            consonant_samples: 0,
+            consonant_index: 0.0,
+            consonant_level: 1.0,
+            consonant_duration_ms: DEFAULT_CONSONANT_DURATION_MS,
+        }
     }
 
     pub fn init(&mut self) {
@@ -41,6 +61,30 @@ impl SamSpeechSynth {
         self.consonant_index = 0.0;
     }
 
+    /// Sets the level of the consonants relative to the vowels (0.0 to 2.0). Default is
+    /// `1.0`.
+    #[inline]
+    pub fn set_consonant_level(&mut self, level: f32) {
+        self.consonant_level = level.clamp(0.0, 2.0);
+    }
+
+    #[inline]
+    pub fn consonant_level(&self) -> f32 {
+        self.consonant_level
+    }
+
+    /// Sets the duration of the consonants in milliseconds (0.0 to
+    /// [`MAX_CONSONANT_DURATION_MS`]). Default is [`DEFAULT_CONSONANT_DURATION_MS`].
+    #[inline]
+    pub fn set_consonant_duration_ms(&mut self, duration: f32) {
+        self.consonant_duration_ms = duration.clamp(0.0, MAX_CONSONANT_DURATION_MS);
+    }
+
+    #[inline]
+    pub fn consonant_duration_ms(&self) -> f32 {
+        self.consonant_duration_ms
+    }
+
     #[inline]
     pub fn render(
         &mut self,
@@ -56,7 +100,7 @@ impl SamSpeechSynth {
         }
 
         if consonant {
-            self.consonant_samples = (SAMPLE_RATE * 0.05) as usize;
+            self.consonant_samples = (SAMPLE_RATE * self.consonant_duration_ms / 1000.0) as usize;
             let r = (vowel + 3.0 * frequency + 7.0 * formant_shift) * 8.0;
             self.consonant_index = (r as usize % NUM_CONSONANTS) as f32;
         }
@@ -78,6 +122,12 @@ impl SamSpeechSynth {
             formant_amplitude.as_mut_slice(),
         );
 
+        if self.consonant_samples != 0 {
+            for amplitude in formant_amplitude.iter_mut() {
+                *amplitude *= self.consonant_level;
+            }
+        }
+
         let mut fm = ParameterInterpolator::new(&mut self.frequency, frequency, output.len());
         let mut pulse_next_sample = self.pulse_next_sample;
 
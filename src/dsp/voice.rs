@@ -30,22 +30,78 @@ use super::engine2::six_op_engine::SixOpEngine;
 use super::engine2::string_machine_engine::StringMachineEngine;
 use super::engine2::virtual_analog_vcf_engine::VirtualAnalogVcfEngine;
 use super::engine2::wave_terrain_engine::WaveTerrainEngine;
-use super::envelope::{DecayEnvelope, LpgEnvelope};
+use super::envelope::{DecayEnvelope, LpgEnvelope, DEFAULT_BRIGHTNESS_TRACKING};
 use super::fx::low_pass_gate::LowPassGate;
 use super::physical_modelling::delay_line::DelayLine;
 use crate::dsp::resources::sysex::{SYX_BANK_0, SYX_BANK_1, SYX_BANK_2};
 use crate::dsp::resources::waves::WAV_INTEGRATED_WAVES;
-use crate::dsp::{allocate_buffer, SAMPLE_RATE};
-use crate::stmlib::dsp::clip_16;
+use crate::dsp::{allocate_buffer, allocate_value, SAMPLE_RATE};
+use crate::stmlib::dsp::filter::{
+    CrossoverSvf, DcBlocker, FilterMode, FrequencyApproximation, OnePole,
+};
 use crate::stmlib::dsp::hysteresis_quantizer::HysteresisQuantizer2;
 use crate::stmlib::dsp::limiter::Limiter;
 use crate::stmlib::dsp::units::semitones_to_ratio;
+use crate::stmlib::dsp::{clip_16, one_pole, soft_clip, soft_limit};
+use crate::utils::modulation::ring_mod;
 
 const MAX_TRIGGER_DELAY: usize = 8;
+
+/// Length of the fade-in after a reset of the post processor, 2 ms.
+const DEFAULT_FADE_IN_LENGTH: usize = (SAMPLE_RATE * 0.002) as usize;
+const RETRIGGER_NOTE_THRESHOLD: f32 = 0.5;
+
+// Notes are offset by a whole number of octaves to be positive when snapped to a scale.
+const SCALE_NOTE_OFFSET: f32 = 132.0;
+const SCALE_OCTAVES: usize = 22;
+const SCALE_HYSTERESIS: f32 = 0.15;
+#[cfg(feature = "alloc")]
+const ENVELOPE_PREVIEW_BLOCK_SIZE: usize = 24;
 pub const NUM_ENGINES: usize = 24;
 
+/// Maximum number of copies of the engine rendered in unison, see [`Voice::set_unison`].
+pub const MAX_UNISON: usize = 4;
+
+/// RMS level of *OUT* before the low-pass gate the engines are trimmed to, see
+/// [`Voice::set_auto_level`].
+pub const AUTO_LEVEL_TARGET: f32 = 0.2;
+
+// Time constant of the level measurement and of the trim in seconds.
+const AUTO_LEVEL_TIME: f32 = 0.3;
+
+// Blocks quieter than -60 dB, e.g. decaying tails, are not measured.
+const AUTO_LEVEL_GATE: f32 = 0.001;
+
+// Range of the trim, +/-12 dB.
+const AUTO_LEVEL_MIN_GAIN: f32 = 0.25;
+const AUTO_LEVEL_MAX_GAIN: f32 = 4.0;
+
+/// Default frequency of A4 (MIDI note 69) in Hz.
+pub const DEFAULT_TUNING_REFERENCE: f32 = 440.0;
+
+/// Low-pass gate mode.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LpgMode {
+    /// The LPG is active when trigger or level are patched and the engine
+    /// is not already enveloped. This is the default.
+    #[default]
+    Auto,
+
+    /// The LPG is always active, also on engines with their own envelope.
+    ForceOn,
+
+    /// The LPG is always bypassed.
+    ForceOff,
+}
+
 /// Patch parameters.
+///
+/// With the `serde` feature enabled, missing fields are deserialized with their default
+/// values, so that patches saved by earlier versions can still be loaded.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Patch {
     /// Note number in the range from `-119.0` to `120.0`. Default is `48.0`.
     pub note: f32,
@@ -76,6 +132,25 @@ pub struct Patch {
 
     /// Low-pass gate color in the range from `0.0` to `1.0`. Default is `0.5`.
     pub lpg_colour: f32,
+
+    /// Amount by which the low-pass gate darkens the sound as it decays, in the range from
+    /// `0.0` (constant brightness) to `1.0`. Default is `0.5`.
+    pub lpg_brightness_tracking: f32,
+
+    /// Fine tuning in semitones in the range from `-24.0` to `24.0`, added to the note.
+    /// Default is `0.0`.
+    pub fine_tune: f32,
+
+    /// Low-pass gate mode. Default is `LpgMode::Auto`.
+    pub lpg_mode: LpgMode,
+
+    /// Retrigger the internal envelope when the note changes, without a new trigger.
+    /// Default is `false`.
+    pub retrigger_on_note_change: bool,
+
+    /// Delay the trigger by 1ms to compensate CV lagging behind the gate.
+    /// Default is `true`.
+    pub trigger_delay_enabled: bool,
 }
 
 impl Default for Patch {
@@ -91,10 +166,76 @@ impl Default for Patch {
             engine: 0,
             decay: 0.5,
             lpg_colour: 0.5,
+            lpg_brightness_tracking: DEFAULT_BRIGHTNESS_TRACKING,
+            fine_tune: 0.0,
+            lpg_mode: LpgMode::Auto,
+            retrigger_on_note_change: false,
+            trigger_delay_enabled: true,
         }
     }
 }
 
+impl Patch {
+    /// Clamp all parameters to their documented ranges.
+    pub fn clamp(&mut self) {
+        self.note = self.note.clamp(-119.0, 120.0);
+        self.harmonics = self.harmonics.clamp(0.0, 1.0);
+        self.timbre = self.timbre.clamp(0.0, 1.0);
+        self.morph = self.morph.clamp(0.0, 1.0);
+        self.frequency_modulation_amount = self.frequency_modulation_amount.clamp(-1.0, 1.0);
+        self.timbre_modulation_amount = self.timbre_modulation_amount.clamp(-1.0, 1.0);
+        self.morph_modulation_amount = self.morph_modulation_amount.clamp(-1.0, 1.0);
+        self.engine = self.engine.min(NUM_ENGINES - 1);
+        self.decay = self.decay.clamp(0.0, 1.0);
+        self.lpg_colour = self.lpg_colour.clamp(0.0, 1.0);
+        self.lpg_brightness_tracking = self.lpg_brightness_tracking.clamp(0.0, 1.0);
+        self.fine_tune = self.fine_tune.clamp(-24.0, 24.0);
+    }
+
+    /// Return `true` if all parameters are finite numbers.
+    pub fn is_finite(&self) -> bool {
+        self.note.is_finite()
+            && self.harmonics.is_finite()
+            && self.timbre.is_finite()
+            && self.morph.is_finite()
+            && self.frequency_modulation_amount.is_finite()
+            && self.timbre_modulation_amount.is_finite()
+            && self.morph_modulation_amount.is_finite()
+            && self.decay.is_finite()
+            && self.lpg_colour.is_finite()
+            && self.lpg_brightness_tracking.is_finite()
+            && self.fine_tune.is_finite()
+    }
+
+    /// Replace all non-finite parameters with their default values.
+    pub fn sanitize(&mut self) {
+        let default = Self::default();
+        sanitize(&mut self.note, default.note);
+        sanitize(&mut self.harmonics, default.harmonics);
+        sanitize(&mut self.timbre, default.timbre);
+        sanitize(&mut self.morph, default.morph);
+        sanitize(
+            &mut self.frequency_modulation_amount,
+            default.frequency_modulation_amount,
+        );
+        sanitize(
+            &mut self.timbre_modulation_amount,
+            default.timbre_modulation_amount,
+        );
+        sanitize(
+            &mut self.morph_modulation_amount,
+            default.morph_modulation_amount,
+        );
+        sanitize(&mut self.decay, default.decay);
+        sanitize(&mut self.lpg_colour, default.lpg_colour);
+        sanitize(
+            &mut self.lpg_brightness_tracking,
+            default.lpg_brightness_tracking,
+        );
+        sanitize(&mut self.fine_tune, default.fine_tune);
+    }
+}
+
 /// Modulation parameters.
 #[derive(Debug, Default, Clone)]
 pub struct Modulations {
@@ -104,6 +245,10 @@ pub struct Modulations {
     /// Note number modulation in the range from `-119.0` to `120.0`. Default is `0.0`.
     pub note: f32,
 
+    /// Pitch bend in semitones in the range from `-24.0` to `24.0`, added to the note.
+    /// Default is `0.0`.
+    pub pitch_bend: f32,
+
     /// Frequency modulation in the range from `-1.0` to `1.0`. Default is `0.0`.
     pub frequency: f32,
 
@@ -136,6 +281,62 @@ pub struct Modulations {
 
     /// Flag if level modulation is used. Default is `false`.
     pub level_patched: bool,
+
+    /// Position of the rising edge of the trigger within the block, in samples.
+    /// Default is `0`.
+    pub trigger_sample_offset: usize,
+}
+
+impl Modulations {
+    /// Clamp all parameters to their documented ranges.
+    pub fn clamp(&mut self) {
+        self.engine = self.engine.clamp(-1.0, 1.0);
+        self.note = self.note.clamp(-119.0, 120.0);
+        self.pitch_bend = self.pitch_bend.clamp(-24.0, 24.0);
+        self.frequency = self.frequency.clamp(-1.0, 1.0);
+        self.harmonics = self.harmonics.clamp(-1.0, 1.0);
+        self.timbre = self.timbre.clamp(-1.0, 1.0);
+        self.morph = self.morph.clamp(-1.0, 1.0);
+        self.trigger = self.trigger.clamp(0.0, 1.0);
+        self.level = self.level.clamp(0.0, 1.0);
+    }
+
+    /// Return `true` if all parameters are finite numbers.
+    pub fn is_finite(&self) -> bool {
+        self.engine.is_finite()
+            && self.note.is_finite()
+            && self.pitch_bend.is_finite()
+            && self.frequency.is_finite()
+            && self.harmonics.is_finite()
+            && self.timbre.is_finite()
+            && self.morph.is_finite()
+            && self.trigger.is_finite()
+            && self.level.is_finite()
+    }
+
+    /// Replace all non-finite parameters with `0.0`.
+    pub fn sanitize(&mut self) {
+        sanitize(&mut self.engine, 0.0);
+        sanitize(&mut self.note, 0.0);
+        sanitize(&mut self.pitch_bend, 0.0);
+        sanitize(&mut self.frequency, 0.0);
+        sanitize(&mut self.harmonics, 0.0);
+        sanitize(&mut self.timbre, 0.0);
+        sanitize(&mut self.morph, 0.0);
+        sanitize(&mut self.trigger, 0.0);
+        sanitize(&mut self.level, 0.0);
+    }
+
+    /// Return a copy with all non-finite parameters replaced, clamped if `clamp` is set.
+    #[inline]
+    fn sanitized(&self, clamp: bool) -> Self {
+        let mut modulations = self.clone();
+        modulations.sanitize();
+        if clamp {
+            modulations.clamp();
+        }
+        modulations
+    }
 }
 
 /// Resources used by some of the engines. The provided data is loaded when an engine
@@ -170,38 +371,61 @@ impl<'a> Default for Resources<'a> {
     }
 }
 
+/// Synthesis voice.
+///
+/// The engines are accessed with methods like [`Voice::string_engine`], which return
+/// `None` for engines disabled with [`VoiceBuilder`].
 #[derive(Debug)]
 pub struct Voice<'a> {
-    pub additive_engine: AdditiveEngine,
-    pub bass_drum_engine: BassDrumEngine,
-    pub chiptune_engine: ChiptuneEngine,
-    pub chord_engine: ChordEngine<'a>,
-    pub fm_engine: FmEngine,
-    pub grain_engine: GrainEngine,
-    pub hihat_engine: HihatEngine<'a>,
-    pub modal_engine: ModalEngine<'a>,
-    pub noise_engine: NoiseEngine<'a>,
-    pub particle_engine: ParticleEngine<'a>,
-    pub phase_distortion_engine: PhaseDistortionEngine<'a>,
-    pub six_op_engine: SixOpEngine<'a>,
-    pub snare_drum_engine: SnareDrumEngine,
-    pub speech_engine: SpeechEngine<'a>,
-    pub string_engine: StringEngine<'a>,
-    pub string_machine_engine: StringMachineEngine,
-    pub swarm_engine: SwarmEngine,
-    pub virtual_analog_engine: VirtualAnalogEngine<'a>,
-    pub virtual_analog_vcf_engine: VirtualAnalogVcfEngine,
-    pub waveshaping_engine: WaveshapingEngine,
-    pub wavetable_engine: WavetableEngine<'a>,
-    pub waveterrain_engine: WaveTerrainEngine<'a>,
+    additive_engine: Option<&'a mut AdditiveEngine>,
+    bass_drum_engine: Option<&'a mut BassDrumEngine>,
+    chiptune_engine: Option<&'a mut ChiptuneEngine>,
+    chord_engine: Option<&'a mut ChordEngine<'a>>,
+    fm_engine: Option<&'a mut FmEngine>,
+    grain_engine: Option<&'a mut GrainEngine>,
+    hihat_engine: Option<&'a mut HihatEngine<'a>>,
+    modal_engine: Option<&'a mut ModalEngine<'a>>,
+    noise_engine: Option<&'a mut NoiseEngine<'a>>,
+    particle_engine: Option<&'a mut ParticleEngine<'a>>,
+    phase_distortion_engine: Option<&'a mut PhaseDistortionEngine<'a>>,
+    six_op_engine: Option<&'a mut SixOpEngine<'a>>,
+    snare_drum_engine: Option<&'a mut SnareDrumEngine>,
+    speech_engine: Option<&'a mut SpeechEngine<'a>>,
+    string_engine: Option<&'a mut StringEngine<'a>>,
+    string_machine_engine: Option<&'a mut StringMachineEngine>,
+    swarm_engine: Option<&'a mut SwarmEngine>,
+    virtual_analog_engine: Option<&'a mut VirtualAnalogEngine<'a>>,
+    virtual_analog_vcf_engine: Option<&'a mut VirtualAnalogVcfEngine>,
+    waveshaping_engine: Option<&'a mut WaveshapingEngine>,
+    wavetable_engine: Option<&'a mut WavetableEngine<'a>>,
+    waveterrain_engine: Option<&'a mut WaveTerrainEngine<'a>>,
 
     pub resources: Resources<'a>,
 
+    builder: VoiceBuilder,
+    block_size: usize,
+
     engine_quantizer: HysteresisQuantizer2,
 
     reload_resources: bool,
+    clamp_parameters: bool,
+    parameter_smoothing: usize,
+    smoothed_macros: Option<[f32; 3]>,
     previous_engine_index: usize,
     engine_cv: f32,
+    tuning_reference: f32,
+    tuning_offset: f32,
+    scale: [u8; 12],
+    scale_size: usize,
+    scale_quantizer: HysteresisQuantizer2,
+    unison_count: usize,
+    unison_detune: f32,
+    unison_engines: [UnisonEngines<'a>; MAX_UNISON - 1],
+    unison_out: &'a mut [f32],
+    unison_aux: &'a mut [f32],
+    auto_level_enabled: bool,
+    auto_level: AutoLevel,
+    lpg_freeze: bool,
 
     previous_note: f32,
     trigger_state: bool,
@@ -210,43 +434,165 @@ pub struct Voice<'a> {
     lpg_envelope: LpgEnvelope,
 
     trigger_delay: DelayLine<'a, f32, MAX_TRIGGER_DELAY>,
+    trigger_sample_offset: [usize; MAX_TRIGGER_DELAY],
 
     out_post_processor: ChannelPostProcessor,
     aux_post_processor: ChannelPostProcessor,
+    mono_mixer: MonoMixer,
 }
 
-impl<'a> Voice<'a> {
-    pub fn new<T: GlobalAlloc>(buffer_allocator: &T, block_size: usize) -> Self {
+/// Builder for a [`Voice`] with a subset of the engines.
+///
+/// Engines are placed in memory provided by the buffer allocator, so disabled engines
+/// do not use any memory. A voice built with all engines enabled is equivalent to
+/// [`Voice::new`]. Selecting a disabled engine renders silence.
+///
+/// Like the other buffers of the voice, the engines are never returned to the allocator,
+/// nor dropped. The allocator is meant to provide memory for the lifetime of the
+/// program, so dropping a voice leaks its memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoiceBuilder {
+    enabled_engines: [bool; NUM_ENGINES],
+    unison: bool,
+}
+
+impl Default for VoiceBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VoiceBuilder {
+    /// Returns a builder with all engines enabled.
+    pub fn new() -> Self {
         Self {
-            additive_engine: AdditiveEngine::new(),
-            bass_drum_engine: BassDrumEngine::new(),
-            chiptune_engine: ChiptuneEngine::new(),
-            chord_engine: ChordEngine::new(),
-            fm_engine: FmEngine::new(),
-            grain_engine: GrainEngine::new(),
-            hihat_engine: HihatEngine::new(buffer_allocator, block_size),
-            modal_engine: ModalEngine::new(buffer_allocator, block_size),
-            noise_engine: NoiseEngine::new(buffer_allocator, block_size),
-            particle_engine: ParticleEngine::new(buffer_allocator, block_size),
-            phase_distortion_engine: PhaseDistortionEngine::new(buffer_allocator, block_size),
-            six_op_engine: SixOpEngine::new(buffer_allocator, block_size),
-            snare_drum_engine: SnareDrumEngine::new(),
-            speech_engine: SpeechEngine::new(buffer_allocator, block_size),
-            string_engine: StringEngine::new(buffer_allocator, block_size),
-            string_machine_engine: StringMachineEngine::new(),
-            swarm_engine: SwarmEngine::new(),
-            virtual_analog_engine: VirtualAnalogEngine::new(buffer_allocator, block_size),
-            virtual_analog_vcf_engine: VirtualAnalogVcfEngine::new(),
-            waveshaping_engine: WaveshapingEngine::new(),
-            wavetable_engine: WavetableEngine::new(),
-            waveterrain_engine: WaveTerrainEngine::new(buffer_allocator, block_size),
+            enabled_engines: [true; NUM_ENGINES],
+            unison: false,
+        }
+    }
+
+    /// Enables only the engines at the given indices and disables all others.
+    pub fn with_engines(mut self, indices: &[usize]) -> Self {
+        self.enabled_engines = [false; NUM_ENGINES];
+
+        for &index in indices {
+            self = self.enable_engine(index, true);
+        }
+
+        self
+    }
+
+    /// Enables or disables the engine at `index`. Out of range indices are ignored.
+    pub fn enable_engine(mut self, index: usize, enabled: bool) -> Self {
+        if let Some(engine_enabled) = self.enabled_engines.get_mut(index) {
+            *engine_enabled = enabled;
+        }
+
+        self
+    }
+
+    /// Returns `true` if the engine at `index` is enabled.
+    pub fn is_engine_enabled(&self, index: usize) -> bool {
+        self.enabled_engines.get(index).copied().unwrap_or(false)
+    }
+
+    /// Allocates the additional copies of the enabled engines rendered by
+    /// [`Voice::set_unison`]. Default is `false`, which makes `set_unison` ineffective.
+    pub fn enable_unison(mut self, enabled: bool) -> Self {
+        self.unison = enabled;
+        self
+    }
+
+    /// Returns `true` if the copies of the engines rendered in unison are allocated.
+    pub fn is_unison_enabled(&self) -> bool {
+        self.unison
+    }
+
+    /// Builds the voice, allocating all enabled engines and their buffers.
+    pub fn build<'a, T: GlobalAlloc>(&self, buffer_allocator: &T, block_size: usize) -> Voice<'a> {
+        Voice {
+            additive_engine: self.allocate_engine(buffer_allocator, &[12], AdditiveEngine::new),
+            bass_drum_engine: self.allocate_engine(buffer_allocator, &[21], BassDrumEngine::new),
+            chiptune_engine: self.allocate_engine(buffer_allocator, &[7], ChiptuneEngine::new),
+            chord_engine: self.allocate_engine(buffer_allocator, &[14], ChordEngine::new),
+            fm_engine: self.allocate_engine(buffer_allocator, &[10], FmEngine::new),
+            grain_engine: self.allocate_engine(buffer_allocator, &[11], GrainEngine::new),
+            hihat_engine: self.allocate_engine(buffer_allocator, &[23], || {
+                HihatEngine::new(buffer_allocator, block_size)
+            }),
+            modal_engine: self.allocate_engine(buffer_allocator, &[20], || {
+                ModalEngine::new(buffer_allocator, block_size)
+            }),
+            noise_engine: self.allocate_engine(buffer_allocator, &[17], || {
+                NoiseEngine::new(buffer_allocator, block_size)
+            }),
+            particle_engine: self.allocate_engine(buffer_allocator, &[18], || {
+                ParticleEngine::new(buffer_allocator, block_size)
+            }),
+            phase_distortion_engine: self.allocate_engine(buffer_allocator, &[1], || {
+                PhaseDistortionEngine::new(buffer_allocator, block_size)
+            }),
+            six_op_engine: self.allocate_engine(buffer_allocator, &[2, 3, 4], || {
+                SixOpEngine::new(buffer_allocator, block_size)
+            }),
+            snare_drum_engine: self.allocate_engine(buffer_allocator, &[22], SnareDrumEngine::new),
+            speech_engine: self.allocate_engine(buffer_allocator, &[15], || {
+                SpeechEngine::new(buffer_allocator, block_size)
+            }),
+            string_engine: self.allocate_engine(buffer_allocator, &[19], || {
+                StringEngine::new(buffer_allocator, block_size)
+            }),
+            string_machine_engine: self.allocate_engine(
+                buffer_allocator,
+                &[6],
+                StringMachineEngine::new,
+            ),
+            swarm_engine: self.allocate_engine(buffer_allocator, &[16], SwarmEngine::new),
+            virtual_analog_engine: self.allocate_engine(buffer_allocator, &[8], || {
+                VirtualAnalogEngine::new(buffer_allocator, block_size)
+            }),
+            virtual_analog_vcf_engine: self.allocate_engine(
+                buffer_allocator,
+                &[0],
+                VirtualAnalogVcfEngine::new,
+            ),
+            waveshaping_engine: self.allocate_engine(
+                buffer_allocator,
+                &[9],
+                WaveshapingEngine::new,
+            ),
+            wavetable_engine: self.allocate_engine(buffer_allocator, &[13], WavetableEngine::new),
+            waveterrain_engine: self.allocate_engine(buffer_allocator, &[5], || {
+                WaveTerrainEngine::new(buffer_allocator, block_size)
+            }),
 
             resources: Resources::default(),
 
+            builder: *self,
+            block_size,
+
             engine_quantizer: HysteresisQuantizer2::new(),
             reload_resources: false,
+            clamp_parameters: false,
+            parameter_smoothing: 0,
+            smoothed_macros: None,
             previous_engine_index: 0,
             engine_cv: 0.0,
+            tuning_reference: DEFAULT_TUNING_REFERENCE,
+            tuning_offset: 0.0,
+            scale: [0; 12],
+            scale_size: 0,
+            scale_quantizer: HysteresisQuantizer2::new(),
+            unison_count: 1,
+            unison_detune: 0.0,
+            unison_engines: core::array::from_fn(|_| {
+                self.allocate_unison_engines(buffer_allocator, block_size)
+            }),
+            unison_out: self.allocate_unison_buffer(buffer_allocator, block_size),
+            unison_aux: self.allocate_unison_buffer(buffer_allocator, block_size),
+            auto_level_enabled: false,
+            auto_level: AutoLevel::new(),
+            lpg_freeze: false,
 
             previous_note: 0.0,
             trigger_state: false,
@@ -260,22 +606,153 @@ impl<'a> Voice<'a> {
                     .try_into()
                     .unwrap(),
             ),
+            trigger_sample_offset: [0; MAX_TRIGGER_DELAY],
 
             out_post_processor: ChannelPostProcessor::new(),
             aux_post_processor: ChannelPostProcessor::new(),
+            mono_mixer: MonoMixer::new(),
+        }
+    }
+
+    /// Moves an engine into allocated memory if any of the engine indices it serves is
+    /// enabled. The engine is only constructed in that case.
+    fn allocate_engine<'a, T: GlobalAlloc, E>(
+        &self,
+        buffer_allocator: &T,
+        indices: &[usize],
+        engine: impl FnOnce() -> E,
+    ) -> Option<&'a mut E> {
+        if indices.iter().any(|&index| self.is_engine_enabled(index)) {
+            Some(allocate_value(buffer_allocator, engine()).unwrap())
+        } else {
+            None
+        }
+    }
+
+    /// Allocates a copy of each enabled engine with unison, if unison is enabled.
+    fn allocate_unison_engines<'a, T: GlobalAlloc>(
+        &self,
+        buffer_allocator: &T,
+        block_size: usize,
+    ) -> UnisonEngines<'a> {
+        if !self.unison {
+            return UnisonEngines::default();
+        }
+
+        UnisonEngines {
+            virtual_analog_vcf_engine: self.allocate_engine(
+                buffer_allocator,
+                &[0],
+                VirtualAnalogVcfEngine::new,
+            ),
+            phase_distortion_engine: self.allocate_engine(buffer_allocator, &[1], || {
+                PhaseDistortionEngine::new(buffer_allocator, block_size)
+            }),
+            virtual_analog_engine: self.allocate_engine(buffer_allocator, &[8], || {
+                VirtualAnalogEngine::new(buffer_allocator, block_size)
+            }),
+            waveshaping_engine: self.allocate_engine(
+                buffer_allocator,
+                &[9],
+                WaveshapingEngine::new,
+            ),
+            fm_engine: self.allocate_engine(buffer_allocator, &[10], FmEngine::new),
+        }
+    }
+
+    /// Allocates a buffer for the output of a unison copy, if unison is enabled.
+    fn allocate_unison_buffer<'a, T: GlobalAlloc>(
+        &self,
+        buffer_allocator: &T,
+        block_size: usize,
+    ) -> &'a mut [f32] {
+        if self.unison {
+            allocate_buffer(buffer_allocator, block_size).unwrap()
+        } else {
+            &mut []
+        }
+    }
+}
+
+impl<'a> Voice<'a> {
+    /// Creates a voice with all engines, see [`VoiceBuilder`] to select a subset.
+    pub fn new<T: GlobalAlloc>(buffer_allocator: &T, block_size: usize) -> Self {
+        VoiceBuilder::new().build(buffer_allocator, block_size)
+    }
+
+    /// Creates a new voice with the same engines, engine selection and settings as this
+    /// one, e.g. for stacking detuned voices. The envelopes and parameter smoothing are
+    /// copied as well, but not the internal state of the engines, which start from
+    /// `init`. Settings made directly on the engines are not copied either.
+    pub fn duplicate<T: GlobalAlloc>(&self, buffer_allocator: &T) -> Self {
+        let mut voice = self.builder.build(buffer_allocator, self.block_size);
+        voice.init();
+
+        voice.resources = self.resources.clone();
+        voice.engine_quantizer = self.engine_quantizer.clone();
+        // Loads the resources and resets the engine on the next render.
+        voice.reload_resources = true;
+        voice.clamp_parameters = self.clamp_parameters;
+        voice.parameter_smoothing = self.parameter_smoothing;
+        voice.smoothed_macros = self.smoothed_macros;
+        voice.previous_engine_index = self.previous_engine_index;
+        voice.engine_cv = self.engine_cv;
+        voice.set_tuning_reference(self.tuning_reference);
+        voice.set_scale(self.scale());
+        voice.scale_quantizer = self.scale_quantizer.clone();
+        voice.set_unison(self.unison_count, self.unison_detune);
+        voice.auto_level_enabled = self.auto_level_enabled;
+        voice.auto_level = self.auto_level.clone();
+        voice.lpg_freeze = self.lpg_freeze;
+
+        voice.previous_note = self.previous_note;
+        voice.trigger_state = self.trigger_state;
+
+        voice.decay_envelope = self.decay_envelope.clone();
+        voice.lpg_envelope = self.lpg_envelope.clone();
+
+        for (post_processor, original) in [
+            (&mut voice.out_post_processor, &self.out_post_processor),
+            (&mut voice.aux_post_processor, &self.aux_post_processor),
+        ] {
+            post_processor.set_saturation(original.saturation);
+            post_processor.set_dc_block(original.dc_block);
+            post_processor.set_fade_in_length(original.fade_in_length);
+            post_processor.set_tone(original.tone);
+            let (attack, release) = original.limiter_times();
+            post_processor.set_limiter_times(attack, release);
+            post_processor.set_limiter_enabled(original.limiter_enabled);
+            post_processor.set_invert(original.invert);
         }
+
+        voice
+            .mono_mixer
+            .set_crossover_frequency(self.mono_mixer.crossover_frequency);
+
+        voice
     }
 
     pub fn init(&mut self) {
         for i in 0..NUM_ENGINES {
-            self.get_engine(i).unwrap().0.init();
+            if let Some(engine) = self.get_engine(i) {
+                engine.0.init();
+            }
+
+            for unison_engines in self.unison_engines.iter_mut() {
+                if let Some(engine) = unison_engines.get(i) {
+                    engine.init();
+                }
+            }
         }
 
         self.engine_quantizer.init(NUM_ENGINES as i32, 0.05, true);
         self.out_post_processor.init();
         self.aux_post_processor.init();
+        self.mono_mixer.init();
+        self.auto_level.init();
         self.decay_envelope.init();
         self.lpg_envelope.init();
+        self.smoothed_macros = None;
     }
 
     #[inline]
@@ -283,6 +760,142 @@ impl<'a> Voice<'a> {
         self.reload_resources = true;
     }
 
+    /// Enable clamping of patch and modulation parameters to their documented ranges
+    /// before rendering. Default is `false`.
+    #[inline]
+    pub fn set_clamp_parameters(&mut self, enabled: bool) {
+        self.clamp_parameters = enabled;
+    }
+
+    /// Smooth *HARMONICS*, *TIMBRE* and *MORPH* of the patch between `render` calls with a
+    /// time constant of `blocks` blocks, to prevent zipper noise on engines that do not
+    /// interpolate these parameters themselves. Default is `0`, which disables smoothing.
+    #[inline]
+    pub fn set_parameter_smoothing(&mut self, blocks: usize) {
+        self.parameter_smoothing = blocks;
+        if blocks == 0 {
+            self.smoothed_macros = None;
+        }
+    }
+
+    /// Set the frequency of A4 (MIDI note 69) in Hz, from 220 Hz to 880 Hz, e.g. 432 Hz or
+    /// 442 Hz. This transposes all engines, including their note-dependent parameters.
+    /// Default is [`DEFAULT_TUNING_REFERENCE`].
+    pub fn set_tuning_reference(&mut self, a4_hz: f32) {
+        self.tuning_reference = a4_hz.clamp(220.0, 880.0);
+        self.tuning_offset = if self.tuning_reference == DEFAULT_TUNING_REFERENCE {
+            0.0
+        } else {
+            12.0 * (self.tuning_reference / DEFAULT_TUNING_REFERENCE).log2()
+        };
+    }
+
+    #[inline]
+    pub fn tuning_reference(&self) -> f32 {
+        self.tuning_reference
+    }
+
+    /// Snaps the note to the nearest degree of a scale, given as pitch classes from `0`
+    /// (C) to `11` (B), e.g. `&[0, 2, 4, 7, 9]` for a major pentatonic scale. Hysteresis
+    /// prevents jitter between two degrees. Fine tuning, pitch bend and *FM* are applied
+    /// after snapping. `None` or an empty scale disables snapping, which is the default.
+    pub fn set_scale(&mut self, scale: Option<&[u8]>) {
+        let mut pitch_classes = [false; 12];
+        for degree in scale.unwrap_or(&[]) {
+            pitch_classes[(*degree % 12) as usize] = true;
+        }
+
+        self.scale_size = 0;
+        for (pitch_class, _) in pitch_classes.iter().enumerate().filter(|(_, used)| **used) {
+            self.scale[self.scale_size] = pitch_class as u8;
+            self.scale_size += 1;
+        }
+
+        self.scale_quantizer.init(
+            (self.scale_size * SCALE_OCTAVES).max(1) as i32,
+            SCALE_HYSTERESIS,
+            true,
+        );
+    }
+
+    /// Returns the pitch classes of the scale, sorted and without duplicates.
+    #[inline]
+    pub fn scale(&self) -> Option<&[u8]> {
+        (self.scale_size > 0).then_some(&self.scale[..self.scale_size])
+    }
+
+    /// Render `count` copies of the engine, from `1` to [`MAX_UNISON`], spread evenly over
+    /// `detune` semitones below and above the note, for a thicker sound without stacking
+    /// voices. The copies are summed and normalized by the square root of `count`. Default
+    /// is a single copy.
+    ///
+    /// The copies are only allocated by a voice built with [`VoiceBuilder::enable_unison`],
+    /// other voices ignore this setting. Only the engines for which
+    /// [`Voice::engine_has_unison`] returns `true` render copies: virtual analog VCF, phase
+    /// distortion, virtual analog, waveshaping and FM. Settings made directly on the
+    /// engines are not applied to the copies.
+    pub fn set_unison(&mut self, count: usize, detune: f32) {
+        self.unison_count = count.clamp(1, MAX_UNISON);
+        self.unison_detune = detune.clamp(0.0, 12.0);
+    }
+
+    /// Returns the number of copies and the detuning in semitones.
+    #[inline]
+    pub fn unison(&self) -> (usize, f32) {
+        (self.unison_count, self.unison_detune)
+    }
+
+    /// Trim the level of each engine toward [`AUTO_LEVEL_TARGET`], measured as the RMS of
+    /// *OUT* before the low-pass gate over the last few hundred milliseconds. The trim is
+    /// limited to +/-12 dB and kept per engine, and quiet passages such as decaying tails
+    /// do not change it. The same trim is applied to *AUX*. Default is `false`.
+    pub fn set_auto_level(&mut self, enabled: bool) {
+        self.auto_level_enabled = enabled;
+    }
+
+    #[inline]
+    pub fn auto_level(&self) -> bool {
+        self.auto_level_enabled
+    }
+
+    /// Hold the gain and cutoff of the LPG once the attack of a trigger has completed, for
+    /// as long as the trigger stays high, so that the engines gated by the LPG sustain
+    /// indefinitely, e.g. for drones. The LPG decays as usual when the trigger goes low.
+    /// When the level is patched, the LPG follows it as usual. Default is `false`.
+    pub fn set_lpg_freeze(&mut self, enabled: bool) {
+        self.lpg_freeze = enabled;
+    }
+
+    #[inline]
+    pub fn lpg_freeze(&self) -> bool {
+        self.lpg_freeze
+    }
+
+    /// Returns the degree of the scale `note` is snapped to.
+    fn snap_to_scale(&mut self, note: f32) -> f32 {
+        let size = self.scale_size;
+        let degree = |index: isize| {
+            let octave = index.div_euclid(size as isize) as f32;
+            octave * 12.0 + self.scale[index.rem_euclid(size as isize) as usize] as f32
+        };
+
+        // Continuous index of the degrees, interpolated linearly between them.
+        let note = (note + SCALE_NOTE_OFFSET).max(0.0);
+        let octave = (note / 12.0) as isize;
+        let mut index = octave * size as isize - 1;
+        while degree(index + 1) <= note {
+            index += 1;
+        }
+        let position = index as f32 + (note - degree(index)) / (degree(index + 1) - degree(index));
+
+        let num_steps = self.scale_quantizer.num_steps();
+        let quantized = self
+            .scale_quantizer
+            .process(position / (num_steps - 1) as f32);
+
+        degree(quantized as isize) - SCALE_NOTE_OFFSET
+    }
+
     #[inline]
     pub fn render(
         &mut self,
@@ -291,13 +904,239 @@ impl<'a> Voice<'a> {
         out: &mut [f32],
         aux: &mut [f32],
     ) {
+        self.render_chunks(patch, core::slice::from_ref(modulations), out, aux);
+    }
+
+    /// Render like `render` and additionally write *OUT* ring-modulated by *AUX*
+    /// into `ring`.
+    #[inline]
+    pub fn render_ring_mod(
+        &mut self,
+        patch: &Patch,
+        modulations: &Modulations,
+        out: &mut [f32],
+        aux: &mut [f32],
+        ring: &mut [f32],
+    ) {
+        self.render(patch, modulations, out, aux);
+        ring_mod(out, aux, ring);
+    }
+
+    /// Render like `render` and additionally write the sum of *OUT* and *AUX* into `mono`,
+    /// with bass management applied if enabled by `set_bass_management`.
+    #[inline]
+    pub fn render_mono(
+        &mut self,
+        patch: &Patch,
+        modulations: &Modulations,
+        out: &mut [f32],
+        aux: &mut [f32],
+        mono: &mut [f32],
+    ) {
+        self.render(patch, modulations, out, aux);
+        self.mono_mixer.process(out, aux, mono);
+    }
+
+    /// Render like `render` with modulations changing within the block, e.g. for audio-rate
+    /// *FM* or *TIMBRE* modulation. The block is split into `modulations.len()` chunks of
+    /// equal length, so the block size must be a multiple of the number of modulations.
+    /// One `Modulations` per sample gives sample-accurate modulation, at the cost of
+    /// rendering the engine sample by sample.
+    ///
+    /// The parameter smoothing, the trigger delay and the gate are processed once per
+    /// block, as by `render`. The first rising edge of the trigger within the block starts
+    /// the note at the beginning of its chunk, while a falling edge is detected by the next
+    /// block. The envelopes and the LPG are updated once per chunk.
+    pub fn render_modulated(
+        &mut self,
+        patch: &Patch,
+        modulations: &[Modulations],
+        out: &mut [f32],
+        aux: &mut [f32],
+    ) {
+        debug_assert!(
+            !modulations.is_empty()
+                && modulations.len() <= out.len()
+                && out.len().is_multiple_of(modulations.len()),
+            "The block size must be a multiple of the number of modulations"
+        );
+
+        if modulations.is_empty() {
+            self.render(patch, &Modulations::default(), out, aux);
+        } else {
+            self.render_chunks(patch, modulations, out, aux);
+        }
+    }
+
+    /// Render a block with each of `modulations` applied to a chunk of equal length.
+    #[inline]
+    fn render_chunks(
+        &mut self,
+        patch: &Patch,
+        modulations: &[Modulations],
+        out: &mut [f32],
+        aux: &mut [f32],
+    ) {
+        let clamped_patch: Patch;
+
+        // Non-finite values would latch recursive filters and phase accumulators,
+        // so they are always replaced before dispatch.
+        let patch = if self.clamp_parameters || !patch.is_finite() {
+            clamped_patch = {
+                let mut patch = patch.clone();
+                patch.sanitize();
+                if self.clamp_parameters {
+                    patch.clamp();
+                }
+                patch
+            };
+            &clamped_patch
+        } else {
+            patch
+        };
+
+        let smoothed_patch: Patch;
+
+        let patch = if self.parameter_smoothing > 0 {
+            let target = [patch.harmonics, patch.timbre, patch.morph];
+            let coefficient = 1.0 / self.parameter_smoothing as f32;
+            let smoothed = self.smoothed_macros.get_or_insert(target);
+            for (value, target) in smoothed.iter_mut().zip(target) {
+                one_pole(value, target, coefficient);
+            }
+
+            smoothed_patch = Patch {
+                harmonics: smoothed[0],
+                timbre: smoothed[1],
+                morph: smoothed[2],
+                ..patch.clone()
+            };
+            &smoothed_patch
+        } else {
+            patch
+        };
+
+        let chunk_size = (out.len() / modulations.len()).max(1);
+        let clamp_parameters = self.clamp_parameters;
+        let chunk_modulations = |index: usize| {
+            modulations[index.min(modulations.len() - 1)].sanitized(clamp_parameters)
+        };
+
         // Trigger, LPG, internal envelope.
 
+        // A rising edge after the first chunk is passed on like a trigger sample offset.
+        let (trigger, trigger_sample_offset) = match modulations
+            .iter()
+            .position(|modulations| modulations.trigger > 0.3)
+        {
+            Some(index) if index > 0 => {
+                let modulations = chunk_modulations(index);
+                (
+                    modulations.trigger,
+                    index * chunk_size + modulations.trigger_sample_offset,
+                )
+            }
+            _ => {
+                let modulations = chunk_modulations(0);
+                (modulations.trigger, modulations.trigger_sample_offset)
+            }
+        };
+
         // Delay trigger by 1ms to deal with sequencers or MIDI interfaces whose
         // CV out lags behind the GATE out.
-        self.trigger_delay.write(modulations.trigger);
-        let trigger_value = self.trigger_delay.read_with_delay(MAX_TRIGGER_DELAY);
+        self.trigger_delay.write(trigger);
+
+        // The sample offset is delayed along with the trigger.
+        self.trigger_sample_offset
+            .copy_within(0..MAX_TRIGGER_DELAY - 1, 1);
+        self.trigger_sample_offset[0] = trigger_sample_offset;
+
+        let (trigger_value, trigger_sample_offset) = if patch.trigger_delay_enabled {
+            (
+                self.trigger_delay.read_with_delay(MAX_TRIGGER_DELAY),
+                self.trigger_sample_offset[MAX_TRIGGER_DELAY - 1],
+            )
+        } else {
+            (trigger, trigger_sample_offset)
+        };
+
+        if !self.trigger_state
+            && trigger_value > 0.3
+            && trigger_sample_offset > 0
+            && trigger_sample_offset < out.len()
+        {
+            // Render the part of the block before the rising edge separately.
+            let (out_head, out_tail) = out.split_at_mut(trigger_sample_offset);
+            let (aux_head, aux_tail) = aux.split_at_mut(trigger_sample_offset);
+            self.render_engine_chunks(patch, modulations, chunk_size, 0, false, out_head, aux_head);
+
+            let edge_modulations = chunk_modulations(trigger_sample_offset / chunk_size);
+            let previous_trigger_state = self.process_trigger(&edge_modulations, trigger_value);
+            self.render_engine_chunks(
+                patch,
+                modulations,
+                chunk_size,
+                trigger_sample_offset,
+                previous_trigger_state,
+                out_tail,
+                aux_tail,
+            );
+        } else {
+            let previous_trigger_state = self.process_trigger(&chunk_modulations(0), trigger_value);
+            self.render_engine_chunks(
+                patch,
+                modulations,
+                chunk_size,
+                0,
+                previous_trigger_state,
+                out,
+                aux,
+            );
+        }
+    }
+
+    /// Render the engine into `out`, which starts at sample `start` of the block, with the
+    /// modulations of the chunks it covers.
+    #[allow(clippy::too_many_arguments)]
+    #[inline]
+    fn render_engine_chunks(
+        &mut self,
+        patch: &Patch,
+        modulations: &[Modulations],
+        chunk_size: usize,
+        start: usize,
+        mut previous_trigger_state: bool,
+        out: &mut [f32],
+        aux: &mut [f32],
+    ) {
+        let mut position = 0;
+
+        while position < out.len() {
+            let index = ((start + position) / chunk_size).min(modulations.len() - 1);
+            let end = if index + 1 < modulations.len() {
+                ((index + 1) * chunk_size - start).min(out.len())
+            } else {
+                out.len()
+            };
+
+            let chunk_modulations = modulations[index].sanitized(self.clamp_parameters);
+            self.render_engine(
+                patch,
+                &chunk_modulations,
+                previous_trigger_state,
+                &mut out[position..end],
+                &mut aux[position..end],
+            );
+
+            // Only the first chunk sees the rising edge.
+            previous_trigger_state = self.trigger_state;
+            position = end;
+        }
+    }
 
+    /// Update the trigger state, returning the previous state.
+    #[inline]
+    fn process_trigger(&mut self, modulations: &Modulations, trigger_value: f32) -> bool {
         let previous_trigger_state = self.trigger_state;
 
         if !previous_trigger_state {
@@ -317,44 +1156,75 @@ impl<'a> Voice<'a> {
             self.engine_cv = modulations.engine;
         }
 
+        previous_trigger_state
+    }
+
+    #[inline]
+    fn render_engine(
+        &mut self,
+        patch: &Patch,
+        modulations: &Modulations,
+        previous_trigger_state: bool,
+        out: &mut [f32],
+        aux: &mut [f32],
+    ) {
         // Engine selection.
         let mut engine_index =
             self.engine_quantizer
                 .process_with_base(patch.engine as i32, self.engine_cv) as usize;
         engine_index = engine_index.clamp(0, NUM_ENGINES);
 
-        if engine_index != self.previous_engine_index || self.reload_resources {
+        let engine_changed = engine_index != self.previous_engine_index || self.reload_resources;
+
+        if engine_changed {
             match engine_index {
-                2 => {
-                    self.six_op_engine.load_syx_bank(self.resources.syx_bank_a);
-                }
-                3 => {
-                    self.six_op_engine.load_syx_bank(self.resources.syx_bank_b);
-                }
-                4 => {
-                    self.six_op_engine.load_syx_bank(self.resources.syx_bank_c);
+                2..=4 => {
+                    let syx_bank = match engine_index {
+                        2 => self.resources.syx_bank_a,
+                        3 => self.resources.syx_bank_b,
+                        _ => self.resources.syx_bank_c,
+                    };
+                    if let Some(six_op_engine) = self.six_op_engine.as_deref_mut() {
+                        six_op_engine.load_syx_bank(syx_bank);
+                    }
                 }
                 5 => {
-                    self.waveterrain_engine
-                        .set_user_terrain(self.resources.user_wave_terrain);
+                    if let Some(waveterrain_engine) = self.waveterrain_engine.as_deref_mut() {
+                        waveterrain_engine.set_user_terrain(self.resources.user_wave_terrain);
+                    }
                 }
                 13 => {
-                    self.wavetable_engine
-                        .set_wavetables(self.resources.wavetables);
+                    if let Some(wavetable_engine) = self.wavetable_engine.as_deref_mut() {
+                        wavetable_engine.set_wavetables(self.resources.wavetables);
+                    }
                 }
                 _ => {}
             }
 
-            let engine = self.get_engine(engine_index).unwrap().0;
-            engine.reset();
+            if let Some(engine) = self.get_engine(engine_index) {
+                engine.0.reset();
+            }
+
+            for unison_engines in self.unison_engines.iter_mut() {
+                if let Some(engine) = unison_engines.get(engine_index) {
+                    engine.reset();
+                }
+            }
 
             self.out_post_processor.reset();
+            self.aux_post_processor.reset();
             self.previous_engine_index = engine_index;
             self.reload_resources = false;
         }
 
         let mut p = EngineParameters::default();
 
+        if patch.retrigger_on_note_change
+            && (modulations.note - self.previous_note).abs() > RETRIGGER_NOTE_THRESHOLD
+        {
+            self.decay_envelope.trigger();
+        }
+
         let rising_edge = self.trigger_state && !previous_trigger_state;
         let note = (modulations.note + self.previous_note) * 0.5;
         self.previous_note = modulations.note;
@@ -376,8 +1246,7 @@ impl<'a> Voice<'a> {
 
         self.decay_envelope.process(short_decay * 2.0);
 
-        let compressed_level =
-            (1.3 * modulations.level / (0.3 + modulations.level.abs())).clamp(0.0, 1.0);
+        let compressed_level = compress_level(modulations.level);
         p.accent = if modulations.level_patched {
             compressed_level
         } else {
@@ -397,37 +1266,46 @@ impl<'a> Voice<'a> {
         if engine_index == 15 {
             internal_envelope_amplitude = 2.0 - p.harmonics * 6.0;
             internal_envelope_amplitude = internal_envelope_amplitude.clamp(0.0, 1.0);
-            self.speech_engine.set_prosody_amount(
-                if !modulations.trigger_patched || modulations.frequency_patched {
-                    0.0
-                } else {
-                    patch.frequency_modulation_amount
-                },
-            );
-            self.speech_engine.set_speed(
-                if !modulations.trigger_patched || modulations.morph_patched {
-                    0.0
-                } else {
-                    patch.morph_modulation_amount
-                },
-            );
+            if let Some(speech_engine) = self.speech_engine.as_deref_mut() {
+                speech_engine.set_prosody_amount(
+                    if !modulations.trigger_patched || modulations.frequency_patched {
+                        0.0
+                    } else {
+                        patch.frequency_modulation_amount
+                    },
+                );
+                speech_engine.set_speed(
+                    if !modulations.trigger_patched || modulations.morph_patched {
+                        0.0
+                    } else {
+                        patch.morph_modulation_amount
+                    },
+                );
+            }
         } else if engine_index == 7 {
-            if modulations.trigger_patched && !modulations.timbre_patched {
+            let envelope_shape = if modulations.trigger_patched && !modulations.timbre_patched {
                 // Disable internal envelope on TIMBRE, and enable the envelope generator
                 // built into the chiptune engine.
                 internal_envelope_amplitude_timbre = 0.0;
                 // Envelope shape is determined by TIMBRE modulation amount. A minimum value
                 // is forced to prevent infinite decay time.
-                self.chiptune_engine
-                    .set_envelope_shape(patch.timbre_modulation_amount.max(0.05));
+                patch.timbre_modulation_amount.max(0.05)
             } else {
-                self.chiptune_engine
-                    .set_envelope_shape(chiptune_engine::NO_ENVELOPE);
+                chiptune_engine::NO_ENVELOPE
+            };
+            if let Some(chiptune_engine) = self.chiptune_engine.as_deref_mut() {
+                chiptune_engine.set_envelope_shape(envelope_shape);
             }
         }
 
-        p.note = apply_modulations(
-            patch.note + note,
+        let note = if self.scale_size > 0 {
+            self.snap_to_scale(patch.note + note)
+        } else {
+            patch.note + note
+        };
+
+        p.note = apply_modulations(
+            note + patch.fine_tune + modulations.pitch_bend + self.tuning_offset,
             patch.frequency_modulation_amount,
             modulations.frequency_patched,
             modulations.frequency,
@@ -465,27 +1343,84 @@ impl<'a> Voice<'a> {
             1.0,
         );
 
-        let engine = self.get_engine(engine_index).unwrap();
+        let unison_count = if Self::engine_has_unison(engine_index) && self.builder.unison {
+            self.unison_count
+        } else {
+            1
+        };
+        let unison_detune = self.unison_detune;
+        let note = p.note;
+        p.note = note + unison_offset(0, unison_count, unison_detune);
+
+        let Some(engine) = self.get_engine(engine_index) else {
+            // The engine is disabled.
+            out.fill(0.0);
+            aux.fill(0.0);
+            return;
+        };
         let mut already_enveloped = engine.1;
-        let out_gain = engine.2;
-        let aux_gain = engine.3;
+        let mut out_gain = engine.2;
+        let mut aux_gain = engine.3;
+
+        if engine_changed {
+            engine.0.prime(&p);
+        }
 
         engine.0.render(&p, out, aux, &mut already_enveloped);
 
-        let lpg_bypass =
-            already_enveloped || (!modulations.level_patched && !modulations.trigger_patched);
+        if !out
+            .iter()
+            .chain(aux.iter())
+            .all(|sample| sample.is_finite())
+        {
+            // Recover from an engine whose state has diverged.
+            engine.0.init();
+            out.fill(0.0);
+            aux.fill(0.0);
+        }
+
+        if unison_count > 1 {
+            for copy in 1..unison_count {
+                p.note = note + unison_offset(copy, unison_count, unison_detune);
+                self.render_unison_copy(copy, engine_index, engine_changed, &p, out, aux);
+            }
+
+            let gain = 1.0 / (unison_count as f32).sqrt();
+            for sample in out.iter_mut().chain(aux.iter_mut()) {
+                *sample *= gain;
+            }
+        }
+        p.note = note;
+
+        if self.auto_level_enabled {
+            let trim = self.auto_level.process(engine_index, out_gain, out);
+            out_gain *= trim;
+            aux_gain *= trim;
+        }
+
+        let lpg_bypass = match patch.lpg_mode {
+            LpgMode::Auto => {
+                already_enveloped || (!modulations.level_patched && !modulations.trigger_patched)
+            }
+            LpgMode::ForceOn => false,
+            LpgMode::ForceOff => true,
+        };
 
         // Compute LPG parameters.
         if !lpg_bypass {
             let hf = patch.lpg_colour;
+            self.lpg_envelope
+                .set_brightness_tracking(patch.lpg_brightness_tracking);
             let decay_tail = (20.0 * out.len() as f32) / SAMPLE_RATE
                 * semitones_to_ratio(-72.0 * patch.decay + 12.0 * hf)
                 - short_decay;
 
+            let frozen = self.lpg_freeze && self.trigger_state && !self.lpg_envelope.ramping_up();
+
             if modulations.level_patched {
                 self.lpg_envelope
                     .process_lp(compressed_level, short_decay, decay_tail, hf);
-            } else {
+            } else if !frozen {
                 let attack = note_to_frequency(p.note) * out.len() as f32 * 2.0;
                 self.lpg_envelope
                     .process_ping(attack, short_decay, decay_tail, hf);
@@ -513,63 +1448,770 @@ impl<'a> Voice<'a> {
         );
     }
 
+    /// Render a unison copy of the engine and add it to `out` and `aux`.
+    fn render_unison_copy(
+        &mut self,
+        copy: usize,
+        engine_index: usize,
+        engine_changed: bool,
+        p: &EngineParameters,
+        out: &mut [f32],
+        aux: &mut [f32],
+    ) {
+        let Some(engine) = self.unison_engines[copy - 1].get(engine_index) else {
+            return;
+        };
+
+        let unison_out = &mut self.unison_out[..out.len()];
+        let unison_aux = &mut self.unison_aux[..aux.len()];
+
+        if engine_changed {
+            engine.prime(p);
+        }
+
+        engine.render(p, unison_out, unison_aux, &mut false);
+
+        if !unison_out
+            .iter()
+            .chain(unison_aux.iter())
+            .all(|sample| sample.is_finite())
+        {
+            engine.init();
+            return;
+        }
+
+        for (sample, unison_sample) in out.iter_mut().zip(unison_out.iter()) {
+            *sample += *unison_sample;
+        }
+        for (sample, unison_sample) in aux.iter_mut().zip(unison_aux.iter()) {
+            *sample += *unison_sample;
+        }
+    }
+
     pub fn active_engine(&self) -> usize {
         self.previous_engine_index
     }
 
-    /// Return reference to engine by index as well as additional parameters
+    /// Return a rough upper bound of the number of samples the active engine keeps
+    /// sounding after a trigger, until it has decayed by about 60 dB.
+    ///
+    /// Self-enveloped engines are estimated from the decay time set by *MORPH*, all others
+    /// from the LPG settings of `patch`. Returns `usize::MAX` if the sound does not decay,
+    /// e.g. when the LPG is forced off for an engine without envelope.
+    pub fn estimated_tail_samples(&self, patch: &Patch) -> usize {
+        let engine_index = self.previous_engine_index;
+        let already_enveloped = ENGINE_INFO
+            .get(engine_index)
+            .is_some_and(|info| info.already_enveloped);
+
+        let engine_tail = if already_enveloped {
+            Some(engine_tail_time(engine_index, patch.morph.clamp(0.0, 1.0)) * SAMPLE_RATE)
+        } else {
+            None
+        };
+
+        // When fully closing, the vactrol model of the LPG decays exponentially with the
+        // sum of the short decay and the decay tail.
+        let decay_rate = 20.0 / SAMPLE_RATE
+            * semitones_to_ratio(
+                -72.0 * patch.decay.clamp(0.0, 1.0) + 12.0 * patch.lpg_colour.clamp(0.0, 1.0),
+            );
+        let lpg_tail = LN_1000 / decay_rate;
+
+        let tail = match (patch.lpg_mode, engine_tail) {
+            (LpgMode::ForceOff, None) => return usize::MAX,
+            (LpgMode::ForceOff, Some(engine_tail)) | (LpgMode::Auto, Some(engine_tail)) => {
+                engine_tail
+            }
+            (LpgMode::ForceOn, Some(engine_tail)) => engine_tail.min(lpg_tail),
+            (_, None) => lpg_tail,
+        };
+
+        tail as usize
+    }
+
+    /// Render the amplitude contour the LPG applies to `patch`, without running an engine.
+    ///
+    /// `trigger_length` is the length in samples of a gate on *LEVEL*, which opens the LPG
+    /// while high. A `trigger_length` of `0` pings the LPG with a trigger on *TRIG* instead.
+    /// The returned gain curve has `length` samples and is `1.0` throughout if the LPG is
+    /// bypassed for the engine of `patch`. The envelope is computed in blocks of 24 samples,
+    /// like `render` it scales its rates with the block size.
+    #[cfg(feature = "alloc")]
+    pub fn envelope_preview(
+        &self,
+        patch: &Patch,
+        trigger_length: usize,
+        length: usize,
+    ) -> alloc::vec::Vec<f32> {
+        let mut curve = alloc::vec![1.0; length];
+
+        let already_enveloped = ENGINE_INFO
+            .get(patch.engine)
+            .is_some_and(|info| info.already_enveloped);
+        let lpg_bypass = match patch.lpg_mode {
+            LpgMode::Auto => already_enveloped,
+            LpgMode::ForceOn => false,
+            LpgMode::ForceOff => true,
+        };
+
+        if lpg_bypass {
+            return curve;
+        }
+
+        let block_size = ENVELOPE_PREVIEW_BLOCK_SIZE as f32;
+        let short_decay = (200.0 * block_size) / SAMPLE_RATE
+            * semitones_to_ratio(-96.0 * patch.decay.clamp(0.1, 1.0));
+        let hf = patch.lpg_colour;
+        let decay_tail = (20.0 * block_size) / SAMPLE_RATE
+            * semitones_to_ratio(-72.0 * patch.decay + 12.0 * hf)
+            - short_decay;
+        let attack =
+            note_to_frequency(patch.note + patch.fine_tune + self.tuning_offset) * block_size * 2.0;
+
+        let mut lpg_envelope = LpgEnvelope::new();
+        lpg_envelope.init();
+        if trigger_length == 0 {
+            lpg_envelope.trigger();
+        }
+
+        // The LPG interpolates the gain over each block, starting closed.
+        let mut previous_gain = 0.0;
+
+        for (block, samples) in curve.chunks_mut(ENVELOPE_PREVIEW_BLOCK_SIZE).enumerate() {
+            if trigger_length == 0 {
+                lpg_envelope.process_ping(attack, short_decay, decay_tail, hf);
+            } else {
+                let level = if block * ENVELOPE_PREVIEW_BLOCK_SIZE < trigger_length {
+                    1.0
+                } else {
+                    0.0
+                };
+                lpg_envelope.process_lp(level, short_decay, decay_tail, hf);
+            }
+
+            let gain = lpg_envelope.gain();
+            let increment = (gain - previous_gain) / samples.len() as f32;
+            for sample in samples.iter_mut() {
+                previous_gain += increment;
+                *sample = previous_gain;
+            }
+            previous_gain = gain;
+        }
+
+        curve
+    }
+
+    /// Return the engine index that would be selected for a patch engine and an
+    /// engine modulation value, without changing the state of the voice.
+    pub fn preview_engine_index(&self, patch_engine: usize, engine_cv: f32) -> usize {
+        let engine_index = self
+            .engine_quantizer
+            .preview_with_base(patch_engine as i32, engine_cv) as usize;
+
+        engine_index.clamp(0, NUM_ENGINES)
+    }
+
+    /// Set the saturation applied to both output channels after the gain stage.
+    pub fn set_saturation(&mut self, saturation: OutputSaturation) {
+        self.out_post_processor.set_saturation(saturation);
+        self.aux_post_processor.set_saturation(saturation);
+    }
+
+    /// Returns the highest absolute peak of both output channels in the last render, after
+    /// the gain of the engine and before the limiter and the low-pass gate.
+    pub fn peak(&self) -> f32 {
+        f32::max(
+            self.out_post_processor.peak(),
+            self.aux_post_processor.peak(),
+        )
+    }
+
+    /// Returns `true` if either output channel exceeded the range from `-1.0` to `1.0`
+    /// in the last render, before the limiter. Self-limiting engines are limited then,
+    /// all others clip at the output unless the low-pass gate attenuates them.
+    pub fn clipped(&self) -> bool {
+        self.out_post_processor.clipped() || self.aux_post_processor.clipped()
+    }
+
+    /// Set the attack and release coefficients of the limiter of the self-limiting
+    /// engines on both output channels, from transparent to pumping. See
+    /// [`Limiter::set_times`].
+    pub fn set_limiter_times(&mut self, attack: f32, release: f32) {
+        self.out_post_processor.set_limiter_times(attack, release);
+        self.aux_post_processor.set_limiter_times(attack, release);
+    }
+
+    pub fn limiter_times(&self) -> (f32, f32) {
+        self.out_post_processor.limiter_times()
+    }
+
+    /// Enable the limiter of the self-limiting engines on both output channels. When
+    /// disabled, their gain is applied linearly, leaving the level to external processing
+    /// or to [`OutputSaturation::SoftClip`]. Default is `true`.
+    pub fn set_limiter_enabled(&mut self, enabled: bool) {
+        self.out_post_processor.set_limiter_enabled(enabled);
+        self.aux_post_processor.set_limiter_enabled(enabled);
+    }
+
+    pub fn limiter_enabled(&self) -> bool {
+        self.out_post_processor.limiter_enabled()
+    }
+
+    /// Set the length of the fade-in in samples after an engine change. Default is
+    /// 2 ms, `0` disables it.
+    pub fn set_fade_in_length(&mut self, length: usize) {
+        self.out_post_processor.set_fade_in_length(length);
+        self.aux_post_processor.set_fade_in_length(length);
+    }
+
+    /// Keep the bass of the mono sum written by `render_mono` from *OUT* only, by
+    /// high-passing *AUX* above the crossover `frequency`, normalized to the sample rate.
+    /// This avoids low frequencies cancelling out when both channels are in opposite
+    /// phase. Default is `None`, which sums both channels unfiltered.
+    pub fn set_bass_management(&mut self, frequency: Option<f32>) {
+        self.mono_mixer.set_crossover_frequency(frequency);
+    }
+
+    /// Set a tone control tilting the spectrum of both output channels from `-1.0` (dark)
+    /// to `1.0` (bright), applied after the low-pass gate. Default is `0.0` (flat).
+    pub fn set_tone(&mut self, tone: f32) {
+        self.out_post_processor.set_tone(tone);
+        self.aux_post_processor.set_tone(tone);
+    }
+
+    pub fn tone(&self) -> f32 {
+        self.out_post_processor.tone()
+    }
+
+    /// Invert the polarity of *OUT* and *AUX*, e.g. to avoid cancellation when layering
+    /// the voice with other sources. Default is `false` for both channels.
+    pub fn set_invert(&mut self, out: bool, aux: bool) {
+        self.out_post_processor.set_invert(out);
+        self.aux_post_processor.set_invert(aux);
+    }
+
+    /// Returns the polarity inversion of *OUT* and *AUX*.
+    pub fn invert(&self) -> (bool, bool) {
+        (
+            self.out_post_processor.invert(),
+            self.aux_post_processor.invert(),
+        )
+    }
+
+    /// Enable removal of DC offsets from both output channels before the limiter.
+    /// Default is `false`.
+    pub fn set_dc_block(&mut self, enabled: bool) {
+        self.out_post_processor.set_dc_block(enabled);
+        self.aux_post_processor.set_dc_block(enabled);
+    }
+
+    /// Returns `true` if the engine at `index` is a one-shot model responding to triggers.
+    pub fn engine_is_percussion(index: usize) -> bool {
+        ENGINE_INFO.get(index).is_some_and(|info| info.percussion)
+    }
+
+    /// Returns `true` if the engine at `index` is melodic, i.e. renders a tone with a
+    /// clear pitch at the note.
+    ///
+    /// The drum engines are tuned by the note as well, but are not considered pitched.
+    /// The string and modal engines are both pitched and percussion, the noise and
+    /// particle engines are neither.
+    pub fn engine_is_pitched(index: usize) -> bool {
+        ENGINE_INFO.get(index).is_some_and(|info| info.pitched)
+    }
+
+    /// Returns `true` if the engine at `index` renders unison copies, see
+    /// [`Voice::set_unison`]. These are the oscillator-based engines: virtual analog VCF,
+    /// phase distortion, virtual analog, waveshaping and FM.
+    pub fn engine_has_unison(index: usize) -> bool {
+        matches!(index, 0 | 1 | 8 | 9 | 10)
+    }
+
+    /// Returns the relative CPU cost of the engine at `index`, from `1` for the cheapest
+    /// engine to `10` for the most expensive one, or `0` for an invalid index.
+    ///
+    /// The scale is logarithmic, each step being about 1.4 times the cost of the previous
+    /// one, so that the most expensive engine (string) costs about 25 times as much as the
+    /// cheapest one (chiptune). The ratings were measured as the render time of a voice
+    /// with the notes, *HARMONICS* and triggers changing, and include the post processing
+    /// of the voice. They are meant for budgeting polyphony, actual costs vary with the
+    /// parameters and the platform.
+    pub fn engine_cost(index: usize) -> u8 {
+        ENGINE_INFO.get(index).map_or(0, |info| info.cost)
+    }
+
+    /// Returns the additive engine, or `None` if it is disabled.
+    #[inline]
+    pub fn additive_engine(&mut self) -> Option<&mut AdditiveEngine> {
+        self.additive_engine.as_deref_mut()
+    }
+
+    /// Returns the bass drum engine, or `None` if it is disabled.
+    #[inline]
+    pub fn bass_drum_engine(&mut self) -> Option<&mut BassDrumEngine> {
+        self.bass_drum_engine.as_deref_mut()
+    }
+
+    /// Returns the chiptune engine, or `None` if it is disabled.
+    #[inline]
+    pub fn chiptune_engine(&mut self) -> Option<&mut ChiptuneEngine> {
+        self.chiptune_engine.as_deref_mut()
+    }
+
+    /// Returns the chord engine, or `None` if it is disabled.
+    #[inline]
+    pub fn chord_engine(&mut self) -> Option<&mut ChordEngine<'a>> {
+        self.chord_engine.as_deref_mut()
+    }
+
+    /// Returns the FM engine, or `None` if it is disabled.
+    #[inline]
+    pub fn fm_engine(&mut self) -> Option<&mut FmEngine> {
+        self.fm_engine.as_deref_mut()
+    }
+
+    /// Returns the grain engine, or `None` if it is disabled.
+    #[inline]
+    pub fn grain_engine(&mut self) -> Option<&mut GrainEngine> {
+        self.grain_engine.as_deref_mut()
+    }
+
+    /// Returns the hi-hat engine, or `None` if it is disabled.
+    #[inline]
+    pub fn hihat_engine(&mut self) -> Option<&mut HihatEngine<'a>> {
+        self.hihat_engine.as_deref_mut()
+    }
+
+    /// Returns the modal engine, or `None` if it is disabled.
+    #[inline]
+    pub fn modal_engine(&mut self) -> Option<&mut ModalEngine<'a>> {
+        self.modal_engine.as_deref_mut()
+    }
+
+    /// Returns the noise engine, or `None` if it is disabled.
+    #[inline]
+    pub fn noise_engine(&mut self) -> Option<&mut NoiseEngine<'a>> {
+        self.noise_engine.as_deref_mut()
+    }
+
+    /// Returns the particle engine, or `None` if it is disabled.
+    #[inline]
+    pub fn particle_engine(&mut self) -> Option<&mut ParticleEngine<'a>> {
+        self.particle_engine.as_deref_mut()
+    }
+
+    /// Returns the phase distortion engine, or `None` if it is disabled.
+    #[inline]
+    pub fn phase_distortion_engine(&mut self) -> Option<&mut PhaseDistortionEngine<'a>> {
+        self.phase_distortion_engine.as_deref_mut()
+    }
+
+    /// Returns the six op FM engine, or `None` if it is disabled.
+    #[inline]
+    pub fn six_op_engine(&mut self) -> Option<&mut SixOpEngine<'a>> {
+        self.six_op_engine.as_deref_mut()
+    }
+
+    /// Returns the snare drum engine, or `None` if it is disabled.
+    #[inline]
+    pub fn snare_drum_engine(&mut self) -> Option<&mut SnareDrumEngine> {
+        self.snare_drum_engine.as_deref_mut()
+    }
+
+    /// Returns the speech engine, or `None` if it is disabled.
+    #[inline]
+    pub fn speech_engine(&mut self) -> Option<&mut SpeechEngine<'a>> {
+        self.speech_engine.as_deref_mut()
+    }
+
+    /// Returns the string engine, or `None` if it is disabled.
+    #[inline]
+    pub fn string_engine(&mut self) -> Option<&mut StringEngine<'a>> {
+        self.string_engine.as_deref_mut()
+    }
+
+    /// Returns the string machine engine, or `None` if it is disabled.
+    #[inline]
+    pub fn string_machine_engine(&mut self) -> Option<&mut StringMachineEngine> {
+        self.string_machine_engine.as_deref_mut()
+    }
+
+    /// Returns the swarm engine, or `None` if it is disabled.
+    #[inline]
+    pub fn swarm_engine(&mut self) -> Option<&mut SwarmEngine> {
+        self.swarm_engine.as_deref_mut()
+    }
+
+    /// Returns the virtual analog engine, or `None` if it is disabled.
+    #[inline]
+    pub fn virtual_analog_engine(&mut self) -> Option<&mut VirtualAnalogEngine<'a>> {
+        self.virtual_analog_engine.as_deref_mut()
+    }
+
+    /// Returns the virtual analog VCF engine, or `None` if it is disabled.
+    #[inline]
+    pub fn virtual_analog_vcf_engine(&mut self) -> Option<&mut VirtualAnalogVcfEngine> {
+        self.virtual_analog_vcf_engine.as_deref_mut()
+    }
+
+    /// Returns the waveshaping engine, or `None` if it is disabled.
+    #[inline]
+    pub fn waveshaping_engine(&mut self) -> Option<&mut WaveshapingEngine> {
+        self.waveshaping_engine.as_deref_mut()
+    }
+
+    /// Returns the wavetable engine, or `None` if it is disabled.
+    #[inline]
+    pub fn wavetable_engine(&mut self) -> Option<&mut WavetableEngine<'a>> {
+        self.wavetable_engine.as_deref_mut()
+    }
+
+    /// Returns the wave terrain engine, or `None` if it is disabled.
+    #[inline]
+    pub fn waveterrain_engine(&mut self) -> Option<&mut WaveTerrainEngine<'a>> {
+        self.waveterrain_engine.as_deref_mut()
+    }
+
+    /// Return reference to engine by index as well as additional parameters, or `None` if
+    /// the engine is disabled
     fn get_engine(&mut self, index: usize) -> Option<(&mut dyn Engine, bool, f32, f32)> {
-        match index {
-            0 => Some((&mut self.virtual_analog_vcf_engine, false, 1.0, 1.0)),
-            1 => Some((&mut self.phase_distortion_engine, false, 0.7, 0.7)),
-            2 => Some((&mut self.six_op_engine, true, 1.0, 1.0)),
-            3 => Some((&mut self.six_op_engine, true, 1.0, 1.0)),
-            4 => Some((&mut self.six_op_engine, true, 1.0, 1.0)),
-            5 => Some((&mut self.waveterrain_engine, false, 0.7, 0.7)),
-            6 => Some((&mut self.string_machine_engine, false, 0.8, 0.8)),
-            7 => Some((&mut self.chiptune_engine, false, 0.5, 0.5)),
-            8 => Some((&mut self.virtual_analog_engine, false, 0.8, 0.8)),
-            9 => Some((&mut self.waveshaping_engine, false, 0.7, 0.6)),
-            10 => Some((&mut self.fm_engine, false, 0.6, 0.6)),
-            11 => Some((&mut self.grain_engine, false, 0.7, 0.6)),
-            12 => Some((&mut self.additive_engine, false, 0.8, 0.8)),
-            13 => Some((&mut self.wavetable_engine, false, 0.6, 0.6)),
-            14 => Some((&mut self.chord_engine, false, 0.8, 0.8)),
-            15 => Some((&mut self.speech_engine, false, -0.7, 0.8)),
-            16 => Some((&mut self.swarm_engine, false, -3.0, 1.0)),
-            17 => Some((&mut self.noise_engine, false, -1.0, -1.0)),
-            18 => Some((&mut self.particle_engine, false, -2.0, 1.0)),
-            19 => Some((&mut self.string_engine, true, -1.0, 0.8)),
-            20 => Some((&mut self.modal_engine, true, -1.0, 0.8)),
-            21 => Some((&mut self.bass_drum_engine, true, 0.8, 0.8)),
-            22 => Some((&mut self.snare_drum_engine, true, 0.8, 0.8)),
-            23 => Some((&mut self.hihat_engine, true, 0.8, 0.8)),
-            _ => None,
+        let engine: &mut dyn Engine = match index {
+            0 => self.virtual_analog_vcf_engine.as_deref_mut()?,
+            1 => self.phase_distortion_engine.as_deref_mut()?,
+            2..=4 => self.six_op_engine.as_deref_mut()?,
+            5 => self.waveterrain_engine.as_deref_mut()?,
+            6 => self.string_machine_engine.as_deref_mut()?,
+            7 => self.chiptune_engine.as_deref_mut()?,
+            8 => self.virtual_analog_engine.as_deref_mut()?,
+            9 => self.waveshaping_engine.as_deref_mut()?,
+            10 => self.fm_engine.as_deref_mut()?,
+            11 => self.grain_engine.as_deref_mut()?,
+            12 => self.additive_engine.as_deref_mut()?,
+            13 => self.wavetable_engine.as_deref_mut()?,
+            14 => self.chord_engine.as_deref_mut()?,
+            15 => self.speech_engine.as_deref_mut()?,
+            16 => self.swarm_engine.as_deref_mut()?,
+            17 => self.noise_engine.as_deref_mut()?,
+            18 => self.particle_engine.as_deref_mut()?,
+            19 => self.string_engine.as_deref_mut()?,
+            20 => self.modal_engine.as_deref_mut()?,
+            21 => self.bass_drum_engine.as_deref_mut()?,
+            22 => self.snare_drum_engine.as_deref_mut()?,
+            23 => self.hihat_engine.as_deref_mut()?,
+            _ => return None,
+        };
+
+        let info = &ENGINE_INFO[index];
+
+        Some((engine, info.already_enveloped, info.out_gain, info.aux_gain))
+    }
+}
+
+/// Static properties of an engine.
+struct EngineInfo {
+    already_enveloped: bool,
+    out_gain: f32,
+    aux_gain: f32,
+    pitched: bool,
+    percussion: bool,
+    cost: u8,
+}
+
+impl EngineInfo {
+    const fn new(
+        already_enveloped: bool,
+        out_gain: f32,
+        aux_gain: f32,
+        pitched: bool,
+        percussion: bool,
+        cost: u8,
+    ) -> Self {
+        Self {
+            already_enveloped,
+            out_gain,
+            aux_gain,
+            pitched,
+            percussion,
+            cost,
         }
     }
 }
 
+const ENGINE_INFO: [EngineInfo; NUM_ENGINES] = [
+    EngineInfo::new(false, 1.0, 1.0, true, false, 4), // Virtual analog VCF
+    EngineInfo::new(false, 0.7, 0.7, true, false, 4), // Phase distortion
+    EngineInfo::new(true, 1.0, 1.0, true, false, 5),  // Six op FM, bank A
+    EngineInfo::new(true, 1.0, 1.0, true, false, 5),  // Six op FM, bank B
+    EngineInfo::new(true, 1.0, 1.0, true, false, 5),  // Six op FM, bank C
+    EngineInfo::new(false, 0.7, 0.7, true, false, 6), // Wave terrain
+    EngineInfo::new(false, 0.8, 0.8, true, false, 6), // String machine
+    EngineInfo::new(false, 0.5, 0.5, true, false, 1), // Chiptune
+    EngineInfo::new(false, 0.8, 0.8, true, false, 3), // Virtual analog
+    EngineInfo::new(false, 0.7, 0.6, true, false, 4), // Waveshaping
+    EngineInfo::new(false, 0.6, 0.6, true, false, 7), // FM
+    EngineInfo::new(false, 0.7, 0.6, true, false, 5), // Grain
+    EngineInfo::new(false, 0.8, 0.8, true, false, 6), // Additive
+    EngineInfo::new(false, 0.6, 0.6, true, false, 6), // Wavetable
+    EngineInfo::new(false, 0.8, 0.8, true, false, 5), // Chord
+    EngineInfo::new(false, -0.7, 0.8, true, false, 4), // Speech
+    EngineInfo::new(false, -3.0, 1.0, true, false, 6), // Swarm
+    EngineInfo::new(false, -1.0, -1.0, false, false, 3), // Noise
+    EngineInfo::new(false, -2.0, 1.0, false, false, 8), // Particle
+    EngineInfo::new(true, -1.0, 0.8, true, true, 10), // String
+    EngineInfo::new(true, -1.0, 0.8, true, true, 9),  // Modal
+    EngineInfo::new(true, 0.8, 0.8, false, true, 9),  // Bass drum
+    EngineInfo::new(true, 0.8, 0.8, false, true, 7),  // Snare drum
+    EngineInfo::new(true, 0.8, 0.8, false, true, 4),  // Hi-hat
+];
+
+/// Additional instances of the engines rendering unison copies, one per copy beyond the
+/// first. Only the enabled engines are allocated.
+#[derive(Debug, Default)]
+struct UnisonEngines<'a> {
+    virtual_analog_vcf_engine: Option<&'a mut VirtualAnalogVcfEngine>,
+    phase_distortion_engine: Option<&'a mut PhaseDistortionEngine<'a>>,
+    virtual_analog_engine: Option<&'a mut VirtualAnalogEngine<'a>>,
+    waveshaping_engine: Option<&'a mut WaveshapingEngine>,
+    fm_engine: Option<&'a mut FmEngine>,
+}
+
+impl<'a> UnisonEngines<'a> {
+    fn get(&mut self, index: usize) -> Option<&mut dyn Engine> {
+        let engine: &mut dyn Engine = match index {
+            0 => self.virtual_analog_vcf_engine.as_deref_mut()?,
+            1 => self.phase_distortion_engine.as_deref_mut()?,
+            8 => self.virtual_analog_engine.as_deref_mut()?,
+            9 => self.waveshaping_engine.as_deref_mut()?,
+            10 => self.fm_engine.as_deref_mut()?,
+            _ => return None,
+        };
+
+        Some(engine)
+    }
+}
+
+/// Returns the detuning in semitones of a unison copy, spreading `count` copies evenly
+/// from `-detune` to `detune`.
+fn unison_offset(copy: usize, count: usize, detune: f32) -> f32 {
+    if count > 1 {
+        detune * (2.0 * copy as f32 / (count - 1) as f32 - 1.0)
+    } else {
+        0.0
+    }
+}
+
+const LN_1000: f32 = 6.907_755;
+
+/// Decay time in seconds of a self-enveloped engine for a *MORPH* setting, measured on
+/// the rendered output with some headroom.
+fn engine_tail_time(engine_index: usize, morph: f32) -> f32 {
+    let (shortest, longest) = match engine_index {
+        2..=4 => return 20.0, // Six op FM, envelopes of the patch
+        19 => (1.2, 30.0),    // String
+        20 => (0.5, 30.0),    // Modal
+        21 | 22 => (0.15, 8.0),
+        23 => (0.1, 8.0),
+        _ => return 0.0,
+    };
+
+    shortest * (longest / shortest).powf(morph)
+}
+
+/// Saturation applied by the channel post processor.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputSaturation {
+    /// No saturation, output is passed unchanged. This is the default.
+    #[default]
+    None,
+
+    /// Gentle saturation using `soft_limit`. Not bounded for very hot signals.
+    SoftLimit,
+
+    /// Saturation using `soft_clip`, bounding the output to `-1.0..1.0`.
+    SoftClip,
+}
+
+// Corner frequencies of the low and high shelves of the tone control.
+const TONE_LOW_SHELF_FREQUENCY: f32 = 300.0 / SAMPLE_RATE;
+const TONE_HIGH_SHELF_FREQUENCY: f32 = 3000.0 / SAMPLE_RATE;
+
+// Gain of the shelves at the ends of the tone control, about 6 dB.
+const TONE_SHELF_GAIN: f32 = 2.0;
+
 #[derive(Debug, Default)]
 pub struct ChannelPostProcessor {
+    dc_blocker: DcBlocker,
     limiter: Limiter,
     lpg: LowPassGate,
+    low_shelf: OnePole,
+    high_shelf: OnePole,
+    saturation: OutputSaturation,
+    dc_block: bool,
+    limiter_enabled: bool,
+    tone: f32,
+    fade_in_length: usize,
+    fade_in_position: usize,
+    peak: f32,
+    invert: bool,
 }
 
 impl ChannelPostProcessor {
     pub fn new() -> Self {
         Self {
+            dc_blocker: DcBlocker::new(),
             limiter: Limiter::new(),
             lpg: LowPassGate::new(),
+            low_shelf: OnePole::new(),
+            high_shelf: OnePole::new(),
+            saturation: OutputSaturation::None,
+            dc_block: false,
+            limiter_enabled: true,
+            tone: 0.0,
+            fade_in_length: DEFAULT_FADE_IN_LENGTH,
+            fade_in_position: 0,
+            peak: 0.0,
+            invert: false,
         }
     }
 
+    #[inline]
+    pub fn set_saturation(&mut self, saturation: OutputSaturation) {
+        self.saturation = saturation;
+    }
+
+    #[inline]
+    pub fn set_dc_block(&mut self, enabled: bool) {
+        self.dc_block = enabled;
+    }
+
+    /// Set the attack and release coefficients of the limiter used with negative gains,
+    /// see [`Limiter::set_times`]. Defaults match the original firmware.
+    #[inline]
+    pub fn set_limiter_times(&mut self, attack: f32, release: f32) {
+        self.limiter.set_times(attack, release);
+    }
+
+    #[inline]
+    pub fn limiter_times(&self) -> (f32, f32) {
+        self.limiter.times()
+    }
+
+    /// Enable the limiter used with negative gains. When disabled, the magnitude of the
+    /// gain is applied linearly instead. Default is `true`.
+    #[inline]
+    pub fn set_limiter_enabled(&mut self, enabled: bool) {
+        self.limiter_enabled = enabled;
+    }
+
+    #[inline]
+    pub fn limiter_enabled(&self) -> bool {
+        self.limiter_enabled
+    }
+
+    /// Set the length of the fade-in applied after a reset in samples, `0` disables it.
+    #[inline]
+    pub fn set_fade_in_length(&mut self, length: usize) {
+        self.fade_in_length = length;
+        self.fade_in_position = self.fade_in_position.min(length);
+    }
+
+    /// Set the tilt of the spectrum from `-1.0` (dark) to `1.0` (bright), cutting one end
+    /// of the spectrum and boosting the other by up to about 6 dB. Default is `0.0`, which
+    /// leaves the signal unchanged.
+    #[inline]
+    pub fn set_tone(&mut self, tone: f32) {
+        self.tone = tone.clamp(-1.0, 1.0);
+    }
+
+    #[inline]
+    pub fn tone(&self) -> f32 {
+        self.tone
+    }
+
+    /// Invert the polarity of the output. Default is `false`.
+    #[inline]
+    pub fn set_invert(&mut self, invert: bool) {
+        self.invert = invert;
+    }
+
+    #[inline]
+    pub fn invert(&self) -> bool {
+        self.invert
+    }
+
     pub fn init(&mut self) {
         self.lpg.init();
+        self.low_shelf.init();
+        self.low_shelf
+            .set_f(TONE_LOW_SHELF_FREQUENCY, FrequencyApproximation::Dirty);
+        self.high_shelf.init();
+        self.high_shelf
+            .set_f(TONE_HIGH_SHELF_FREQUENCY, FrequencyApproximation::Dirty);
         self.reset();
     }
 
     pub fn reset(&mut self) {
+        self.dc_blocker.init(1.0 - 20.0 / SAMPLE_RATE);
         self.limiter.init();
+        self.low_shelf.reset();
+        self.high_shelf.reset();
+        self.fade_in_position = 0;
+    }
+
+    /// Returns the absolute peak of the last processed block after the channel gain, before
+    /// the limiter and the low-pass gate.
+    #[inline]
+    pub fn peak(&self) -> f32 {
+        self.peak
+    }
+
+    /// Returns `true` if the last processed block exceeded the range from `-1.0` to `1.0`
+    /// before the limiter, i.e. it was limited or would clip at the output.
+    #[inline]
+    pub fn clipped(&self) -> bool {
+        self.peak > 1.0
+    }
+
+    #[inline]
+    fn measure_peak(&mut self, gain: f32, in_: &[f32]) {
+        self.peak = in_.iter().fold(0.0, |peak: f32, x| peak.max(x.abs())) * gain.abs();
+    }
+
+    /// Tilt the spectrum with a low shelf and a high shelf of opposite gains.
+    #[inline]
+    fn apply_tone(&mut self, in_out: &mut [f32]) {
+        if self.tone == 0.0 {
+            return;
+        }
+
+        let high_gain = TONE_SHELF_GAIN.powf(self.tone);
+        let low_gain = 1.0 / high_gain;
+
+        for in_out_sample in in_out.iter_mut() {
+            let mut sample = *in_out_sample;
+            sample += (low_gain - 1.0) * self.low_shelf.process(sample, FilterMode::LowPass);
+            sample += (high_gain - 1.0) * self.high_shelf.process(sample, FilterMode::HighPass);
+            *in_out_sample = sample;
+        }
+    }
+
+    /// Ramp up the level from zero after a reset to avoid clicks.
+    #[inline]
+    fn fade_in(&mut self, in_out: &mut [f32]) {
+        if self.fade_in_position >= self.fade_in_length {
+            return;
+        }
+
+        let increment = 1.0 / self.fade_in_length as f32;
+
+        for in_out_sample in in_out.iter_mut() {
+            if self.fade_in_position >= self.fade_in_length {
+                break;
+            }
+            *in_out_sample *= self.fade_in_position as f32 * increment;
+            self.fade_in_position += 1;
+        }
     }
 
     #[inline]
@@ -582,11 +2224,21 @@ impl ChannelPostProcessor {
         low_pass_gate_hf_bleed: f32,
         in_out: &mut [f32],
     ) {
-        if gain < 0.0 {
+        self.fade_in(in_out);
+
+        if self.dc_block {
+            self.dc_blocker.process(in_out);
+        }
+
+        self.measure_peak(gain, in_out);
+
+        let limit = gain < 0.0 && self.limiter_enabled;
+
+        if limit {
             self.limiter.process(-gain, in_out);
         }
 
-        let post_gain = if gain < 0.0 { 1.0 } else { gain };
+        let post_gain = if limit { 1.0 } else { gain.abs() };
 
         if !bypass_lpg {
             self.lpg.process_replacing(
@@ -598,11 +2250,34 @@ impl ChannelPostProcessor {
         } else {
             for in_out_sample in in_out.iter_mut() {
                 *in_out_sample *= post_gain;
-                // *in_out_sample = soft_limit(*in_out_sample);
+            }
+        }
+
+        self.apply_tone(in_out);
+
+        match self.saturation {
+            OutputSaturation::None => {}
+            OutputSaturation::SoftLimit => {
+                for in_out_sample in in_out.iter_mut() {
+                    *in_out_sample = soft_limit(*in_out_sample);
+                }
+            }
+            OutputSaturation::SoftClip => {
+                for in_out_sample in in_out.iter_mut() {
+                    *in_out_sample = soft_clip(*in_out_sample);
+                }
+            }
+        }
+
+        if self.invert {
+            for in_out_sample in in_out.iter_mut() {
+                *in_out_sample = -*in_out_sample;
             }
         }
     }
 
+    /// Process like `process` and convert the result to integers, with the inverted
+    /// polarity of the DAC of the original firmware.
     #[allow(clippy::too_many_arguments)]
     #[inline]
     pub fn process_to_i16(
@@ -615,29 +2290,129 @@ impl ChannelPostProcessor {
         in_: &mut [f32],
         out: &mut [i16],
     ) {
-        if gain < 0.0 {
-            self.limiter.process(-gain, in_);
+        self.process(
+            gain,
+            bypass_lpg,
+            low_pass_gate_gain,
+            low_pass_gate_frequency,
+            low_pass_gate_hf_bleed,
+            in_,
+        );
+
+        for (in_sample, out_sample) in in_.iter().zip(out.iter_mut()) {
+            *out_sample = clip_16(1 + (*in_sample * -32767.0) as i32) as i16;
         }
+    }
+}
 
-        let post_gain = (if gain < 0.0 { 1.0 } else { gain }) * -32767.0;
+/// Per-engine level trim of [`Voice::set_auto_level`].
+#[derive(Debug, Clone)]
+struct AutoLevel {
+    power: [f32; NUM_ENGINES],
+    gain: [f32; NUM_ENGINES],
+}
 
-        if !bypass_lpg {
-            self.lpg.process_to_i16(
-                post_gain * low_pass_gate_gain,
-                low_pass_gate_frequency,
-                low_pass_gate_hf_bleed,
-                in_,
-                out,
-                1,
-            );
+impl AutoLevel {
+    fn new() -> Self {
+        Self {
+            power: [AUTO_LEVEL_TARGET * AUTO_LEVEL_TARGET; NUM_ENGINES],
+            gain: [1.0; NUM_ENGINES],
+        }
+    }
+
+    fn init(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Measures a block of `engine` scaled by `gain` and returns the trim to apply.
+    fn process(&mut self, engine: usize, gain: f32, in_: &[f32]) -> f32 {
+        if in_.is_empty() {
+            return self.gain[engine];
+        }
+
+        let power =
+            in_.iter().map(|sample| sample * sample).sum::<f32>() / in_.len() as f32 * gain * gain;
+        let coefficient = (in_.len() as f32 / (AUTO_LEVEL_TIME * SAMPLE_RATE)).min(1.0);
+
+        if power > AUTO_LEVEL_GATE * AUTO_LEVEL_GATE {
+            one_pole(&mut self.power[engine], power, coefficient);
+        }
+
+        let target = (AUTO_LEVEL_TARGET / self.power[engine].sqrt())
+            .clamp(AUTO_LEVEL_MIN_GAIN, AUTO_LEVEL_MAX_GAIN);
+        one_pole(&mut self.gain[engine], target, coefficient);
+
+        self.gain[engine]
+    }
+}
+
+/// Sums two channels to mono, optionally with bass management.
+#[derive(Debug, Default)]
+pub struct MonoMixer {
+    crossover: CrossoverSvf,
+    crossover_frequency: Option<f32>,
+}
+
+impl MonoMixer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn init(&mut self) {
+        self.crossover.init();
+    }
+
+    pub fn reset(&mut self) {
+        self.crossover.reset();
+    }
+
+    /// Set the crossover frequency, normalized to the sample rate, below which the second
+    /// channel is removed from the sum by a Linkwitz-Riley high-pass filter. `None`
+    /// disables the filter. Default is `None`.
+    #[inline]
+    pub fn set_crossover_frequency(&mut self, frequency: Option<f32>) {
+        self.crossover_frequency = frequency.map(|frequency| frequency.clamp(0.0, 0.25));
+        if let Some(frequency) = self.crossover_frequency {
+            // Butterworth damping, scaled by the frequency coefficient.
+            let f = 2.0 * f32::sin(core::f32::consts::PI * frequency);
+            self.crossover.set_f_fq(f, f * core::f32::consts::SQRT_2);
+        }
+    }
+
+    #[inline]
+    pub fn crossover_frequency(&self) -> Option<f32> {
+        self.crossover_frequency
+    }
+
+    /// Write the sum of `a` and `b` into `out`. All buffers must have the same length.
+    #[inline]
+    pub fn process(&mut self, a: &[f32], b: &[f32], out: &mut [f32]) {
+        if self.crossover_frequency.is_some() {
+            self.crossover.process(b, out, FilterMode::HighPass);
+            for (out_sample, a_sample) in out.iter_mut().zip(a.iter()) {
+                *out_sample += *a_sample;
+            }
         } else {
-            for (in_sample, out_sample) in in_.iter().zip(out.iter_mut()) {
-                *out_sample = clip_16(1 + (*in_sample * post_gain) as i32) as i16;
+            for ((out_sample, a_sample), b_sample) in out.iter_mut().zip(a.iter()).zip(b.iter()) {
+                *out_sample = *a_sample + *b_sample;
             }
         }
     }
 }
 
+/// Maps the level modulation to the opening of the LPG and the accent of the engines.
+#[inline]
+pub(crate) fn compress_level(level: f32) -> f32 {
+    (1.3 * level / (0.3 + level.abs())).clamp(0.0, 1.0)
+}
+
+#[inline]
+fn sanitize(value: &mut f32, fallback: f32) {
+    if !value.is_finite() {
+        *value = fallback;
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 #[inline]
 fn apply_modulations(
@@ -1,5 +1,11 @@
 #![doc = include_str!("../README.md")]
 #![cfg_attr(not(test), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub mod dsp;
 pub mod stmlib;
+pub mod utils;
+
+pub use stmlib::utils::random::set_deterministic;
@@ -5,6 +5,8 @@
 #[allow(unused_imports)]
 use num_traits::float::Float;
 
+use super::flush_denormal;
+
 #[derive(Debug)]
 pub enum FilterMode {
     LowPass,
@@ -61,6 +63,13 @@ impl DcBlocker {
         self.x = x;
         self.y = y;
     }
+
+    /// Sets the states that decayed below [`super::DENORMAL_THRESHOLD`] to zero.
+    #[inline]
+    pub fn flush_denormals(&mut self) {
+        self.x = flush_denormal(self.x);
+        self.y = flush_denormal(self.y);
+    }
 }
 
 #[derive(Debug, Default)]
@@ -162,6 +171,13 @@ impl Svf {
         self.state_2 = 0.0;
     }
 
+    /// Sets the states that decayed below [`super::DENORMAL_THRESHOLD`] to zero.
+    #[inline]
+    pub fn flush_denormals(&mut self) {
+        self.state_1 = flush_denormal(self.state_1);
+        self.state_2 = flush_denormal(self.state_2);
+    }
+
     /// Copy settings from another filter.
     #[inline]
     pub fn set(&mut self, f: &Self) {
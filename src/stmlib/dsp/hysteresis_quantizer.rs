@@ -50,7 +50,7 @@ impl HysteresisQuantizer {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct HysteresisQuantizer2 {
     num_steps: i32,
     hysteresis: f32,
@@ -80,7 +80,16 @@ impl HysteresisQuantizer2 {
     }
 
     #[inline]
-    pub fn process_with_base(&mut self, base: i32, mut value: f32) -> i32 {
+    pub fn process_with_base(&mut self, base: i32, value: f32) -> i32 {
+        let q = self.preview_with_base(base, value);
+        self.quantized_value = q;
+
+        q
+    }
+
+    /// Returns the value `process_with_base` would return, without updating the state.
+    #[inline]
+    pub fn preview_with_base(&self, base: i32, mut value: f32) -> i32 {
         value *= self.scale;
         value += self.offset;
         value += base as f32;
@@ -90,11 +99,8 @@ impl HysteresisQuantizer2 {
         } else {
             1.0
         };
-        let q =
-            ((value + hysteresis_sign * self.hysteresis + 0.5) as i32).clamp(0, self.num_steps - 1);
-        self.quantized_value = q;
 
-        q
+        ((value + hysteresis_sign * self.hysteresis + 0.5) as i32).clamp(0, self.num_steps - 1)
     }
 
     #[inline]
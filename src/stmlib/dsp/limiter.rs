@@ -7,25 +7,90 @@ use super::slope;
 #[allow(unused_imports)]
 use num_traits::float::FloatCore;
 
-#[derive(Debug, Default)]
+/// Maximum lookahead of the limiter in samples.
+pub const MAX_LOOKAHEAD: usize = 32;
+
+/// Default attack coefficient of the peak detector.
+pub const DEFAULT_ATTACK: f32 = 0.05;
+
+/// Default release coefficient of the peak detector.
+pub const DEFAULT_RELEASE: f32 = 0.00002;
+
+#[derive(Debug)]
 pub struct Limiter {
     peak: f32,
+    attack: f32,
+    release: f32,
+
+    lookahead: usize,
+    delay_line: [f32; MAX_LOOKAHEAD],
+    write_index: usize,
+}
+
+impl Default for Limiter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Limiter {
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            peak: 0.0,
+            attack: DEFAULT_ATTACK,
+            release: DEFAULT_RELEASE,
+
+            lookahead: 0,
+            delay_line: [0.0; MAX_LOOKAHEAD],
+            write_index: 0,
+        }
     }
 
     pub fn init(&mut self) {
         self.peak = 0.5;
+        self.delay_line = [0.0; MAX_LOOKAHEAD];
+        self.write_index = 0;
+    }
+
+    /// Set the lookahead in samples, up to `MAX_LOOKAHEAD`. The output is delayed by the
+    /// lookahead, so that the gain reduction has started when a transient comes out.
+    /// Default is `0`.
+    #[inline]
+    pub fn set_lookahead(&mut self, lookahead: usize) {
+        self.lookahead = lookahead.min(MAX_LOOKAHEAD);
+    }
+
+    #[inline]
+    pub fn lookahead(&self) -> usize {
+        self.lookahead
+    }
+
+    /// Set the per-sample attack and release coefficients of the peak detector
+    /// (0.0 to 1.0). Higher values react faster. Fast releases are transparent on
+    /// sustained sounds, slow releases pump after transients. Defaults are
+    /// [`DEFAULT_ATTACK`] and [`DEFAULT_RELEASE`].
+    #[inline]
+    pub fn set_times(&mut self, attack: f32, release: f32) {
+        self.attack = attack.clamp(0.0, 1.0);
+        self.release = release.clamp(0.0, 1.0);
+    }
+
+    /// Returns the attack and release coefficients.
+    #[inline]
+    pub fn times(&self) -> (f32, f32) {
+        (self.attack, self.release)
     }
 
     #[inline]
     pub fn process(&mut self, pre_gain: f32, in_out: &mut [f32]) {
+        if self.lookahead > 0 {
+            self.process_lookahead(pre_gain, in_out);
+            return;
+        }
+
         for sample in in_out.iter_mut() {
             let s = *sample * pre_gain;
-            slope(&mut self.peak, s.abs(), 0.05, 0.00002);
+            slope(&mut self.peak, s.abs(), self.attack, self.release);
             let gain = if self.peak <= 1.0 {
                 1.0
             } else {
@@ -34,4 +99,31 @@ impl Limiter {
             *sample = s * gain * 0.8;
         }
     }
+
+    #[inline]
+    fn process_lookahead(&mut self, pre_gain: f32, in_out: &mut [f32]) {
+        for sample in in_out.iter_mut() {
+            let s = *sample * pre_gain;
+            self.delay_line[self.write_index] = s;
+
+            // The detector follows the loudest sample that is yet to come out.
+            let window_peak = (0..=self.lookahead)
+                .map(|delay| {
+                    self.delay_line[(self.write_index + MAX_LOOKAHEAD - delay) % MAX_LOOKAHEAD]
+                })
+                .fold(0.0, |peak: f32, x| peak.max(x.abs()));
+            slope(&mut self.peak, window_peak, self.attack, self.release);
+            let gain = if self.peak <= 1.0 {
+                1.0
+            } else {
+                1.0 / self.peak
+            };
+
+            let delayed = self.delay_line
+                [(self.write_index + MAX_LOOKAHEAD - self.lookahead) % MAX_LOOKAHEAD];
+            self.write_index = (self.write_index + 1) % MAX_LOOKAHEAD;
+
+            *sample = delayed * gain * 0.8;
+        }
+    }
 }
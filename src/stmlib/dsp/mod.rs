@@ -93,6 +93,21 @@ pub fn crossfade(a: f32, b: f32, fade: f32) -> f32 {
     a + (b - a) * fade
 }
 
+/// Magnitude below which [`flush_denormal`] sets a value to zero, about -400 dB and far
+/// above the denormal range.
+pub const DENORMAL_THRESHOLD: f32 = 1e-20;
+
+/// Returns `0.0` for a value below [`DENORMAL_THRESHOLD`], so that decaying feedback
+/// states do not end up in the slow denormal range of the FPU.
+#[inline]
+pub fn flush_denormal(x: f32) -> f32 {
+    if x.abs() < DENORMAL_THRESHOLD {
+        0.0
+    } else {
+        x
+    }
+}
+
 #[inline]
 pub fn soft_limit(x: f32) -> f32 {
     x * (27.0 + x * x) / (27.0 + 9.0 * x * x)
@@ -6,9 +6,9 @@
 pub fn fast_rsqrt_carmack(x: f32) -> f32 {
     const THREEHALFS: f32 = 1.5;
     let mut y = x;
-    let mut i = y as u32;
+    let mut i = y.to_bits();
     i = 0x5f3759df - (i >> 1);
-    y = i as f32;
+    y = f32::from_bits(i);
     let x2 = x * 0.5;
     y = y * (THREEHALFS - (x2 * y * y));
 
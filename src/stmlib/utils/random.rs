@@ -2,10 +2,13 @@
 
 // Based on MIT-licensed code (c) 2012 by Olivier Gillet (ol.gillet@gmail.com)
 
-use core::sync::atomic::{AtomicU32, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 
 static RNG_STATE: AtomicU32 = AtomicU32::new(0x21);
 
+static DETERMINISTIC: AtomicBool = AtomicBool::new(false);
+static DETERMINISTIC_SEED: AtomicU32 = AtomicU32::new(0);
+
 #[inline]
 fn state() -> u32 {
     RNG_STATE.load(Ordering::Relaxed)
@@ -16,6 +19,30 @@ pub fn seed(seed: u32) {
     RNG_STATE.store(seed, Ordering::Relaxed);
 }
 
+/// Makes the random sources of all engines reproducible from `seed`, e.g. for comparing
+/// renders against reference files. `None` (default) lets them draw from the shared
+/// generator.
+///
+/// The setting takes effect on the next `init` of an engine, e.g. by `Voice::init`:
+/// the engine then runs its own generators seeded from `seed`, regardless of other
+/// users of the shared generator. The setting itself is shared by all threads.
+pub fn set_deterministic(seed: Option<u32>) {
+    if let Some(seed) = seed {
+        DETERMINISTIC_SEED.store(seed, Ordering::Relaxed);
+    }
+    DETERMINISTIC.store(seed.is_some(), Ordering::Relaxed);
+}
+
+/// Returns the seed set with [`set_deterministic`], if any.
+#[inline]
+pub fn deterministic_seed() -> Option<u32> {
+    if DETERMINISTIC.load(Ordering::Relaxed) {
+        Some(DETERMINISTIC_SEED.load(Ordering::Relaxed))
+    } else {
+        None
+    }
+}
+
 #[inline]
 pub fn get_word() -> u32 {
     RNG_STATE.store(
@@ -37,3 +64,78 @@ pub fn get_sample() -> i16 {
 pub fn get_float() -> f32 {
     get_word() as f32 / 4294967296.0
 }
+
+/// Generator with the same sequence as the shared one, but with its own state, so that
+/// its output is reproducible regardless of other users of the shared generator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RandomGenerator {
+    state: u32,
+}
+
+impl RandomGenerator {
+    pub fn new(seed: u32) -> Self {
+        Self { state: seed }
+    }
+
+    #[inline]
+    pub fn seed(&mut self, seed: u32) {
+        self.state = seed;
+    }
+
+    #[inline]
+    pub fn get_word(&mut self) -> u32 {
+        self.state = self.state.wrapping_mul(1664525).wrapping_add(1013904223);
+        self.state
+    }
+
+    #[inline]
+    pub fn get_sample(&mut self) -> i16 {
+        (self.get_word() >> 16) as i16
+    }
+
+    #[inline]
+    pub fn get_float(&mut self) -> f32 {
+        self.get_word() as f32 / 4294967296.0
+    }
+}
+
+/// Random source of a DSP block. It draws from the shared generator, or from its own
+/// generator once seeded by `init`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RandomSource {
+    generator: Option<RandomGenerator>,
+}
+
+impl RandomSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seeds an own generator from `seed`, or from the seed set with
+    /// [`set_deterministic`] if `seed` is `None`. Without either, the source draws from
+    /// the shared generator. Blocks of the same kind within an engine pass different
+    /// `stream`s, so that they do not produce the same sequence.
+    pub fn init(&mut self, seed: Option<u32>, stream: u32) {
+        self.generator = seed
+            .or_else(deterministic_seed)
+            .map(|seed| RandomGenerator::new(seed ^ stream.wrapping_mul(0x9e3779b9)));
+    }
+
+    #[inline]
+    pub fn get_word(&mut self) -> u32 {
+        match &mut self.generator {
+            Some(generator) => generator.get_word(),
+            None => get_word(),
+        }
+    }
+
+    #[inline]
+    pub fn get_sample(&mut self) -> i16 {
+        (self.get_word() >> 16) as i16
+    }
+
+    #[inline]
+    pub fn get_float(&mut self) -> f32 {
+        self.get_word() as f32 / 4294967296.0
+    }
+}
@@ -0,0 +1,88 @@
+//! Envelope follower for deriving control signals from audio.
+
+#[allow(unused_imports)]
+use num_traits::float::Float;
+
+use crate::dsp::SAMPLE_RATE;
+use crate::stmlib::dsp::slope;
+
+#[derive(Debug, Default)]
+pub struct EnvelopeFollower {
+    attack: f32,
+    release: f32,
+    level: f32,
+}
+
+impl EnvelopeFollower {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Initialize with an attack time of 1 ms and a release time of 100 ms.
+    pub fn init(&mut self) {
+        self.set_attack_time(0.001);
+        self.set_release_time(0.1);
+        self.reset();
+    }
+
+    pub fn reset(&mut self) {
+        self.level = 0.0;
+    }
+
+    /// Set the attack time in seconds. The level rises linearly, by 1.0 within the attack
+    /// time, like the peak detector of the limiter.
+    pub fn set_attack_time(&mut self, time: f32) {
+        self.attack = if time <= 0.0 {
+            1.0
+        } else {
+            (1.0 / (time * SAMPLE_RATE)).min(1.0)
+        };
+    }
+
+    /// Set the release time constant in seconds.
+    pub fn set_release_time(&mut self, time: f32) {
+        self.release = time_to_coefficient(time);
+    }
+
+    /// Return the current level.
+    #[inline]
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// Process a block and return the smoothed level at its end.
+    #[inline]
+    pub fn process(&mut self, in_: &[f32]) -> f32 {
+        for in_sample in in_.iter() {
+            self.process_sample(*in_sample);
+        }
+
+        self.level
+    }
+
+    /// Process a block and write the smoothed level for every sample.
+    #[inline]
+    pub fn process_buffer(&mut self, in_: &[f32], out: &mut [f32]) {
+        for (in_sample, out_sample) in in_.iter().zip(out.iter_mut()) {
+            *out_sample = self.process_sample(*in_sample);
+        }
+    }
+
+    /// Process a single sample and return the smoothed level.
+    #[inline]
+    pub fn process_sample(&mut self, in_: f32) -> f32 {
+        let rectified = in_.abs();
+        slope(&mut self.level, rectified, self.attack, self.release);
+
+        self.level
+    }
+}
+
+#[inline]
+fn time_to_coefficient(time: f32) -> f32 {
+    if time <= 0.0 {
+        1.0
+    } else {
+        1.0 - (-1.0 / (time * SAMPLE_RATE)).exp()
+    }
+}
@@ -0,0 +1,33 @@
+//! Translation of MIDI messages to the parameters of the voice.
+//!
+//! These helpers encode the mappings the crate uses internally, so that hosts driving a
+//! [`Voice`](crate::dsp::voice::Voice) from MIDI stay consistent with it.
+
+use crate::dsp::voice::compress_level;
+
+/// Converts a MIDI note number to [`Patch::note`](crate::dsp::voice::Patch::note).
+///
+/// Notes are MIDI notes already, so note 69 renders A4 at the tuning reference of the
+/// voice, 440 Hz by default.
+#[inline]
+pub fn note_number_to_patch_note(note: u8) -> f32 {
+    f32::from(note.min(127))
+}
+
+/// Converts a MIDI note-on velocity to the level the LPG opens to and the accent passed to
+/// the engines, i.e. the curve the voice applies to
+/// [`Modulations::level`](crate::dsp::voice::Modulations::level).
+///
+/// As the voice applies this curve itself, set the level modulation to
+/// [`cc_to_unit`] of the velocity instead of this value.
+#[inline]
+pub fn velocity_to_level(velocity: u8) -> f32 {
+    compress_level(cc_to_unit(velocity))
+}
+
+/// Converts a MIDI controller value to the range from `0.0` to `1.0` of the patch and
+/// modulation parameters.
+#[inline]
+pub fn cc_to_unit(value: u8) -> f32 {
+    f32::from(value.min(127)) / 127.0
+}
@@ -0,0 +1,26 @@
+//! Additional utilities that are not part of the original firmware.
+//!
+//! These are small helpers for integrating the DSP building blocks into a host,
+//! built on the same primitives as the ported code.
+
+pub mod envelope_follower;
+pub mod meter;
+pub mod modulation;
+pub mod vocoder;
+pub mod voice_stream;
+
+#[cfg(feature = "alloc")]
+pub mod preset_bank;
+
+#[cfg(feature = "midi")]
+pub mod midi;
+
+/// Reverses a buffer in place, e.g. a sample or a single-cycle wave to play it backward.
+///
+/// The integrated waves of the wavetable engines can not be reversed this way, as this
+/// also inverts their polarity. Use [`set_reverse`] to read them backward instead.
+///
+/// [`set_reverse`]: crate::dsp::oscillator::wavetable_oscillator::WavetableOscillator::set_reverse
+pub fn reverse(buffer: &mut [f32]) {
+    buffer.reverse();
+}
@@ -0,0 +1,23 @@
+//! Cross-modulation of two signals, e.g. *OUT* and *AUX* or two voices.
+
+/// Multiply two buffers, producing the sum and difference frequencies of their inputs.
+#[inline]
+pub fn ring_mod(a: &[f32], b: &[f32], out: &mut [f32]) {
+    for ((a_sample, b_sample), out_sample) in a.iter().zip(b.iter()).zip(out.iter_mut()) {
+        *out_sample = a_sample * b_sample;
+    }
+}
+
+/// Modulate the amplitude of `a` by `b`.
+///
+/// `b` is expected in the range from `-1.0` to `1.0` and is mapped to a gain from
+/// `1.0 - depth` to `1.0`, so that the level of `a` never increases. `depth` is
+/// clamped to the range from `0.0` (no modulation) to `1.0`.
+#[inline]
+pub fn amp_mod(a: &[f32], b: &[f32], depth: f32, out: &mut [f32]) {
+    let depth = depth.clamp(0.0, 1.0);
+
+    for ((a_sample, b_sample), out_sample) in a.iter().zip(b.iter()).zip(out.iter_mut()) {
+        *out_sample = a_sample * (1.0 - depth * 0.5 * (1.0 - b_sample));
+    }
+}
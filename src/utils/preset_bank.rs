@@ -0,0 +1,109 @@
+//! Bank of named patch presets.
+//!
+//! Patches are clamped to their documented ranges when they are stored in the bank.
+//! With the `serde` feature enabled, a bank can be serialized and deserialized as a
+//! sequence of presets.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::dsp::voice::Patch;
+
+/// Patch with a name.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Preset {
+    pub name: String,
+    pub patch: Patch,
+}
+
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "Vec<Preset>", into = "Vec<Preset>")
+)]
+pub struct PresetBank {
+    presets: Vec<Preset>,
+}
+
+impl PresetBank {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the number of presets in the bank.
+    pub fn len(&self) -> usize {
+        self.presets.len()
+    }
+
+    /// Return `true` if the bank contains no presets.
+    pub fn is_empty(&self) -> bool {
+        self.presets.is_empty()
+    }
+
+    /// Return the patch at `index`.
+    pub fn get(&self, index: usize) -> Option<&Patch> {
+        self.presets.get(index).map(|preset| &preset.patch)
+    }
+
+    /// Return the name of the preset at `index`.
+    pub fn name(&self, index: usize) -> Option<&str> {
+        self.presets.get(index).map(|preset| preset.name.as_str())
+    }
+
+    /// Return the index of the first preset named `name`.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.presets.iter().position(|preset| preset.name == name)
+    }
+
+    /// Replace the patch at `index`, keeping its name. Returns the previous patch or
+    /// `None` if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, mut patch: Patch) -> Option<Patch> {
+        let preset = self.presets.get_mut(index)?;
+        patch.clamp();
+
+        Some(core::mem::replace(&mut preset.patch, patch))
+    }
+
+    /// Append a named patch and return its index.
+    pub fn insert(&mut self, name: &str, mut patch: Patch) -> usize {
+        patch.clamp();
+        self.presets.push(Preset {
+            name: String::from(name),
+            patch,
+        });
+
+        self.presets.len() - 1
+    }
+
+    /// Remove and return the preset at `index`.
+    pub fn remove(&mut self, index: usize) -> Option<Preset> {
+        if index < self.presets.len() {
+            Some(self.presets.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Return an iterator over all presets.
+    pub fn iter(&self) -> impl Iterator<Item = &Preset> {
+        self.presets.iter()
+    }
+}
+
+impl From<Vec<Preset>> for PresetBank {
+    fn from(mut presets: Vec<Preset>) -> Self {
+        for preset in presets.iter_mut() {
+            preset.patch.clamp();
+        }
+
+        Self { presets }
+    }
+}
+
+impl From<PresetBank> for Vec<Preset> {
+    fn from(bank: PresetBank) -> Self {
+        bank.presets
+    }
+}
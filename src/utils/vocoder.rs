@@ -0,0 +1,133 @@
+//! Channel vocoder built from two banks of band-pass filters.
+//!
+//! The analysis bank splits the modulator into bands whose levels are tracked by
+//! envelope followers. The synthesis bank splits the carrier into the same bands,
+//! each of which is scaled by the level of the matching modulator band.
+
+#[allow(unused_imports)]
+use num_traits::float::Float;
+
+use super::envelope_follower::EnvelopeFollower;
+use crate::dsp::SAMPLE_RATE;
+use crate::stmlib::dsp::filter::{FilterMode, FrequencyApproximation, Svf};
+
+pub const MAX_VOCODER_BANDS: usize = 16;
+
+const LOWEST_BAND_FREQUENCY: f32 = 100.0;
+const HIGHEST_BAND_FREQUENCY: f32 = 8000.0;
+
+#[derive(Debug)]
+pub struct Vocoder {
+    analysis: [Svf; MAX_VOCODER_BANDS],
+    synthesis: [Svf; MAX_VOCODER_BANDS],
+    follower: [EnvelopeFollower; MAX_VOCODER_BANDS],
+
+    num_bands: usize,
+    gain: f32,
+}
+
+impl Default for Vocoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Vocoder {
+    pub fn new() -> Self {
+        Self {
+            analysis: core::array::from_fn(|_| Svf::new()),
+            synthesis: core::array::from_fn(|_| Svf::new()),
+            follower: core::array::from_fn(|_| EnvelopeFollower::new()),
+
+            num_bands: MAX_VOCODER_BANDS,
+            gain: 1.0,
+        }
+    }
+
+    /// Initialize with all bands, an attack time of 2 ms and a release time of 20 ms.
+    pub fn init(&mut self) {
+        for follower in self.follower.iter_mut() {
+            follower.init();
+            follower.set_attack_time(0.002);
+            follower.set_release_time(0.02);
+        }
+
+        self.set_bands(MAX_VOCODER_BANDS);
+        self.reset();
+    }
+
+    pub fn reset(&mut self) {
+        for ((analysis, synthesis), follower) in self
+            .analysis
+            .iter_mut()
+            .zip(self.synthesis.iter_mut())
+            .zip(self.follower.iter_mut())
+        {
+            analysis.reset();
+            synthesis.reset();
+            follower.reset();
+        }
+    }
+
+    /// Set the number of bands in the range from `1` to `MAX_VOCODER_BANDS`.
+    ///
+    /// The bands are spaced logarithmically between 100 Hz and 8 kHz, with a bandwidth
+    /// matching the spacing.
+    pub fn set_bands(&mut self, num_bands: usize) {
+        self.num_bands = num_bands.clamp(1, MAX_VOCODER_BANDS);
+
+        let span = HIGHEST_BAND_FREQUENCY / LOWEST_BAND_FREQUENCY;
+        let octaves = span.log2();
+        let spacing = if self.num_bands > 1 {
+            octaves / (self.num_bands - 1) as f32
+        } else {
+            octaves
+        };
+        let ratio = 2.0f32.powf(spacing);
+        let q = (ratio.sqrt() / (ratio - 1.0)).max(0.5);
+
+        for i in 0..self.num_bands {
+            let frequency = if self.num_bands > 1 {
+                LOWEST_BAND_FREQUENCY * span.powf(i as f32 / (self.num_bands - 1) as f32)
+            } else {
+                (LOWEST_BAND_FREQUENCY * HIGHEST_BAND_FREQUENCY).sqrt()
+            };
+            let f = frequency / SAMPLE_RATE;
+
+            self.analysis[i].set_f_q(f, q, FrequencyApproximation::Accurate);
+            self.synthesis[i].set_f_q(f, q, FrequencyApproximation::Accurate);
+        }
+
+        // Compensates the level lost by narrower bands.
+        self.gain = q.sqrt() * 2.0;
+    }
+
+    #[inline]
+    pub fn bands(&self) -> usize {
+        self.num_bands
+    }
+
+    /// Process a block, imposing the spectral envelope of `modulator` on `carrier`.
+    #[inline]
+    pub fn process(&mut self, modulator: &[f32], carrier: &[f32], out: &mut [f32]) {
+        for ((modulator_sample, carrier_sample), out_sample) in
+            modulator.iter().zip(carrier.iter()).zip(out.iter_mut())
+        {
+            let mut sum = 0.0;
+
+            for ((analysis, synthesis), follower) in self
+                .analysis
+                .iter_mut()
+                .zip(self.synthesis.iter_mut())
+                .zip(self.follower.iter_mut())
+                .take(self.num_bands)
+            {
+                let level = follower
+                    .process_sample(analysis.process(*modulator_sample, FilterMode::BandPass));
+                sum += synthesis.process(*carrier_sample, FilterMode::BandPass) * level;
+            }
+
+            *out_sample = sum * self.gain;
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Sample by sample access to a voice.
+//!
+//! Audio callbacks of hosts like `cpal` or `rodio` often pull samples one at a time or in
+//! buffers of arbitrary length. [`VoiceStream`] renders the voice in fixed blocks of
+//! `BLOCK_SIZE` samples and hands them out through an [`Iterator`].
+
+use crate::dsp::voice::{Modulations, Patch, Voice};
+
+#[derive(Debug)]
+pub struct VoiceStream<'a, const BLOCK_SIZE: usize> {
+    voice: Voice<'a>,
+    patch: Patch,
+    modulations: Modulations,
+
+    out: [f32; BLOCK_SIZE],
+    aux: [f32; BLOCK_SIZE],
+    position: usize,
+    channel: usize,
+
+    interleaved: bool,
+}
+
+impl<'a, const BLOCK_SIZE: usize> VoiceStream<'a, BLOCK_SIZE> {
+    /// Wrap an initialized voice. It must have been created with a block size of at
+    /// least `BLOCK_SIZE`.
+    pub fn new(voice: Voice<'a>, patch: Patch, modulations: Modulations) -> Self {
+        Self {
+            voice,
+            patch,
+            modulations,
+
+            out: [0.0; BLOCK_SIZE],
+            aux: [0.0; BLOCK_SIZE],
+            position: BLOCK_SIZE,
+            channel: 0,
+
+            interleaved: false,
+        }
+    }
+
+    /// Yield *OUT* and *AUX* as interleaved stereo frames instead of *OUT* only.
+    #[inline]
+    pub fn set_interleaved(&mut self, interleaved: bool) {
+        self.interleaved = interleaved;
+        self.channel = 0;
+    }
+
+    #[inline]
+    pub fn voice(&self) -> &Voice<'a> {
+        &self.voice
+    }
+
+    #[inline]
+    pub fn voice_mut(&mut self) -> &mut Voice<'a> {
+        &mut self.voice
+    }
+
+    /// Patch used for the next rendered block.
+    #[inline]
+    pub fn patch_mut(&mut self) -> &mut Patch {
+        &mut self.patch
+    }
+
+    /// Modulations used for the next rendered block.
+    #[inline]
+    pub fn modulations_mut(&mut self) -> &mut Modulations {
+        &mut self.modulations
+    }
+
+    /// Return the wrapped voice, dropping any samples not yet consumed.
+    pub fn into_inner(self) -> Voice<'a> {
+        self.voice
+    }
+
+    /// Fill `out` with the next samples.
+    #[inline]
+    pub fn fill(&mut self, out: &mut [f32]) {
+        for (out_sample, sample) in out.iter_mut().zip(self.by_ref()) {
+            *out_sample = sample;
+        }
+    }
+
+    fn render_block(&mut self) {
+        self.voice
+            .render(&self.patch, &self.modulations, &mut self.out, &mut self.aux);
+        self.position = 0;
+    }
+}
+
+impl<const BLOCK_SIZE: usize> Iterator for VoiceStream<'_, BLOCK_SIZE> {
+    type Item = f32;
+
+    /// Return the next sample. The stream never ends.
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if self.position >= BLOCK_SIZE {
+            self.render_block();
+        }
+
+        if !self.interleaved {
+            let sample = self.out[self.position];
+            self.position += 1;
+            return Some(sample);
+        }
+
+        let sample = if self.channel == 0 {
+            self.out[self.position]
+        } else {
+            self.aux[self.position]
+        };
+
+        self.channel += 1;
+        if self.channel == 2 {
+            self.channel = 0;
+            self.position += 1;
+        }
+
+        Some(sample)
+    }
+}
@@ -1,5 +1,6 @@
 //! Tests for the engines
 
+mod analysis;
 mod engines;
 mod modulation;
 mod wav_writer;
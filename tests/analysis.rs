@@ -0,0 +1,138 @@
+//! Signal analysis helpers used for assertions
+
+#![allow(dead_code)]
+
+use std::f32::consts::PI;
+
+/// Returns the amplitude of the component at a normalized frequency (cycles per sample)
+/// using a Hann-windowed single-bin DFT.
+pub fn magnitude(data: &[f32], frequency: f32) -> f32 {
+    let length = data.len() as f32;
+    let mut re = 0.0;
+    let mut im = 0.0;
+
+    for (n, sample) in data.iter().enumerate() {
+        let window = 0.5 - 0.5 * f32::cos(2.0 * PI * n as f32 / length);
+        let phase = 2.0 * PI * frequency * n as f32;
+        re += sample * window * f32::cos(phase);
+        im -= sample * window * f32::sin(phase);
+    }
+
+    // Compensate the coherent gain of the Hann window.
+    f32::sqrt(re * re + im * im) * 4.0 / length
+}
+
+/// Returns the summed energy of the components in a normalized frequency band.
+pub fn band_energy(data: &[f32], low: f32, high: f32, steps: usize) -> f32 {
+    (0..steps)
+        .map(|n| {
+            let frequency = low + (high - low) * n as f32 / (steps - 1).max(1) as f32;
+            let m = magnitude(data, frequency);
+            m * m
+        })
+        .sum()
+}
+
+/// Returns the normalized frequency with the highest magnitude within a band.
+pub fn dominant_frequency(data: &[f32], low: f32, high: f32, steps: usize) -> f32 {
+    let mut best_frequency = low;
+    let mut best_magnitude = 0.0;
+
+    for n in 0..steps {
+        let frequency = low + (high - low) * n as f32 / (steps - 1).max(1) as f32;
+        let m = magnitude(data, frequency);
+        if m > best_magnitude {
+            best_magnitude = m;
+            best_frequency = frequency;
+        }
+    }
+
+    best_frequency
+}
+
+/// Returns the normalized frequency estimated from the rising zero crossings.
+pub fn zero_crossing_frequency(data: &[f32]) -> f32 {
+    let mut first = None;
+    let mut last = 0.0;
+    let mut count = 0;
+
+    for n in 1..data.len() {
+        if data[n - 1] <= 0.0 && data[n] > 0.0 {
+            let position = (n - 1) as f32 + data[n - 1] / (data[n - 1] - data[n]);
+            if first.is_none() {
+                first = Some(position);
+            } else {
+                count += 1;
+            }
+            last = position;
+        }
+    }
+
+    match first {
+        Some(first) if count > 0 => count as f32 / (last - first),
+        _ => 0.0,
+    }
+}
+
+/// Returns the root mean square of a buffer.
+pub fn rms(data: &[f32]) -> f32 {
+    if data.is_empty() {
+        return 0.0;
+    }
+
+    f32::sqrt(data.iter().map(|x| x * x).sum::<f32>() / data.len() as f32)
+}
+
+/// Returns the absolute peak value of a buffer.
+pub fn peak(data: &[f32]) -> f32 {
+    data.iter().fold(0.0, |acc, x| f32::max(acc, x.abs()))
+}
+
+/// Returns the mean value of a buffer.
+pub fn mean(data: &[f32]) -> f32 {
+    if data.is_empty() {
+        return 0.0;
+    }
+
+    data.iter().sum::<f32>() / data.len() as f32
+}
+
+/// Returns the RMS of the first difference, a rough measure of high frequency content.
+pub fn difference_rms(data: &[f32]) -> f32 {
+    let differences: Vec<f32> = data.windows(2).map(|w| w[1] - w[0]).collect();
+
+    rms(&differences)
+}
+
+/// Returns the normalized cross-correlation of two buffers at zero lag.
+pub fn correlation(a: &[f32], b: &[f32]) -> f32 {
+    let ab: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+    let aa: f32 = a.iter().map(|x| x * x).sum();
+    let bb: f32 = b.iter().map(|x| x * x).sum();
+
+    if aa == 0.0 || bb == 0.0 {
+        return 0.0;
+    }
+
+    ab / f32::sqrt(aa * bb)
+}
+
+/// Returns the spectral centroid as a normalized frequency, evaluated on `steps` bins
+/// between DC and Nyquist.
+pub fn spectral_centroid(data: &[f32], steps: usize) -> f32 {
+    let mut weighted = 0.0;
+    let mut total = 0.0;
+
+    for n in 1..steps {
+        let frequency = 0.5 * n as f32 / steps as f32;
+        let m = magnitude(data, frequency);
+        weighted += frequency * m;
+        total += m;
+    }
+
+    if total == 0.0 {
+        return 0.0;
+    }
+
+    weighted / total
+}
@@ -0,0 +1,61 @@
+//! Tests for the deterministic mode.
+//!
+//! The mode is a process-wide setting, so these tests live in their own binary and in a
+//! single test function, so that no other test observes it.
+
+use mi_plaits_dsp::dsp::voice::{Modulations, Patch, Voice};
+
+const BLOCK_SIZE: usize = 24;
+
+#[test]
+fn deterministic_mode() {
+    let render = |engine: usize, timbre: f32| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        voice.init();
+
+        let patch = Patch {
+            note: 48.0,
+            harmonics: 0.5,
+            timbre,
+            morph: 0.5,
+            engine,
+            ..Default::default()
+        };
+        let mut modulations = Modulations {
+            trigger_patched: true,
+            ..Default::default()
+        };
+
+        for n in 0..1000 {
+            modulations.trigger = if n % 200 < 100 { 1.0 } else { 0.0 };
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            wav_data.extend_from_slice(&out);
+            wav_data.extend_from_slice(&aux);
+        }
+
+        wav_data
+    };
+
+    mi_plaits_dsp::set_deterministic(Some(1234));
+
+    // Six-op FM with its LFOs, chiptune with the random arpeggio, speech, swarm, noise,
+    // particle, string, modal, bass drum, snare drum and hi-hat.
+    let engines = [2, 3, 4, 7, 15, 16, 17, 18, 19, 20, 21, 22, 23];
+
+    for engine in engines {
+        let timbre = if engine == 7 { 0.9 } else { 0.5 };
+        let first = render(engine, timbre);
+        let second = render(engine, timbre);
+
+        let first_bytes: Vec<u8> = first.iter().flat_map(|x| x.to_le_bytes()).collect();
+        let second_bytes: Vec<u8> = second.iter().flat_map(|x| x.to_le_bytes()).collect();
+        assert_eq!(first_bytes, second_bytes, "engine {}", engine);
+        assert!(first.iter().any(|&sample| sample != 0.0));
+    }
+
+    mi_plaits_dsp::set_deterministic(None);
+}
@@ -179,3 +179,40 @@ fn synthetic_snare_drum() {
 
     wav_writer::write("drums/synthetic_snare_drum.wav", &wav_data).ok();
 }
+
+#[test]
+fn analog_bass_drum_sample_rate() {
+    let frequency = 50.0;
+    let duration = 2.0;
+
+    // Returns the time in seconds after which the kick stays 60 dB below its peak.
+    let decay_time = |sample_rate: f32| {
+        let mut drum = analog_bass_drum::AnalogBassDrum::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+        drum.init();
+        drum.set_sample_rate(Some(sample_rate));
+
+        let blocks = (duration * sample_rate / (BLOCK_SIZE as f32)) as usize;
+        let f0 = frequency / sample_rate;
+
+        for n in 0..blocks {
+            drum.render(false, n == 0, 1.0, f0, 0.5, 0.5, 0.0, 0.0, &mut out);
+            data.extend_from_slice(&out);
+        }
+
+        let peak = data.iter().fold(0.0, |acc: f32, x| acc.max(x.abs()));
+        let last = data
+            .iter()
+            .rposition(|x| x.abs() > peak * 0.001)
+            .unwrap_or(0);
+
+        last as f32 / sample_rate
+    };
+
+    let time_44k = decay_time(44100.0);
+    let time_96k = decay_time(96000.0);
+
+    assert!(time_44k > 0.1);
+    assert!((time_44k / time_96k - 1.0).abs() < 0.05);
+}
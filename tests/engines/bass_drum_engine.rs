@@ -3,6 +3,7 @@
 use mi_plaits_dsp::dsp::engine::*;
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
 
+use crate::analysis;
 use crate::modulation;
 use crate::wav_writer;
 
@@ -122,3 +123,226 @@ fn bass_drum_engine_morph() {
     wav_writer::write("engines/bass_drum/bass_drum_morph.wav", &wav_data).ok();
     wav_writer::write("engines/bass_drum/bass_drum_morph_aux.wav", &wav_data_aux).ok();
 }
+
+#[test]
+fn bass_drum_engine_sustain() {
+    let mut engine = bass_drum_engine::BassDrumEngine::new();
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let mut wav_data = Vec::new();
+    let mut wav_data_aux = Vec::new();
+
+    engine.init();
+    engine.set_sustain(true);
+
+    let mut already_enveloped = false;
+    let parameters = EngineParameters {
+        trigger: TriggerState::Low,
+        note: 48.0,
+        timbre: 0.5,
+        morph: 0.5,
+        harmonics: 0.5,
+        accent: 1.0,
+    };
+
+    for n in 0..600 {
+        // Toggle sustain mid-render.
+        if n == 200 || n == 400 {
+            engine.set_sustain(!engine.sustain());
+        }
+
+        engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+        wav_data.extend_from_slice(&out);
+        wav_data_aux.extend_from_slice(&aux);
+    }
+
+    wav_writer::write("engines/bass_drum/bass_drum_sustain.wav", &wav_data).ok();
+    wav_writer::write("engines/bass_drum/bass_drum_sustain_aux.wav", &wav_data_aux).ok();
+
+    let max_step = |data: &[f32]| {
+        data.windows(2)
+            .fold(0.0, |acc: f32, w| acc.max((w[1] - w[0]).abs()))
+    };
+
+    for data in [&wav_data, &wav_data_aux] {
+        // Sustained tone before and after toggling, and silence in between.
+        let steady_step = max_step(&data[100 * BLOCK_SIZE..200 * BLOCK_SIZE]);
+        assert!(analysis::rms(&data[100 * BLOCK_SIZE..200 * BLOCK_SIZE]) > 0.01);
+        assert!(analysis::rms(&data[300 * BLOCK_SIZE..400 * BLOCK_SIZE]) < 1e-3);
+        assert!(analysis::rms(&data[500 * BLOCK_SIZE..]) > 0.01);
+
+        // No click when the level ramps between both modes.
+        assert!(max_step(&data[190 * BLOCK_SIZE..230 * BLOCK_SIZE]) < steady_step * 1.5);
+        assert!(max_step(&data[390 * BLOCK_SIZE..430 * BLOCK_SIZE]) < steady_step * 1.5);
+    }
+}
+
+#[test]
+fn bass_drum_engine_fm_amount() {
+    // Time in samples until the period of the synthetic kick on AUX settles near the
+    // period of the note, i.e. the length of the pitch sweep.
+    let sweep_length = |fm_amount: f32| {
+        let mut engine = bass_drum_engine::BassDrumEngine::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data_aux = Vec::new();
+
+        engine.init();
+        engine.set_fm_amount(Some(fm_amount));
+        engine.set_transient(Some(0.0));
+        engine.set_dirtiness(Some(0.0));
+        assert_eq!(engine.fm_amount(), Some(fm_amount));
+
+        let mut already_enveloped = false;
+
+        for n in 0..200 {
+            let parameters = EngineParameters {
+                trigger: if n == 0 {
+                    TriggerState::RisingEdge
+                } else {
+                    TriggerState::Low
+                },
+                note: 48.0,
+                timbre: 0.5,
+                morph: 0.7,
+                harmonics: 0.3,
+                accent: 1.0,
+            };
+
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            wav_data_aux.extend_from_slice(&aux);
+        }
+
+        wav_writer::write(
+            format!("engines/bass_drum/bass_drum_fm_amount_{fm_amount}_aux.wav").as_str(),
+            &wav_data_aux,
+        )
+        .ok();
+
+        let period = 1.0 / note_to_frequency(48.0);
+        let crossings: Vec<usize> = (1..wav_data_aux.len())
+            .filter(|&n| wav_data_aux[n - 1] <= 0.0 && wav_data_aux[n] > 0.0)
+            .collect();
+
+        crossings
+            .windows(2)
+            .find(|crossing| (crossing[1] - crossing[0]) as f32 > 0.9 * period)
+            .map(|crossing| crossing[0])
+            .unwrap()
+    };
+
+    let short = sweep_length(0.2);
+    let long = sweep_length(1.0);
+    assert!(long as f32 > 1.3 * short as f32, "{short} {long}");
+}
+
+#[test]
+fn bass_drum_engine_dirtiness() {
+    // Fraction of the power of the sustained synthetic kick on AUX outside the harmonics.
+    let inharmonic_fraction = |dirtiness: f32| {
+        let mut engine = bass_drum_engine::BassDrumEngine::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data_aux = Vec::new();
+
+        engine.init();
+        engine.set_sustain(true);
+        engine.set_dirtiness(Some(dirtiness));
+        assert_eq!(engine.dirtiness(), Some(dirtiness));
+
+        let mut already_enveloped = false;
+        let parameters = EngineParameters {
+            trigger: TriggerState::Low,
+            note: 48.0,
+            timbre: 0.5,
+            morph: 0.5,
+            harmonics: 0.0,
+            accent: 1.0,
+        };
+
+        for _ in 0..(1.5 * SAMPLE_RATE / BLOCK_SIZE as f32) as usize {
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            wav_data_aux.extend_from_slice(&aux);
+        }
+
+        wav_writer::write(
+            format!("engines/bass_drum/bass_drum_dirtiness_{dirtiness}_aux.wav").as_str(),
+            &wav_data_aux,
+        )
+        .ok();
+
+        let data = &wav_data_aux[wav_data_aux.len() - SAMPLE_RATE as usize..];
+        let f0 = note_to_frequency(48.0);
+        let harmonic_power: f32 = (1..40)
+            .map(|harmonic| analysis::magnitude(data, f0 * harmonic as f32).powi(2) * 0.5)
+            .sum();
+        let mean = analysis::mean(data);
+        let power = analysis::rms(data).powi(2) - mean * mean;
+
+        1.0 - harmonic_power / power
+    };
+
+    let clean = inharmonic_fraction(0.0);
+    let dirty = inharmonic_fraction(1.0);
+    assert!(
+        dirty > 2.0 * clean.max(0.0) && dirty > 0.002,
+        "{clean} {dirty}"
+    );
+}
+
+#[test]
+fn bass_drum_engine_model_blend() {
+    let render = |model_blend: Option<f32>| {
+        let mut engine = bass_drum_engine::BassDrumEngine::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        let mut wav_data_aux = Vec::new();
+
+        if let Some(model_blend) = model_blend {
+            engine.set_model_blend(model_blend);
+            assert_eq!(engine.model_blend(), model_blend);
+        }
+        engine.init();
+
+        let mut already_enveloped = false;
+
+        for n in 0..1000 {
+            let parameters = EngineParameters {
+                trigger: if n % 250 == 0 {
+                    TriggerState::RisingEdge
+                } else {
+                    TriggerState::Low
+                },
+                note: 48.0,
+                timbre: 0.5,
+                morph: 0.5,
+                harmonics: 0.5,
+                accent: 1.0,
+            };
+
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            wav_data.extend_from_slice(&out);
+            wav_data_aux.extend_from_slice(&aux);
+        }
+
+        (wav_data, wav_data_aux)
+    };
+
+    // The analog model, as without blending.
+    let (reference, _) = render(None);
+    let (analog, synthetic) = render(Some(0.0));
+    assert_eq!(analog, reference);
+    let difference: Vec<f32> = analog
+        .iter()
+        .zip(synthetic.iter())
+        .map(|(a, s)| a - s)
+        .collect();
+    assert!(analysis::rms(&difference) > 0.1 * analysis::rms(&analog));
+
+    // The synthetic model on both outputs.
+    let (out, aux) = render(Some(1.0));
+    for (out_sample, aux_sample) in out.iter().zip(aux.iter()) {
+        assert!((out_sample - aux_sample).abs() < 1e-6);
+    }
+}
@@ -4,6 +4,7 @@ use mi_plaits_dsp::dsp::engine::*;
 use mi_plaits_dsp::dsp::engine2::*;
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
 
+use crate::analysis;
 use crate::modulation;
 use crate::wav_writer;
 
@@ -119,3 +120,124 @@ fn chiptune_engine_morph() {
     wav_writer::write("engines/chiptune/chiptune_morph.wav", &wav_data).ok();
     wav_writer::write("engines/chiptune/chiptune_morph_aux.wav", &wav_data_aux).ok();
 }
+
+/// Renders a sustained chord with the given DAC resolution, returning *OUT* and *AUX*.
+fn render_sustained(dac_bits: Option<u8>) -> (Vec<f32>, Vec<f32>) {
+    let mut engine = chiptune_engine::ChiptuneEngine::new();
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let mut wav_data = Vec::new();
+    let mut wav_data_aux = Vec::new();
+
+    engine.init();
+    assert_eq!(engine.dac_bits(), None);
+    engine.set_dac_bits(dac_bits);
+    engine.set_envelope_shape(chiptune_engine::NO_ENVELOPE);
+
+    let mut already_enveloped = false;
+
+    for _ in 0..1000 {
+        let parameters = EngineParameters {
+            trigger: TriggerState::Unpatched,
+            note: 48.0,
+            timbre: 0.5,
+            morph: 0.5,
+            harmonics: 0.5,
+            accent: 1.0,
+        };
+
+        engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+        wav_data.extend_from_slice(&out);
+        wav_data_aux.extend_from_slice(&aux);
+    }
+
+    (wav_data, wav_data_aux)
+}
+
+#[test]
+fn chiptune_engine_dac_bits_default() {
+    // Without a DAC resolution, the engine renders like the original firmware.
+    let (out, aux) = render_sustained(None);
+
+    // Samples rendered before the DAC resolution was added.
+    let expected_out = [
+        0.0,
+        -0.5,
+        0.32838917,
+        0.5,
+        -0.5,
+        -0.49411967,
+        0.5,
+        0.0,
+        -0.5,
+        0.0,
+        0.5,
+        -0.5,
+        -0.5,
+        0.5,
+        0.0,
+        -0.5,
+        0.0,
+    ];
+    let expected_aux = [
+        -1.0,
+        -0.8666667,
+        -0.73333335,
+        -0.59999996,
+        -0.3333333,
+        -0.19999993,
+        0.06641793,
+        0.20000005,
+        0.33333337,
+        0.60000014,
+        0.73333347,
+        0.8666668,
+        1.0,
+        0.8666668,
+        0.73333347,
+        0.4666667,
+        0.33333337,
+    ];
+
+    assert!(out.iter().step_by(1499).eq(expected_out.iter()));
+    assert!(aux.iter().step_by(1499).eq(expected_aux.iter()));
+}
+
+#[test]
+fn chiptune_engine_dac_bits() {
+    let (out_none, _) = render_sustained(None);
+    let mut out_error = Vec::new();
+    let mut aux_noise = Vec::new();
+
+    for bits in [3, 8] {
+        let (out, aux) = render_sustained(Some(bits));
+
+        wav_writer::write(
+            &format!("engines/chiptune/chiptune_dac_bits_{}.wav", bits),
+            &out,
+        )
+        .ok();
+        wav_writer::write(
+            &format!("engines/chiptune/chiptune_dac_bits_{}_aux.wav", bits),
+            &aux,
+        )
+        .ok();
+
+        // Every sample of the square voices is rounded to the grid of the DAC.
+        let scale = (1 << (bits - 1)) as f32;
+        let error: Vec<f32> = out
+            .iter()
+            .zip(out_none.iter())
+            .map(|(q, x)| q - x)
+            .collect();
+        assert!(out.iter().all(|sample| (sample * scale).fract() == 0.0));
+        assert!(error.iter().all(|error| error.abs() <= 0.5 / scale + 1e-6));
+        out_error.push(analysis::rms(&error));
+
+        // Energy of the quantization noise above the fundamental of the triangle.
+        aux_noise.push(analysis::band_energy(&aux[4000..20384], 0.1, 0.5, 400));
+    }
+
+    assert!(out_error[0] > out_error[1] * 10.0);
+    assert!(aux_noise[0] > aux_noise[1] * 10.0);
+}
@@ -3,6 +3,7 @@
 use mi_plaits_dsp::dsp::engine::*;
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
 
+use crate::analysis;
 use crate::modulation;
 use crate::wav_writer;
 
@@ -118,3 +119,222 @@ fn chord_engine_morph() {
     wav_writer::write("engines/chord/chord_morph.wav", &wav_data).ok();
     wav_writer::write("engines/chord/chord_morph_aux.wav", &wav_data_aux).ok();
 }
+
+#[test]
+fn chord_engine_stereo_spread() {
+    let mut mono_engine = chord_engine::ChordEngine::new();
+    let mut stereo_engine = chord_engine::ChordEngine::new();
+    let mut wide_engine = chord_engine::ChordEngine::new();
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let mut left = [0.0; BLOCK_SIZE];
+    let mut right = [0.0; BLOCK_SIZE];
+    let mut mono = Vec::new();
+    let mut narrow_sum = Vec::new();
+    let mut wide_sum = Vec::new();
+    let mut wide_left = Vec::new();
+    let mut wide_right = Vec::new();
+
+    mono_engine.init();
+    stereo_engine.init();
+    wide_engine.init();
+
+    let duration = 1.0;
+    let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+    let mut already_enveloped = false;
+
+    for n in 0..blocks {
+        let parameters = EngineParameters {
+            trigger: if n == 0 {
+                TriggerState::RisingEdge
+            } else {
+                TriggerState::Low
+            },
+            note: 48.0,
+            timbre: 0.5,
+            morph: 0.3,
+            harmonics: 0.5,
+            accent: 1.0,
+        };
+
+        mono_engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+        stereo_engine.render_stereo(&parameters, &mut left, &mut right, 0.0);
+        for i in 0..BLOCK_SIZE {
+            mono.push(out[i]);
+            narrow_sum.push(left[i] + right[i]);
+        }
+
+        wide_engine.render_stereo(&parameters, &mut left, &mut right, 1.0);
+        for i in 0..BLOCK_SIZE {
+            wide_sum.push(left[i] + right[i]);
+        }
+        wide_left.extend_from_slice(&left);
+        wide_right.extend_from_slice(&right);
+    }
+
+    wav_writer::write("engines/chord/chord_stereo_spread_left.wav", &wide_left).ok();
+    wav_writer::write("engines/chord/chord_stereo_spread_right.wav", &wide_right).ok();
+
+    for (a, b) in mono.iter().zip(narrow_sum.iter()) {
+        assert!((a - b).abs() < 1e-4);
+    }
+
+    let mono_rms = analysis::rms(&mono);
+    assert!(mono_rms > 0.01);
+    assert!((analysis::rms(&wide_sum) - mono_rms).abs() < mono_rms * 0.01);
+    assert!(analysis::correlation(&wide_left, &wide_right) < 0.5);
+}
+
+#[test]
+fn chord_engine_current_notes() {
+    let render = |timbre: f32| {
+        let mut engine = chord_engine::ChordEngine::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut already_enveloped = false;
+
+        engine.init();
+
+        // Major chord (0, 4, 7, 12), settle the smoothed inversion.
+        let parameters = EngineParameters {
+            trigger: TriggerState::Unpatched,
+            note: 48.0,
+            timbre,
+            morph: 0.0,
+            harmonics: 1.0,
+            accent: 1.0,
+        };
+
+        for _ in 0..500 {
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+        }
+
+        (engine.current_notes(), engine.current_amplitudes())
+    };
+
+    let assert_notes = |notes: [f32; 5], expected: [f32; 5]| {
+        for (note, expected) in notes.iter().zip(expected.iter()) {
+            assert!((note - expected).abs() < 0.02, "{notes:?} != {expected:?}");
+        }
+    };
+
+    // Root position two octaves down, the fifth voice is silent.
+    let (notes, amplitudes) = render(0.0);
+    assert_notes(notes, [-24.0, -20.0, -17.0, -12.0, -12.0]);
+    assert_eq!(amplitudes[4], 0.0);
+
+    // Halfway into the first inversion, the root has moved up an octave and the third
+    // crossfades between the second voice and the first one, an octave up.
+    let (notes, amplitudes) = render(1.5 / 20.0);
+    assert_notes(notes, [-8.0, -20.0, -17.0, -12.0, -12.0]);
+    assert!((amplitudes[0] - amplitudes[1]).abs() < 0.01);
+}
+
+#[test]
+fn chord_engine_voice_pans() {
+    let mut mono_engine = chord_engine::ChordEngine::new();
+    let mut stereo_engine = chord_engine::ChordEngine::new();
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let mut left = [0.0; BLOCK_SIZE];
+    let mut right = [0.0; BLOCK_SIZE];
+    let mut mono = Vec::new();
+    let mut wav_data_left = Vec::new();
+    let mut wav_data_right = Vec::new();
+
+    mono_engine.init();
+    stereo_engine.init();
+
+    // Root position of a major chord: C, E, G, C an octave up and a silent fifth voice.
+    // Root left, fifth right, third and octave in the center.
+    stereo_engine.set_voice_pans(Some(&[-1.0, 0.0, 1.0, 0.0, 0.0]));
+
+    let duration = 0.5;
+    let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+    let mut already_enveloped = false;
+
+    let parameters = EngineParameters {
+        trigger: TriggerState::Unpatched,
+        note: 60.0,
+        timbre: 0.0,
+        morph: 0.3,
+        harmonics: 1.0,
+        accent: 1.0,
+    };
+
+    for _ in 0..blocks {
+        mono_engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+        stereo_engine.render_stereo(&parameters, &mut left, &mut right, 1.0);
+        mono.extend_from_slice(&out);
+        wav_data_left.extend_from_slice(&left);
+        wav_data_right.extend_from_slice(&right);
+    }
+
+    wav_writer::write("engines/chord/chord_voice_pans_left.wav", &wav_data_left).ok();
+    wav_writer::write("engines/chord/chord_voice_pans_right.wav", &wav_data_right).ok();
+
+    // The pans do not change the mono mix.
+    for ((mono, left), right) in mono
+        .iter()
+        .zip(wav_data_left.iter())
+        .zip(wav_data_right.iter())
+    {
+        assert!((mono - (left + right)).abs() < 1e-4);
+    }
+
+    let f0 = note_to_frequency(60.0) * 0.998;
+    let notes = stereo_engine.current_notes();
+    let frequency = |voice: usize| f0 * 2.0f32.powf(notes[voice] / 12.0);
+
+    let left = &wav_data_left[wav_data_left.len() / 2..];
+    let right = &wav_data_right[wav_data_right.len() / 2..];
+
+    let root = frequency(0);
+    let fifth = frequency(2);
+    let third = frequency(1);
+
+    assert!(analysis::magnitude(left, root) > 10.0 * analysis::magnitude(right, root));
+    assert!(analysis::magnitude(right, fifth) > 10.0 * analysis::magnitude(left, fifth));
+
+    let third_balance = analysis::magnitude(left, third) / analysis::magnitude(right, third);
+    assert!((third_balance - 1.0).abs() < 0.1);
+}
+
+#[test]
+fn chord_engine_voice_pans_long_blocks() {
+    // Longer blocks than the ones of the voice, with all voices hard left.
+    const LONG_BLOCK_SIZE: usize = 256;
+
+    let mut mono_engine = chord_engine::ChordEngine::new();
+    let mut stereo_engine = chord_engine::ChordEngine::new();
+    let mut out = [0.0; LONG_BLOCK_SIZE];
+    let mut aux = [0.0; LONG_BLOCK_SIZE];
+    let mut left = [0.0; LONG_BLOCK_SIZE];
+    let mut right = [0.0; LONG_BLOCK_SIZE];
+    let mut already_enveloped = false;
+
+    mono_engine.init();
+    stereo_engine.init();
+    stereo_engine.set_voice_pans(Some(&[-1.0; 5]));
+
+    let parameters = EngineParameters {
+        trigger: TriggerState::Unpatched,
+        note: 60.0,
+        timbre: 0.3,
+        morph: 0.7,
+        harmonics: 0.5,
+        accent: 1.0,
+    };
+
+    for _ in 0..50 {
+        mono_engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+        stereo_engine.render_stereo(&parameters, &mut left, &mut right, 1.0);
+
+        for i in 0..LONG_BLOCK_SIZE {
+            assert!((out[i] - left[i]).abs() < 1e-4);
+            assert!(right[i].abs() < 1e-4);
+        }
+    }
+
+    assert!(analysis::rms(&left) > 0.01);
+}
@@ -3,6 +3,7 @@
 use mi_plaits_dsp::dsp::engine::*;
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
 
+use crate::analysis;
 use crate::modulation;
 use crate::wav_writer;
 
@@ -118,3 +119,50 @@ fn grain_engine_morph() {
     wav_writer::write("engines/grain/grain_morph.wav", &wav_data).ok();
     wav_writer::write("engines/grain/grain_morph_aux.wav", &wav_data_aux).ok();
 }
+
+#[test]
+fn grain_engine_grain_size() {
+    let render = |grain_size: f32, overlap: f32| {
+        let mut engine = grain_engine::GrainEngine::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        engine.init();
+        engine.set_grain_size(Some(grain_size));
+        engine.set_overlap(overlap);
+        assert_eq!(engine.grain_size(), Some(grain_size));
+        assert_eq!(engine.overlap(), overlap);
+
+        let duration = 1.0;
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+        let mut already_enveloped = false;
+
+        let parameters = EngineParameters {
+            trigger: TriggerState::Unpatched,
+            note: 48.0,
+            timbre: 0.5,
+            morph: 0.5,
+            harmonics: 0.5,
+            accent: 1.0,
+        };
+
+        for _ in 0..blocks {
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("engines/grain/grain_size_{grain_size}_{overlap}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        let data = &wav_data[wav_data.len() / 2..];
+        analysis::peak(data) / analysis::rms(data)
+    };
+
+    let clicks = render(0.002, 0.0);
+    let pad = render(0.05, 0.75);
+    assert!(pad < 0.75 * clicks);
+}
@@ -20,3 +20,29 @@ mod virtual_analog_vcf_engine;
 mod wave_terrain_engine;
 mod waveshaping_engine;
 mod wavetable_engine;
+
+use mi_plaits_dsp::dsp::engine::{frequency_to_note, note_to_frequency};
+use mi_plaits_dsp::dsp::{hz_to_normalized, normalized_to_hz, A0, A0_HZ};
+
+#[test]
+fn frequency_to_note_round_trip() {
+    // Quarter semitones, which note_to_frequency resolves exactly.
+    for n in 0..=(127 * 4) {
+        let note = n as f32 * 0.25;
+        let error = frequency_to_note(note_to_frequency(note)) - note;
+        assert!(error.abs() < 0.001, "Note {note} off by {error}");
+    }
+}
+
+#[test]
+fn a0_conventions() {
+    // MIDI note 48 is C3, 15 semitones above the reference note (MIDI note 33).
+    let expected_hz = A0_HZ * f32::powf(2.0, 15.0 / 12.0);
+    let note_hz = normalized_to_hz(note_to_frequency(48.0));
+    assert!((note_hz - expected_hz).abs() < 0.01);
+    assert!((note_hz - 130.813).abs() < 0.01);
+
+    assert!((normalized_to_hz(note_to_frequency(33.0)) - A0_HZ).abs() < 0.01);
+    assert!((normalized_to_hz(A0) - A0_HZ).abs() < 1e-3);
+    assert!((hz_to_normalized(A0_HZ) - A0).abs() < 1e-9);
+}
@@ -3,6 +3,7 @@
 use mi_plaits_dsp::dsp::engine::*;
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
 
+use crate::analysis;
 use crate::modulation;
 use crate::wav_writer;
 
@@ -118,3 +119,187 @@ fn modal_engine_morph() {
     wav_writer::write("engines/modal/modal_morph.wav", &wav_data).ok();
     wav_writer::write("engines/modal/modal_morph_aux.wav", &wav_data_aux).ok();
 }
+
+#[test]
+fn modal_engine_resolution() {
+    let render = |resolution: usize| {
+        let mut engine = modal_engine::ModalEngine::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        engine.set_resolution(resolution);
+        engine.init();
+        assert_eq!(engine.resolution(), resolution);
+
+        let duration = 1.0;
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+        let mut already_enveloped = false;
+
+        for n in 0..blocks {
+            let parameters = EngineParameters {
+                trigger: if n == 0 {
+                    TriggerState::RisingEdge
+                } else {
+                    TriggerState::Low
+                },
+                note: 36.0,
+                timbre: 0.8,
+                morph: 0.7,
+                harmonics: 0.3,
+                accent: 1.0,
+            };
+
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("engines/modal/modal_resolution_{resolution}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        wav_data
+    };
+
+    // Count the resonant peaks standing out of the spectrum.
+    let count_peaks = |data: &[f32]| {
+        let steps = 1000;
+        let spectrum: Vec<f32> = (0..steps)
+            .map(|n| analysis::magnitude(&data[..8192], 0.25 * n as f32 / steps as f32))
+            .collect();
+        let threshold = spectrum.iter().fold(0.0f32, |a, b| a.max(*b)) * 0.01;
+
+        spectrum
+            .windows(3)
+            .filter(|w| w[1] > w[0] && w[1] > w[2] && w[1] > threshold)
+            .count()
+    };
+
+    let sparse = render(4);
+    let dense = render(24);
+
+    assert!(count_peaks(&dense) > count_peaks(&sparse) + 8);
+}
+
+#[test]
+fn modal_engine_position() {
+    let note = 48.0;
+    let f0 = note_to_frequency(note);
+
+    let render = |position: f32| {
+        let mut engine = modal_engine::ModalEngine::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        engine.init();
+        engine.set_position(position);
+        // No stiffness, the modes are harmonics of the fundamental.
+        engine.set_structure(Some(0.3));
+
+        let blocks = (1.0 * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+        let trigger_block = blocks / 2;
+        let mut already_enveloped = false;
+
+        for n in 0..blocks {
+            let parameters = EngineParameters {
+                trigger: if n == trigger_block {
+                    TriggerState::RisingEdge
+                } else {
+                    TriggerState::Low
+                },
+                note,
+                timbre: 0.5,
+                morph: 0.8,
+                harmonics: 0.0,
+                accent: 1.0,
+            };
+
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            if n >= trigger_block {
+                wav_data.extend_from_slice(&out);
+            }
+        }
+
+        wav_writer::write(
+            format!("engines/modal/modal_position_{position}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        let harmonics: Vec<f32> = (1..=8)
+            .map(|k| analysis::magnitude(&wav_data[..8192], k as f32 * f0))
+            .collect();
+        let odd: f32 = harmonics.iter().step_by(2).sum();
+        let even: f32 = harmonics.iter().skip(1).step_by(2).sum();
+
+        even / odd
+    };
+
+    // Struck in the middle, the modes with a node there are not excited.
+    let edge = render(0.015);
+    let middle = render(0.5);
+    assert!(edge > 0.3);
+    assert!(middle < 0.1 * edge);
+}
+
+#[test]
+fn modal_engine_sympathetic() {
+    // Returns the level of the tail from 2 to 3 seconds after a note.
+    let render = |note: f32, sympathetic: Option<&[f32]>| {
+        let mut engine = modal_engine::ModalEngine::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        engine.init();
+        engine.set_sympathetic(sympathetic);
+        assert_eq!(engine.sympathetic(), sympathetic);
+
+        let blocks = (3.0 * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+        let mut already_enveloped = false;
+
+        for n in 0..blocks {
+            let parameters = EngineParameters {
+                trigger: if n == 0 {
+                    TriggerState::RisingEdge
+                } else {
+                    TriggerState::Low
+                },
+                note,
+                timbre: 0.5,
+                morph: 0.3,
+                harmonics: 0.3,
+                accent: 0.8,
+            };
+
+            // Setting the same notes again keeps the strings ringing.
+            engine.set_sympathetic(sympathetic);
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!(
+                "engines/modal/modal_sympathetic_{note}_{}.wav",
+                sympathetic.is_some()
+            )
+            .as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        analysis::rms(&wav_data[96000..])
+    };
+
+    let scale = [60.0, 62.0, 64.0, 65.0, 67.0, 69.0, 71.0, 72.0];
+
+    let dry = render(60.0, None);
+    let in_scale = render(60.0, Some(&scale));
+    let out_of_scale = render(61.0, Some(&scale));
+
+    assert!(in_scale > dry * 100.0);
+    assert!(in_scale > out_of_scale * 4.0);
+}
@@ -1,8 +1,10 @@
 //! Tests for noise engine
 
 use mi_plaits_dsp::dsp::engine::*;
+use mi_plaits_dsp::dsp::noise::noise_color;
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
 
+use crate::analysis;
 use crate::modulation;
 use crate::wav_writer;
 
@@ -118,3 +120,114 @@ fn noise_engine_morph() {
     wav_writer::write("engines/noise/noise_morph.wav", &wav_data).ok();
     wav_writer::write("engines/noise/noise_morph_aux.wav", &wav_data_aux).ok();
 }
+
+#[test]
+fn noise_engine_noise_color() {
+    let render = |color: noise_color::NoiseColor| {
+        let mut engine = noise_engine::NoiseEngine::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        engine.init();
+        engine.set_seed(Some(1234));
+        engine.set_noise_color(color);
+        assert_eq!(engine.noise_color(), color);
+
+        let duration = 0.25;
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+        let mut already_enveloped = false;
+
+        // Wide open low-pass filter at the full clock rate.
+        let parameters = EngineParameters {
+            trigger: TriggerState::Unpatched,
+            note: 120.0,
+            timbre: 1.0,
+            morph: 0.0,
+            harmonics: 0.0,
+            accent: 1.0,
+        };
+
+        for _ in 0..blocks {
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("engines/noise/noise_color_{color:?}.wav")
+                .to_lowercase()
+                .as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        // Level change in dB across 3 octaves, well below the cutoff.
+        let wav_data = &wav_data[wav_data.len() / 2..];
+        let low = analysis::band_energy(wav_data, 0.005, 0.01, 128);
+        let high = analysis::band_energy(wav_data, 0.04, 0.08, 128);
+        10.0 * f32::log10(high / low)
+    };
+
+    let white = render(noise_color::NoiseColor::White);
+    let pink = render(noise_color::NoiseColor::Pink);
+    let brown = render(noise_color::NoiseColor::Brown);
+
+    // About -9 dB more per 3 octaves for each step from white to brown.
+    assert!(pink < white - 6.0);
+    assert!(brown < pink - 6.0);
+}
+
+#[test]
+fn noise_engine_aux_resonance() {
+    let note = 60.0;
+    let f0 = note_to_frequency(note);
+
+    let render = |resonance: f32| {
+        let mut engine = noise_engine::NoiseEngine::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+        let mut data_aux = Vec::new();
+        let mut already_enveloped = false;
+
+        engine.init();
+        engine.set_aux_resonance(resonance);
+
+        let parameters = EngineParameters {
+            trigger: TriggerState::Unpatched,
+            note,
+            timbre: 1.0,
+            morph: 0.2,
+            harmonics: 0.2,
+            accent: 1.0,
+        };
+
+        for _ in 0..1000 {
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            data.extend_from_slice(&out);
+            data_aux.extend_from_slice(&aux);
+        }
+
+        (data.split_off(12000), data_aux.split_off(12000))
+    };
+
+    let (out, aux) = render(0.0);
+    let (resonant_out, resonant_aux) = render(1.0);
+
+    wav_writer::write("engines/noise/noise_aux_resonance_aux.wav", &resonant_aux).ok();
+
+    // Ratio of the energy at the note to the energy of the bands around it.
+    let peak_ratio = |data: &[f32]| {
+        let peak = analysis::band_energy(data, f0 * 0.98, f0 * 1.02, 5);
+        let around = analysis::band_energy(data, f0 * 0.6, f0 * 0.8, 5)
+            + analysis::band_energy(data, f0 * 1.3, f0 * 1.6, 5);
+        peak / (around * 0.5)
+    };
+
+    assert!(peak_ratio(&resonant_aux) > 30.0);
+    assert!(peak_ratio(&aux) < 10.0);
+
+    // OUT is not affected.
+    assert!(peak_ratio(&resonant_out) < 10.0);
+    assert!((analysis::rms(&resonant_out) / analysis::rms(&out) - 1.0).abs() < 0.1);
+}
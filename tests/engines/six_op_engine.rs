@@ -2,9 +2,12 @@
 
 use mi_plaits_dsp::dsp::engine::*;
 use mi_plaits_dsp::dsp::engine2::*;
-use mi_plaits_dsp::dsp::resources::sysex::SYX_BANK_0;
+use mi_plaits_dsp::dsp::fm::algorithms::Algorithms;
+use mi_plaits_dsp::dsp::fm::patch::{Patch, SYX_SIZE};
+use mi_plaits_dsp::dsp::resources::sysex::{SYX_BANK_0, SYX_BANK_1};
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
 
+use crate::analysis;
 use crate::modulation;
 use crate::wav_writer;
 
@@ -111,3 +114,247 @@ fn six_op_engine_morph() {
     wav_writer::write("engines/six_op/six_op_morph.wav", &wav_data).ok();
     wav_writer::write("engines/six_op/six_op_morph_aux.wav", &wav_data_aux).ok();
 }
+
+#[test]
+fn six_op_engine_feedback() {
+    // Returns the ratio of high frequency content to level.
+    let brightness = |feedback: u8| {
+        let mut engine = six_op_engine::SixOpEngine::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        engine.init();
+        engine.load_syx_bank(&SYX_BANK_0);
+
+        let duration = 1.0;
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+        let mut already_enveloped = false;
+
+        for _ in 0..blocks {
+            let parameters = EngineParameters {
+                trigger: TriggerState::Unpatched,
+                note: 48.0,
+                timbre: 0.5,
+                morph: 0.5,
+                harmonics: 0.383, // Patch 13
+                accent: 1.0,
+            };
+
+            engine.set_feedback(feedback);
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("engines/six_op/six_op_feedback_{feedback}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        let tail = &wav_data[wav_data.len() / 2..];
+        analysis::difference_rms(tail) / analysis::rms(tail)
+    };
+
+    let low = brightness(0);
+    let medium = brightness(4);
+    let high = brightness(7);
+
+    assert!(medium > low * 1.1);
+    assert!(high > medium * 2.0);
+}
+
+#[test]
+fn six_op_engine_independent_banks() {
+    let render = |engine: &mut six_op_engine::SixOpEngine| {
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+        let mut already_enveloped = false;
+
+        for _ in 0..100 {
+            let parameters = EngineParameters {
+                trigger: TriggerState::Unpatched,
+                note: 48.0,
+                timbre: 0.5,
+                morph: 0.5,
+                harmonics: 0.0,
+                accent: 1.0,
+            };
+
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            data.extend_from_slice(&out);
+        }
+
+        data
+    };
+
+    let mut reference = six_op_engine::SixOpEngine::new(&std::alloc::System, BLOCK_SIZE);
+    reference.init();
+    reference.load_syx_bank(&SYX_BANK_0);
+    let reference_data = render(&mut reference);
+
+    let mut engine_1 = six_op_engine::SixOpEngine::new(&std::alloc::System, BLOCK_SIZE);
+    let mut engine_2 = six_op_engine::SixOpEngine::new(&std::alloc::System, BLOCK_SIZE);
+    engine_1.init();
+    engine_2.init();
+    engine_1.load_syx_bank(&SYX_BANK_0);
+    engine_2.load_syx_bank(&SYX_BANK_1);
+
+    let data_1 = render(&mut engine_1);
+    let data_2 = render(&mut engine_2);
+
+    assert_eq!(data_1, reference_data);
+    assert!(analysis::rms(&data_2) > 0.0);
+    assert_ne!(data_1, data_2);
+}
+
+#[test]
+fn six_op_engine_morph_patches() {
+    // Patch 19 (bright) and a copy of it with silenced modulators (dark).
+    let mut bright = Patch::new();
+    bright.unpack(&SYX_BANK_0[19 * SYX_SIZE..]);
+
+    let mut algorithms = Algorithms::<6, 32>::new();
+    algorithms.init();
+
+    let mut bank = [0; 4096];
+    for slot in 0..2 {
+        bank[slot * SYX_SIZE..(slot + 1) * SYX_SIZE]
+            .copy_from_slice(&SYX_BANK_0[19 * SYX_SIZE..20 * SYX_SIZE]);
+    }
+    for op in 0..6 {
+        if algorithms.is_modulator(bright.algorithm as u32, op as u32) {
+            bank[op * 17 + 14] = 0;
+        }
+    }
+
+    let centroid = |t: f32| {
+        let mut engine = six_op_engine::SixOpEngine::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        let mut already_enveloped = false;
+
+        engine.init();
+        engine.load_syx_bank(&bank);
+        engine.morph_patches(0, 1, t);
+
+        for _ in 0..400 {
+            let parameters = EngineParameters {
+                trigger: TriggerState::Unpatched,
+                note: 48.0,
+                timbre: 0.5,
+                morph: 0.5,
+                harmonics: 0.0,
+                accent: 1.0,
+            };
+
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("engines/six_op/six_op_morph_patches_{t}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        analysis::spectral_centroid(&wav_data[wav_data.len() - 4096..], 128)
+    };
+
+    let centroids: Vec<f32> = (0..=4).map(|n| centroid(n as f32 / 4.0)).collect();
+
+    for pair in centroids.windows(2) {
+        assert!(pair[1] > pair[0]);
+    }
+}
+
+#[test]
+fn six_op_engine_builtin_banks() {
+    let mut engine = six_op_engine::SixOpEngine::new(&std::alloc::System, BLOCK_SIZE);
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let mut already_enveloped = false;
+
+    let parameters = EngineParameters {
+        trigger: TriggerState::Unpatched,
+        note: 48.0,
+        timbre: 0.5,
+        morph: 0.5,
+        harmonics: 0.0,
+        accent: 1.0,
+    };
+
+    engine.init();
+    assert_eq!(six_op_engine::SixOpEngine::builtin_bank_count(), 3);
+    assert!(!engine.load_builtin_bank(3));
+
+    let mut names = Vec::new();
+
+    for bank in [0, 1, 0] {
+        assert!(engine.load_builtin_bank(bank));
+        engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+        names.push(engine.active_patch().unwrap().name);
+    }
+
+    let mut expected = Patch::new();
+    expected.unpack(&SYX_BANK_1);
+
+    assert_eq!(names[1], expected.name);
+    assert_ne!(names[0], names[1]);
+    assert_eq!(names[0], names[2]);
+}
+
+#[test]
+fn six_op_engine_scrub() {
+    // All operators of patch 0 rise slowly to full level and hold it.
+    let mut bank = SYX_BANK_0;
+    for op in 0..6 {
+        let op_data = &mut bank[op * 17..];
+        op_data[..8].copy_from_slice(&[40, 99, 99, 99, 99, 99, 99, 0]);
+    }
+
+    let level = |scrub: f32| {
+        let mut engine = six_op_engine::SixOpEngine::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        let mut already_enveloped = false;
+
+        engine.init();
+        engine.load_syx_bank(&bank);
+        engine.set_scrub(scrub);
+
+        for _ in 0..200 {
+            let parameters = EngineParameters {
+                trigger: TriggerState::Unpatched,
+                note: 48.0,
+                timbre: 0.5,
+                morph: 0.0,
+                harmonics: 0.0,
+                accent: 1.0,
+            };
+
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            wav_data.extend_from_slice(&out);
+        }
+
+        assert_eq!(engine.scrub(), scrub);
+
+        wav_writer::write(
+            format!("engines/six_op/six_op_scrub_{scrub}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        analysis::rms(&wav_data[wav_data.len() / 2..])
+    };
+
+    let early = level(0.02);
+    let middle = level(0.2);
+    let late = level(0.9);
+
+    assert!(middle > early * 2.0);
+    assert!(late > middle);
+}
@@ -3,6 +3,7 @@
 use mi_plaits_dsp::dsp::engine::*;
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
 
+use crate::analysis;
 use crate::modulation;
 use crate::wav_writer;
 
@@ -126,3 +127,62 @@ fn snare_drum_engine_morph() {
     wav_writer::write("engines/snare_drum/snare_drum_morph.wav", &wav_data).ok();
     wav_writer::write("engines/snare_drum/snare_drum_morph_aux.wav", &wav_data_aux).ok();
 }
+
+#[test]
+fn snare_drum_engine_model_blend() {
+    let render = |model_blend: f32| {
+        let mut engine = snare_drum_engine::SnareDrumEngine::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        let mut wav_data_aux = Vec::new();
+
+        engine.set_model_blend(model_blend);
+        assert_eq!(engine.model_blend(), model_blend);
+        engine.init();
+
+        let mut already_enveloped = false;
+
+        for n in 0..1000 {
+            let parameters = EngineParameters {
+                trigger: if n % 250 == 0 {
+                    TriggerState::RisingEdge
+                } else {
+                    TriggerState::Low
+                },
+                note: 48.0,
+                timbre: 0.5,
+                morph: 0.5,
+                harmonics: 0.5,
+                accent: 1.0,
+            };
+
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            wav_data.extend_from_slice(&out);
+            wav_data_aux.extend_from_slice(&aux);
+        }
+
+        wav_writer::write(
+            format!("engines/snare_drum/snare_drum_model_blend_{model_blend}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        (wav_data, wav_data_aux)
+    };
+
+    // The analog model on OUT, the synthetic model on AUX.
+    let (analog, synthetic) = render(0.0);
+    let difference: Vec<f32> = analog
+        .iter()
+        .zip(synthetic.iter())
+        .map(|(a, s)| a - s)
+        .collect();
+    assert!(analysis::rms(&difference) > 0.1 * analysis::rms(&analog));
+
+    // The synthetic model on both outputs.
+    let (out, aux) = render(1.0);
+    for (out_sample, aux_sample) in out.iter().zip(aux.iter()) {
+        assert!((out_sample - aux_sample).abs() < 1e-6);
+    }
+}
@@ -3,6 +3,7 @@
 use mi_plaits_dsp::dsp::engine::*;
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
 
+use crate::analysis;
 use crate::modulation;
 use crate::wav_writer;
 
@@ -118,3 +119,135 @@ fn speech_engine_morph() {
     wav_writer::write("engines/speech/speech_morph.wav", &wav_data).ok();
     wav_writer::write("engines/speech/speech_morph_aux.wav", &wav_data_aux).ok();
 }
+
+#[test]
+fn speech_engine_trigger_word() {
+    let mut engine = speech_engine::SpeechEngine::new(&std::alloc::System, BLOCK_SIZE);
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let mut wav_data = Vec::new();
+
+    engine.init();
+
+    let duration = 1.5;
+    let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+    let mut already_enveloped = false;
+
+    let parameters = EngineParameters {
+        trigger: TriggerState::Low,
+        note: 48.0,
+        timbre: 0.5,
+        morph: 0.5,
+        harmonics: 0.7,
+        accent: 1.0,
+    };
+
+    let mut renders = Vec::new();
+
+    for _ in 0..2 {
+        let mut data = Vec::new();
+        engine.trigger_word(1, 3, 1.0);
+
+        for _ in 0..blocks {
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            data.extend_from_slice(&out);
+        }
+
+        wav_data.extend_from_slice(&data);
+        renders.push(data);
+    }
+
+    wav_writer::write("engines/speech/speech_trigger_word.wav", &wav_data).ok();
+
+    let chunk_size = (0.01 * SAMPLE_RATE) as usize;
+    let envelopes: Vec<Vec<f32>> = renders
+        .iter()
+        .map(|data| data.chunks(chunk_size).map(analysis::rms).collect())
+        .collect();
+    let envelope_peak = envelopes[0].iter().cloned().fold(0.0, f32::max);
+    let lengths: Vec<usize> = envelopes
+        .iter()
+        .map(|envelope| {
+            envelope
+                .iter()
+                .rposition(|x| *x > 0.1 * envelope_peak)
+                .unwrap_or(0)
+        })
+        .collect();
+
+    assert!(lengths[0] > 10);
+    assert!(lengths[0] < envelopes[0].len() / 2);
+    assert!(lengths[0].abs_diff(lengths[1]) <= 1);
+
+    for (a, b) in envelopes[0].iter().zip(envelopes[1].iter()) {
+        assert!((a - b).abs() < 0.1 * envelope_peak);
+    }
+}
+
+#[test]
+fn speech_engine_formant_shift() {
+    // Returns the fundamental and the spectral centroid of a sustained vowel.
+    let analyze = |harmonics: f32, formant_shift: f32| {
+        let mut engine = speech_engine::SpeechEngine::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        let mut already_enveloped = false;
+
+        engine.init();
+        engine.set_formant_shift(formant_shift);
+
+        let duration = 0.5;
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+
+        for _ in 0..blocks {
+            let parameters = EngineParameters {
+                trigger: TriggerState::Low,
+                note: 48.0,
+                timbre: 0.5,
+                morph: 0.5,
+                harmonics,
+                accent: 1.0,
+            };
+
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("engines/speech/speech_formant_shift_{harmonics}_{formant_shift}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        let tail = &wav_data[wav_data.len() - 8192..];
+        let fundamental =
+            analysis::dominant_frequency(tail, 100.0 / SAMPLE_RATE, 160.0 / SAMPLE_RATE, 121);
+
+        // Centroid of the harmonics, following the formant envelope.
+        let harmonics: Vec<f32> = (1..=30)
+            .map(|k| analysis::magnitude(tail, k as f32 * fundamental))
+            .collect();
+        let centroid = harmonics
+            .iter()
+            .enumerate()
+            .map(|(k, m)| (k + 1) as f32 * m)
+            .sum::<f32>()
+            / harmonics.iter().sum::<f32>();
+
+        (
+            fundamental * SAMPLE_RATE,
+            centroid * fundamental * SAMPLE_RATE,
+        )
+    };
+
+    // Formant filtering, SAM and LPC vowels.
+    for harmonics in [0.0, 1.0 / 6.0, 1.0 / 3.0] {
+        let (fundamental, formant) = analyze(harmonics, 0.0);
+        let (shifted_fundamental, shifted_formant) = analyze(harmonics, 0.4);
+
+        assert!((shifted_fundamental - fundamental).abs() < 2.0);
+        // The direction of the shift depends on the model, as with TIMBRE.
+        assert!((shifted_formant / formant - 1.0).abs() > 0.15);
+    }
+}
@@ -4,6 +4,7 @@ use mi_plaits_dsp::dsp::engine::*;
 use mi_plaits_dsp::dsp::engine2::*;
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
 
+use crate::analysis;
 use crate::modulation;
 use crate::wav_writer;
 
@@ -139,3 +140,140 @@ fn string_machine_engine_morph() {
     )
     .ok();
 }
+
+#[test]
+fn string_machine_engine_stereo_spread() {
+    let mut mono_engine = string_machine_engine::StringMachineEngine::new();
+    let mut stereo_engine = string_machine_engine::StringMachineEngine::new();
+    let mut wide_engine = string_machine_engine::StringMachineEngine::new();
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let mut left = [0.0; BLOCK_SIZE];
+    let mut right = [0.0; BLOCK_SIZE];
+    let mut mono = Vec::new();
+    let mut narrow_sum = Vec::new();
+    let mut wide_sum = Vec::new();
+    let mut wide_left = Vec::new();
+    let mut wide_right = Vec::new();
+
+    mono_engine.init();
+    stereo_engine.init();
+    wide_engine.init();
+
+    let duration = 1.0;
+    let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+    let mut already_enveloped = false;
+
+    for n in 0..blocks {
+        let parameters = EngineParameters {
+            trigger: if n == 0 {
+                TriggerState::RisingEdge
+            } else {
+                TriggerState::Low
+            },
+            note: 48.0,
+            timbre: 0.5,
+            morph: 0.3,
+            harmonics: 0.5,
+            accent: 1.0,
+        };
+
+        mono_engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+        stereo_engine.render_stereo(&parameters, &mut left, &mut right, 0.0);
+        for i in 0..BLOCK_SIZE {
+            mono.push(out[i] + aux[i]);
+            narrow_sum.push(left[i] + right[i]);
+        }
+
+        wide_engine.render_stereo(&parameters, &mut left, &mut right, 1.0);
+        for i in 0..BLOCK_SIZE {
+            wide_sum.push(left[i] + right[i]);
+        }
+        wide_left.extend_from_slice(&left);
+        wide_right.extend_from_slice(&right);
+    }
+
+    wav_writer::write(
+        "engines/string_machine/string_machine_stereo_spread_left.wav",
+        &wide_left,
+    )
+    .ok();
+    wav_writer::write(
+        "engines/string_machine/string_machine_stereo_spread_right.wav",
+        &wide_right,
+    )
+    .ok();
+
+    // With the ensemble dry (TIMBRE at 0.5), the mixdown keeps the sum of both channels.
+    for ((a, b), c) in mono.iter().zip(narrow_sum.iter()).zip(wide_sum.iter()) {
+        assert!((a - b).abs() < 1e-4);
+        assert!((a - c).abs() < 1e-4);
+    }
+
+    let mono_rms = analysis::rms(&mono);
+    assert!(mono_rms > 0.01);
+    assert!(analysis::correlation(&wide_left, &wide_right) < 0.5);
+    assert!(analysis::peak(&wide_left).max(analysis::peak(&wide_right)) < analysis::peak(&mono));
+}
+
+#[test]
+fn string_machine_engine_chorus_depth() {
+    // Measures the deviation caused by the delay modulation against an unmodulated render.
+    let deviation = |depth: f32| {
+        let mut reference = string_machine_engine::StringMachineEngine::new();
+        let mut engine = string_machine_engine::StringMachineEngine::new();
+        let mut reference_out = [0.0; BLOCK_SIZE];
+        let mut reference_aux = [0.0; BLOCK_SIZE];
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut difference = Vec::new();
+        let mut wav_data = Vec::new();
+
+        reference.init();
+        reference.set_chorus_depth(0.0);
+        engine.init();
+        engine.set_chorus_depth(depth);
+
+        let duration = 2.0;
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+        let mut already_enveloped = false;
+
+        for _ in 0..blocks {
+            let parameters = EngineParameters {
+                trigger: TriggerState::Low,
+                note: 48.0,
+                timbre: 1.0,
+                morph: 0.0,
+                harmonics: 0.0,
+                accent: 1.0,
+            };
+
+            reference.render(
+                &parameters,
+                &mut reference_out,
+                &mut reference_aux,
+                &mut already_enveloped,
+            );
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+
+            for (a, b) in out.iter().zip(reference_out.iter()) {
+                difference.push(a - b);
+            }
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("engines/string_machine/string_machine_chorus_depth_{depth}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        analysis::rms(&difference)
+    };
+
+    let shallow = deviation(0.05);
+    let deep = deviation(0.5);
+
+    assert!(shallow > 0.0);
+    assert!(deep > shallow * 2.0);
+}
@@ -3,6 +3,7 @@
 use mi_plaits_dsp::dsp::engine::*;
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
 
+use crate::analysis;
 use crate::modulation;
 use crate::wav_writer;
 
@@ -141,3 +142,111 @@ fn virtual_analog_engine_morph() {
     )
     .ok();
 }
+
+#[test]
+fn virtual_analog_engine_sync() {
+    let render = |sync: Option<f32>| {
+        let mut engine =
+            virtual_analog_engine::VirtualAnalogEngine::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        engine.init();
+        engine.set_sync(sync);
+
+        let duration = 0.5;
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+        let mut already_enveloped = false;
+
+        // Narrow unsynced square with the saw mostly faded out.
+        let parameters = EngineParameters {
+            trigger: TriggerState::Low,
+            note: 48.0,
+            timbre: 0.4,
+            morph: 1.0,
+            harmonics: 0.5,
+            accent: 1.0,
+        };
+
+        for _ in 0..blocks {
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            wav_data.extend_from_slice(&out);
+        }
+
+        let file_name = if sync.is_some() { "sync" } else { "free" };
+        wav_writer::write(
+            format!("engines/virtual_analog/virtual_analog_{file_name}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        wav_data[wav_data.len() / 2..].to_vec()
+    };
+
+    let f0 = note_to_frequency(48.0);
+
+    let free = render(None);
+    let synced = render(Some(2.0 * f0));
+
+    // Free running, the square has a strong fundamental.
+    assert!(analysis::magnitude(&free, f0) > 0.1);
+
+    // Synced, the spectrum moves to multiples of the master frequency.
+    let fundamental = analysis::magnitude(&synced, f0);
+    let master = analysis::magnitude(&synced, 2.0 * f0);
+    let master_harmonic = analysis::magnitude(&synced, 4.0 * f0);
+    assert!(master > 10.0 * fundamental);
+    assert!(master_harmonic > 10.0 * fundamental);
+}
+
+#[test]
+fn virtual_analog_engine_sub_octave() {
+    let mut engine =
+        virtual_analog_engine::VirtualAnalogEngine::new(&std::alloc::System, BLOCK_SIZE);
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let mut wav_data = Vec::new();
+    let mut wav_data_aux = Vec::new();
+
+    engine.init();
+    engine.set_aux_mode(AuxMode::SubOctave);
+    assert_eq!(engine.aux_mode(), AuxMode::SubOctave);
+
+    let duration = 0.5;
+    let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+    let mut already_enveloped = false;
+
+    let parameters = EngineParameters {
+        trigger: TriggerState::Low,
+        note: 48.0,
+        timbre: 0.5,
+        morph: 0.5,
+        harmonics: 0.5,
+        accent: 1.0,
+    };
+
+    for _ in 0..blocks {
+        engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+        wav_data.extend_from_slice(&out);
+        wav_data_aux.extend_from_slice(&aux);
+    }
+
+    wav_writer::write(
+        "engines/virtual_analog/virtual_analog_sub_octave.wav",
+        &wav_data,
+    )
+    .ok();
+    wav_writer::write(
+        "engines/virtual_analog/virtual_analog_sub_octave_aux.wav",
+        &wav_data_aux,
+    )
+    .ok();
+
+    let f0 = note_to_frequency(48.0);
+    let out_f = analysis::dominant_frequency(&wav_data, 0.75 * f0, 1.25 * f0, 201);
+    let aux_f = analysis::zero_crossing_frequency(&wav_data_aux);
+
+    assert!((out_f / f0 - 1.0).abs() < 0.005);
+    assert!((aux_f / out_f - 0.5).abs() < 0.005);
+}
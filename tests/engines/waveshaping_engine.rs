@@ -3,6 +3,7 @@
 use mi_plaits_dsp::dsp::engine::*;
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
 
+use crate::analysis;
 use crate::modulation;
 use crate::wav_writer;
 
@@ -130,3 +131,52 @@ fn waveshaping_engine_morph() {
     )
     .ok();
 }
+
+#[test]
+fn waveshaping_engine_sub_octave() {
+    let render = |aux_mode: AuxMode| {
+        let mut engine = waveshaping_engine::WaveshapingEngine::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        let mut wav_data_aux = Vec::new();
+
+        engine.init();
+        engine.set_aux_mode(aux_mode);
+
+        let duration = 0.5;
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+        let mut already_enveloped = false;
+
+        let parameters = EngineParameters {
+            trigger: TriggerState::Low,
+            note: 48.0,
+            timbre: 0.5,
+            morph: 0.5,
+            harmonics: 0.5,
+            accent: 1.0,
+        };
+
+        for _ in 0..blocks {
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            wav_data.extend_from_slice(&out);
+            wav_data_aux.extend_from_slice(&aux);
+        }
+
+        (wav_data, wav_data_aux)
+    };
+
+    let f0 = note_to_frequency(48.0);
+
+    let (out, aux) = render(AuxMode::SubOctave);
+    wav_writer::write("engines/waveshaping/waveshaping_sub_octave_aux.wav", &aux).ok();
+
+    let out_f = analysis::dominant_frequency(&out, 0.75 * f0, 1.25 * f0, 201);
+    let aux_f = analysis::zero_crossing_frequency(&aux);
+    assert!((out_f / f0 - 1.0).abs() < 0.005);
+    assert!((aux_f / out_f - 0.5).abs() < 0.005);
+
+    let (_, aux) = render(AuxMode::SubTwoOctaves);
+    let aux_f = analysis::zero_crossing_frequency(&aux);
+    assert!((aux_f / out_f - 0.25).abs() < 0.005);
+}
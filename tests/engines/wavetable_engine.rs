@@ -3,6 +3,7 @@
 use mi_plaits_dsp::dsp::engine::*;
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
 
+use crate::analysis;
 use crate::modulation;
 use crate::wav_writer;
 
@@ -122,3 +123,111 @@ fn wavetable_engine_morph() {
     wav_writer::write("engines/wavetable/wavetable_morph.wav", &wav_data).ok();
     wav_writer::write("engines/wavetable/wavetable_morph_aux.wav", &wav_data_aux).ok();
 }
+
+#[test]
+fn wavetable_engine_aux_bit_depth() {
+    let render = |bits: Option<u8>| {
+        let mut engine = wavetable_engine::WavetableEngine::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+        let mut data_aux = Vec::new();
+        let mut already_enveloped = false;
+
+        engine.init();
+        if let Some(bits) = bits {
+            engine.set_aux_bit_depth(bits);
+        }
+
+        let parameters = EngineParameters {
+            trigger: TriggerState::Unpatched,
+            note: 48.0,
+            timbre: 0.5,
+            morph: 0.5,
+            harmonics: 0.3,
+            accent: 1.0,
+        };
+
+        for _ in 0..200 {
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            data.extend_from_slice(&out);
+            data_aux.extend_from_slice(&aux);
+        }
+
+        (data, data_aux)
+    };
+
+    let (out, default_aux) = render(None);
+    let (_, explicit_aux) = render(Some(5));
+    let (_, coarse_aux) = render(Some(3));
+    wav_writer::write("engines/wavetable/wavetable_aux_3_bit.wav", &coarse_aux).ok();
+
+    // The default matches an explicit depth of 5 bits.
+    assert_eq!(default_aux, explicit_aux);
+
+    let levels = |data: &[f32]| {
+        let mut levels: Vec<u32> = data.iter().map(|x| x.to_bits()).collect();
+        levels.sort_unstable();
+        levels.dedup();
+        levels.len()
+    };
+    assert!(levels(&coarse_aux) < levels(&default_aux));
+
+    // The quantization error is larger with fewer bits.
+    let error = |aux: &[f32]| {
+        let error: Vec<f32> = out.iter().zip(aux.iter()).map(|(a, b)| a - b).collect();
+        analysis::rms(&error)
+    };
+    assert!(error(&coarse_aux) > error(&default_aux) * 2.0);
+}
+
+#[test]
+fn wavetable_engine_harmonics_midpoint() {
+    let mut engine = wavetable_engine::WavetableEngine::new();
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let mut wav_data = Vec::new();
+    let mut already_enveloped = false;
+
+    engine.init();
+
+    // Windows of 8 blocks span exactly one period of 250 Hz.
+    let note = 69.0 + 12.0 * f32::log2(250.0 / 440.0);
+    let windows = 1000;
+    let mut previous_rms = None;
+    let mut max_delta: f32 = 0.0;
+
+    for n in 0..windows {
+        let parameters = EngineParameters {
+            trigger: TriggerState::Unpatched,
+            note,
+            timbre: 0.5,
+            morph: 0.5,
+            harmonics: 0.4 + 0.2 * n as f32 / windows as f32,
+            accent: 1.0,
+        };
+
+        let mut window = Vec::new();
+        for _ in 0..8 {
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            window.extend_from_slice(&out);
+        }
+        wav_data.extend_from_slice(&window);
+
+        let rms = analysis::rms(&window);
+        if let Some(previous_rms) = previous_rms {
+            if n > 50 {
+                max_delta = max_delta.max(f32::abs(rms - previous_rms));
+            }
+        }
+        previous_rms = Some(rms);
+    }
+
+    wav_writer::write(
+        "engines/wavetable/wavetable_harmonics_midpoint.wav",
+        &wav_data,
+    )
+    .ok();
+
+    assert!(max_delta < 0.002);
+}
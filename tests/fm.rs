@@ -0,0 +1,342 @@
+//! Tests for the FM voice
+
+mod analysis;
+mod wav_writer;
+
+use core::cell::RefCell;
+
+use mi_plaits_dsp::dsp::fm::algorithms::Algorithms;
+use mi_plaits_dsp::dsp::fm::patch::{Patch, SYX_SIZE};
+use mi_plaits_dsp::dsp::fm::voice::{Voice, VoiceParameters};
+use mi_plaits_dsp::dsp::resources::sysex::SYX_BANK_0;
+use mi_plaits_dsp::dsp::SAMPLE_RATE;
+
+const BLOCK_SIZE: usize = 24;
+
+#[test]
+fn render_operators_separately() {
+    let mut algorithms = Algorithms::<6, 32>::new();
+    algorithms.init();
+
+    let mut patch = Patch::new();
+    patch.unpack(&SYX_BANK_0[3 * SYX_SIZE..]);
+    patch.algorithm = 31; // Algorithm 32, all operators are carriers.
+
+    let mut voice = Voice::new();
+    let mut reference_voice = Voice::new();
+    voice.init(&algorithms, SAMPLE_RATE);
+    voice.set_patch(Some(&patch));
+    reference_voice.init(&algorithms, SAMPLE_RATE);
+    reference_voice.set_patch(Some(&patch));
+
+    let mut parameters = VoiceParameters::new();
+    parameters.gate = true;
+    parameters.note = 48.0;
+    parameters.velocity = 0.8;
+    parameters.brightness = 0.5;
+    parameters.envelope_control = 0.5;
+
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut temp = [[0.0; BLOCK_SIZE]; 3];
+    let mut operator_out = [[0.0; BLOCK_SIZE]; 6];
+    let mut wav_data = Vec::new();
+    let mut operator_wav_data = vec![Vec::new(); 6];
+
+    let duration = 0.5;
+    let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+
+    for _ in 0..blocks {
+        let mut reference_out = [0.0; BLOCK_SIZE];
+        {
+            let [temp_1, temp_2, temp_3] = &mut temp;
+            let buffers = [
+                RefCell::new(&mut reference_out[..]),
+                RefCell::new(&mut temp_1[..]),
+                RefCell::new(&mut temp_2[..]),
+                RefCell::new(&mut temp_3[..]),
+            ];
+            reference_voice.render(&parameters, &buffers);
+        }
+
+        out.fill(0.0);
+        let mut operators = operator_out.each_mut().map(|buffer| &mut buffer[..]);
+        voice.render_operators_separately(&parameters, &mut out, &mut operators);
+
+        for (i, operator) in operator_out.iter().enumerate() {
+            operator_wav_data[i].extend_from_slice(operator);
+        }
+
+        for n in 0..BLOCK_SIZE {
+            let sum: f32 = operator_out.iter().map(|operator| operator[n]).sum();
+            assert!((sum - out[n]).abs() < 1e-5);
+            assert!((out[n] - reference_out[n]).abs() < 1e-5);
+        }
+
+        wav_data.extend_from_slice(&out);
+    }
+
+    wav_writer::write("fm/operators_mix.wav", &wav_data).ok();
+
+    for (i, data) in operator_wav_data.iter().enumerate() {
+        wav_writer::write(format!("fm/operator_{}.wav", 6 - i).as_str(), data).ok();
+        assert!(analysis::rms(data) > 1e-4);
+    }
+}
+
+#[test]
+fn algorithm_routing() {
+    let algorithms = Algorithms::<6, 32>::new();
+
+    // Operator n has the index 6 - n.
+    let op = |n: usize| 6 - n;
+
+    // Algorithm 1: 6 -> 5 -> 4 -> 3 and 2 -> 1, with feedback on 6.
+    let graph = algorithms.routing(0);
+
+    let carriers: Vec<usize> = (1..=6).filter(|n| graph.carriers[op(*n)]).collect();
+    assert_eq!(carriers, [1, 3]);
+
+    for (modulator, carrier) in [(6, 5), (5, 4), (4, 3), (2, 1)] {
+        assert_eq!(graph.modulators[op(carrier)], 1 << op(modulator));
+    }
+    assert!(graph.is_modulated_by(op(6), op(6)));
+    assert_eq!(graph.modulators[op(2)], 0);
+    assert_eq!(graph.feedback_source, Some(op(6)));
+    assert_eq!(graph.feedback_destination, Some(op(6)));
+
+    // Algorithm 4: feedback from 4 to 6.
+    let graph = algorithms.routing(3);
+    assert!(graph.is_modulated_by(op(6), op(4)));
+    assert_eq!(graph.feedback_source, Some(op(4)));
+    assert_eq!(graph.feedback_destination, Some(op(6)));
+
+    // Algorithm 32: all carriers, not modulated apart from the feedback.
+    let graph = algorithms.routing(31);
+    assert!(graph.carriers.iter().all(|carrier| *carrier));
+    assert_eq!(graph.modulators[1..], [0; 5]);
+}
+
+#[test]
+fn disable_pitch_envelope() {
+    let mut algorithms = Algorithms::<6, 32>::new();
+    algorithms.init();
+
+    // A single sine carrier with a steep pitch envelope.
+    let mut patch = Patch::new();
+    patch.algorithm = 31;
+    for op in patch.op.iter_mut() {
+        op.envelope.rate = [99, 99, 99, 99];
+        op.envelope.level = [99, 99, 99, 0];
+        op.coarse = 1;
+        op.detune = 7;
+    }
+    patch.op[5].level = 99;
+    patch.pitch_envelope.rate = [40, 40, 99, 99];
+    patch.pitch_envelope.level = [99, 0, 50, 50];
+
+    // Returns the pitch at the start and at the end of the rendered note.
+    let render = |disable_pitch_envelope: bool| {
+        let mut voice = Voice::new();
+        voice.init(&algorithms, SAMPLE_RATE);
+        voice.set_patch(Some(&patch));
+
+        let mut parameters = VoiceParameters::new();
+        parameters.gate = true;
+        parameters.note = 48.0;
+        parameters.velocity = 0.8;
+        parameters.envelope_control = 0.5;
+        parameters.disable_pitch_envelope = disable_pitch_envelope;
+
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut temp = [[0.0; BLOCK_SIZE]; 3];
+        let mut wav_data = Vec::new();
+
+        let duration = 1.0;
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+
+        for _ in 0..blocks {
+            out.fill(0.0);
+            let [temp_1, temp_2, temp_3] = &mut temp;
+            let buffers = [
+                RefCell::new(&mut out[..]),
+                RefCell::new(&mut temp_1[..]),
+                RefCell::new(&mut temp_2[..]),
+                RefCell::new(&mut temp_3[..]),
+            ];
+            voice.render(&parameters, &buffers);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("fm/disable_pitch_envelope_{disable_pitch_envelope}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        let window = (0.1 * SAMPLE_RATE) as usize;
+        (
+            analysis::zero_crossing_frequency(&wav_data[window..window * 2]),
+            analysis::zero_crossing_frequency(&wav_data[wav_data.len() - window..]),
+        )
+    };
+
+    let (start, end) = render(false);
+    assert!((start / end - 1.0).abs() > 0.1);
+
+    let (start, end) = render(true);
+    assert!((start / end - 1.0).abs() < 0.01);
+    assert!((end * SAMPLE_RATE - 130.8).abs() < 2.0);
+}
+
+#[test]
+fn operator_detune() {
+    let mut algorithms = Algorithms::<6, 32>::new();
+    algorithms.init();
+
+    // Two sine carriers at the same frequency.
+    let mut patch = Patch::new();
+    patch.algorithm = 31;
+    for op in patch.op.iter_mut() {
+        op.envelope.rate = [99, 99, 99, 99];
+        op.envelope.level = [99, 99, 99, 0];
+        op.coarse = 1;
+        op.detune = 7;
+    }
+    patch.op[4].level = 99;
+    patch.op[5].level = 99;
+    patch.pitch_envelope.rate = [99, 99, 99, 99];
+    patch.pitch_envelope.level = [50, 50, 50, 50];
+
+    let render = |detune: Option<[f32; 6]>| {
+        let mut voice = Voice::new();
+        voice.init(&algorithms, SAMPLE_RATE);
+        voice.set_patch(Some(&patch));
+        if let Some(detune) = detune {
+            voice.set_operator_detune(detune);
+        }
+
+        let mut parameters = VoiceParameters::new();
+        parameters.gate = true;
+        parameters.note = 48.0;
+        parameters.velocity = 0.8;
+        parameters.envelope_control = 0.5;
+
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut temp = [[0.0; BLOCK_SIZE]; 3];
+        let mut wav_data = Vec::new();
+
+        let duration = 8.0;
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+
+        for _ in 0..blocks {
+            out.fill(0.0);
+            let [temp_1, temp_2, temp_3] = &mut temp;
+            let buffers = [
+                RefCell::new(&mut out[..]),
+                RefCell::new(&mut temp_1[..]),
+                RefCell::new(&mut temp_2[..]),
+                RefCell::new(&mut temp_3[..]),
+            ];
+            voice.render(&parameters, &buffers);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_data
+    };
+
+    let reference = render(None);
+    assert_eq!(reference, render(Some([0.0; 6])));
+
+    let detuned = render(Some([0.0, 0.0, 0.0, 0.0, 7.0, 0.0]));
+    wav_writer::write("fm/operator_detune.wav", &detuned).ok();
+
+    // The product of two sines has a component at their difference frequency.
+    let squared: Vec<f32> = detuned.iter().map(|x| x * x).collect();
+    let mean = analysis::mean(&squared);
+    let squared: Vec<f32> = squared.iter().map(|x| x - mean).collect();
+    let low = 0.25 / SAMPLE_RATE;
+    let high = 2.0 / SAMPLE_RATE;
+    let beating = analysis::dominant_frequency(&squared, low, high, 96) * SAMPLE_RATE;
+    let expected = 130.81 * (f32::powf(2.0, 7.0 / 1200.0) - 1.0);
+    assert!((beating / expected - 1.0).abs() < 0.1);
+}
+
+#[test]
+fn operator_mute() {
+    let mut algorithms = Algorithms::<6, 32>::new();
+    algorithms.init();
+
+    // Six sine carriers at the first six harmonics.
+    let mut patch = Patch::new();
+    patch.algorithm = 31;
+    for (n, op) in patch.op.iter_mut().enumerate() {
+        op.envelope.rate = [99, 99, 99, 99];
+        op.envelope.level = [99, 99, 99, 0];
+        op.coarse = 6 - n as u8;
+        op.detune = 7;
+        op.level = 99;
+    }
+    patch.feedback = 0;
+    patch.pitch_envelope.rate = [99, 99, 99, 99];
+    patch.pitch_envelope.level = [50, 50, 50, 50];
+
+    let render = |patch: &Patch, mute: Option<[bool; 6]>| {
+        let mut voice = Voice::new();
+        voice.init(&algorithms, SAMPLE_RATE);
+        voice.set_patch(Some(patch));
+        if let Some(mute) = mute {
+            voice.set_operator_mute(mute);
+            assert_eq!(voice.operator_mute(), mute);
+        }
+
+        let mut parameters = VoiceParameters::new();
+        parameters.gate = true;
+        parameters.note = 48.0;
+        parameters.velocity = 0.8;
+        parameters.envelope_control = 0.5;
+
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut temp = [[0.0; BLOCK_SIZE]; 3];
+        let mut wav_data = Vec::new();
+
+        for _ in 0..2000 {
+            out.fill(0.0);
+            let [temp_1, temp_2, temp_3] = &mut temp;
+            let buffers = [
+                RefCell::new(&mut out[..]),
+                RefCell::new(&mut temp_1[..]),
+                RefCell::new(&mut temp_2[..]),
+                RefCell::new(&mut temp_3[..]),
+            ];
+            voice.render(&parameters, &buffers);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_data
+    };
+
+    // Solo operator 3, the third harmonic.
+    let soloed = render(&patch, Some([true, true, true, false, true, true]));
+    wav_writer::write("fm/operator_mute.wav", &soloed).ok();
+
+    let mut single_patch = patch.clone();
+    for (n, op) in single_patch.op.iter_mut().enumerate() {
+        if n != 3 {
+            op.level = 0;
+        }
+    }
+    let single = render(&single_patch, None);
+
+    let tail = &soloed[soloed.len() - 4800..];
+    let frequency = analysis::zero_crossing_frequency(tail) * SAMPLE_RATE;
+    assert!((frequency / (3.0 * 130.81) - 1.0).abs() < 0.01);
+
+    for (soloed, single) in soloed.iter().zip(single.iter()) {
+        assert!((soloed - single).abs() < 1e-3);
+    }
+
+    // The patch itself is unchanged.
+    let all = render(&patch, Some([false; 6]));
+    assert_eq!(all, render(&patch, None));
+    assert!(analysis::magnitude(&all[all.len() - 4800..], 130.81 / SAMPLE_RATE) > 0.01);
+}
@@ -1,12 +1,17 @@
 //! Tests for the effects
 
+mod analysis;
 mod modulation;
 mod wav_writer;
 
+use std::f32::consts::PI;
+
 use mi_plaits_dsp::dsp::oscillator::sine_oscillator::SineOscillator;
 
 use mi_plaits_dsp::dsp::fx::*;
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
+use mi_plaits_dsp::stmlib::dsp::limiter::Limiter;
+use mi_plaits_dsp::stmlib::utils::random;
 
 const BLOCK_SIZE: usize = 24;
 
@@ -65,6 +70,129 @@ fn ensemble() {
     wav_writer::write("fx/ensemble_right.wav", &wav_data_right).ok();
 }
 
+#[test]
+fn ensemble_voice_count() {
+    let frequency = 220.0 / SAMPLE_RATE;
+
+    let render = |voice_count: usize, stereo: bool| {
+        let mut osc = SineOscillator::new();
+        let mut fx = ensemble::Ensemble::new();
+        let mut left = [0.0; BLOCK_SIZE];
+        let mut wav_data_left = Vec::new();
+        let mut wav_data_right = Vec::new();
+        osc.init();
+        fx.init();
+        fx.set_amount(1.0);
+        fx.set_depth(1.0);
+        fx.set_voice_count(voice_count);
+        fx.set_stereo(stereo);
+
+        for _ in 0..4000 {
+            osc.render(frequency, &mut left);
+            let mut right = left;
+            fx.process(&mut left, &mut right);
+            wav_data_left.extend_from_slice(&left);
+            wav_data_right.extend_from_slice(&right);
+        }
+
+        (wav_data_left, wav_data_right)
+    };
+
+    // Spectral flatness of the sidebands within 30 Hz around the tone.
+    let smearing = |data: &[f32]| {
+        let energies: Vec<f32> = (0..300)
+            .map(|n| -30.0 + n as f32 * 0.2)
+            .filter(|offset: &f32| offset.abs() >= 1.0)
+            .map(|offset| {
+                analysis::magnitude(data, frequency + offset / SAMPLE_RATE).powi(2) + 1e-12
+            })
+            .collect();
+        let geometric_mean =
+            (energies.iter().map(|x| x.ln()).sum::<f32>() / energies.len() as f32).exp();
+
+        geometric_mean / analysis::mean(&energies)
+    };
+
+    let (left, _) = render(ensemble::DEFAULT_VOICES, true);
+    let (dense_left, dense_right) = render(ensemble::MAX_VOICES, true);
+    wav_writer::write("fx/ensemble_voice_count_left.wav", &dense_left).ok();
+    wav_writer::write("fx/ensemble_voice_count_right.wav", &dense_right).ok();
+
+    assert!(smearing(&dense_left[12000..77536]) > smearing(&left[12000..77536]) * 2.0);
+
+    let (mono_left, mono_right) = render(ensemble::MAX_VOICES, false);
+    assert_eq!(mono_left, mono_right);
+    assert_ne!(dense_left, dense_right);
+}
+
+#[test]
+fn mix() {
+    let frequency = 220.0 / SAMPLE_RATE;
+
+    // Returns the input and the output of the three effects with a given mix.
+    let render = |mix: f32| {
+        let mut osc = SineOscillator::new();
+        let mut diffuser = diffuser::Diffuser::new();
+        let mut ensemble = ensemble::Ensemble::new();
+        let mut overdrive = overdrive::Overdrive::new();
+        let mut in_ = [0.0; BLOCK_SIZE];
+        let mut wav_data_in = Vec::new();
+        let mut wav_data_out = [Vec::new(), Vec::new(), Vec::new()];
+        osc.init();
+        diffuser.init();
+        ensemble.init();
+        overdrive.init();
+        ensemble.set_amount(1.0);
+        ensemble.set_depth(1.0);
+        diffuser.set_mix(mix);
+        ensemble.set_mix(mix);
+        overdrive.set_mix(mix);
+        assert_eq!(overdrive.mix(), mix);
+
+        for _ in 0..200 {
+            osc.render(frequency, &mut in_);
+            wav_data_in.extend_from_slice(&in_);
+
+            let mut in_out = in_;
+            diffuser.process(0.8, 0.7, &mut in_out);
+            wav_data_out[0].extend_from_slice(&in_out);
+
+            let mut left = in_;
+            let mut right = in_;
+            ensemble.process(&mut left, &mut right);
+            wav_data_out[1].extend_from_slice(&left);
+
+            let mut in_out = in_;
+            overdrive.process(0.8, &mut in_out);
+            wav_data_out[2].extend_from_slice(&in_out);
+        }
+
+        (wav_data_in, wav_data_out)
+    };
+
+    let (in_, dry) = render(0.0);
+    let (_, half) = render(0.5);
+    let (_, wet) = render(1.0);
+
+    // The processed signal is the default.
+    assert_eq!(diffuser::Diffuser::new().mix(), 1.0);
+    assert_eq!(ensemble::Ensemble::new().mix(), 1.0);
+    assert_eq!(overdrive::Overdrive::new().mix(), 1.0);
+
+    for n in 0..3 {
+        assert_eq!(dry[n], in_);
+        assert_ne!(wet[n], in_);
+
+        for ((half, wet), in_) in half[n].iter().zip(wet[n].iter()).zip(in_.iter()) {
+            assert!((half - (in_ + wet) * 0.5).abs() < 1e-6);
+        }
+    }
+
+    wav_writer::write("fx/mix_diffuser.wav", &half[0]).ok();
+    wav_writer::write("fx/mix_ensemble.wav", &half[1]).ok();
+    wav_writer::write("fx/mix_overdrive.wav", &half[2]).ok();
+}
+
 #[test]
 fn sample_rate_reducer() {
     let frequency = 110.0;
@@ -114,3 +242,305 @@ fn overdrive() {
 
     wav_writer::write("fx/overdrive.wav", &wav_data).ok();
 }
+
+#[test]
+fn overdrive_antialias() {
+    // Returns the level of the aliased partials relative to the harmonic ones.
+    let aliasing = |antialias: bool| {
+        // The odd harmonics above Nyquist fold back to multiples of 5 kHz plus 500 Hz.
+        let frequency = 2500.0;
+
+        let mut osc = SineOscillator::new();
+        let mut fx = overdrive::Overdrive::new();
+        let mut in_out = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        osc.init();
+        fx.init();
+        fx.set_antialias(antialias);
+
+        for _ in 0..400 {
+            osc.render(frequency / SAMPLE_RATE, &mut in_out);
+            fx.process(1.0, &mut in_out);
+            wav_data.extend_from_slice(&in_out);
+        }
+
+        wav_writer::write(
+            format!("fx/overdrive_antialias_{antialias}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        let tail = &wav_data[wav_data.len() - 4800..];
+        let energy = |frequencies: &[f32]| {
+            frequencies
+                .iter()
+                .map(|f| analysis::magnitude(tail, f / SAMPLE_RATE).powi(2))
+                .sum::<f32>()
+        };
+
+        energy(&[500.0, 5500.0, 10500.0, 15500.0, 20500.0])
+            / energy(&[2500.0, 7500.0, 12500.0, 17500.0])
+    };
+
+    let aliased = aliasing(false);
+    let antialiased = aliasing(true);
+
+    assert!(antialiased < aliased * 0.25);
+}
+
+#[test]
+fn overdrive_antialias_mix() {
+    // Returns the output of the oversampled overdrive with a given mix.
+    let render = |mix: f32| {
+        let mut osc = SineOscillator::new();
+        let mut fx = overdrive::Overdrive::new();
+        let mut in_out = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        osc.init();
+        fx.init();
+        fx.set_antialias(true);
+        fx.set_mix(mix);
+
+        for _ in 0..200 {
+            osc.render(3000.0 / SAMPLE_RATE, &mut in_out);
+            fx.process(0.2, &mut in_out);
+            wav_data.extend_from_slice(&in_out);
+        }
+
+        wav_data
+    };
+
+    let dry = render(0.0);
+    let wet = render(1.0);
+
+    // At a low drive, the processed signal is in phase with the delayed dry signal.
+    let tail = dry.len() - 2400;
+    let correlation = analysis::correlation(&dry[tail..], &wet[tail..]);
+    assert!(correlation > 0.99);
+}
+
+#[test]
+fn low_pass_gate_response() {
+    // Returns the level of a high partial relative to a low one, with the gate open and closed.
+    let tilt = |response: low_pass_gate::LpgResponse| {
+        let mut lpg = low_pass_gate::LowPassGate::new();
+        let mut in_out = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        let mut open = Vec::new();
+        let mut closed = Vec::new();
+        lpg.init();
+        lpg.set_response(response);
+
+        let low = 200.0 / SAMPLE_RATE;
+        let high = 4000.0 / SAMPLE_RATE;
+        let blocks = (SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+
+        for n in 0..blocks * 2 {
+            for (i, sample) in in_out.iter_mut().enumerate() {
+                let t = (n * BLOCK_SIZE + i) as f32;
+                *sample = f32::sin(2.0 * PI * low * t) + f32::sin(2.0 * PI * high * t);
+            }
+
+            if n < blocks {
+                lpg.process_replacing(1.0, 0.4, 0.2, &mut in_out);
+            } else {
+                lpg.process_replacing(0.25, 0.01, 0.2, &mut in_out);
+            }
+
+            wav_data.extend_from_slice(&in_out);
+            if n > blocks / 2 && n < blocks {
+                open.extend_from_slice(&in_out);
+            } else if n > blocks * 3 / 2 {
+                closed.extend_from_slice(&in_out);
+            }
+        }
+
+        wav_writer::write(
+            format!("fx/low_pass_gate_{response:?}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        (
+            analysis::magnitude(&open, high) / analysis::magnitude(&open, low),
+            analysis::magnitude(&closed, high) / analysis::magnitude(&closed, low),
+        )
+    };
+
+    let (open, closed) = tilt(low_pass_gate::LpgResponse::Vca);
+    assert!((closed / open - 1.0).abs() < 0.01);
+
+    let (open, closed) = tilt(low_pass_gate::LpgResponse::Blended);
+    assert!(closed < open * 0.5);
+}
+
+#[test]
+fn widener() {
+    let duration = 0.5;
+
+    let render = |width: f32| {
+        let mut fx = widener::Widener::new();
+        let mut in_ = [0.0; BLOCK_SIZE];
+        let mut left = [0.0; BLOCK_SIZE];
+        let mut right = [0.0; BLOCK_SIZE];
+        let mut wav_data_in = Vec::new();
+        let mut wav_data_left = Vec::new();
+        let mut wav_data_right = Vec::new();
+        fx.set_width(width);
+        fx.set_delay_samples(480);
+        fx.init();
+
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+
+        for _ in 0..blocks {
+            for sample in in_.iter_mut() {
+                *sample = random::get_float() * 2.0 - 1.0;
+            }
+            fx.process(&in_, &mut left, &mut right);
+            wav_data_in.extend_from_slice(&in_);
+            wav_data_left.extend_from_slice(&left);
+            wav_data_right.extend_from_slice(&right);
+        }
+
+        wav_writer::write(
+            format!("fx/widener_{width}_left.wav").as_str(),
+            &wav_data_left,
+        )
+        .ok();
+        wav_writer::write(
+            format!("fx/widener_{width}_right.wav").as_str(),
+            &wav_data_right,
+        )
+        .ok();
+
+        (wav_data_in, wav_data_left, wav_data_right)
+    };
+
+    let (_, left, right) = render(0.0);
+    assert_eq!(left, right);
+
+    let (in_, left, right) = render(1.0);
+    let skip = 1000;
+    assert!(analysis::correlation(&left[skip..], &right[skip..]).abs() < 0.1);
+
+    // Summing to mono returns the dry signal, without comb filtering.
+    for ((in_sample, left_sample), right_sample) in in_.iter().zip(left.iter()).zip(right.iter()) {
+        assert!(((left_sample + right_sample) * 0.5 - in_sample).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn tape() {
+    let render = |frequency: f32, amplitude: f32, flutter: f32, saturation: f32| {
+        let mut fx = tape::Tape::new();
+        let mut osc = SineOscillator::new();
+        let mut in_out = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        osc.init();
+        fx.set_flutter(flutter);
+        fx.set_saturation(saturation);
+        fx.init();
+
+        let blocks = (2.0 * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+
+        for _ in 0..blocks {
+            osc.render(frequency / SAMPLE_RATE, &mut in_out);
+            for sample in in_out.iter_mut() {
+                *sample *= amplitude;
+            }
+            fx.process(&mut in_out);
+            wav_data.extend_from_slice(&in_out);
+        }
+
+        wav_writer::write(
+            format!("fx/tape_{flutter}_{saturation}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        wav_data[4800..].to_vec()
+    };
+
+    // Pitch deviation in Hz, measured on 25 ms windows.
+    let deviation = |data: &[f32]| {
+        let frequencies: Vec<f32> = data
+            .chunks(1200)
+            .map(|window| analysis::zero_crossing_frequency(window) * SAMPLE_RATE)
+            .collect();
+        let max = frequencies.iter().fold(f32::MIN, |a, &b| a.max(b));
+        let min = frequencies.iter().fold(f32::MAX, |a, &b| a.min(b));
+        (max - min) * 0.5
+    };
+
+    // Flutter modulates 1 kHz by up to 3 Hz.
+    let clean = render(1000.0, 0.3, 0.0, 0.0);
+    let fluttering = render(1000.0, 0.3, 1.0, 0.0);
+    assert!(deviation(&clean) < 0.2);
+    assert!((2.0..4.0).contains(&deviation(&fluttering)));
+
+    // Saturation adds odd harmonics.
+    let third_harmonic = |data: &[f32]| {
+        analysis::magnitude(data, 600.0 / SAMPLE_RATE)
+            / analysis::magnitude(data, 200.0 / SAMPLE_RATE)
+    };
+
+    let clean = render(200.0, 0.5, 0.0, 0.0);
+    let saturated = render(200.0, 0.5, 0.0, 1.0);
+    assert!(third_harmonic(&clean) < 0.001);
+    assert!(third_harmonic(&saturated) > 0.05);
+}
+
+#[test]
+fn limiter_lookahead() {
+    let render = |lookahead: usize| {
+        let mut limiter = Limiter::new();
+        limiter.init();
+        limiter.set_lookahead(lookahead);
+        assert_eq!(limiter.lookahead(), lookahead);
+
+        let mut wav_data = Vec::new();
+
+        for n in 0..40 {
+            // Quiet signal with a single-sample spike.
+            let mut block = [0.1; BLOCK_SIZE];
+            if n == 20 {
+                block[BLOCK_SIZE / 2] = 4.0;
+            }
+            limiter.process(1.0, &mut block);
+            wav_data.extend_from_slice(&block);
+        }
+
+        wav_writer::write(
+            format!("fx/limiter_lookahead_{lookahead}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        wav_data
+    };
+
+    let direct = render(0);
+    let lookahead = render(16);
+
+    // The spike is delayed by the lookahead.
+    let position = |data: &[f32]| {
+        data.iter()
+            .enumerate()
+            .fold((0, 0.0), |(index, peak), (n, x)| {
+                if x.abs() > peak {
+                    (n, x.abs())
+                } else {
+                    (index, peak)
+                }
+            })
+            .0
+    };
+    assert_eq!(position(&lookahead), position(&direct) + 16);
+
+    // The overshoot is smaller.
+    assert!(analysis::peak(&lookahead) < 0.8 * analysis::peak(&direct));
+
+    // Below the threshold, the output is only delayed.
+    assert!(lookahead[..16].iter().all(|&x| x == 0.0));
+    assert_eq!(lookahead[16..400], direct[..384]);
+}
@@ -1,9 +1,11 @@
 //! Tests for the noise generators
 
+mod analysis;
 mod wav_writer;
 
 use mi_plaits_dsp::dsp::noise::*;
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
+use mi_plaits_dsp::stmlib::utils::random;
 
 const BLOCK_SIZE: usize = 24;
 
@@ -97,3 +99,56 @@ fn smooth_random_generator() {
 
     wav_writer::write("noise/smooth_random_generator.wav", &wav_data).ok();
 }
+
+#[test]
+fn noise_color() {
+    let length = 32768;
+
+    let render = |color: noise_color::NoiseColor| {
+        let mut filter = noise_color::NoiseColorFilter::new();
+        filter.init();
+        filter.set_color(color);
+        assert_eq!(filter.color(), color);
+
+        let mut wav_data = Vec::new();
+        let mut out = [0.0; BLOCK_SIZE];
+
+        // Let the filters settle before analysis.
+        for n in 0..(2 * length / BLOCK_SIZE) {
+            for sample in out.iter_mut() {
+                *sample = random::get_float() * 2.0 - 1.0;
+            }
+            filter.process(&mut out);
+            if n >= length / BLOCK_SIZE {
+                wav_data.extend_from_slice(&out);
+            }
+        }
+
+        wav_writer::write(
+            format!("noise/noise_color_{color:?}.wav")
+                .to_lowercase()
+                .as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        // Level change in dB per octave, measured across 5 octaves.
+        let low = analysis::band_energy(&wav_data, 0.0025, 0.005, 256);
+        let high = analysis::band_energy(&wav_data, 0.08, 0.16, 256);
+        let tilt = 10.0 * f32::log10(high / low) / 5.0;
+
+        (tilt, analysis::rms(&wav_data))
+    };
+
+    let (white_tilt, white_rms) = render(noise_color::NoiseColor::White);
+    let (pink_tilt, pink_rms) = render(noise_color::NoiseColor::Pink);
+    let (brown_tilt, brown_rms) = render(noise_color::NoiseColor::Brown);
+
+    assert!(white_tilt.abs() < 0.5);
+    assert!((pink_tilt + 3.0).abs() < 0.5);
+    assert!((brown_tilt + 6.0).abs() < 0.5);
+
+    // The level stays comparable.
+    assert!(pink_rms > 0.5 * white_rms && pink_rms < 2.0 * white_rms);
+    assert!(brown_rms > 0.5 * white_rms && brown_rms < 2.0 * white_rms);
+}
@@ -1,10 +1,12 @@
 //! Tests for the oscillators
 
+mod analysis;
 mod modulation;
 mod wav_writer;
 
 use mi_plaits_dsp::dsp::oscillator::*;
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
+use mi_plaits_dsp::stmlib::dsp::cosine_oscillator::{CosineOscillator, CosineOscillatorMode};
 
 const BLOCK_SIZE: usize = 24;
 
@@ -33,6 +35,46 @@ fn formant_oscillator() {
     wav_writer::write("oscillator/formant.wav", &wav_data).ok();
 }
 
+#[test]
+fn formant_oscillator_phase_shift_sweep() {
+    // Formant is a multiple of the carrier, so the phase reset itself is continuous.
+    let carrier_frequency = 200.0;
+    let formant_frequency = 800.0;
+    let sweep_blocks = 20;
+
+    let mut osc = formant_oscillator::FormantOscillator::new();
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut wav_data = Vec::new();
+    osc.init();
+
+    let f_carrier = carrier_frequency / SAMPLE_RATE;
+    let f_formant = formant_frequency / SAMPLE_RATE;
+
+    for _ in 0..sweep_blocks {
+        osc.render(f_carrier, f_formant, 0.0, &mut out);
+    }
+
+    for n in 0..=sweep_blocks {
+        let phase_shift = n as f32 / sweep_blocks as f32;
+        osc.render(f_carrier, f_formant, phase_shift, &mut out);
+        wav_data.extend_from_slice(&out);
+    }
+
+    wav_writer::write("oscillator/formant_phase_shift_sweep.wav", &wav_data).ok();
+
+    // Largest step of the formant sine plus the phase shift moving one sample.
+    let max_step =
+        2.0 * std::f32::consts::PI * (f_formant + 1.0 / (sweep_blocks * BLOCK_SIZE) as f32) * 1.1;
+
+    for (n, w) in wav_data.windows(2).enumerate() {
+        assert!(
+            (w[1] - w[0]).abs() < max_step,
+            "Discontinuity at sample {}",
+            n + 1
+        );
+    }
+}
+
 #[test]
 fn grainlet_oscillator() {
     let carrier_frequency = 80.0;
@@ -58,6 +100,57 @@ fn grainlet_oscillator() {
     wav_writer::write("oscillator/grainlet.wav", &wav_data).ok();
 }
 
+#[test]
+fn grainlet_oscillator_window() {
+    let carrier_frequency = 100.0;
+    let formant_frequency = 1000.0;
+    let duration = 0.5;
+
+    let render = |window: grainlet_oscillator::GrainletWindow| {
+        let mut osc = grainlet_oscillator::GrainletOscillator::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        osc.init();
+        osc.set_window(window);
+
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+        let f_carrier = carrier_frequency / SAMPLE_RATE;
+        let f_formant = formant_frequency / SAMPLE_RATE;
+
+        for _ in 0..blocks {
+            osc.render(f_carrier, f_formant, 1.0, 0.0, &mut out);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("oscillator/grainlet_window_{window:?}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        wav_data[wav_data.len() / 2..].to_vec()
+    };
+
+    let hann = render(grainlet_oscillator::GrainletWindow::Hann);
+    let triangular = render(grainlet_oscillator::GrainletWindow::Triangular);
+
+    // Sidebands around the formant depend on the window.
+    let sideband = 1100.0 / SAMPLE_RATE;
+    let hann_sideband = analysis::magnitude(&hann, sideband);
+    let triangular_sideband = analysis::magnitude(&triangular, sideband);
+    assert!((hann_sideband - triangular_sideband).abs() > 0.1 * hann_sideband);
+
+    // The corners of the triangular window leave more high frequency content.
+    let high = |data: &[f32]| -> f32 {
+        (25..60)
+            .map(|n| analysis::magnitude(data, n as f32 * carrier_frequency / SAMPLE_RATE))
+            .sum()
+    };
+    let hann_high = high(&hann);
+    let triangular_high = high(&triangular);
+    assert!(hann_high < 0.5 * triangular_high);
+}
+
 #[test]
 fn harmonic_oscillator() {
     let frequency = 110.0;
@@ -165,6 +258,49 @@ fn oscillator_saw() {
     wav_writer::write("oscillator/oscillator_saw.wav", &wav_data).ok();
 }
 
+#[test]
+fn oscillator_fm_limit() {
+    let render = |limit: Option<f32>, depth: f32| {
+        let mut osc = oscillator::Oscillator::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut fm = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+        let mut phase = 0.0;
+        osc.init();
+        osc.set_fm_limit(limit);
+
+        for _ in 0..1000 {
+            for fm_sample in fm.iter_mut() {
+                phase += 197.0 / SAMPLE_RATE;
+                *fm_sample = depth * f32::sin(2.0 * std::f32::consts::PI * phase);
+            }
+            osc.render(
+                440.0 / SAMPLE_RATE,
+                0.5,
+                Some(&fm),
+                &mut out,
+                oscillator::OscillatorShape::Saw,
+                false,
+            );
+            data.extend_from_slice(&out);
+        }
+
+        data
+    };
+
+    // Moderate FM stays below the limit and is untouched.
+    assert_eq!(render(None, 0.5), render(Some(0.1), 0.5));
+
+    // Extreme FM reaches the frequency clamp, producing aliases near Nyquist.
+    let clamped = render(None, 30.0);
+    let limited = render(Some(0.1), 30.0);
+    wav_writer::write("oscillator/oscillator_fm_limit.wav", &limited).ok();
+
+    let clamped_aliases = analysis::band_energy(&clamped[4000..20384], 0.35, 0.5, 300);
+    let limited_aliases = analysis::band_energy(&limited[4000..20384], 0.35, 0.5, 300);
+    assert!(limited_aliases < clamped_aliases * 0.6);
+}
+
 #[test]
 fn oscillator_triangle() {
     let frequency = 110.0;
@@ -387,6 +523,80 @@ fn fast_sine_oscillator() {
     wav_writer::write("oscillator/fastsine.wav", &wav_data).ok();
 }
 
+#[test]
+fn fast_sine_oscillator_resync() {
+    let mut osc = sine_oscillator::FastSineOscillator::new();
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut wav_data = Vec::new();
+    osc.init();
+
+    let f = 440.0 / SAMPLE_RATE;
+
+    // Heavy frequency modulation lets the state drift away from the unit circle.
+    for n in 0..4000 {
+        let modulation = if n % 2 == 0 { 0.2 } else { 0.001 };
+        osc.render(f + modulation * ((n % 7) as f32 / 7.0), &mut out);
+        wav_data.extend_from_slice(&out);
+    }
+
+    // Let the frequency settle before resyncing, so that it is constant afterwards.
+    osc.render(f, &mut out);
+    wav_data.extend_from_slice(&out);
+
+    let phase = 0.3;
+    osc.resync(phase);
+
+    let mut resynced = Vec::new();
+    for _ in 0..100 {
+        osc.render(f, &mut out);
+        resynced.extend_from_slice(&out);
+    }
+    wav_data.extend_from_slice(&resynced);
+
+    wav_writer::write("oscillator/fastsine_resync.wav", &wav_data).ok();
+
+    assert!((analysis::peak(&resynced) - 1.0).abs() < 0.01);
+    for (n, sample) in resynced.iter().take(10).enumerate() {
+        let expected = f32::cos(2.0 * std::f32::consts::PI * (phase + f * (n + 1) as f32));
+        assert!((sample - expected).abs() < 0.01);
+    }
+}
+
+#[test]
+fn cosine_oscillator() {
+    let render = |frequency: f32, mode: CosineOscillatorMode| {
+        let mut osc = CosineOscillator::new();
+        osc.init(frequency, mode);
+
+        (0..4800)
+            .map(|_| {
+                let value = osc.next();
+                assert_eq!(osc.value(), value);
+                value
+            })
+            .collect::<Vec<f32>>()
+    };
+
+    for frequency in [0.001, 0.01, 0.1, 0.3] {
+        let reference: Vec<f32> = (0..4800)
+            .map(|n| 0.5 + 0.5 * f32::cos(2.0 * std::f32::consts::PI * frequency * n as f32))
+            .collect();
+
+        let exact = render(frequency, CosineOscillatorMode::Exact);
+        for (sample, expected) in exact.iter().zip(reference.iter()).take(1000) {
+            assert!((sample - expected).abs() < 1e-3);
+        }
+
+        // The frequency of the approximation is within 10%.
+        let approximate = render(frequency, CosineOscillatorMode::Approximate);
+        let centered: Vec<f32> = approximate.iter().map(|x| x - 0.5).collect();
+        let ratio = analysis::zero_crossing_frequency(&centered) / frequency;
+        assert!(ratio > 0.9 && ratio < 1.1);
+        assert_eq!(approximate[0], 1.0);
+        assert!(analysis::peak(&centered) <= 0.5 + 1e-3);
+    }
+}
+
 #[test]
 fn string_synth_oscillator() {
     let frequency = 55.0;
@@ -433,6 +643,53 @@ fn super_square_oscillator() {
     wav_writer::write("oscillator/super_square.wav", &wav_data).ok();
 }
 
+#[test]
+fn super_square_oscillator_detune() {
+    // Returns the spectral spread around the fundamental in Hz.
+    let spread = |detune: f32| {
+        let frequency = 220.0;
+
+        let mut osc = super_square_oscillator::SuperSquareOscillator::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        osc.init();
+        osc.set_voice_count(5);
+        osc.set_detune(detune);
+
+        let blocks = (2.0 * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+
+        for _ in 0..blocks {
+            osc.render(frequency / SAMPLE_RATE, 0.5, &mut out);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("oscillator/super_square_detune_{detune}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        let steps = 201;
+        let (mut weighted, mut total) = (0.0, 0.0);
+
+        for n in 0..steps {
+            let f = frequency * (0.95 + 0.1 * n as f32 / (steps - 1) as f32);
+            let m = analysis::magnitude(&wav_data, f / SAMPLE_RATE);
+            weighted += m * m * (f - frequency) * (f - frequency);
+            total += m * m;
+        }
+
+        f32::sqrt(weighted / total)
+    };
+
+    let narrow = spread(0.0);
+    let medium = spread(20.0);
+    let wide = spread(50.0);
+
+    assert!(medium > narrow * 1.5);
+    assert!(wide > medium * 1.5);
+}
+
 #[test]
 fn variable_saw_oscillator() {
     let frequency = 110.0;
@@ -483,6 +740,40 @@ fn variable_shape_oscillator() {
     wav_writer::write("oscillator/variable_shape.wav", &wav_data).ok();
 }
 
+#[test]
+fn variable_shape_oscillator_reset_phases() {
+    let mut osc = variable_shape_oscillator::VariableShapeOscillator::new();
+    let mut out = [0.0; 1];
+    osc.init();
+
+    let master_f = 1.0 / 64.0;
+    let f = 1.0 / 128.0;
+
+    // Settle the interpolated frequencies.
+    for _ in 0..100 {
+        osc.render(master_f, f, 0.5, 0.5, 0.0, &mut out, true, false);
+    }
+
+    osc.reset_phases(0.5, 0.25);
+    assert_eq!(osc.master_phase(), 0.5);
+    assert_eq!(osc.slave_phase(), 0.25);
+
+    // The master reaches the end of its cycle after 32 samples, before the slave does.
+    let mut sync_sample = None;
+    for n in 0..64 {
+        let slave_phase = osc.slave_phase();
+        osc.render(master_f, f, 0.5, 0.5, 0.0, &mut out, true, false);
+        if osc.slave_phase() < slave_phase {
+            sync_sample = Some(n);
+            break;
+        }
+    }
+
+    assert_eq!(sync_sample, Some(31));
+    assert_eq!(osc.master_phase(), 0.0);
+    assert_eq!(osc.slave_phase(), 0.0);
+}
+
 #[test]
 fn vosim_oscillator() {
     let carrier_frequency = 105.0;
@@ -515,6 +806,53 @@ fn vosim_oscillator() {
     wav_writer::write("oscillator/vosim.wav", &wav_data).ok();
 }
 
+#[test]
+fn vosim_oscillator_formant_amplitudes() {
+    let carrier_frequency = 100.0;
+    let formant_frequency_1 = 800.0;
+    let formant_frequency_2 = 2000.0;
+    let duration = 0.5;
+
+    let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+    let carrier_f = carrier_frequency / SAMPLE_RATE;
+    let formant_f_1 = formant_frequency_1 / SAMPLE_RATE;
+    let formant_f_2 = formant_frequency_2 / SAMPLE_RATE;
+
+    let render = |amplitude_2: f32| {
+        let mut osc = vosim_oscillator::VosimOscillator::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+        osc.init();
+
+        for _ in 0..blocks {
+            osc.render_ex(
+                carrier_f,
+                formant_f_1,
+                formant_f_2,
+                0.5,
+                1.0,
+                amplitude_2,
+                &mut out,
+            );
+            data.extend_from_slice(&out);
+        }
+
+        data
+    };
+
+    let both = render(1.0);
+    let first_only = render(0.0);
+
+    wav_writer::write("oscillator/vosim_first_formant.wav", &first_only).ok();
+
+    let formant_1_level = analysis::magnitude(&first_only, formant_f_1);
+    let formant_2_level = analysis::magnitude(&first_only, formant_f_2);
+    let formant_2_level_both = analysis::magnitude(&both, formant_f_2);
+
+    assert!(formant_1_level > 10.0 * formant_2_level);
+    assert!(formant_2_level_both > 10.0 * formant_2_level);
+}
+
 #[test]
 fn wavetable_oscillator() {
     let frequency = 110.0;
@@ -548,6 +886,138 @@ fn wavetable_oscillator() {
     wav_writer::write("oscillator/wavetable.wav", &wav_data).ok();
 }
 
+#[test]
+fn wavetable_oscillator_interpolation() {
+    let mut wavetable = [&mi_plaits_dsp::dsp::resources::waves::WAV_INTEGRATED_WAVES[0..132]; 128];
+
+    for (n, wt) in mi_plaits_dsp::dsp::resources::waves::WAV_INTEGRATED_WAVES
+        .chunks(260)
+        .enumerate()
+    {
+        wavetable[n] = wt;
+    }
+
+    let render = |interpolation: wavetable_oscillator::WaveInterp| {
+        let mut osc = wavetable_oscillator::WavetableOscillator::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        osc.init();
+        osc.set_interpolation(interpolation);
+        assert_eq!(osc.interpolation(), interpolation);
+
+        // 20 blocks per cycle, sweeping over 8 waves.
+        let blocks = 20 * 400;
+        let f = 1.0 / (20 * BLOCK_SIZE) as f32;
+
+        for n in 0..blocks {
+            let waveform = 0.2 + 0.08 * modulation::ramp_up(n, blocks);
+            out.fill(0.0);
+            osc.render(f, 1.0, waveform, &wavetable, &mut out, 128, 96, true, true);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("oscillator/wavetable_{interpolation:?}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        // Follow the same point of the cycle while the waveform changes.
+        let points: Vec<f32> = wav_data.iter().step_by(20 * BLOCK_SIZE).copied().collect();
+        let second_differences: Vec<f32> = points
+            .windows(3)
+            .skip(10)
+            .map(|w| (w[2] - 2.0 * w[1] + w[0]).abs())
+            .collect();
+        second_differences
+            .iter()
+            .fold(0.0, |acc: f32, x| acc.max(*x))
+    };
+
+    assert_eq!(
+        wavetable_oscillator::WavetableOscillator::new().interpolation(),
+        wavetable_oscillator::WaveInterp::Hermite
+    );
+
+    let linear = render(wavetable_oscillator::WaveInterp::Linear);
+    let hermite = render(wavetable_oscillator::WaveInterp::Hermite);
+    assert!(hermite < linear);
+    let cubic = render(wavetable_oscillator::WaveInterp::Cubic);
+    assert!(cubic < 0.5 * linear);
+}
+
+#[test]
+fn wavetable_oscillator_reverse() {
+    let mut ramp: Vec<f32> = (0..128).map(|n| n as f32 / 127.0).collect();
+    mi_plaits_dsp::utils::reverse(&mut ramp);
+    for (n, sample) in ramp.iter().enumerate() {
+        assert_eq!(*sample, (127 - n) as f32 / 127.0);
+    }
+
+    let mut wavetable = [&mi_plaits_dsp::dsp::resources::waves::WAV_INTEGRATED_WAVES[0..132]; 128];
+
+    for (n, wt) in mi_plaits_dsp::dsp::resources::waves::WAV_INTEGRATED_WAVES
+        .chunks(260)
+        .enumerate()
+    {
+        wavetable[n] = wt;
+    }
+
+    // One cycle of an asymmetric wave, after the filters have settled.
+    let period = 20 * BLOCK_SIZE;
+    let render = |reverse: bool| {
+        let mut osc = wavetable_oscillator::WavetableOscillator::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        osc.init();
+        osc.set_reverse(reverse);
+        assert_eq!(osc.reverse(), reverse);
+
+        for _ in 0..20 * 50 {
+            out.fill(0.0);
+            osc.render(
+                1.0 / period as f32,
+                1.0,
+                0.3,
+                &wavetable,
+                &mut out,
+                128,
+                96,
+                true,
+                true,
+            );
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("oscillator/wavetable_reverse_{reverse}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        wav_data[wav_data.len() - period..].to_vec()
+    };
+
+    let forward = render(false);
+    let mut backward = render(true);
+
+    // The best match over all alignments of the cycles.
+    let similarity = |a: &[f32], b: &[f32]| {
+        (0..period)
+            .map(|lag| {
+                let shifted: Vec<f32> = (0..period).map(|n| b[(n + lag) % period]).collect();
+                analysis::correlation(a, &shifted)
+            })
+            .fold(-1.0, f32::max)
+    };
+
+    assert!(similarity(&forward, &backward) < 0.7);
+
+    // The match is not exact, as the band-limiting filters are not time-symmetric.
+    mi_plaits_dsp::utils::reverse(&mut backward);
+    assert!(similarity(&forward, &backward) > 0.9);
+}
+
 #[test]
 fn z_oscillator() {
     let carrier_frequency = 80.0;
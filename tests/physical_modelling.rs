@@ -1,9 +1,13 @@
 //! Tests for the physical modelling
 
+mod analysis;
 mod wav_writer;
 
+use mi_plaits_dsp::dsp::physical_modelling::exciter::ExciterType;
 use mi_plaits_dsp::dsp::physical_modelling::*;
+use mi_plaits_dsp::dsp::resources::stiffness::LUT_STIFFNESS;
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
+use mi_plaits_dsp::stmlib::dsp::interpolate;
 
 const BLOCK_SIZE: usize = 24;
 
@@ -86,6 +90,35 @@ fn resonator() {
     wav_writer::write("physical_modelling/resonator.wav", &wav_data).ok();
 }
 
+#[test]
+fn resonator_mode_frequencies() {
+    let f0 = 110.0 / SAMPLE_RATE;
+
+    let mut model = resonator::Resonator::new();
+    model.init(0.015, 16);
+
+    for structure in [0.1, 0.5, 0.9] {
+        let frequencies = model.mode_frequencies(f0, structure);
+        let mut stiffness = interpolate(&LUT_STIFFNESS, structure, 64.0);
+
+        // The third mode is in tune, with the first one compensated for the stretching.
+        assert!((frequencies[2] / (3.0 * f0) - 1.0).abs() < 1e-5);
+        let stretch_3 = frequencies[2] / (3.0 * frequencies[0]);
+        assert!((frequencies[0] * stretch_3 / f0 - 1.0).abs() < 1e-5);
+
+        let mut stretch_factor = 1.0;
+        for (n, frequency) in frequencies.iter().enumerate().take(16) {
+            let expected = f32::min((n + 1) as f32 * frequencies[0] * stretch_factor, 0.499);
+            assert!((frequency / expected - 1.0).abs() < 1e-4);
+
+            stretch_factor += stiffness;
+            stiffness *= if stiffness < 0.0 { 0.93 } else { 0.98 };
+        }
+
+        assert!(frequencies[16..].iter().all(|frequency| *frequency == 0.0));
+    }
+}
+
 #[test]
 fn string() {
     let frequency = 110.0;
@@ -122,6 +155,170 @@ fn string() {
     wav_writer::write("physical_modelling/string.wav", &wav_data).ok();
 }
 
+#[test]
+fn string_material() {
+    let render = |material: string::StringMaterial| {
+        let mut model = string::String::new(&std::alloc::System);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        model.reset();
+        assert_eq!(model.material(), None);
+        model.set_material(Some(material));
+
+        let blocks = (2.0 * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+
+        for n in 0..blocks {
+            let mut in_ = [0.0; BLOCK_SIZE];
+            if n == 0 {
+                in_[0] = 1.0;
+            }
+            out.fill(0.0);
+            // The raw parameters are overridden by the material.
+            model.process(110.0 / SAMPLE_RATE, 0.0, 0.0, 0.0, &in_, &mut out);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("physical_modelling/string_material_{material:?}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        // High band energy and level of windows at 0 and 1 second.
+        let high_band = |start: usize| {
+            let window = &wav_data[start..start + 4800];
+            analysis::band_energy(window, 2000.0 / SAMPLE_RATE, 6000.0 / SAMPLE_RATE, 200)
+        };
+        let level = |start: usize| analysis::rms(&wav_data[start..start + 4800]);
+
+        (high_band(0), high_band(48000), level(48000) / level(0))
+    };
+
+    let (steel_attack, steel_tail, steel_decay) = render(string::StringMaterial::Steel);
+    let (nylon_attack, nylon_tail, nylon_decay) = render(string::StringMaterial::Nylon);
+
+    assert!(steel_attack > nylon_attack * 100.0);
+    assert!(steel_tail > nylon_tail * 100.0);
+    assert!(steel_decay > nylon_decay * 2.0);
+}
+
+#[test]
+fn string_bow() {
+    let f0 = 110.0 / SAMPLE_RATE;
+    let blocks = (2.0 * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+
+    let mut model = string::String::new(&std::alloc::System);
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut wav_data = Vec::new();
+    model.reset();
+
+    // The bow is held for the whole duration, without any other excitation.
+    for _ in 0..blocks {
+        let in_ = [0.0; BLOCK_SIZE];
+        out.fill(0.0);
+        model.process_bowed(f0, 0.5, 0.3, 0.5, 0.7, &in_, &mut out);
+        wav_data.extend_from_slice(&out);
+    }
+
+    wav_writer::write("physical_modelling/string_bow.wav", &wav_data).ok();
+
+    let mut plucked = Vec::new();
+    model.reset();
+
+    for n in 0..blocks / 4 {
+        let mut in_ = [0.0; BLOCK_SIZE];
+        if n == 0 {
+            in_[0] = 1.0;
+        }
+        out.fill(0.0);
+        model.process(f0, 0.0, 0.5, 0.7, &in_, &mut out);
+        plucked.extend_from_slice(&out);
+    }
+
+    let early = &wav_data[24000..48000];
+    let late = &wav_data[wav_data.len() - 24000..];
+
+    // The string keeps oscillating at the pitch it has when plucked.
+    assert!(analysis::rms(late) > 0.1);
+    assert!(analysis::rms(late) > analysis::rms(early) * 0.8);
+
+    let low = 50.0 / SAMPLE_RATE;
+    let high = 1000.0 / SAMPLE_RATE;
+    let bowed_frequency = analysis::dominant_frequency(late, low, high, 951);
+    let plucked_frequency = analysis::dominant_frequency(&plucked[2400..], low, high, 951);
+    assert!((bowed_frequency / plucked_frequency - 1.0).abs() < 0.03);
+    assert!((analysis::zero_crossing_frequency(late) / bowed_frequency - 1.0).abs() < 0.03);
+
+    // A bow away from the middle of the string excites the even harmonics as well.
+    let harmonics: Vec<f32> = (1..=4)
+        .map(|n| analysis::magnitude(late, bowed_frequency * n as f32))
+        .collect();
+    assert!(harmonics[1] > 0.1 * harmonics[0]);
+    assert!(harmonics[3] > 0.05 * harmonics[0]);
+}
+
+#[test]
+fn string_denormals() {
+    // Returns the output of a string excited once and rendered for 20 seconds.
+    let render = |damping: f32| {
+        let mut model = string::String::new(&std::alloc::System);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        model.reset();
+
+        let blocks = (20.0 * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+        let f0 = 110.0 / SAMPLE_RATE;
+
+        for n in 0..blocks {
+            let mut in_ = [0.0; BLOCK_SIZE];
+            if n == 0 {
+                in_[0] = 1.0;
+            }
+            out.fill(0.0);
+            model.process(f0, 0.2, 0.5, damping, &in_, &mut out);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("physical_modelling/string_denormals_{damping}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        wav_data
+    };
+
+    let is_normal = |data: &[f32]| data.iter().all(|x| *x == 0.0 || x.is_normal());
+
+    // A decaying string ends in silence instead of denormals.
+    let decaying = render(0.2);
+    assert!(is_normal(&decaying));
+    assert!(decaying[decaying.len() - 48000..].iter().all(|x| *x == 0.0));
+
+    // An infinite decay keeps ringing.
+    let infinite = render(1.0);
+    assert!(is_normal(&infinite));
+    assert!(analysis::rms(&infinite[infinite.len() - 48000..]) > 0.001);
+
+    let mut model = resonator::Resonator::new();
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut wav_data = Vec::new();
+    model.init(0.015, 24);
+
+    for n in 0..20000 {
+        let mut in_ = [0.0; BLOCK_SIZE];
+        if n == 0 {
+            in_[0] = 1.0;
+        }
+        out.fill(0.0);
+        model.process(110.0 / SAMPLE_RATE, 0.5, 0.5, 0.0, &in_, &mut out);
+        wav_data.extend_from_slice(&out);
+    }
+
+    assert!(is_normal(&wav_data));
+    assert!(wav_data[wav_data.len() - 48000..].iter().all(|x| *x == 0.0));
+}
+
 #[test]
 fn string_voice() {
     let frequency = 110.0;
@@ -169,3 +366,140 @@ fn string_voice() {
     wav_writer::write("physical_modelling/string_voice.wav", &wav_data).ok();
     wav_writer::write("physical_modelling/string_voice_aux.wav", &wav_data_aux).ok();
 }
+
+#[test]
+fn modal_voice_exciter() {
+    let frequency = 110.0;
+    let gate_duration = 1.0;
+    let duration = 2.0;
+
+    let render = |exciter: ExciterType| {
+        let mut model = modal_voice::ModalVoice::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut temp = [0.0; BLOCK_SIZE];
+        let mut temp_2 = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        model.init();
+        model.set_exciter(exciter);
+
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+        let gate_blocks = (gate_duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+        let f0 = frequency / SAMPLE_RATE;
+
+        for n in 0..blocks {
+            out.fill(0.0);
+            aux.fill(0.0);
+            model.set_gate(n < gate_blocks);
+            model.render(
+                false,
+                n == 0,
+                1.0,
+                f0,
+                0.3,
+                0.5,
+                0.3,
+                &mut temp,
+                &mut temp_2,
+                &mut out,
+                &mut aux,
+            );
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("physical_modelling/modal_voice_exciter_{exciter:?}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        let window = (0.1 * SAMPLE_RATE) as usize;
+        let gate_end = gate_blocks * BLOCK_SIZE;
+        let strike = analysis::rms(&wav_data[..window]);
+        let held = analysis::rms(&wav_data[gate_end - window..gate_end]);
+        let released = analysis::rms(&wav_data[wav_data.len() - window..]);
+
+        (strike, held, released)
+    };
+
+    // A single strike decays while the gate is still held.
+    let (strike, held, _) = render(ExciterType::Mallet);
+    assert!(held < 0.05 * strike);
+
+    // Bowing and blowing sustain the sound until the gate is released.
+    for exciter in [ExciterType::Bow, ExciterType::Blow] {
+        let (strike, held, released) = render(exciter);
+        assert!(held > strike);
+        assert!(held > 0.05);
+        assert!(released < 0.01 * held);
+    }
+}
+
+#[test]
+fn string_voice_exciter() {
+    let frequency = 110.0;
+    let gate_duration = 1.0;
+    let duration = 2.0;
+
+    let render = |exciter: ExciterType| {
+        let mut model = string_voice::StringVoice::new(&std::alloc::System);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut temp = [0.0; BLOCK_SIZE];
+        let mut temp_2 = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        model.init();
+        model.set_exciter(exciter);
+
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+        let gate_blocks = (gate_duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+        let f0 = frequency / SAMPLE_RATE;
+
+        for n in 0..blocks {
+            out.fill(0.0);
+            aux.fill(0.0);
+            temp.fill(0.0);
+            temp_2.fill(0.0);
+            model.set_gate(n < gate_blocks);
+            model.render(
+                false,
+                n == 0,
+                1.0,
+                f0,
+                0.5,
+                0.5,
+                0.7,
+                &mut temp,
+                &mut temp_2,
+                &mut out,
+                &mut aux,
+            );
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("physical_modelling/string_voice_exciter_{exciter:?}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        let window = (0.1 * SAMPLE_RATE) as usize;
+        let gate_end = gate_blocks * BLOCK_SIZE;
+        let strike = analysis::rms(&wav_data[..window]);
+        let held = analysis::rms(&wav_data[gate_end - window..gate_end]);
+
+        (wav_data[gate_end - window..gate_end].to_vec(), strike, held)
+    };
+
+    // A pluck decays while the gate is still held.
+    let (plucked, strike, held) = render(ExciterType::Mallet);
+    assert!(held < strike);
+
+    // Bowing and blowing build up a sustained sound unlike the pluck.
+    for exciter in [ExciterType::Bow, ExciterType::Blow] {
+        let (wav_data, strike, held) = render(exciter);
+        assert!(held > strike);
+        assert!(held > 0.02);
+        assert!(analysis::correlation(&wav_data, &plucked).abs() < 0.5);
+    }
+}
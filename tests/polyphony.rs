@@ -0,0 +1,98 @@
+//! Tests for the polyphonic voice pool.
+
+mod analysis;
+mod wav_writer;
+
+use mi_plaits_dsp::dsp::engine::note_to_frequency;
+use mi_plaits_dsp::dsp::polyphony::Polyphony;
+use mi_plaits_dsp::dsp::voice::{Patch, VoiceBuilder};
+use mi_plaits_dsp::dsp::SAMPLE_RATE;
+
+const BLOCK_SIZE: usize = 24;
+
+#[test]
+fn chord() {
+    let mut polyphony: Polyphony<4> = Polyphony::from_builder(
+        &VoiceBuilder::new().with_engines(&[8]),
+        &std::alloc::System,
+        BLOCK_SIZE,
+    );
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let mut held = Vec::new();
+    let mut released = Vec::new();
+
+    polyphony.init();
+
+    let patch = Patch {
+        engine: 8,
+        decay: 0.2,
+        ..Default::default()
+    };
+
+    let notes = [48, 52, 55];
+    for note in notes {
+        polyphony.note_on(note, 100);
+    }
+    assert_eq!(polyphony.active_voices(), 3);
+
+    let blocks = (0.5 * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+    for _ in 0..blocks {
+        polyphony.render(&patch, &mut out, &mut aux);
+        held.extend_from_slice(&out);
+    }
+
+    for note in notes {
+        polyphony.note_off(note);
+    }
+    assert_eq!(polyphony.active_voices(), 0);
+
+    for _ in 0..blocks * 4 {
+        polyphony.render(&patch, &mut out, &mut aux);
+        released.extend_from_slice(&out);
+    }
+
+    let mut wav_data = held.clone();
+    wav_data.extend_from_slice(&released);
+    wav_writer::write("polyphony/chord.wav", &wav_data).ok();
+
+    // Each note of the chord is present, but nothing in between.
+    let chord = &held[held.len() / 2..];
+    for note in notes {
+        let note = f32::from(note);
+        let magnitude = analysis::magnitude(chord, note_to_frequency(note));
+        assert!(magnitude > 0.2);
+        assert!(magnitude > analysis::magnitude(chord, note_to_frequency(note - 1.0)) * 20.0);
+    }
+
+    // The LPG closes on note-off.
+    assert!(analysis::peak(&released[released.len() - BLOCK_SIZE * 100..]) < 1e-4);
+}
+
+#[test]
+fn voice_stealing() {
+    let mut polyphony: Polyphony<4> = Polyphony::from_builder(
+        &VoiceBuilder::new().with_engines(&[8]),
+        &std::alloc::System,
+        BLOCK_SIZE,
+    );
+
+    polyphony.init();
+
+    for note in 60..66 {
+        polyphony.note_on(note, 100);
+    }
+    assert_eq!(polyphony.active_voices(), 4);
+
+    // The two oldest notes were stolen.
+    polyphony.note_off(60);
+    polyphony.note_off(61);
+    assert_eq!(polyphony.active_voices(), 4);
+
+    polyphony.note_off(62);
+    assert_eq!(polyphony.active_voices(), 3);
+
+    // A velocity of 0 releases the note.
+    polyphony.note_on(63, 0);
+    assert_eq!(polyphony.active_voices(), 2);
+}
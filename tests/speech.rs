@@ -0,0 +1,158 @@
+//! Tests for the speech synths
+
+mod analysis;
+mod wav_writer;
+
+use mi_plaits_dsp::dsp::resources::lpc::LUT_LPC_EXCITATION_PULSE_SIZE;
+use mi_plaits_dsp::dsp::speech::lpc_speech_synth::LpcSpeechSynth;
+use mi_plaits_dsp::dsp::speech::lpc_speech_synth_phonemes::PHONEMES;
+use mi_plaits_dsp::dsp::speech::naive_speech_synth::NaiveSpeechSynth;
+use mi_plaits_dsp::dsp::speech::sam_speech_synth::SamSpeechSynth;
+use mi_plaits_dsp::dsp::speech::MAX_CONSONANT_DURATION_MS;
+use mi_plaits_dsp::dsp::SAMPLE_RATE;
+
+const BLOCK_SIZE: usize = 24;
+
+#[test]
+fn lpc_speech_synth_excitation_pulse() {
+    // One full-scale sample per pulse.
+    let mut impulse = [0; LUT_LPC_EXCITATION_PULSE_SIZE];
+    impulse[..32].fill(127);
+
+    let render = |pulse: Option<&[i8; LUT_LPC_EXCITATION_PULSE_SIZE]>| {
+        let mut synth = LpcSpeechSynth::new();
+        let mut excitation = [0.0; BLOCK_SIZE];
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+        synth.init();
+        synth.set_excitation_pulse(pulse);
+        synth.play_frame(&PHONEMES, 0.0, false);
+
+        for _ in 0..200 {
+            synth.render(1.0, 1.0, &mut excitation, &mut out);
+            data.extend_from_slice(&out);
+        }
+
+        data.split_off(800)
+    };
+
+    let original = render(None);
+    let impulse_out = render(Some(&impulse));
+
+    wav_writer::write("speech/lpc_speech_synth_impulse.wav", &impulse_out).ok();
+
+    // The flat impulse brightens the output...
+    assert!(
+        analysis::spectral_centroid(&impulse_out, 200)
+            > analysis::spectral_centroid(&original, 200) * 1.15
+    );
+
+    // ...while the filter of the frame still puts the formant at the same place.
+    let formant = analysis::dominant_frequency(&original, 0.0, 0.5, 401);
+    let impulse_formant = analysis::dominant_frequency(&impulse_out, 0.0, 0.5, 401);
+    assert!((formant - impulse_formant).abs() < 0.0125);
+}
+
+#[test]
+fn sam_speech_synth_consonant_duration() {
+    let frequency = 100.0 / SAMPLE_RATE;
+    let window = (0.02 * SAMPLE_RATE) as usize;
+
+    let render = |duration: f32| {
+        let mut synth = SamSpeechSynth::new();
+        let mut excitation = [0.0; BLOCK_SIZE];
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+        synth.init();
+        synth.set_consonant_duration_ms(duration);
+
+        for n in 0..300 {
+            synth.render(n == 0, frequency, 0.3, 0.0, &mut excitation, &mut out);
+            data.extend_from_slice(&out);
+        }
+
+        data
+    };
+
+    // The consonant picked for this vowel has much less energy between 1 and 3 kHz.
+    let energy = |data: &[f32], start: usize| {
+        analysis::band_energy(&data[start..start + window], 0.02, 0.06, 100)
+    };
+
+    let short = render(50.0);
+    let long = render(100.0);
+    wav_writer::write("speech/sam_speech_synth_long_consonant.wav", &long).ok();
+
+    // Both start with the consonant...
+    assert!(energy(&short, 0) < energy(&short, 6 * window) * 0.1);
+    assert!(energy(&long, 0) < energy(&long, 6 * window) * 0.1);
+
+    // ...but only the longer one still plays it 60 ms after the onset.
+    assert!(energy(&short, 3 * window) > energy(&short, 6 * window) * 0.8);
+    assert!(energy(&long, 3 * window) < energy(&long, 6 * window) * 0.1);
+}
+
+#[test]
+fn naive_speech_synth_consonant() {
+    let mut synth = NaiveSpeechSynth::new();
+    assert_eq!(synth.consonant_level(), 1.0);
+    synth.set_consonant_level(3.0);
+    assert_eq!(synth.consonant_level(), 2.0);
+    synth.set_consonant_duration_ms(-1.0);
+    assert_eq!(synth.consonant_duration_ms(), 0.0);
+    synth.set_consonant_duration_ms(1000.0);
+    assert_eq!(synth.consonant_duration_ms(), MAX_CONSONANT_DURATION_MS);
+
+    let frequency = 100.0 / SAMPLE_RATE;
+    let window = (0.02 * SAMPLE_RATE) as usize;
+
+    let render = |level: f32, duration: f32| {
+        let mut synth = NaiveSpeechSynth::new();
+        let mut temp = [0.0; BLOCK_SIZE];
+        let mut excitation = [0.0; BLOCK_SIZE];
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+        synth.init();
+        synth.set_consonant_level(level);
+        synth.set_consonant_duration_ms(duration);
+
+        for n in 0..300 {
+            synth.render(
+                n == 0,
+                frequency,
+                0.3,
+                0.5,
+                &mut temp,
+                &mut excitation,
+                &mut out,
+            );
+            data.extend_from_slice(&out);
+        }
+
+        data
+    };
+    let level = |data: &[f32], start: usize| analysis::rms(&data[start..start + window]);
+
+    let normal = render(1.0, 50.0);
+    let loud = render(2.0, 50.0);
+    let silent = render(0.0, 50.0);
+    let long = render(0.0, 100.0);
+    wav_writer::write("speech/naive_speech_synth_loud_consonant.wav", &loud).ok();
+
+    // The level only applies to the consonant...
+    assert!(
+        (level(&loud, window) - 2.0 * level(&normal, window)).abs() < 0.05 * level(&loud, window)
+    );
+    assert!(level(&silent, window) < 0.01 * level(&normal, window));
+    assert!(
+        (level(&loud, 6 * window) - level(&normal, 6 * window)).abs()
+            < 0.01 * level(&normal, 6 * window)
+    );
+    assert!(
+        (level(&silent, 6 * window) - level(&normal, 6 * window)).abs()
+            < 0.01 * level(&normal, 6 * window)
+    );
+
+    // ...which lasts for the set duration.
+    assert!(level(&long, 3 * window) < 0.01 * level(&silent, 3 * window));
+}
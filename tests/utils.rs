@@ -0,0 +1,433 @@
+//! Tests for the utilities
+
+mod analysis;
+mod wav_writer;
+
+use mi_plaits_dsp::dsp::oscillator::sine_oscillator::SineOscillator;
+use mi_plaits_dsp::dsp::voice::{Modulations, Patch, Voice};
+use mi_plaits_dsp::dsp::SAMPLE_RATE;
+use mi_plaits_dsp::stmlib::utils::random;
+use mi_plaits_dsp::utils::*;
+
+const BLOCK_SIZE: usize = 24;
+
+#[test]
+fn envelope_follower_gated_sine() {
+    let attack_time = 0.005;
+    let release_time = 0.05;
+    let frequency = 1000.0 / SAMPLE_RATE;
+
+    let mut follower = envelope_follower::EnvelopeFollower::new();
+    let mut osc = SineOscillator::new();
+    let mut input = [0.0; BLOCK_SIZE];
+    let mut level = [0.0; BLOCK_SIZE];
+    let mut wav_data = Vec::new();
+
+    follower.init();
+    follower.set_attack_time(attack_time);
+    follower.set_release_time(release_time);
+    osc.init();
+
+    let gate_on = (0.1 * SAMPLE_RATE) as usize;
+    let gate_off = (0.3 * SAMPLE_RATE) as usize;
+    let blocks = (0.6 * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+
+    for n in 0..blocks {
+        osc.render(frequency, &mut input);
+        for (i, sample) in input.iter_mut().enumerate() {
+            let position = n * BLOCK_SIZE + i;
+            if position < gate_on || position >= gate_off {
+                *sample = 0.0;
+            }
+        }
+        follower.process_buffer(&input, &mut level);
+        wav_data.extend_from_slice(&level);
+    }
+
+    wav_writer::write("utils/envelope_follower.wav", &wav_data).ok();
+
+    let sustain_level = wav_data[gate_off - 1];
+    let attack_samples = (attack_time * SAMPLE_RATE) as usize;
+    let release_samples = (release_time * SAMPLE_RATE) as usize;
+
+    assert!(wav_data[gate_on - 1] == 0.0);
+    assert!(sustain_level > 0.5);
+    assert!(wav_data[gate_on + 3 * attack_samples] > 0.75 * sustain_level);
+    assert!(wav_data[gate_off + 3 * release_samples] < 0.1 * sustain_level);
+}
+
+#[test]
+fn meter_peak() {
+    let data = [0.1, -0.25, 0.75, -0.9, 0.3, 0.0];
+
+    assert_eq!(meter::peak(&data), 0.9);
+    assert_eq!(meter::peak(&[]), 0.0);
+}
+
+#[test]
+fn meter_rms() {
+    let data: Vec<f32> = (0..4800)
+        .map(|n| f32::sin(2.0 * std::f32::consts::PI * 100.0 * n as f32 / SAMPLE_RATE))
+        .collect();
+
+    assert!((meter::rms(&data) - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.005);
+}
+
+#[test]
+fn meter_true_peak() {
+    // Sine at a quarter of the sample rate, sampled 45 degrees off its peaks.
+    let data: Vec<f32> = (0..128)
+        .map(|n| {
+            f32::sin(std::f32::consts::FRAC_PI_2 * n as f32 + std::f32::consts::FRAC_PI_4)
+                * fade(n, 128)
+        })
+        .collect();
+
+    let sample_peak = meter::peak(&data);
+    let true_peak = meter::true_peak_2x(&data);
+
+    assert!(sample_peak < 0.71);
+    assert!(true_peak > sample_peak + 0.1);
+    assert!((true_peak - 1.0).abs() < 0.001);
+}
+
+#[test]
+fn meter_true_peak_near_nyquist() {
+    // Sine at 0.375 times the sample rate, peaking in between the samples 40 and 41.
+    let data: Vec<f32> = (0..80)
+        .map(|n| f32::cos(2.0 * std::f32::consts::PI * 0.375 * (n as f32 - 40.5)) * fade(n, 80))
+        .collect();
+
+    let sample_peak = meter::peak(&data);
+    let true_peak = meter::true_peak_2x(&data);
+
+    assert!(sample_peak < 0.93);
+    assert!(true_peak > 0.99);
+    assert!(true_peak < 1.01);
+}
+
+#[test]
+fn meter_true_peak_block_edges() {
+    // Overs in between the first and the last two samples of a block shorter than the FIR.
+    let mut data = [0.0; 8];
+    data[..2].fill(0.9);
+    assert!(meter::true_peak_2x(&data) > 1.0);
+
+    data.reverse();
+    assert!(meter::true_peak_2x(&data) > 1.0);
+
+    assert_eq!(meter::true_peak_2x(&[0.5]), 0.5);
+    assert_eq!(meter::true_peak_2x(&[]), 0.0);
+}
+
+/// Raised cosine fade in and out over 24 samples, so that the signals of the true peak tests
+/// don't overshoot at the edges of the buffer.
+fn fade(n: usize, len: usize) -> f32 {
+    let distance = n.min(len - 1 - n) as f32;
+    if distance >= 24.0 {
+        1.0
+    } else {
+        0.5 - 0.5 * f32::cos(std::f32::consts::PI * distance / 24.0)
+    }
+}
+
+#[test]
+fn vocoder_tracks_modulator() {
+    let mut vocoder = vocoder::Vocoder::new();
+    let mut osc = SineOscillator::new();
+    let mut modulator = [0.0; BLOCK_SIZE];
+    let mut carrier = [0.0; BLOCK_SIZE];
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut wav_data = Vec::new();
+
+    vocoder.init();
+    vocoder.set_bands(12);
+    osc.init();
+
+    assert_eq!(vocoder.bands(), 12);
+
+    // Sine sweep from 200 Hz to 3.2 kHz over 4 segments, one octave per segment.
+    let segments = 4;
+    let segment_blocks = (0.5 * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+    let blocks = segments * segment_blocks;
+
+    for n in 0..blocks {
+        let frequency = 200.0 * 16.0f32.powf(n as f32 / blocks as f32);
+        osc.render(frequency / SAMPLE_RATE, &mut modulator);
+
+        for sample in carrier.iter_mut() {
+            *sample = random::get_float() * 2.0 - 1.0;
+        }
+
+        vocoder.process(&modulator, &carrier, &mut out);
+        wav_data.extend_from_slice(&out);
+    }
+
+    wav_writer::write("utils/vocoder.wav", &wav_data).ok();
+
+    // Compares the output energy in the band of the modulator with the energy in
+    // the band it occupied two octaves earlier or later.
+    let band_energy = |data: &[f32], frequency: f32| {
+        analysis::band_energy(
+            data,
+            frequency * 0.8 / SAMPLE_RATE,
+            frequency * 1.25 / SAMPLE_RATE,
+            16,
+        )
+    };
+
+    let segment_length = segment_blocks * BLOCK_SIZE;
+
+    for segment in 0..segments {
+        let start = segment * segment_length + segment_length / 4;
+        let data = &wav_data[start..start + segment_length / 2];
+        let frequency = 200.0 * 2.0f32.powf(segment as f32 + 0.5);
+        let other_frequency = if segment < 2 {
+            frequency * 4.0
+        } else {
+            frequency * 0.25
+        };
+
+        assert!(band_energy(data, frequency) > 5.0 * band_energy(data, other_frequency));
+    }
+}
+
+#[test]
+fn modulation_ring_mod() {
+    let f1 = 1000.0 / SAMPLE_RATE;
+    let f2 = 300.0 / SAMPLE_RATE;
+
+    let a: Vec<f32> = (0..4800)
+        .map(|n| f32::sin(2.0 * std::f32::consts::PI * f1 * n as f32))
+        .collect();
+    let b: Vec<f32> = (0..4800)
+        .map(|n| f32::sin(2.0 * std::f32::consts::PI * f2 * n as f32))
+        .collect();
+    let mut out = vec![0.0; 4800];
+
+    modulation::ring_mod(&a, &b, &mut out);
+
+    wav_writer::write("utils/ring_mod.wav", &out).ok();
+
+    // Two components at half amplitude, none at the input frequencies.
+    assert!((analysis::magnitude(&out, f1 + f2) - 0.5).abs() < 0.02);
+    assert!((analysis::magnitude(&out, f1 - f2) - 0.5).abs() < 0.02);
+    assert!(analysis::magnitude(&out, f1) < 0.01);
+    assert!(analysis::magnitude(&out, f2) < 0.01);
+
+    modulation::amp_mod(&a, &b, 0.0, &mut out);
+    assert_eq!(out, a);
+
+    // Full depth keeps the carrier at half amplitude with sidebands at a quarter.
+    modulation::amp_mod(&a, &b, 1.0, &mut out);
+    assert!((analysis::magnitude(&out, f1) - 0.5).abs() < 0.02);
+    assert!((analysis::magnitude(&out, f1 + f2) - 0.25).abs() < 0.02);
+    assert!((analysis::magnitude(&out, f1 - f2) - 0.25).abs() < 0.02);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn preset_bank_clamps_patches() {
+    use mi_plaits_dsp::dsp::voice::{Patch, NUM_ENGINES};
+
+    let mut bank = preset_bank::PresetBank::new();
+
+    let index = bank.insert(
+        "Out of range",
+        Patch {
+            note: 500.0,
+            harmonics: 5.0,
+            timbre: -2.0,
+            morph: 1.5,
+            frequency_modulation_amount: 3.0,
+            timbre_modulation_amount: -3.0,
+            morph_modulation_amount: 0.5,
+            engine: 99,
+            decay: -1.0,
+            lpg_colour: 2.0,
+            fine_tune: -30.0,
+            ..Default::default()
+        },
+    );
+
+    let patch = bank.get(index).unwrap();
+    assert_eq!(bank.name(index), Some("Out of range"));
+    assert_eq!(patch.note, 120.0);
+    assert_eq!(patch.harmonics, 1.0);
+    assert_eq!(patch.timbre, 0.0);
+    assert_eq!(patch.morph, 1.0);
+    assert_eq!(patch.frequency_modulation_amount, 1.0);
+    assert_eq!(patch.timbre_modulation_amount, -1.0);
+    assert_eq!(patch.morph_modulation_amount, 0.5);
+    assert_eq!(patch.engine, NUM_ENGINES - 1);
+    assert_eq!(patch.decay, 0.0);
+    assert_eq!(patch.lpg_colour, 1.0);
+    assert_eq!(patch.fine_tune, -24.0);
+
+    let previous = bank
+        .set(
+            index,
+            Patch {
+                note: -500.0,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(previous.note, 120.0);
+    assert_eq!(bank.get(index).unwrap().note, -119.0);
+
+    assert!(bank.remove(index).is_some());
+    assert!(bank.is_empty());
+    assert!(bank.set(0, Patch::default()).is_none());
+}
+
+#[cfg(all(feature = "alloc", feature = "serde"))]
+#[test]
+fn preset_bank_serde_round_trip() {
+    use mi_plaits_dsp::dsp::voice::Patch;
+
+    let mut bank = preset_bank::PresetBank::new();
+
+    for n in 0..4 {
+        bank.insert(
+            &format!("Preset {n}"),
+            Patch {
+                note: 36.0 + n as f32 * 12.0,
+                timbre: n as f32 * 0.25,
+                engine: n * 5,
+                ..Default::default()
+            },
+        );
+    }
+
+    let json = serde_json::to_string(&bank).unwrap();
+    let restored: preset_bank::PresetBank = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored.len(), 4);
+
+    for n in 0..4 {
+        let original = bank.get(n).unwrap();
+        let patch = restored.get(n).unwrap();
+        assert_eq!(restored.name(n), bank.name(n));
+        assert_eq!(patch.note, original.note);
+        assert_eq!(patch.timbre, original.timbre);
+        assert_eq!(patch.engine, original.engine);
+    }
+}
+
+#[cfg(all(feature = "alloc", feature = "serde"))]
+#[test]
+fn preset_bank_serde_missing_fields() {
+    // Patch without the fields that were added later, e.g. lpg_brightness_tracking.
+    let json = r#"[{"name":"Old","patch":{"note":60.0,"timbre":0.25,"engine":3}}]"#;
+    let bank: preset_bank::PresetBank = serde_json::from_str(json).unwrap();
+    let patch = bank.get(0).unwrap();
+    let default = Patch::default();
+
+    assert_eq!(bank.name(0), Some("Old"));
+    assert_eq!(patch.note, 60.0);
+    assert_eq!(patch.timbre, 0.25);
+    assert_eq!(patch.engine, 3);
+    assert_eq!(patch.harmonics, default.harmonics);
+    assert_eq!(
+        patch.lpg_brightness_tracking,
+        default.lpg_brightness_tracking
+    );
+    assert_eq!(patch.fine_tune, default.fine_tune);
+    assert_eq!(patch.trigger_delay_enabled, default.trigger_delay_enabled);
+}
+
+#[test]
+fn voice_stream_matches_block_render() {
+    let length = 2000;
+    let patch = Patch {
+        note: 48.0,
+        engine: 8,
+        harmonics: 0.3,
+        timbre: 0.6,
+        morph: 0.4,
+        ..Default::default()
+    };
+    let modulations = Modulations::default();
+
+    let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+    voice.init();
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let mut expected_out = Vec::new();
+    let mut expected_aux = Vec::new();
+
+    while expected_out.len() < length {
+        voice.render(&patch, &modulations, &mut out, &mut aux);
+        expected_out.extend_from_slice(&out);
+        expected_aux.extend_from_slice(&aux);
+    }
+
+    let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+    voice.init();
+    let stream = voice_stream::VoiceStream::<BLOCK_SIZE>::new(voice, patch.clone(), modulations);
+    let wav_data: Vec<f32> = stream.take(length).collect();
+
+    wav_writer::write("utils/voice_stream.wav", &wav_data).ok();
+
+    assert_eq!(wav_data, expected_out[..length]);
+
+    // Stereo frames, pulled in buffers that do not align with the blocks.
+    let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+    voice.init();
+    let mut stream =
+        voice_stream::VoiceStream::<BLOCK_SIZE>::new(voice, patch.clone(), Modulations::default());
+    stream.set_interleaved(true);
+
+    let mut interleaved = Vec::new();
+    let mut buffer = [0.0; 2 * 17];
+    while interleaved.len() < 2 * length {
+        stream.fill(&mut buffer);
+        interleaved.extend_from_slice(&buffer);
+    }
+
+    for n in 0..length {
+        assert_eq!(interleaved[2 * n], expected_out[n]);
+        assert_eq!(interleaved[2 * n + 1], expected_aux[n]);
+    }
+}
+
+#[cfg(feature = "midi")]
+#[test]
+fn midi_mappings() {
+    use mi_plaits_dsp::dsp::engine::note_to_frequency;
+
+    // Note 69 is A4 at the default tuning reference.
+    let note = midi::note_number_to_patch_note(69);
+    assert!((note_to_frequency(note) * SAMPLE_RATE - 440.0).abs() < 0.01);
+
+    // A patch playing the note renders A4.
+    let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let mut data = Vec::new();
+    voice.init();
+
+    let patch = Patch {
+        note,
+        engine: 8,
+        ..Default::default()
+    };
+    let modulations = Modulations::default();
+
+    for _ in 0..400 {
+        voice.render(&patch, &modulations, &mut out, &mut aux);
+        data.extend_from_slice(&out);
+    }
+
+    let frequency = analysis::zero_crossing_frequency(&data[4800..]) * SAMPLE_RATE;
+    assert!((frequency - 440.0).abs() < 2.0);
+
+    assert_eq!(midi::velocity_to_level(127), 1.0);
+    assert_eq!(midi::velocity_to_level(0), 0.0);
+    assert!(midi::velocity_to_level(64) > midi::cc_to_unit(64));
+
+    assert_eq!(midi::cc_to_unit(0), 0.0);
+    assert_eq!(midi::cc_to_unit(127), 1.0);
+    assert_eq!(midi::cc_to_unit(255), 1.0);
+}
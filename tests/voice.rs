@@ -1,9 +1,19 @@
 //! Tests for the voice module.
 
+mod analysis;
 mod wav_writer;
 
-use mi_plaits_dsp::dsp::voice::{Modulations, Patch, Voice, NUM_ENGINES};
+use mi_plaits_dsp::dsp::engine::virtual_analog_engine::VirtualAnalogEngine;
+use mi_plaits_dsp::dsp::engine::{note_to_frequency, Engine, EngineParameters, TriggerState};
+use mi_plaits_dsp::dsp::oscillator::sine_oscillator::SineOscillator;
+use mi_plaits_dsp::dsp::voice::{
+    ChannelPostProcessor, LpgMode, Modulations, MonoMixer, OutputSaturation, Patch, Voice,
+    VoiceBuilder, AUTO_LEVEL_TARGET, NUM_ENGINES,
+};
 use mi_plaits_dsp::dsp::SAMPLE_RATE;
+use mi_plaits_dsp::stmlib::dsp::limiter::{DEFAULT_ATTACK, DEFAULT_RELEASE};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 const BLOCK_SIZE: usize = 24;
 
@@ -31,11 +41,17 @@ fn all_engines() {
         engine: 0,
         decay: 0.5,
         lpg_colour: 0.5,
+        lpg_brightness_tracking: 0.5,
+        fine_tune: 0.0,
+        lpg_mode: LpgMode::Auto,
+        retrigger_on_note_change: false,
+        trigger_delay_enabled: true,
     };
 
     let modulations = Modulations {
         engine: 0.0,
         note: 0.0,
+        pitch_bend: 0.0,
         frequency: 0.0,
         harmonics: 0.0,
         timbre: 0.0,
@@ -47,6 +63,7 @@ fn all_engines() {
         morph_patched: false,
         trigger_patched: false,
         level_patched: false,
+        trigger_sample_offset: 0,
     };
 
     for engine in 0..NUM_ENGINES {
@@ -87,11 +104,17 @@ fn all_engines_trigger() {
         engine: 0,
         decay: 0.5,
         lpg_colour: 0.5,
+        lpg_brightness_tracking: 0.5,
+        fine_tune: 0.0,
+        lpg_mode: LpgMode::Auto,
+        retrigger_on_note_change: false,
+        trigger_delay_enabled: true,
     };
 
     let mut modulations = Modulations {
         engine: 0.0,
         note: 0.0,
+        pitch_bend: 0.0,
         frequency: 0.0,
         harmonics: 0.0,
         timbre: 0.0,
@@ -103,6 +126,7 @@ fn all_engines_trigger() {
         morph_patched: false,
         trigger_patched: true,
         level_patched: false,
+        trigger_sample_offset: 0,
     };
 
     for engine in 0..NUM_ENGINES {
@@ -119,3 +143,1942 @@ fn all_engines_trigger() {
     wav_writer::write("voice/all_engines_trigger.wav", &wav_data).ok();
     wav_writer::write("voice/all_engines_trigger_aux.wav", &wav_data_aux).ok();
 }
+
+#[test]
+fn post_processor_saturation() {
+    let render = |saturation: OutputSaturation| {
+        let mut engine = VirtualAnalogEngine::new(&std::alloc::System, BLOCK_SIZE);
+        let mut post_processor = ChannelPostProcessor::new();
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut already_enveloped = false;
+        let mut data = Vec::new();
+
+        engine.init();
+        post_processor.init();
+        post_processor.set_saturation(saturation);
+
+        let parameters = EngineParameters {
+            trigger: TriggerState::Unpatched,
+            note: 48.0,
+            timbre: 0.5,
+            morph: 0.5,
+            harmonics: 0.5,
+            accent: 1.0,
+        };
+
+        for _ in 0..1000 {
+            engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+            // Overdrive the channel with the LPG bypassed.
+            post_processor.process(4.0, true, 1.0, 0.5, 0.0, &mut out);
+            data.extend_from_slice(&out);
+        }
+
+        data
+    };
+
+    let unsaturated = render(OutputSaturation::None);
+    let soft_clipped = render(OutputSaturation::SoftClip);
+
+    wav_writer::write("voice/saturation_soft_clip.wav", &soft_clipped).ok();
+
+    assert!(analysis::peak(&unsaturated) > 1.0);
+    assert!(analysis::peak(&soft_clipped) <= 1.0);
+}
+
+#[test]
+fn post_processor_saturation_i16() {
+    let render = |saturation: OutputSaturation| {
+        let mut post_processor = ChannelPostProcessor::new();
+        let mut osc = SineOscillator::new();
+        let mut in_ = [0.0; BLOCK_SIZE];
+        let mut out = [0; BLOCK_SIZE];
+        let mut data = Vec::new();
+
+        osc.init();
+        post_processor.init();
+        post_processor.set_saturation(saturation);
+
+        for _ in 0..1000 {
+            osc.render(100.0 / SAMPLE_RATE, &mut in_);
+            // Drive the channel just below full scale with the LPG bypassed.
+            post_processor.process_to_i16(0.9, true, 1.0, 0.5, 0.0, &mut in_, &mut out);
+            data.extend_from_slice(&out);
+        }
+
+        data.iter()
+            .map(|sample| sample.unsigned_abs())
+            .max()
+            .unwrap()
+    };
+
+    let unsaturated = render(OutputSaturation::None);
+    let soft_clipped = render(OutputSaturation::SoftClip);
+
+    assert!(unsaturated > 29000);
+    assert!(soft_clipped < unsaturated - 2000);
+}
+
+#[test]
+fn post_processor_i16_tone() {
+    // The integer output follows the float output, with the tone control after the LPG.
+    let mut float_post_processor = ChannelPostProcessor::new();
+    let mut i16_post_processor = ChannelPostProcessor::new();
+    let mut osc = SineOscillator::new();
+    let mut float_out = [0.0; BLOCK_SIZE];
+    let mut i16_in = [0.0; BLOCK_SIZE];
+    let mut i16_out = [0; BLOCK_SIZE];
+
+    osc.init();
+
+    for post_processor in [&mut float_post_processor, &mut i16_post_processor] {
+        post_processor.init();
+        post_processor.set_tone(0.8);
+    }
+
+    for n in 0..1000 {
+        osc.render(1000.0 / SAMPLE_RATE, &mut float_out);
+        i16_in.copy_from_slice(&float_out);
+
+        // A closing LPG, whose filter depends on its gain.
+        let lpg_gain = 1.0 - n as f32 / 1000.0;
+        let lpg_frequency = 0.002 + 0.2 * lpg_gain;
+        float_post_processor.process(0.5, false, lpg_gain, lpg_frequency, 0.0, &mut float_out);
+        i16_post_processor.process_to_i16(
+            0.5,
+            false,
+            lpg_gain,
+            lpg_frequency,
+            0.0,
+            &mut i16_in,
+            &mut i16_out,
+        );
+
+        for (float_sample, i16_sample) in float_out.iter().zip(i16_out.iter()) {
+            assert!((*i16_sample as f32 + float_sample * 32767.0).abs() <= 2.0);
+        }
+    }
+}
+
+#[test]
+fn post_processor_limiter_times() {
+    // Returns the number of samples until the gain reduction has recovered after a burst.
+    let recovery = |release: f32| {
+        let mut post_processor = ChannelPostProcessor::new();
+        post_processor.init();
+        post_processor.set_fade_in_length(0);
+        if release != DEFAULT_RELEASE {
+            post_processor.set_limiter_times(DEFAULT_ATTACK, release);
+        }
+        assert_eq!(post_processor.limiter_times(), (DEFAULT_ATTACK, release));
+
+        let mut block = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+
+        for n in 0..8000 {
+            block.fill(if (100..120).contains(&n) { 4.0 } else { 0.5 });
+            // A negative gain selects the limiter.
+            post_processor.process(-1.0, true, 1.0, 0.5, 0.0, &mut block);
+            data.extend_from_slice(&block);
+        }
+
+        wav_writer::write(
+            format!("voice/limiter_release_{release}.wav").as_str(),
+            &data,
+        )
+        .ok();
+
+        let burst_end = 120 * BLOCK_SIZE;
+        data[burst_end..]
+            .iter()
+            .position(|&sample| sample >= 0.99 * 0.8 * 0.5)
+            .unwrap_or(data.len())
+    };
+
+    let fast = recovery(DEFAULT_RELEASE * 10.0);
+    let default = recovery(DEFAULT_RELEASE);
+    assert!(fast > 0);
+    assert!(default > 5 * fast);
+}
+
+#[test]
+fn clipping() {
+    let render = |engine: usize| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut clipped = false;
+        let mut peak: f32 = 0.0;
+
+        voice.init();
+
+        // Draw the same grains, whatever other tests drew before.
+        voice.swarm_engine().unwrap().set_seed(Some(5));
+
+        let patch = Patch {
+            engine,
+            ..Default::default()
+        };
+        let modulations = Modulations::default();
+
+        for _ in 0..200 {
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            clipped |= voice.clipped();
+            peak = peak.max(voice.peak());
+            assert_eq!(voice.clipped(), voice.peak() > 1.0);
+        }
+
+        (clipped, peak)
+    };
+
+    // The swarm engine drives its limiter with a gain of 3.
+    let (clipped, peak) = render(16);
+    assert!(clipped);
+    assert!(peak > 1.0);
+
+    let (clipped, peak) = render(8);
+    assert!(!clipped);
+    assert!(peak > 0.1 && peak <= 1.0);
+}
+
+#[test]
+fn limiter_enabled() {
+    let render = |enabled: bool| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+
+        voice.init();
+        voice.set_limiter_enabled(enabled);
+        assert_eq!(voice.limiter_enabled(), enabled);
+
+        // Both renders draw the same grains, whatever other tests drew before.
+        let swarm_engine = voice.swarm_engine().unwrap();
+        swarm_engine.set_seed(Some(5));
+        assert_eq!(swarm_engine.seed(), Some(5));
+
+        let patch = Patch {
+            engine: 16,
+            ..Default::default()
+        };
+        let modulations = Modulations::default();
+
+        for _ in 0..400 {
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            data.extend_from_slice(&out);
+        }
+
+        data.split_off(4800)
+    };
+
+    let limited = render(true);
+    let raw = render(false);
+    wav_writer::write("voice/limiter_disabled.wav", &raw).ok();
+
+    // The swarm engine has a gain of 3, which the limiter keeps within range.
+    assert!(analysis::peak(&limited) <= 1.0);
+    assert!(analysis::peak(&raw) > analysis::peak(&limited) * 1.2);
+    assert!(analysis::rms(&raw) > analysis::rms(&limited) * 1.4);
+    assert!(raw.iter().all(|sample| sample.is_finite()));
+}
+
+#[test]
+fn pitch_bend() {
+    // Returns the frequency of the note in Hz.
+    let frequency = |pitch_bend: f32, fine_tune: f32| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+
+        voice.init();
+
+        let patch = Patch {
+            engine: 8,
+            fine_tune,
+            ..Default::default()
+        };
+
+        let modulations = Modulations {
+            pitch_bend,
+            ..Default::default()
+        };
+
+        for _ in 0..1000 {
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("voice/pitch_bend_{pitch_bend}_{fine_tune}.wav").as_str(),
+            &data,
+        )
+        .ok();
+
+        let low = 100.0 / SAMPLE_RATE;
+        let high = 200.0 / SAMPLE_RATE;
+        analysis::dominant_frequency(&data[12000..], low, high, 1001) * SAMPLE_RATE
+    };
+
+    let reference = frequency(0.0, 0.0);
+    assert!((reference - note_to_frequency(48.0) * SAMPLE_RATE).abs() < 0.2);
+
+    // The bend and the fine tuning add up in semitones.
+    for (pitch_bend, fine_tune) in [(2.0, 0.0), (0.0, 0.5), (0.0, -0.25), (-2.0, 0.5)] {
+        let ratio = frequency(pitch_bend, fine_tune) / reference;
+        let expected = 2.0_f32.powf((pitch_bend + fine_tune) / 12.0);
+        assert!(
+            (ratio - expected).abs() < 0.002,
+            "bend {pitch_bend} fine tune {fine_tune}: {ratio} instead of {expected}"
+        );
+    }
+}
+
+#[test]
+fn scale() {
+    let pentatonic = [0, 2, 4, 7, 9];
+
+    let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let mut data = Vec::new();
+
+    voice.init();
+    assert_eq!(voice.scale(), None);
+    voice.set_scale(Some(&[9, 2, 0, 4, 7, 16]));
+    assert_eq!(voice.scale(), Some(&pentatonic[..]));
+
+    // A slow ramp over one octave, 2 semitones per second.
+    let blocks = (6.0 * SAMPLE_RATE / BLOCK_SIZE as f32) as usize;
+
+    for n in 0..blocks {
+        let patch = Patch {
+            engine: 8,
+            note: 47.6 + 12.8 * n as f32 / blocks as f32,
+            ..Default::default()
+        };
+        voice.render(&patch, &Modulations::default(), &mut out, &mut aux);
+        data.extend_from_slice(&out);
+    }
+
+    wav_writer::write("voice/scale.wav", &data).ok();
+
+    // The note of each 50 ms window.
+    let notes: Vec<f32> = data
+        .chunks(2400)
+        .map(|window| {
+            let frequency = analysis::zero_crossing_frequency(window) * SAMPLE_RATE;
+            69.0 + 12.0 * (frequency / 440.0).log2()
+        })
+        .collect();
+
+    // Steady notes, ignoring the windows with a step.
+    let mut steady_notes = Vec::new();
+    for window in notes.windows(3) {
+        if (window[0] - window[1]).abs() < 0.05 && (window[1] - window[2]).abs() < 0.05 {
+            let note = window[1].round();
+            assert!((window[1] - note).abs() < 0.05);
+            if steady_notes.last() != Some(&note) {
+                steady_notes.push(note);
+            }
+        }
+    }
+
+    assert_eq!(steady_notes, [48.0, 50.0, 52.0, 55.0, 57.0, 60.0]);
+}
+
+#[test]
+fn unison() {
+    // Relative deviation of the level of 25 ms windows, i.e. the depth of the beating.
+    let level_deviation = |engine: usize, count: usize, builder: VoiceBuilder| {
+        let mut voice = builder.build(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+
+        voice.init();
+        voice.set_unison(count, 0.2);
+        assert_eq!(voice.unison(), (count, 0.2));
+
+        // The virtual analog engine detunes its oscillators away from the center.
+        let patch = Patch {
+            engine,
+            note: 48.0,
+            harmonics: if engine == 8 { 0.5 } else { 0.3 },
+            timbre: 0.6,
+            morph: 0.3,
+            ..Default::default()
+        };
+
+        for _ in 0..(2.0 * SAMPLE_RATE / BLOCK_SIZE as f32) as usize {
+            voice.render(&patch, &Modulations::default(), &mut out, &mut aux);
+            data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(&format!("voice/unison_{engine}_{count}.wav"), &data).ok();
+
+        let levels: Vec<f32> = data[4800..].chunks(1200).map(analysis::rms).collect();
+        let mean = levels.iter().sum::<f32>() / levels.len() as f32;
+        let deviation = (levels
+            .iter()
+            .map(|level| (level - mean).powi(2))
+            .sum::<f32>()
+            / levels.len() as f32)
+            .sqrt();
+
+        assert!(mean > 0.05);
+        deviation / mean
+    };
+
+    let builder = VoiceBuilder::new().enable_unison(true);
+    assert!(builder.is_unison_enabled());
+    assert!(!VoiceBuilder::new().is_unison_enabled());
+
+    for engine in [0, 8] {
+        assert!(Voice::engine_has_unison(engine));
+
+        let single = level_deviation(engine, 1, builder);
+        let unison = level_deviation(engine, 3, builder);
+        // Without the copies allocated, the setting is ignored.
+        let unallocated = level_deviation(engine, 3, VoiceBuilder::new());
+
+        assert!(single < 0.05, "engine {engine} single {single}");
+        assert!(unison > 0.2, "engine {engine} unison {unison}");
+        assert!(
+            unallocated < 0.05,
+            "engine {engine} unallocated {unallocated}"
+        );
+    }
+
+    assert!(!Voice::engine_has_unison(19));
+}
+
+#[test]
+fn unison_allocation() {
+    let allocator = CountingAllocator(AtomicUsize::new(0));
+    let unison_allocator = CountingAllocator(AtomicUsize::new(0));
+    let subset_allocator = CountingAllocator(AtomicUsize::new(0));
+    let subset_unison_allocator = CountingAllocator(AtomicUsize::new(0));
+
+    Voice::new(&allocator, BLOCK_SIZE);
+    VoiceBuilder::new()
+        .enable_unison(true)
+        .build(&unison_allocator, BLOCK_SIZE);
+    VoiceBuilder::new()
+        .with_engines(&[13])
+        .build(&subset_allocator, BLOCK_SIZE);
+    VoiceBuilder::new()
+        .with_engines(&[13])
+        .enable_unison(true)
+        .build(&subset_unison_allocator, BLOCK_SIZE);
+
+    let size = |allocator: &CountingAllocator| allocator.0.load(Ordering::Relaxed);
+
+    // The copies are only allocated on request, and only for the enabled engines.
+    assert!(size(&unison_allocator) > size(&allocator));
+    assert!(size(&subset_unison_allocator) - size(&subset_allocator) <= 2 * BLOCK_SIZE * 4);
+}
+
+#[test]
+fn auto_level() {
+    let level = |engine: usize, auto_level: bool| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+
+        voice.init();
+        voice.set_auto_level(auto_level);
+        assert_eq!(voice.auto_level(), auto_level);
+
+        let patch = Patch {
+            engine,
+            note: 48.0,
+            harmonics: 0.5,
+            timbre: 0.5,
+            morph: 0.5,
+            ..Default::default()
+        };
+
+        for _ in 0..(2.0 * SAMPLE_RATE / BLOCK_SIZE as f32) as usize {
+            voice.render(&patch, &Modulations::default(), &mut out, &mut aux);
+            data.extend_from_slice(&out);
+        }
+
+        analysis::rms(&data[data.len() / 2..])
+    };
+
+    // FM and wavetable.
+    let fm = level(10, false);
+    let wavetable = level(13, false);
+    assert!(fm / wavetable > 2.0);
+
+    for engine in [10, 13] {
+        let level = level(engine, true);
+        assert!(
+            (level / AUTO_LEVEL_TARGET - 1.0).abs() < 0.1,
+            "engine {engine}: {level}"
+        );
+    }
+}
+
+#[test]
+fn render_modulated() {
+    // A TIMBRE LFO above the Nyquist frequency of the block rate (1 kHz).
+    // The sideband above the fundamental is halfway between two harmonics.
+    let lfo_frequency = 1550.0 / SAMPLE_RATE;
+    let f0 = lfo_frequency / 7.5;
+
+    let render = |per_sample: bool| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut modulations = vec![Modulations::default(); BLOCK_SIZE];
+        let mut data = Vec::new();
+
+        voice.init();
+
+        let patch = Patch {
+            engine: 0,
+            note: 69.0 + 12.0 * (f0 * SAMPLE_RATE / 440.0).log2(),
+            harmonics: 0.0,
+            timbre: 0.5,
+            morph: 0.0,
+            timbre_modulation_amount: 0.8,
+            ..Default::default()
+        };
+
+        for n in 0..(SAMPLE_RATE / BLOCK_SIZE as f32) as usize {
+            for (i, modulations) in modulations.iter_mut().enumerate() {
+                let phase = ((n * BLOCK_SIZE + i) as f32 * lfo_frequency).fract();
+                modulations.timbre = 0.3 * (2.0 * std::f32::consts::PI * phase).sin();
+                modulations.timbre_patched = true;
+            }
+
+            if per_sample {
+                voice.render_modulated(&patch, &modulations, &mut out, &mut aux);
+            } else {
+                voice.render(&patch, &modulations[0], &mut out, &mut aux);
+            }
+            data.extend_from_slice(&out);
+        }
+
+        let name = if per_sample { "sample" } else { "block" };
+        wav_writer::write(&format!("voice/render_modulated_{name}.wav"), &data).ok();
+
+        data
+    };
+
+    let per_block = render(false);
+    let per_sample = render(true);
+
+    let sideband = |data: &[f32]| analysis::magnitude(&data[4800..], f0 + lfo_frequency);
+
+    assert!(
+        sideband(&per_sample) > 4.0 * sideband(&per_block),
+        "{} {}",
+        sideband(&per_sample),
+        sideband(&per_block)
+    );
+}
+
+#[test]
+fn render_modulated_constant() {
+    // Renders `chunks` copies of the modulations per block, or uses `render` for `None`.
+    let render = |chunks: Option<usize>, trigger_patched: bool| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+
+        voice.init();
+
+        // A plain saw, so that the level does not depend on the phase between the
+        // oscillators of the engine.
+        let patch = Patch {
+            engine: 8,
+            note: 48.0,
+            harmonics: 0.5,
+            timbre: 0.0,
+            morph: 0.5,
+            ..Default::default()
+        };
+        let mut modulations = Modulations {
+            trigger_patched,
+            ..Default::default()
+        };
+
+        for n in 0..400 {
+            modulations.trigger = if n % 200 < 100 { 1.0 } else { 0.0 };
+
+            match chunks {
+                Some(chunks) => {
+                    let modulations = vec![modulations.clone(); chunks];
+                    voice.render_modulated(&patch, &modulations, &mut out, &mut aux);
+                }
+                None => voice.render(&patch, &modulations, &mut out, &mut aux),
+            }
+            data.extend_from_slice(&out);
+        }
+
+        data
+    };
+
+    // A single chunk renders like `render`.
+    let block = render(None, true);
+    assert!(analysis::rms(&block) > 0.01);
+    assert_eq!(render(Some(1), true), block);
+
+    // Smaller chunks only change the interpolation of the engine parameters, which
+    // shifts the phase of the oscillators, but not the level and pitch.
+    let block = render(None, false);
+    let low = 50.0 / SAMPLE_RATE;
+    let high = 500.0 / SAMPLE_RATE;
+    let block_frequency = analysis::dominant_frequency(&block, low, high, 451);
+
+    for chunks in [4, BLOCK_SIZE] {
+        let chunked = render(Some(chunks), false);
+        assert!((analysis::rms(&chunked) / analysis::rms(&block) - 1.0).abs() < 0.05);
+        assert_eq!(
+            analysis::dominant_frequency(&chunked, low, high, 451),
+            block_frequency
+        );
+    }
+}
+
+#[test]
+fn render_modulated_trigger() {
+    // Renders notes starting halfway through a block, either with a rising edge in the
+    // second of two chunks or with a trigger sample offset.
+    let render = |chunked: bool| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+
+        voice.init();
+
+        let patch = Patch {
+            engine: 8,
+            note: 48.0,
+            ..Default::default()
+        };
+        let mut modulations = [
+            Modulations {
+                trigger_patched: true,
+                ..Default::default()
+            },
+            Modulations {
+                trigger_patched: true,
+                ..Default::default()
+            },
+        ];
+
+        for n in 0..200 {
+            let gate = n % 100 >= 10 && n % 100 < 60;
+            let rising_edge = n % 100 == 10;
+
+            if chunked {
+                modulations[0].trigger = if gate && !rising_edge { 1.0 } else { 0.0 };
+                modulations[1].trigger = if gate { 1.0 } else { 0.0 };
+                voice.render_modulated(&patch, &modulations, &mut out, &mut aux);
+            } else {
+                modulations[0].trigger = if gate { 1.0 } else { 0.0 };
+                modulations[0].trigger_sample_offset = if rising_edge { BLOCK_SIZE / 2 } else { 0 };
+                voice.render(&patch, &modulations[0], &mut out, &mut aux);
+            }
+            data.extend_from_slice(&out);
+            data.extend_from_slice(&aux);
+        }
+
+        data
+    };
+
+    // The note starts on the same sample. The envelopes are updated once per chunk, so
+    // the levels only match approximately.
+    let offset = render(false);
+    let chunked = render(true);
+    let onset = |data: &[f32]| data.iter().position(|&sample| sample != 0.0);
+    assert!(analysis::rms(&offset) > 0.01);
+    assert_eq!(onset(&chunked), onset(&offset));
+    assert!((analysis::rms(&chunked) / analysis::rms(&offset) - 1.0).abs() < 0.05);
+}
+
+#[test]
+fn lpg_freeze() {
+    let render = |freeze: bool| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        voice.init();
+        voice.set_lpg_freeze(freeze);
+        assert_eq!(voice.lpg_freeze(), freeze);
+
+        let patch = Patch {
+            note: 48.0,
+            engine: 8,
+            decay: 0.3,
+            ..Default::default()
+        };
+
+        // The gate is held for 3 seconds.
+        let modulations = Modulations {
+            trigger: 1.0,
+            trigger_patched: true,
+            ..Default::default()
+        };
+
+        for _ in 0..(3.0 * SAMPLE_RATE / BLOCK_SIZE as f32) as usize {
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(format!("voice/lpg_freeze_{freeze}.wav").as_str(), &wav_data).ok();
+
+        let length = wav_data.len();
+        (
+            analysis::rms(&wav_data[length / 30..length / 10]),
+            analysis::rms(&wav_data[length * 5 / 6..]),
+        )
+    };
+
+    let (attack, tail) = render(false);
+    assert!(attack > 0.05);
+    assert!(tail < 0.01 * attack);
+
+    let (attack, tail) = render(true);
+    assert!(attack > 0.05);
+    assert!(tail > 0.7 * attack, "{attack} {tail}");
+}
+
+#[test]
+fn tuning_reference() {
+    let render = |tuning_reference: Option<f32>, note: f32| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+
+        voice.init();
+        if let Some(tuning_reference) = tuning_reference {
+            voice.set_tuning_reference(tuning_reference);
+        }
+
+        let patch = Patch {
+            engine: 8,
+            note,
+            ..Default::default()
+        };
+        let modulations = Modulations::default();
+
+        for _ in 0..2000 {
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            data.extend_from_slice(&out);
+        }
+
+        data
+    };
+
+    let measure = |data: &[f32], frequency: f32| {
+        let low = (frequency - 20.0) / SAMPLE_RATE;
+        let high = (frequency + 20.0) / SAMPLE_RATE;
+        analysis::dominant_frequency(&data[24000..], low, high, 401) * SAMPLE_RATE
+    };
+
+    // The default reproduces the output without a tuning reference exactly.
+    assert_eq!(render(None, 69.0), render(Some(440.0), 69.0));
+
+    let a4 = render(Some(432.0), 69.0);
+    let a3 = render(Some(432.0), 57.0);
+    wav_writer::write("voice/tuning_reference_432.wav", &a4).ok();
+
+    assert!((measure(&render(None, 69.0), 440.0) - 440.0).abs() < 0.5);
+    assert!((measure(&a4, 432.0) - 432.0).abs() < 0.5);
+    assert!((measure(&a3, 216.0) - 216.0).abs() < 0.5);
+}
+
+#[test]
+fn clamp_parameters() {
+    let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+
+    voice.init();
+    voice.set_clamp_parameters(true);
+
+    let mut patch = Patch {
+        note: 1000.0,
+        harmonics: 5.0,
+        timbre: -3.0,
+        morph: 12.0,
+        frequency_modulation_amount: 4.0,
+        timbre_modulation_amount: -7.0,
+        morph_modulation_amount: 9.0,
+        engine: 0,
+        decay: 100.0,
+        lpg_colour: -20.0,
+        ..Default::default()
+    };
+
+    let mut modulations = Modulations {
+        engine: 30.0,
+        note: -5000.0,
+        pitch_bend: 500.0,
+        frequency: 80.0,
+        harmonics: -9.0,
+        timbre: 11.0,
+        morph: -4.0,
+        trigger: 0.0,
+        level: 50.0,
+        frequency_patched: true,
+        timbre_patched: true,
+        morph_patched: true,
+        trigger_patched: true,
+        level_patched: true,
+        ..Default::default()
+    };
+
+    for engine in (0..NUM_ENGINES).chain([99]) {
+        patch.engine = engine;
+
+        for n in 0..200 {
+            modulations.trigger = if n % 50 < 5 { 20.0 } else { -20.0 };
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            assert!(out.iter().chain(aux.iter()).all(|x| x.is_finite()));
+        }
+
+        // The sustained oscillator engines should be audible again.
+        if (8..=14).contains(&engine) {
+            assert!(analysis::peak(&out) > 0.001);
+        }
+    }
+
+    modulations.clamp();
+    assert_eq!(modulations.note, -119.0);
+    assert_eq!(modulations.pitch_bend, 24.0);
+}
+
+#[test]
+fn non_finite_parameters() {
+    let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+
+    voice.init();
+
+    for engine in 0..NUM_ENGINES {
+        let mut patch = Patch {
+            engine,
+            ..Default::default()
+        };
+        let mut modulations = Modulations::default();
+
+        for n in 0..100 {
+            if n == 50 {
+                patch.note = f32::NAN;
+                patch.harmonics = f32::INFINITY;
+                modulations.frequency = f32::NAN;
+            } else {
+                patch.note = 48.0;
+                patch.harmonics = 0.5;
+                modulations.frequency = 0.0;
+            }
+
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            assert!(out.iter().chain(aux.iter()).all(|x| x.is_finite()));
+        }
+
+        // The sustained oscillator engines should be audible again.
+        if (8..=14).contains(&engine) {
+            assert!(analysis::peak(&out) > 0.001);
+        }
+    }
+}
+
+#[test]
+fn dc_block() {
+    // The wave terrain engine has a strong DC offset on its main output.
+    let render = |dc_block: bool| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        voice.init();
+        voice.set_dc_block(dc_block);
+
+        let patch = Patch {
+            note: 48.0,
+            engine: 5,
+            ..Default::default()
+        };
+        let modulations = Modulations::default();
+
+        let duration = 2.0;
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+
+        for _ in 0..blocks {
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(format!("voice/dc_block_{dc_block}.wav").as_str(), &wav_data).ok();
+
+        // Skip the settling time of the blocker.
+        analysis::mean(&wav_data[wav_data.len() / 2..])
+    };
+
+    assert!(render(false).abs() > 0.1);
+    assert!(render(true).abs() < 0.005);
+}
+
+#[test]
+fn post_processor_fade_in() {
+    let fade_in_length = 4 * BLOCK_SIZE;
+    let mut post_processor = ChannelPostProcessor::new();
+    post_processor.init();
+    post_processor.set_fade_in_length(fade_in_length);
+
+    let render = |post_processor: &mut ChannelPostProcessor| {
+        let mut wav_data = Vec::new();
+
+        for _ in 0..8 {
+            let mut out = [0.5; BLOCK_SIZE];
+            post_processor.process(1.0, true, 1.0, 1.0, 0.0, &mut out);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_data
+    };
+
+    render(&mut post_processor);
+    post_processor.reset();
+    let wav_data = render(&mut post_processor);
+
+    wav_writer::write("voice/post_processor_fade_in.wav", &wav_data).ok();
+
+    // Ramps up from silence instead of jumping to the full level.
+    assert!(wav_data[0].abs() < 0.01);
+    for n in 1..fade_in_length {
+        assert!(wav_data[n] > wav_data[n - 1]);
+    }
+    assert!(wav_data[fade_in_length / 2] < 0.6 * wav_data[wav_data.len() - 1]);
+
+    // Unaffected once the fade is complete.
+    let full = wav_data[wav_data.len() - 1];
+    assert!(full > 0.1);
+    assert!(wav_data[fade_in_length..]
+        .iter()
+        .all(|x| (x - full).abs() < 1e-3));
+}
+
+#[test]
+fn fade_in_on_engine_change() {
+    let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let mut out_data = Vec::new();
+    let mut aux_data = Vec::new();
+
+    voice.init();
+
+    let mut patch = Patch {
+        note: 48.0,
+        engine: 8,
+        ..Default::default()
+    };
+    let modulations = Modulations::default();
+
+    for _ in 0..10 {
+        voice.render(&patch, &modulations, &mut out, &mut aux);
+    }
+
+    patch.engine = 9;
+
+    for _ in 0..10 {
+        voice.render(&patch, &modulations, &mut out, &mut aux);
+        out_data.extend_from_slice(&out);
+        aux_data.extend_from_slice(&aux);
+    }
+
+    wav_writer::write("voice/fade_in_on_engine_change_out.wav", &out_data).ok();
+    wav_writer::write("voice/fade_in_on_engine_change_aux.wav", &aux_data).ok();
+
+    // Both channels start from silence after the switch.
+    assert!(out_data[0].abs() < 1e-6);
+    assert!(aux_data[0].abs() < 1e-6);
+    assert!(analysis::peak(&out_data) > 0.01);
+    assert!(analysis::peak(&aux_data) > 0.01);
+}
+
+#[test]
+fn render_ring_mod() {
+    let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let mut ring = [0.0; BLOCK_SIZE];
+    let mut wav_data = Vec::new();
+
+    voice.init();
+
+    let patch = Patch {
+        note: 48.0,
+        engine: 8,
+        ..Default::default()
+    };
+    let modulations = Modulations::default();
+
+    for _ in 0..100 {
+        voice.render_ring_mod(&patch, &modulations, &mut out, &mut aux, &mut ring);
+        wav_data.extend_from_slice(&ring);
+
+        for ((out_sample, aux_sample), ring_sample) in out.iter().zip(aux.iter()).zip(ring.iter()) {
+            assert_eq!(*ring_sample, out_sample * aux_sample);
+        }
+    }
+
+    wav_writer::write("voice/render_ring_mod.wav", &wav_data).ok();
+
+    assert!(analysis::rms(&wav_data) > 0.01);
+}
+
+#[test]
+fn prime_engine_on_switch() {
+    let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+
+    voice.init();
+
+    let mut patch = Patch {
+        note: 48.0,
+        engine: 8,
+        ..Default::default()
+    };
+    let modulations = Modulations::default();
+
+    for _ in 0..10 {
+        voice.render(&patch, &modulations, &mut out, &mut aux);
+    }
+
+    // The six op engine used to skip its first block for the patch setup.
+    patch.engine = 2;
+    voice.render(&patch, &modulations, &mut out, &mut aux);
+
+    wav_writer::write("voice/prime_engine_on_switch.wav", &out).ok();
+
+    assert_eq!(voice.active_engine(), 2);
+    assert!(analysis::rms(&out) > 0.01);
+}
+
+#[test]
+fn lpg_brightness_tracking() {
+    // Returns the brightness of the tail as the ratio of the high band to the fundamental.
+    let render = |lpg_brightness_tracking: f32| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        voice.init();
+
+        let patch = Patch {
+            note: 48.0,
+            engine: 8,
+            decay: 0.7,
+            lpg_brightness_tracking,
+            ..Default::default()
+        };
+        let mut modulations = Modulations {
+            trigger_patched: true,
+            ..Default::default()
+        };
+
+        for n in 0..1000 {
+            modulations.trigger = if n < 4 { 1.0 } else { 0.0 };
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("voice/lpg_brightness_tracking_{lpg_brightness_tracking}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        let tail = &wav_data[9600..14400];
+        let f0 = note_to_frequency(48.0);
+        analysis::band_energy(tail, 8.0 * f0, 16.0 * f0, 40)
+            / analysis::band_energy(tail, 0.9 * f0, 1.1 * f0, 20)
+    };
+
+    let loose = render(0.1);
+    let default = render(0.5);
+    let tight = render(1.0);
+    assert!(default < loose);
+    assert!(tight < default);
+}
+
+#[test]
+fn lpg_mode() {
+    let render = |engine: usize, lpg_mode: LpgMode| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        voice.init();
+
+        let patch = Patch {
+            note: 48.0,
+            engine,
+            decay: 0.1,
+            lpg_mode,
+            ..Default::default()
+        };
+        let mut modulations = Modulations {
+            trigger_patched: true,
+            ..Default::default()
+        };
+
+        let duration = 1.0;
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+
+        for n in 0..blocks {
+            modulations.trigger = if n < 20 { 1.0 } else { 0.0 };
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("voice/lpg_mode_{engine}_{lpg_mode:?}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        let length = wav_data.len();
+        (
+            analysis::rms(&wav_data[..length / 20]),
+            analysis::rms(&wav_data[length / 4..length / 2]),
+        )
+    };
+
+    // The string engine has its own envelope, forcing the LPG on shortens its decay.
+    let (auto_attack, auto_tail) = render(19, LpgMode::Auto);
+    let (forced_attack, forced_tail) = render(19, LpgMode::ForceOn);
+    assert!(auto_attack > 0.01 && forced_attack > 0.01);
+    assert!(forced_tail / forced_attack < 0.1 * auto_tail / auto_attack);
+
+    // The virtual analog engine is gated by the LPG, forcing it off sustains the tone.
+    let (_, gated_tail) = render(8, LpgMode::Auto);
+    let (_, sustained_tail) = render(8, LpgMode::ForceOff);
+    assert!(sustained_tail > 10.0 * gated_tail);
+}
+
+#[test]
+fn engine_categories() {
+    for engine in 0..=16 {
+        assert!(Voice::engine_is_pitched(engine));
+        assert!(!Voice::engine_is_percussion(engine));
+    }
+
+    for engine in 19..=20 {
+        assert!(Voice::engine_is_pitched(engine));
+        assert!(Voice::engine_is_percussion(engine));
+    }
+
+    for engine in 21..=23 {
+        assert!(!Voice::engine_is_pitched(engine));
+        assert!(Voice::engine_is_percussion(engine));
+    }
+
+    assert!(!Voice::engine_is_pitched(NUM_ENGINES));
+    assert!(!Voice::engine_is_percussion(NUM_ENGINES));
+}
+
+#[test]
+fn engine_cost() {
+    for engine in 0..NUM_ENGINES {
+        assert!((1..=10).contains(&Voice::engine_cost(engine)));
+    }
+    assert_eq!(Voice::engine_cost(NUM_ENGINES), 0);
+
+    // Chiptune is the cheapest engine, the physical models are the most expensive ones.
+    assert!((0..NUM_ENGINES).all(|engine| Voice::engine_cost(engine) >= Voice::engine_cost(7)));
+    assert!(Voice::engine_cost(19) >= Voice::engine_cost(20));
+    assert!(Voice::engine_cost(20) >= Voice::engine_cost(17));
+    assert!(Voice::engine_cost(10) > Voice::engine_cost(8));
+    assert!(Voice::engine_cost(8) >= Voice::engine_cost(17));
+
+    // The six op FM banks only differ by their patches.
+    assert_eq!(Voice::engine_cost(2), Voice::engine_cost(3));
+    assert_eq!(Voice::engine_cost(3), Voice::engine_cost(4));
+}
+
+#[test]
+fn retrigger_on_note_change() {
+    // Returns the brightness before and after a note step.
+    let render = |retrigger_on_note_change: bool| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        voice.init();
+
+        // The internal envelope opens the filter of the virtual analog VCF engine.
+        let patch = Patch {
+            note: 48.0,
+            timbre: 0.0,
+            timbre_modulation_amount: 1.0,
+            engine: 0,
+            decay: 0.3,
+            lpg_mode: LpgMode::ForceOff,
+            retrigger_on_note_change,
+            ..Default::default()
+        };
+        let mut modulations = Modulations {
+            trigger_patched: true,
+            ..Default::default()
+        };
+
+        let blocks = (0.5 * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+
+        for n in 0..blocks * 2 {
+            modulations.trigger = if n < 20 { 1.0 } else { 0.0 };
+            modulations.note = if n < blocks { 0.0 } else { 5.0 };
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("voice/retrigger_on_note_change_{retrigger_on_note_change}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        let middle = wav_data.len() / 2;
+        let window = (0.1 * SAMPLE_RATE) as usize;
+        let before = &wav_data[middle - window..middle];
+        let after = &wav_data[middle..middle + window];
+        (
+            analysis::difference_rms(before) / analysis::rms(before),
+            analysis::difference_rms(after) / analysis::rms(after),
+        )
+    };
+
+    let (before_off, after_off) = render(false);
+    let (before_on, after_on) = render(true);
+
+    assert!((before_on / before_off - 1.0).abs() < 0.01);
+    assert!(after_on > after_off * 1.5);
+}
+
+#[test]
+fn trigger_sample_offset() {
+    // Returns the position of the first sample above a threshold.
+    let onset = |trigger_sample_offset: usize| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        voice.init();
+
+        let patch = Patch {
+            note: 48.0,
+            engine: 21,
+            ..Default::default()
+        };
+        let mut modulations = Modulations {
+            trigger_patched: true,
+            ..Default::default()
+        };
+
+        for n in 0..100 {
+            modulations.trigger = if (10..20).contains(&n) { 1.0 } else { 0.0 };
+            modulations.trigger_sample_offset = if n == 10 { trigger_sample_offset } else { 0 };
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("voice/trigger_sample_offset_{trigger_sample_offset}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        wav_data.iter().position(|x| x.abs() > 0.01).unwrap()
+    };
+
+    let reference = onset(0);
+    let delayed = onset(10);
+    assert!(delayed.abs_diff(reference + 10) <= 1);
+}
+
+#[test]
+fn trigger_sample_offset_all_engines() {
+    // Every engine renders the partial blocks around a rising edge in the middle of a block.
+    for engine in 0..24 {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut data = Vec::new();
+
+        voice.init();
+
+        let patch = Patch {
+            note: 48.0,
+            harmonics: 0.5,
+            timbre: 0.5,
+            morph: 0.5,
+            engine,
+            ..Default::default()
+        };
+        let mut modulations = Modulations {
+            trigger_patched: true,
+            ..Default::default()
+        };
+
+        for n in 0..100 {
+            modulations.trigger = if (10..60).contains(&n) { 1.0 } else { 0.0 };
+            modulations.trigger_sample_offset = if n == 10 { BLOCK_SIZE / 2 - 1 } else { 0 };
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            data.extend_from_slice(&out);
+            data.extend_from_slice(&aux);
+        }
+
+        assert!(
+            data.iter().all(|sample| sample.is_finite()),
+            "engine {engine}"
+        );
+        assert!(data.iter().any(|&sample| sample != 0.0), "engine {engine}");
+    }
+}
+
+#[test]
+fn trigger_delay_enabled() {
+    // Returns the position of the first sample above a threshold.
+    let onset = |trigger_delay_enabled: bool| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        voice.init();
+
+        let patch = Patch {
+            note: 48.0,
+            engine: 21,
+            trigger_delay_enabled,
+            ..Default::default()
+        };
+        let mut modulations = Modulations {
+            trigger_patched: true,
+            ..Default::default()
+        };
+
+        for n in 0..100 {
+            modulations.trigger = if (10..20).contains(&n) { 1.0 } else { 0.0 };
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("voice/trigger_delay_enabled_{trigger_delay_enabled}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        wav_data.iter().position(|x| x.abs() > 0.01).unwrap()
+    };
+
+    let delayed = onset(true);
+    let direct = onset(false);
+
+    // The delay line holds the trigger for 7 blocks, 3.5 ms at this block size.
+    assert_eq!(delayed - direct, 7 * BLOCK_SIZE);
+}
+
+#[test]
+fn preview_engine_index() {
+    let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+
+    voice.init();
+
+    let mut patch = Patch::default();
+    let mut modulations = Modulations::default();
+
+    for (engine, engine_cv) in [
+        (0, 0.0),
+        (5, 0.3),
+        (5, 0.31),
+        (12, -0.2),
+        (23, 1.0),
+        (3, -1.0),
+    ] {
+        patch.engine = engine;
+        modulations.engine = engine_cv;
+
+        // Previewing other values must not change the selection.
+        for other in [-1.0, 0.0, 1.0] {
+            voice.preview_engine_index(engine, other);
+        }
+
+        let preview = voice.preview_engine_index(engine, engine_cv);
+        voice.render(&patch, &modulations, &mut out, &mut aux);
+        assert_eq!(preview, voice.active_engine());
+    }
+}
+
+#[test]
+fn estimated_tail_samples() {
+    let render = |engine: usize, morph: f32| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        voice.init();
+
+        let patch = Patch {
+            note: 48.0,
+            engine,
+            morph,
+            ..Default::default()
+        };
+
+        let duration = 1.0;
+        let blocks = (duration * SAMPLE_RATE / (BLOCK_SIZE as f32)) as usize;
+
+        for n in 0..blocks {
+            let modulations = Modulations {
+                trigger: if n < 10 { 1.0 } else { 0.0 },
+                trigger_patched: true,
+                ..Default::default()
+            };
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(
+            format!("voice/estimated_tail_{engine}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        (voice.estimated_tail_samples(&patch), wav_data)
+    };
+
+    let (string_tail, _) = render(19, 1.0);
+    let (kick_tail, kick_data) = render(21, 0.0);
+
+    assert!(string_tail > 10 * kick_tail);
+
+    // The kick has faded out by 60 dB within the estimate.
+    let peak = analysis::peak(&kick_data);
+    let last = kick_data
+        .iter()
+        .rposition(|sample| sample.abs() > peak * 0.001)
+        .unwrap();
+    assert!(last < kick_tail);
+    assert!(kick_tail < kick_data.len());
+}
+
+#[test]
+fn parameter_smoothing() {
+    const SMOOTHING_BLOCK_SIZE: usize = 256;
+
+    let f0 = note_to_frequency(84.0);
+
+    let render = |smoothing: usize| {
+        let mut voice = Voice::new(&std::alloc::System, SMOOTHING_BLOCK_SIZE);
+        let mut out = [0.0; SMOOTHING_BLOCK_SIZE];
+        let mut aux = [0.0; SMOOTHING_BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+        let mut spectra = Vec::new();
+
+        voice.init();
+        voice.set_parameter_smoothing(smoothing);
+
+        // Grain engine, HARMONICS jumps across most of the formant ratio range.
+        let mut patch = Patch {
+            note: 84.0,
+            engine: 11,
+            ..Default::default()
+        };
+        let modulations = Modulations::default();
+
+        for n in 0..60 {
+            patch.harmonics = if n < 20 { 0.2 } else { 0.8 };
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            wav_data.extend_from_slice(&out);
+            spectra.push(
+                (1..=16)
+                    .map(|k| analysis::magnitude(&out, k as f32 * f0))
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        wav_writer::write(
+            format!("voice/parameter_smoothing_{smoothing}.wav").as_str(),
+            &wav_data,
+        )
+        .ok();
+
+        let distance = |a: &[f32], b: &[f32]| -> f32 {
+            a.iter()
+                .zip(b.iter())
+                .map(|(x, y)| (x - y) * (x - y))
+                .sum::<f32>()
+                .sqrt()
+        };
+
+        // Largest spectral change between two blocks after the jump, relative to the
+        // overall change.
+        let total = distance(&spectra[19], &spectra[59]);
+        let largest_step = spectra[19..]
+            .windows(2)
+            .map(|pair| distance(&pair[0], &pair[1]))
+            .fold(0.0, f32::max)
+            / total;
+
+        // Number of blocks until the spectrum has settled.
+        let settled = spectra[20..]
+            .iter()
+            .position(|spectrum| distance(spectrum, &spectra[59]) < 0.1 * total)
+            .unwrap();
+
+        (largest_step, settled)
+    };
+
+    let (stepped_largest_step, stepped_settled) = render(0);
+    let (smoothed_largest_step, smoothed_settled) = render(8);
+
+    // Without smoothing, the change happens within the first blocks.
+    assert!(stepped_settled <= 2);
+    assert!(stepped_largest_step > 0.5);
+
+    // With smoothing, the change is spread over several blocks.
+    assert!(smoothed_settled >= 8);
+    assert!(smoothed_largest_step < 0.5 * stepped_largest_step);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn envelope_preview() {
+    let voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+    let length = (SAMPLE_RATE * 2.0) as usize;
+
+    for trigger_length in [0, (SAMPLE_RATE * 0.01) as usize] {
+        let preview = |decay: f32| {
+            let patch = Patch {
+                decay,
+                ..Default::default()
+            };
+            voice.envelope_preview(&patch, trigger_length, length)
+        };
+
+        let short = preview(0.2);
+        let long = preview(0.8);
+
+        wav_writer::write(
+            format!("voice/envelope_preview_{trigger_length}.wav").as_str(),
+            &long,
+        )
+        .ok();
+
+        assert_eq!(short.len(), length);
+        assert_eq!(long.len(), length);
+
+        // Both open fully after the trigger.
+        let attack = trigger_length.max(BLOCK_SIZE * 8);
+        assert!(analysis::peak(&short[..attack]) > 0.9);
+        assert!(analysis::peak(&long[..attack]) > 0.9);
+
+        // The short decay falls faster.
+        let t = (SAMPLE_RATE * 0.2) as usize;
+        assert!(short[t] < 0.5 * long[t]);
+        assert!(long[length - 1] < long[t]);
+    }
+
+    // Bypassed on engines with their own envelope.
+    let patch = Patch {
+        engine: 21,
+        ..Default::default()
+    };
+    assert!(voice
+        .envelope_preview(&patch, 0, 100)
+        .iter()
+        .all(|&gain| gain == 1.0));
+}
+
+/// Returns the engine of a voice by its index, see `Voice::active_engine`.
+fn voice_engine<'a, 'b>(voice: &'b mut Voice<'a>, index: usize) -> &'b mut dyn Engine {
+    match index {
+        0 => voice.virtual_analog_vcf_engine().unwrap(),
+        1 => voice.phase_distortion_engine().unwrap(),
+        2..=4 => voice.six_op_engine().unwrap(),
+        5 => voice.waveterrain_engine().unwrap(),
+        6 => voice.string_machine_engine().unwrap(),
+        7 => voice.chiptune_engine().unwrap(),
+        8 => voice.virtual_analog_engine().unwrap(),
+        9 => voice.waveshaping_engine().unwrap(),
+        10 => voice.fm_engine().unwrap(),
+        11 => voice.grain_engine().unwrap(),
+        12 => voice.additive_engine().unwrap(),
+        13 => voice.wavetable_engine().unwrap(),
+        14 => voice.chord_engine().unwrap(),
+        15 => voice.speech_engine().unwrap(),
+        16 => voice.swarm_engine().unwrap(),
+        17 => voice.noise_engine().unwrap(),
+        18 => voice.particle_engine().unwrap(),
+        19 => voice.string_engine().unwrap(),
+        20 => voice.modal_engine().unwrap(),
+        21 => voice.bass_drum_engine().unwrap(),
+        22 => voice.snare_drum_engine().unwrap(),
+        _ => voice.hihat_engine().unwrap(),
+    }
+}
+
+#[test]
+fn extreme_notes() {
+    // The engines are rendered directly, as the voice recovers from non-finite output and
+    // limits the level.
+    for index in 0..NUM_ENGINES {
+        for note in [-119.0, 120.0] {
+            let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+            let mut out = [0.0; BLOCK_SIZE];
+            let mut aux = [0.0; BLOCK_SIZE];
+            let mut data = Vec::new();
+
+            voice.init();
+
+            // Selects the engine, loading its resources.
+            let patch = Patch {
+                engine: index,
+                ..Default::default()
+            };
+            voice.render(&patch, &Modulations::default(), &mut out, &mut aux);
+
+            let engine = voice_engine(&mut voice, index);
+            let mut already_enveloped = false;
+
+            for n in 0..400 {
+                let parameters = EngineParameters {
+                    trigger: match n % 100 {
+                        0 => TriggerState::RisingEdge,
+                        1..=9 => TriggerState::High,
+                        _ => TriggerState::Low,
+                    },
+                    note,
+                    timbre: 0.5,
+                    morph: 0.5,
+                    harmonics: 0.5,
+                    accent: 1.0,
+                };
+
+                engine.render(&parameters, &mut out, &mut aux, &mut already_enveloped);
+                data.extend_from_slice(&out);
+                data.extend_from_slice(&aux);
+            }
+
+            assert!(
+                data.iter().all(|sample| sample.is_finite()),
+                "Engine {index} at note {note} rendered non-finite samples"
+            );
+            assert!(
+                analysis::rms(&data) > 0.0,
+                "Engine {index} at note {note} is silent"
+            );
+        }
+    }
+}
+
+#[test]
+fn mono_mixer_bass_management() {
+    let low = 0.002;
+    let high = 0.05;
+    let crossover = 0.01;
+
+    let render = |crossover_frequency: Option<f32>| {
+        let mut mixer = MonoMixer::new();
+        mixer.init();
+        mixer.set_crossover_frequency(crossover_frequency);
+        assert_eq!(mixer.crossover_frequency(), crossover_frequency);
+
+        let mut wav_data = Vec::new();
+        let mut aux_data = Vec::new();
+        let mut mono = [0.0; BLOCK_SIZE];
+
+        for n in 0..1000 {
+            // Low end in opposite phase, high end only on AUX.
+            let out: Vec<f32> = (0..BLOCK_SIZE)
+                .map(|i| {
+                    let t = (n * BLOCK_SIZE + i) as f32;
+                    f32::sin(2.0 * std::f32::consts::PI * low * t)
+                })
+                .collect();
+            let aux: Vec<f32> = (0..BLOCK_SIZE)
+                .map(|i| {
+                    let t = (n * BLOCK_SIZE + i) as f32;
+                    -f32::sin(2.0 * std::f32::consts::PI * low * t)
+                        + 0.5 * f32::sin(2.0 * std::f32::consts::PI * high * t)
+                })
+                .collect();
+            mixer.process(&out, &aux, &mut mono);
+            wav_data.extend_from_slice(&mono);
+            aux_data.extend_from_slice(&aux);
+        }
+
+        let wav_data = wav_data[wav_data.len() / 2..].to_vec();
+        (
+            analysis::magnitude(&wav_data, low),
+            analysis::magnitude(&wav_data, high),
+        )
+    };
+
+    let (plain_low, plain_high) = render(None);
+    let (managed_low, managed_high) = render(Some(crossover));
+
+    // Without bass management, the low end cancels out.
+    assert!(plain_low < 0.01);
+    assert!((plain_high - 0.5).abs() < 0.02);
+
+    // With bass management, the low end of OUT is preserved, and AUX still passes above
+    // the crossover.
+    assert!((managed_low - 1.0).abs() < 0.05);
+    assert!(managed_high > 0.4);
+}
+
+#[test]
+fn render_mono() {
+    let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let mut mono = [0.0; BLOCK_SIZE];
+
+    voice.init();
+
+    let patch = Patch {
+        engine: 8,
+        ..Default::default()
+    };
+    let modulations = Modulations::default();
+
+    for _ in 0..100 {
+        voice.render_mono(&patch, &modulations, &mut out, &mut aux, &mut mono);
+        for ((out_sample, aux_sample), mono_sample) in out.iter().zip(aux.iter()).zip(mono.iter()) {
+            assert_eq!(*mono_sample, out_sample + aux_sample);
+        }
+    }
+
+    voice.set_bass_management(Some(0.01));
+    voice.render_mono(&patch, &modulations, &mut out, &mut aux, &mut mono);
+    assert!(out
+        .iter()
+        .zip(aux.iter())
+        .zip(mono.iter())
+        .any(|((out_sample, aux_sample), mono_sample)| *mono_sample != out_sample + aux_sample));
+}
+
+/// Allocator counting the allocated bytes.
+struct CountingAllocator(AtomicUsize);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.0.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[test]
+fn voice_builder() {
+    let full_allocator = CountingAllocator(AtomicUsize::new(0));
+    let subset_allocator = CountingAllocator(AtomicUsize::new(0));
+
+    let mut full_voice = Voice::new(&full_allocator, BLOCK_SIZE);
+    let mut subset_voice = VoiceBuilder::new()
+        .with_engines(&[0, 13])
+        .build(&subset_allocator, BLOCK_SIZE);
+
+    let full_size = full_allocator.0.load(Ordering::Relaxed);
+    let subset_size = subset_allocator.0.load(Ordering::Relaxed);
+    assert!(subset_size * 4 < full_size);
+
+    assert!(subset_voice.wavetable_engine().is_some());
+    assert!(subset_voice.virtual_analog_engine().is_none());
+    assert!(full_voice.virtual_analog_engine().is_some());
+
+    full_voice.init();
+    subset_voice.init();
+
+    let mut full_out = [0.0; BLOCK_SIZE];
+    let mut full_aux = [0.0; BLOCK_SIZE];
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+    let modulations = Modulations::default();
+
+    for engine in [0, 13, 8] {
+        let patch = Patch {
+            engine,
+            ..Default::default()
+        };
+        let mut energy = 0.0;
+
+        for _ in 0..100 {
+            full_voice.render(&patch, &modulations, &mut full_out, &mut full_aux);
+            subset_voice.render(&patch, &modulations, &mut out, &mut aux);
+            energy += analysis::rms(&out) + analysis::rms(&aux);
+
+            if engine == 8 {
+                // Disabled engines render silence.
+                assert!(out.iter().chain(aux.iter()).all(|sample| *sample == 0.0));
+            } else {
+                assert_eq!(out, full_out);
+                assert_eq!(aux, full_aux);
+            }
+        }
+
+        assert_eq!(subset_voice.active_engine(), engine);
+        assert_eq!(energy > 0.0, engine != 8);
+    }
+}
+
+#[test]
+fn duplicate() {
+    let mut voice = VoiceBuilder::new()
+        .with_engines(&[0, 13])
+        .build(&std::alloc::System, BLOCK_SIZE);
+    let mut out = [0.0; BLOCK_SIZE];
+    let mut aux = [0.0; BLOCK_SIZE];
+
+    voice.init();
+    voice.set_saturation(OutputSaturation::SoftClip);
+    voice.set_parameter_smoothing(4);
+
+    let patch = Patch {
+        engine: 13,
+        note: 48.0,
+        ..Default::default()
+    };
+    let modulations = Modulations::default();
+
+    for _ in 0..50 {
+        voice.render(&patch, &modulations, &mut out, &mut aux);
+    }
+
+    let mut duplicate = voice.duplicate(&std::alloc::System);
+    assert_eq!(duplicate.active_engine(), 13);
+
+    let mut original_data = Vec::new();
+    let mut duplicate_data = Vec::new();
+
+    for _ in 0..200 {
+        voice.render(&patch, &modulations, &mut out, &mut aux);
+        original_data.extend_from_slice(&out);
+        duplicate.render(&patch, &modulations, &mut out, &mut aux);
+        duplicate_data.extend_from_slice(&out);
+        assert_eq!(duplicate.active_engine(), 13);
+    }
+
+    // Skip the fade-in and compare the steady state.
+    let original_data = &original_data[2400..];
+    let duplicate_data = &duplicate_data[2400..];
+    let f0 = note_to_frequency(48.0);
+
+    assert_eq!(
+        analysis::dominant_frequency(original_data, f0 * 0.5, f0 * 1.5, 101),
+        analysis::dominant_frequency(duplicate_data, f0 * 0.5, f0 * 1.5, 101)
+    );
+    assert!((analysis::rms(duplicate_data) / analysis::rms(original_data) - 1.0).abs() < 0.01);
+    assert!(analysis::peak(duplicate_data) <= 1.0);
+}
+
+#[test]
+fn tone() {
+    // The noise engine with a wide open filter has a flat spectrum.
+    let render = |tone: f32| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut wav_data = Vec::new();
+
+        voice.init();
+        voice.set_tone(tone);
+        assert_eq!(voice.tone(), tone);
+
+        let patch = Patch {
+            note: 48.0,
+            engine: 17,
+            harmonics: 0.5,
+            timbre: 1.0,
+            morph: 0.0,
+            ..Default::default()
+        };
+        let modulations = Modulations::default();
+
+        for _ in 0..500 {
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            wav_data.extend_from_slice(&out);
+        }
+
+        wav_writer::write(format!("voice/tone_{tone}.wav").as_str(), &wav_data).ok();
+
+        let data = &wav_data[4000..];
+        analysis::band_energy(data, 0.1, 0.4, 200)
+    };
+
+    let flat = render(0.0);
+    assert!(render(1.0) > 2.0 * flat);
+    assert!(render(-1.0) < 0.5 * flat);
+}
+
+#[test]
+fn invert() {
+    let render = |invert: bool| {
+        let mut voice = Voice::new(&std::alloc::System, BLOCK_SIZE);
+        let mut out = [0.0; BLOCK_SIZE];
+        let mut aux = [0.0; BLOCK_SIZE];
+        let mut out_data = Vec::new();
+        let mut aux_data = Vec::new();
+
+        voice.init();
+        voice.set_invert(invert, false);
+        assert_eq!(voice.invert(), (invert, false));
+
+        let patch = Patch {
+            note: 48.0,
+            engine: 8,
+            harmonics: 0.5,
+            timbre: 0.5,
+            morph: 0.5,
+            ..Default::default()
+        };
+        let modulations = Modulations::default();
+
+        for _ in 0..100 {
+            voice.render(&patch, &modulations, &mut out, &mut aux);
+            out_data.extend_from_slice(&out);
+            aux_data.extend_from_slice(&aux);
+        }
+
+        (out_data, aux_data)
+    };
+
+    let (out, aux) = render(false);
+    let (inverted_out, inverted_aux) = render(true);
+
+    assert!(out.iter().any(|sample| *sample != 0.0));
+    assert!(out.iter().zip(&inverted_out).all(|(a, b)| *b == -*a));
+    assert_eq!(aux, inverted_aux);
+}